@@ -50,6 +50,17 @@ fn main() {
             // supabase::access_token::get_access_token,
             supabase::accounts::save_plaid_account,
             supabase::accounts::get_plaid_balances,
+            supabase::debts::import_liabilities,
+            supabase::exchange_rates::import_exchange_rates,
+            supabase::forecast::forecast_cash_flow,
+            supabase::rules::preview_rule,
+            supabase::rules::apply_rules,
+            supabase::splits::save_splits,
+            supabase::suggestions::suggest_budgets,
+            supabase::transfers::detect_transfers,
+            supabase::attachments::upload_attachment,
+            supabase::attachments::attachment_url,
+            supabase::attachments::delete_attachment,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,157 @@
+use recurr_core::debt::SchemaDebtTerms;
+use reqwest::header::{HeaderMap, HeaderValue};
+use serde::{Deserialize, Serialize};
+
+use super::{Error, PlaidRequest};
+
+#[derive(Serialize)]
+struct Options {
+    account_ids: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct LiabilitiesGetRequest {
+    access_token: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    options: Option<Options>,
+}
+
+#[derive(Deserialize)]
+struct Apr {
+    apr_percentage: f64,
+    apr_type: String,
+}
+
+#[derive(Deserialize)]
+struct CreditLiability {
+    account_id: Option<String>,
+    #[serde(default)]
+    aprs: Vec<Apr>,
+    minimum_payment_amount: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct StudentLiability {
+    account_id: Option<String>,
+    interest_rate_percentage: Option<f64>,
+    minimum_payment_amount: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct InterestRate {
+    percentage: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct MortgageLiability {
+    account_id: Option<String>,
+    interest_rate: Option<InterestRate>,
+    next_monthly_payment: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct Liabilities {
+    credit: Option<Vec<CreditLiability>>,
+    student: Option<Vec<StudentLiability>>,
+    mortgage: Option<Vec<MortgageLiability>>,
+}
+
+/// APR and minimum payment of the item's credit cards, student loans and mortgages
+pub async fn get_liabilities(
+    auth_key: &str,
+    user_id: &str,
+    access_token: String,
+    account_ids: Vec<String>,
+) -> Result<Vec<SchemaDebtTerms>, Error> {
+    let mut authorization = String::from("Bearer ");
+    authorization.push_str(auth_key);
+
+    let mut headers = HeaderMap::new();
+    headers.insert("Authorization", authorization.parse().unwrap());
+    headers.insert("Content-Type", HeaderValue::from_static("application/json"));
+
+    let options = if account_ids.is_empty() {
+        None
+    } else {
+        Some(Options { account_ids })
+    };
+
+    let data = serde_json::to_value(LiabilitiesGetRequest {
+        access_token,
+        options,
+    })?;
+
+    let req = PlaidRequest {
+        endpoint: "/liabilities/get".to_string(),
+        data: Some(data),
+    };
+
+    let client = reqwest::Client::new();
+    let res = client
+        .post(env!("PLAID_URL"))
+        .json(&req)
+        .headers(headers)
+        .send()
+        .await
+        .and_then(|e| e.error_for_status())
+        .map_err(|e| recurr_core::Error::Other(e.to_string()))?;
+
+    #[derive(Deserialize)]
+    struct LiabilitiesGetResponse {
+        liabilities: Liabilities,
+    }
+
+    let liabilities = res
+        .json::<LiabilitiesGetResponse>()
+        .await
+        .map_err(|e| recurr_core::Error::Request(e.to_string()))?
+        .liabilities;
+
+    let terms =
+        |account_id: String, apr: Option<f64>, minimum_payment: Option<f64>| SchemaDebtTerms {
+            user_id: user_id.to_string(),
+            account_id,
+            apr: apr.unwrap_or_default(),
+            minimum_payment: minimum_payment.unwrap_or_default(),
+        };
+
+    let credit = liabilities
+        .credit
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|c| {
+            // Purchases are what carries a balance month to month
+            let apr = c
+                .aprs
+                .iter()
+                .find(|a| a.apr_type == "purchase_apr")
+                .or(c.aprs.first())
+                .map(|a| a.apr_percentage);
+            Some(terms(c.account_id?, apr, c.minimum_payment_amount))
+        });
+    let student = liabilities
+        .student
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|s| {
+            Some(terms(
+                s.account_id?,
+                s.interest_rate_percentage,
+                s.minimum_payment_amount,
+            ))
+        });
+    let mortgage = liabilities
+        .mortgage
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|m| {
+            Some(terms(
+                m.account_id?,
+                m.interest_rate.and_then(|r| r.percentage),
+                m.next_monthly_payment,
+            ))
+        });
+
+    Ok(credit.chain(student).chain(mortgage).collect())
+}
@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 
 pub mod accounts;
 pub mod institutions;
+pub mod liabilities;
 pub mod link;
 pub mod transactions;
 
@@ -2,8 +2,19 @@ use async_recursion::async_recursion;
 use recurr_core::{Category, Transaction};
 use reqwest::header::{HeaderMap, HeaderValue};
 use serde::{Deserialize, Serialize};
-
-use crate::plaid::PlaidRequest;
+use tauri::AppHandle;
+
+use crate::{
+    plaid::PlaidRequest,
+    supabase::{
+        alerts::check_budgets,
+        balances::snapshot_item,
+        pending::carry_over,
+        rules::run_rules,
+        splits::{delete_splits, rebalance_splits},
+        transfers::{delete_links, match_synced},
+    },
+};
 
 use super::Error;
 
@@ -46,7 +57,12 @@ pub async fn get_categories() -> Result<Vec<Category>, Error> {
 
 #[tauri::command]
 #[async_recursion]
-pub async fn sync(auth_key: &str, access_token: &str, cursor: Option<String>) -> Result<(), Error> {
+pub async fn sync(
+    app: AppHandle,
+    auth_key: &str,
+    access_token: &str,
+    cursor: Option<String>,
+) -> Result<(), Error> {
     let mut authorization = String::from("Bearer ");
     authorization.push_str(auth_key);
 
@@ -54,15 +70,24 @@ pub async fn sync(auth_key: &str, access_token: &str, cursor: Option<String>) ->
     headers.insert("Authorization", authorization.parse().unwrap());
     headers.insert("Content-Type", HeaderValue::from_static("application/json"));
 
+    #[derive(Serialize)]
+    struct Options {
+        include_personal_finance_category: bool,
+    }
+
     #[derive(Serialize)]
     struct Request {
         access_token: String,
         cursor: Option<String>,
+        options: Options,
     }
 
     let data = serde_json::to_value(Request {
         access_token: access_token.to_string(),
         cursor,
+        options: Options {
+            include_personal_finance_category: true,
+        },
     })?;
 
     let req = PlaidRequest {
@@ -118,19 +143,49 @@ pub async fn sync(auth_key: &str, access_token: &str, cursor: Option<String>) ->
         .flatten()
         .map_err(|e| recurr_core::Error::Other(e.to_string()))?;
 
+    rebalance_splits(auth_key, &plaid_response.modified).await?;
+
+    let mut changed = plaid_response.added;
+    changed.extend(plaid_response.modified);
+    run_rules(auth_key, &changed, false).await?;
+    // Before the pending transactions are removed below. Unlike matching
+    // transfers a failure stops the sync, removing them would lose what's moved
+    carry_over(auth_key, &changed).await?;
+
     let _ = client
         .from("transactions")
         .auth(auth_key)
         .delete()
-        .in_("transaction_id", plaid_response.removed)
+        .in_("transaction_id", &plaid_response.removed)
         .execute()
         .await
         .map(|e| e.error_for_status())
         .flatten()
         .map_err(|e| recurr_core::Error::Other(e.to_string()))?;
 
+    delete_splits(auth_key, &plaid_response.removed).await?;
+    delete_links(auth_key, &plaid_response.removed).await?;
+
+    // Like alerts, matching transfers shouldn't fail a sync that saved its transactions
+    if let Err(e) = match_synced(auth_key, &changed).await {
+        log::error!("Failed to match transfers {e}");
+    }
+
     if plaid_response.has_more {
-        sync(auth_key, access_token, Some(plaid_response.next_cursor)).await?;
+        sync(
+            app,
+            auth_key,
+            access_token,
+            Some(plaid_response.next_cursor),
+        )
+        .await?;
+    } else {
+        snapshot_item(auth_key, access_token).await?;
+
+        // Alerts shouldn't fail a sync that already saved its transactions
+        if let Err(e) = check_budgets(&app, auth_key).await {
+            log::error!("Failed to check budgets {e}");
+        }
     }
 
     Ok(())
@@ -1,7 +1,10 @@
 use futures::future;
 use recurr_core::{get_supbase_client, Account, SchemaAccessToken, SchemaPlaidAccount};
 
-use crate::{plaid, supabase::access_token::get_access_token};
+use crate::{
+    plaid,
+    supabase::{access_token::get_access_token, balances::record_snapshots},
+};
 
 #[tauri::command]
 pub async fn get_plaid_balances(
@@ -47,6 +50,8 @@ pub async fn get_plaid_balances(
         all_accounts.extend(res?);
     }
 
+    record_snapshots(auth_key, user_id, &all_accounts).await?;
+
     Ok(all_accounts)
 }
 
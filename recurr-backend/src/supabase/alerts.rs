@@ -0,0 +1,147 @@
+use chrono::Local;
+use recurr_core::{
+    alerts::{self, SchemaBudgetAlert, DEFAULT_THRESHOLDS},
+    budgets::{allocate, rollup, Period},
+    categories::get_taxonomy,
+    currency::get_exchange_rates,
+    get_supbase_client, SchemaBudget,
+};
+use serde::Deserialize;
+use tauri::{api::notification::Notification, AppHandle};
+
+use crate::plaid::transactions::get_categories;
+
+use super::{transactions::get_spending, Error};
+
+/// The user's alert thresholds, and whether pending transactions count towards budgets
+async fn get_settings(auth_key: &str) -> Result<(Vec<u32>, bool), Error> {
+    #[derive(Deserialize)]
+    struct Row {
+        alert_thresholds: Vec<u32>,
+        exclude_pending: bool,
+    }
+
+    let rows: Vec<Row> = get_supbase_client()
+        .from("user_settings")
+        .auth(auth_key)
+        .select("alert_thresholds,exclude_pending")
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    Ok(rows
+        .into_iter()
+        .next()
+        .map_or((DEFAULT_THRESHOLDS.to_vec(), false), |r| {
+            (r.alert_thresholds, r.exclude_pending)
+        }))
+}
+
+/// Compares each budget's spending in its current period to the user's thresholds,
+/// storing and notifying about the ones crossed since the last check. When several
+/// are crossed at once only the highest is shown
+pub async fn check_budgets(app: &AppHandle, auth_key: &str) -> Result<(), Error> {
+    let client = get_supbase_client();
+
+    let budgets: Vec<SchemaBudget> = client
+        .from("budgets")
+        .auth(auth_key)
+        .select("*")
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    let Some(user_id) = budgets.first().map(|b| b.user_id.clone()) else {
+        return Ok(());
+    };
+
+    let (thresholds, exclude_pending) = get_settings(auth_key).await?;
+    if thresholds.is_empty() {
+        return Ok(());
+    }
+
+    let today = Local::now().date_naive();
+    let periods: Vec<(&SchemaBudget, Period)> =
+        budgets.iter().map(|b| (b, b.period(today, 0))).collect();
+    let window = Period {
+        start: periods.iter().map(|(_, p)| p.start).min().unwrap_or(today),
+        end: periods.iter().map(|(_, p)| p.end).max().unwrap_or(today),
+    };
+
+    let mut transactions = get_spending(auth_key, &window).await?;
+    if exclude_pending {
+        transactions.retain(|t| !t.pending);
+    }
+    let taxonomy = get_taxonomy(auth_key, &get_categories().await?).await?;
+    let rates = get_exchange_rates(auth_key, &user_id).await?;
+
+    let unconverted =
+        rates.unconverted(transactions.iter().map(|t| t.iso_currency_code.as_deref()));
+    if !unconverted.is_empty() {
+        log::warn!(
+            "No exchange rate for {}, leaving them out of budgets",
+            unconverted.join(", ")
+        );
+    }
+
+    let owners = allocate(&taxonomy, &periods, &transactions);
+    let spent = rollup(&taxonomy, &periods, &transactions, &owners, |t| {
+        rates
+            .to_base(t.amount, t.iso_currency_code.as_deref())
+            .unwrap_or_default()
+    });
+
+    let fired: Vec<SchemaBudgetAlert> = client
+        .from("budget_alerts")
+        .auth(auth_key)
+        .select("*")
+        .gte("period_start", window.start.format("%Y-%m-%d").to_string())
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    let due: Vec<Vec<SchemaBudgetAlert>> = periods
+        .iter()
+        .zip(&spent)
+        .map(|((budget, period), spent)| {
+            alerts::due(budget, period, spent.total, &thresholds, &fired)
+        })
+        .filter(|due| !due.is_empty())
+        .collect();
+
+    if due.is_empty() {
+        return Ok(());
+    }
+
+    let _ = client
+        .from("budget_alerts")
+        .auth(auth_key)
+        .insert(serde_json::to_string(&due.concat())?)
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    let identifier = &app.config().tauri.bundle.identifier;
+    for alert in due.iter().filter_map(|d| d.last()) {
+        Notification::new(identifier)
+            .title(alert.title(&taxonomy))
+            .body(alert.body(rates.base()))
+            .show()
+            .map_err(|e| Error::Other(e.to_string()))?;
+    }
+
+    Ok(())
+}
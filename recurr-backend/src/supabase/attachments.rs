@@ -0,0 +1,155 @@
+use std::path::Path;
+
+use recurr_core::{
+    annotations::{content_type, SchemaTransactionAttachment, ATTACHMENTS_BUCKET},
+    get_supbase_client,
+};
+use reqwest::RequestBuilder;
+use serde::{Deserialize, Serialize};
+
+use super::Error;
+
+/// Signed links stay valid for an hour
+const SIGNED_URL_EXPIRY: u32 = 3600;
+
+fn storage_request(auth_key: &str, request: RequestBuilder) -> RequestBuilder {
+    request
+        .header("apikey", env!("SUPABASE_KEY"))
+        .bearer_auth(auth_key)
+}
+
+fn object_url(path: &str) -> String {
+    format!(
+        "{}/storage/v1/object/{ATTACHMENTS_BUCKET}/{path}",
+        env!("SUPABASE_URL")
+    )
+}
+
+/// Uploads a receipt from disk to storage and records it against the transaction
+#[tauri::command]
+pub async fn upload_attachment(
+    auth_key: &str,
+    user_id: &str,
+    transaction_id: &str,
+    path: &str,
+) -> Result<SchemaTransactionAttachment, Error> {
+    let file_name = Path::new(path)
+        .file_name()
+        .map(|f| f.to_string_lossy().to_string())
+        .ok_or_else(|| Error::Other(format!("{path} is not a file")))?;
+    let content_type = content_type(&file_name)
+        .ok_or_else(|| Error::Other(format!("{file_name} is not an image or PDF")))?;
+    let bytes = std::fs::read(path).map_err(|e| Error::Other(e.to_string()))?;
+
+    let storage_path = format!(
+        "{user_id}/{transaction_id}/{}-{file_name}",
+        chrono::Utc::now().timestamp_millis()
+    );
+
+    let client = reqwest::Client::new();
+    let _ = storage_request(auth_key, client.post(object_url(&storage_path)))
+        .header("Content-Type", content_type)
+        .body(bytes)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    let attachment = SchemaTransactionAttachment {
+        id: None,
+        user_id: user_id.to_string(),
+        transaction_id: transaction_id.to_string(),
+        file_name,
+        content_type: content_type.to_string(),
+        storage_path,
+    };
+
+    let res = get_supbase_client()
+        .from("transaction_attachments")
+        .auth(auth_key)
+        .insert(serde_json::to_string(&attachment)?)
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    let mut inserted: Vec<SchemaTransactionAttachment> = res
+        .json()
+        .await
+        .map_err(|e| Error::Request(e.to_string()))?;
+    inserted
+        .pop()
+        .ok_or_else(|| Error::Other("Attachment wasn't saved".to_string()))
+}
+
+/// A temporary link to view a private receipt
+#[tauri::command]
+pub async fn attachment_url(auth_key: &str, storage_path: &str) -> Result<String, Error> {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Request {
+        expires_in: u32,
+    }
+
+    #[derive(Deserialize)]
+    struct Response {
+        #[serde(rename = "signedURL")]
+        signed_url: String,
+    }
+
+    let url = format!(
+        "{}/storage/v1/object/sign/{ATTACHMENTS_BUCKET}/{storage_path}",
+        env!("SUPABASE_URL")
+    );
+
+    let client = reqwest::Client::new();
+    let res: Response = storage_request(auth_key, client.post(url))
+        .json(&Request {
+            expires_in: SIGNED_URL_EXPIRY,
+        })
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    Ok(format!(
+        "{}/storage/v1{}",
+        env!("SUPABASE_URL"),
+        res.signed_url
+    ))
+}
+
+#[tauri::command]
+pub async fn delete_attachment(
+    auth_key: &str,
+    attachment: SchemaTransactionAttachment,
+) -> Result<(), Error> {
+    let client = reqwest::Client::new();
+    let _ = storage_request(
+        auth_key,
+        client.delete(object_url(&attachment.storage_path)),
+    )
+    .send()
+    .await
+    .and_then(|r| r.error_for_status())
+    .map_err(|e| Error::Request(e.to_string()))?;
+
+    let id = attachment
+        .id
+        .ok_or_else(|| Error::Other("Attachment has no id".to_string()))?;
+
+    let _ = get_supbase_client()
+        .from("transaction_attachments")
+        .auth(auth_key)
+        .eq("id", id.to_string())
+        .delete()
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    Ok(())
+}
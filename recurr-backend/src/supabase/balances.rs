@@ -0,0 +1,77 @@
+use recurr_core::{balances::SchemaBalanceSnapshot, get_supbase_client, Account};
+use serde::Deserialize;
+
+use super::Error;
+use crate::plaid;
+
+/// Stores today's balance for each account, replacing any earlier snapshot from today
+pub async fn record_snapshots(
+    auth_key: &str,
+    user_id: &str,
+    accounts: &[Account],
+) -> Result<(), Error> {
+    if accounts.is_empty() {
+        return Ok(());
+    }
+
+    let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let snapshots: Vec<SchemaBalanceSnapshot> = accounts
+        .iter()
+        .map(|a| SchemaBalanceSnapshot::from_account(user_id, a, &date))
+        .collect();
+
+    let _ = get_supbase_client()
+        .from("balance_snapshots")
+        .auth(auth_key)
+        .upsert(serde_json::to_string(&snapshots)?)
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    Ok(())
+}
+
+/// Fetches and snapshots the balances of the accounts linked with `access_token`
+pub async fn snapshot_item(auth_key: &str, access_token: &str) -> Result<(), Error> {
+    #[derive(Deserialize)]
+    struct PlaidAccount {
+        account_id: String,
+    }
+
+    #[derive(Deserialize)]
+    struct Row {
+        user_id: String,
+        plaid_accounts: Vec<PlaidAccount>,
+    }
+
+    let rows: Vec<Row> = get_supbase_client()
+        .from("access_tokens")
+        .auth(auth_key)
+        .select("user_id,plaid_accounts(account_id)")
+        .eq("access_token", access_token)
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    for row in rows {
+        if row.plaid_accounts.is_empty() {
+            continue;
+        }
+
+        let account_ids = row
+            .plaid_accounts
+            .into_iter()
+            .map(|a| a.account_id)
+            .collect();
+        let accounts =
+            plaid::accounts::get_balances(auth_key, access_token.to_string(), account_ids).await?;
+        record_snapshots(auth_key, &row.user_id, &accounts).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use recurr_core::{debt::SchemaDebtTerms, get_supbase_client, SchemaAccessToken};
+
+use super::Error;
+use crate::plaid;
+
+/// Saves the APR and minimum payment Plaid has for each linked credit and loan
+/// account. Items without the liabilities product are skipped
+#[tauri::command]
+pub async fn import_liabilities(
+    auth_key: &str,
+    user_id: &str,
+) -> Result<Vec<SchemaDebtTerms>, Error> {
+    let client = get_supbase_client();
+    let access_tokens: Vec<SchemaAccessToken> = client
+        .from("access_tokens")
+        .auth(auth_key)
+        .select("*,plaid_accounts(*)")
+        .eq("user_id", user_id)
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    let mut terms = Vec::new();
+    for access_token in access_tokens {
+        let account_ids = access_token
+            .plaid_accounts
+            .unwrap_or_default()
+            .into_iter()
+            .map(|a| a.account_id)
+            .collect();
+
+        match plaid::liabilities::get_liabilities(
+            auth_key,
+            user_id,
+            access_token.access_token,
+            account_ids,
+        )
+        .await
+        {
+            Ok(t) => terms.extend(t),
+            Err(e) => log::warn!("Skipping liabilities for item {}: {e}", access_token.id),
+        }
+    }
+
+    if !terms.is_empty() {
+        client
+            .from("debt_terms")
+            .auth(auth_key)
+            .upsert(serde_json::to_string(&terms)?)
+            .execute()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| Error::Request(e.to_string()))?;
+    }
+
+    Ok(terms)
+}
@@ -0,0 +1,35 @@
+use recurr_core::{
+    currency::{self, SchemaExchangeRate},
+    get_supbase_client,
+};
+
+use super::Error;
+
+/// Imports an ECB reference rates file and saves the rates quoted against the base currency
+#[tauri::command]
+pub async fn import_exchange_rates(
+    auth_key: &str,
+    user_id: &str,
+    base_currency: &str,
+    path: &str,
+) -> Result<Vec<SchemaExchangeRate>, Error> {
+    let xml = std::fs::read_to_string(path).map_err(|e| Error::Other(e.to_string()))?;
+
+    let rates = currency::parse_ecb_xml(&xml)?
+        .rebase(base_currency)
+        .ok_or_else(|| Error::Other(format!("No exchange rate found for {base_currency}")))?;
+
+    let rows = rates.to_schema(user_id);
+
+    let client = get_supbase_client();
+    let _ = client
+        .from("exchange_rates")
+        .auth(auth_key)
+        .upsert(serde_json::to_string(&rows)?)
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    Ok(rows)
+}
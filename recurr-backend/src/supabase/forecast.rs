@@ -0,0 +1,115 @@
+use chrono::{Duration, Local};
+use recurr_core::{
+    budgets::{allocate, rollup, Period},
+    categories::get_taxonomy,
+    currency::get_exchange_rates,
+    forecast::{self, Forecast, SchemaScheduledItem, HISTORY_DAYS, HORIZONS},
+    get_supbase_client,
+    manual::SchemaManualAccount,
+    SchemaBudget,
+};
+
+use crate::plaid::transactions::get_categories;
+
+use super::{
+    accounts::get_plaid_balances,
+    transactions::{get_spending, get_transactions},
+    Error,
+};
+
+/// Projects cash account balances `days` ahead from current balances, recurring
+/// payments detected in the history, scheduled items and budget burn rates
+#[tauri::command]
+pub async fn forecast_cash_flow(
+    auth_key: &str,
+    user_id: &str,
+    days: usize,
+) -> Result<Forecast, Error> {
+    let client = get_supbase_client();
+    let today = Local::now().date_naive();
+    let days = days.clamp(1, HORIZONS[HORIZONS.len() - 1]);
+
+    let mut accounts = get_plaid_balances(auth_key, user_id).await?;
+    let manual: Vec<SchemaManualAccount> = client
+        .from("manual_accounts")
+        .auth(auth_key)
+        .select("*")
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| Error::Request(e.to_string()))?;
+    accounts.extend(manual.iter().map(|a| a.to_account()));
+
+    let scheduled: Vec<SchemaScheduledItem> = client
+        .from("scheduled_items")
+        .auth(auth_key)
+        .select("*")
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    let budgets: Vec<SchemaBudget> = client
+        .from("budgets")
+        .auth(auth_key)
+        .select("*")
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    let rates = get_exchange_rates(auth_key, user_id).await?;
+    let taxonomy = get_taxonomy(auth_key, &get_categories().await?).await?;
+
+    let history = get_transactions(
+        auth_key,
+        &Period {
+            start: today - Duration::days(HISTORY_DAYS),
+            end: today + Duration::days(1),
+        },
+    )
+    .await?;
+    let streams = forecast::detect_recurring(&history, &rates, today);
+
+    let periods: Vec<(&SchemaBudget, Period)> =
+        budgets.iter().map(|b| (b, b.period(today, 0))).collect();
+    let spent = match (
+        periods.iter().map(|(_, p)| p.start).min(),
+        periods.iter().map(|(_, p)| p.end).max(),
+    ) {
+        (Some(start), Some(end)) => {
+            let transactions = get_spending(auth_key, &Period { start, end }).await?;
+            let owners = allocate(&taxonomy, &periods, &transactions);
+            rollup(&taxonomy, &periods, &transactions, &owners, |t| {
+                rates
+                    .to_base(t.amount, t.iso_currency_code.as_deref())
+                    .unwrap_or_default()
+            })
+        }
+        _ => Vec::new(),
+    };
+    let budgets: Vec<(&SchemaBudget, f64)> = periods
+        .iter()
+        .zip(&spent)
+        .map(|((budget, _), spent)| (*budget, spent.total))
+        .collect();
+
+    let window = Period {
+        start: today,
+        end: today + Duration::days(days as i64),
+    };
+    let burn = forecast::budget_burn(&taxonomy, &budgets, &streams, &window);
+
+    Ok(forecast::forecast(
+        &accounts, &streams, &scheduled, &burn, &rates, today, days,
+    ))
+}
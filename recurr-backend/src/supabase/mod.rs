@@ -2,3 +2,15 @@ use recurr_core::Error;
 
 pub mod access_token;
 pub mod accounts;
+pub mod alerts;
+pub mod attachments;
+pub mod balances;
+pub mod debts;
+pub mod exchange_rates;
+pub mod forecast;
+pub mod pending;
+pub mod rules;
+pub mod splits;
+pub mod suggestions;
+pub mod transactions;
+pub mod transfers;
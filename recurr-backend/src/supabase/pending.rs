@@ -0,0 +1,60 @@
+use recurr_core::{
+    annotations::SchemaTransactionAnnotation, get_supbase_client, pending, Transaction,
+};
+
+use super::{splits::rebalance_splits, Error};
+
+/// Moves what the user added to pending transactions, notes, tags, category
+/// overrides, splits, receipts and transfer links, onto the posted transactions
+/// Plaid replaces them with. Runs after rules so overrides set by hand win
+pub async fn carry_over(auth_key: &str, changed: &[Transaction]) -> Result<(), Error> {
+    let successors = pending::successors(changed);
+    if successors.is_empty() {
+        return Ok(());
+    }
+
+    let client = get_supbase_client();
+    let ids: Vec<&str> = successors
+        .iter()
+        .flat_map(|(pending, posted)| [*pending, *posted])
+        .collect();
+
+    let annotations: Vec<SchemaTransactionAnnotation> = client
+        .from("transaction_annotations")
+        .auth(auth_key)
+        .select("*")
+        .in_("transaction_id", &ids)
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    // Every write happens in `carry_over_pending`, so a failure leaves the
+    // pending transactions as they were for the next sync to retry
+    let carried = pending::carry_annotations(&successors, &annotations);
+    let _ = client
+        .rpc(
+            "carry_over_pending",
+            serde_json::json!({
+                "successors": successors,
+                "annotations": carried,
+            })
+            .to_string(),
+        )
+        .auth(auth_key)
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    // The posted amount often differs from the pending one, like a tip added
+    let posted: Vec<Transaction> = changed
+        .iter()
+        .filter(|t| successors.values().any(|id| *id == t.transaction_id))
+        .cloned()
+        .collect();
+    rebalance_splits(auth_key, &posted).await
+}
@@ -0,0 +1,137 @@
+use std::collections::HashSet;
+
+use recurr_core::{
+    get_all, get_supbase_client,
+    rules::{Rule, RuleSet, SchemaRule, SchemaTransactionOverride},
+    Transaction,
+};
+use serde::Deserialize;
+
+use super::{transactions::get_all_transactions, Error};
+
+/// Keeps `in` filters on transaction ids within URL length limits
+const OVERRIDES_CHUNK: usize = 100;
+
+/// Deletes overrides made by rules, for the transactions in `ids` or all of them
+async fn delete_rule_overrides(auth_key: &str, ids: Option<&[&str]>) -> Result<(), Error> {
+    let mut delete = get_supbase_client()
+        .from("transaction_overrides")
+        .auth(auth_key)
+        .not("is", "rule_id", "null");
+    if let Some(ids) = ids {
+        delete = delete.in_("transaction_id", ids);
+    }
+
+    let _ = delete
+        .delete()
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    Ok(())
+}
+
+async fn get_rules(auth_key: &str) -> Result<Vec<SchemaRule>, Error> {
+    let client = get_supbase_client();
+    let res = client
+        .from("transaction_rules")
+        .auth(auth_key)
+        .select("*")
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    res.json()
+        .await
+        .map_err(|e| Error::Request(e.to_string()))
+}
+
+/// Runs the user's rules over `transactions` and stores the resulting overrides.
+/// Overrides set by hand are never replaced, when `retroactive` every rule
+/// override is rebuilt, otherwise only the ones for `transactions`
+pub async fn run_rules(
+    auth_key: &str,
+    transactions: &[Transaction],
+    retroactive: bool,
+) -> Result<usize, Error> {
+    let (rules, invalid) = RuleSet::new(get_rules(auth_key).await?);
+    for (rule, e) in invalid {
+        log::warn!("Skipping rule {}: {e}", rule.name);
+    }
+    if transactions.is_empty() || (rules.is_empty() && !retroactive) {
+        return Ok(0);
+    }
+
+    let client = get_supbase_client();
+
+    #[derive(Deserialize)]
+    struct Row {
+        transaction_id: String,
+    }
+
+    // Paged, a manual override missing from the set would be overwritten below
+    let manual: HashSet<String> = get_all::<Row, _>(|| {
+        client
+            .from("transaction_overrides")
+            .auth(auth_key)
+            .select("transaction_id")
+            .is("rule_id", "null")
+            .order("transaction_id")
+    })
+    .await?
+    .into_iter()
+    .map(|r| r.transaction_id)
+    .collect();
+
+    if retroactive {
+        delete_rule_overrides(auth_key, None).await?;
+    } else {
+        let ids: Vec<&str> = transactions
+            .iter()
+            .map(|t| t.transaction_id.as_str())
+            .collect();
+        for chunk in ids.chunks(OVERRIDES_CHUNK) {
+            delete_rule_overrides(auth_key, Some(chunk)).await?;
+        }
+    }
+
+    let overrides: Vec<SchemaTransactionOverride> = transactions
+        .iter()
+        .filter(|t| !manual.contains(&t.transaction_id))
+        .filter_map(|t| rules.apply(t))
+        .collect();
+
+    if overrides.is_empty() {
+        return Ok(0);
+    }
+
+    let _ = client
+        .from("transaction_overrides")
+        .auth(auth_key)
+        .upsert(serde_json::to_string(&overrides)?)
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    Ok(overrides.len())
+}
+
+#[tauri::command]
+pub async fn preview_rule(auth_key: &str, rule: SchemaRule) -> Result<Vec<Transaction>, Error> {
+    let rule = Rule::new(rule)?;
+
+    let transactions = get_all_transactions(auth_key, "transactions").await?;
+    Ok(transactions
+        .into_iter()
+        .filter(|t| rule.matches(t))
+        .collect())
+}
+
+#[tauri::command]
+pub async fn apply_rules(auth_key: &str) -> Result<usize, Error> {
+    let transactions = get_all_transactions(auth_key, "transactions").await?;
+    run_rules(auth_key, &transactions, true).await
+}
@@ -0,0 +1,127 @@
+use recurr_core::{
+    get_supbase_client,
+    splits::{self, SchemaTransactionSplit},
+    Transaction,
+};
+
+use super::Error;
+
+pub(crate) async fn get_splits(
+    auth_key: &str,
+    transaction_ids: &[&str],
+) -> Result<Vec<SchemaTransactionSplit>, Error> {
+    let res = get_supbase_client()
+        .from("transaction_splits")
+        .auth(auth_key)
+        .select("*")
+        .in_("transaction_id", transaction_ids)
+        .order("id")
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    res.json().await.map_err(|e| Error::Request(e.to_string()))
+}
+
+/// Keeps splits summing to their parent after Plaid modifies its amount
+pub async fn rebalance_splits(auth_key: &str, transactions: &[Transaction]) -> Result<(), Error> {
+    if transactions.is_empty() {
+        return Ok(());
+    }
+
+    let ids: Vec<&str> = transactions
+        .iter()
+        .map(|t| t.transaction_id.as_str())
+        .collect();
+    let mut grouped = splits::group(get_splits(auth_key, &ids).await?);
+
+    let mut changed = Vec::new();
+    for t in transactions {
+        if let Some(splits) = grouped.get_mut(&t.transaction_id) {
+            if splits::rebalance(t.amount, splits) {
+                changed.extend(splits.drain(..));
+            }
+        }
+    }
+
+    if changed.is_empty() {
+        return Ok(());
+    }
+
+    let _ = get_supbase_client()
+        .from("transaction_splits")
+        .auth(auth_key)
+        .upsert(serde_json::to_string(&changed)?)
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    Ok(())
+}
+
+pub async fn delete_splits(auth_key: &str, transaction_ids: &[String]) -> Result<(), Error> {
+    if transaction_ids.is_empty() {
+        return Ok(());
+    }
+
+    let _ = get_supbase_client()
+        .from("transaction_splits")
+        .auth(auth_key)
+        .in_("transaction_id", transaction_ids)
+        .delete()
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    Ok(())
+}
+
+/// Replaces a transaction's splits, an empty list removes them
+#[tauri::command]
+pub async fn save_splits(
+    auth_key: &str,
+    transaction_id: String,
+    splits: Vec<SchemaTransactionSplit>,
+) -> Result<Vec<SchemaTransactionSplit>, Error> {
+    let client = get_supbase_client();
+
+    let parent: Vec<Transaction> = client
+        .from("transactions")
+        .auth(auth_key)
+        .select("*")
+        .eq("transaction_id", &transaction_id)
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| Error::Request(e.to_string()))?;
+    let parent = parent
+        .first()
+        .ok_or_else(|| Error::Other(format!("No transaction {transaction_id}")))?;
+
+    if !splits.is_empty() {
+        splits::validate(parent.amount, &splits)?;
+    }
+
+    let res = client
+        .rpc(
+            "replace_splits",
+            serde_json::json!({
+                "split_transaction_id": transaction_id,
+                "new_splits": splits,
+            })
+            .to_string(),
+        )
+        .auth(auth_key)
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    res.json().await.map_err(|e| Error::Request(e.to_string()))
+}
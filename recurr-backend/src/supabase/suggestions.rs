@@ -0,0 +1,21 @@
+use chrono::Local;
+use recurr_core::{
+    currency::get_exchange_rates,
+    suggestions::{self, BudgetSuggestion},
+};
+
+use super::{transactions::get_spending, Error};
+
+/// Proposes monthly budgets from the spending in the last `months` whole months
+#[tauri::command]
+pub async fn suggest_budgets(
+    auth_key: &str,
+    user_id: &str,
+    months: u32,
+) -> Result<Vec<BudgetSuggestion>, Error> {
+    let window = suggestions::window(Local::now().date_naive(), months.clamp(1, 24));
+    let rates = get_exchange_rates(auth_key, user_id).await?;
+    let transactions = get_spending(auth_key, &window).await?;
+
+    Ok(suggestions::suggest(&transactions, &rates, &window))
+}
@@ -0,0 +1,66 @@
+use recurr_core::{budgets::Period, get_all, get_supbase_client, splits, Transaction};
+
+use super::{splits::get_splits, Error};
+
+/// Keeps `in` filters on transaction ids within URL length limits
+const SPLITS_CHUNK: usize = 100;
+
+/// Reads every row from `table`, paging past PostgREST's max rows limit
+pub async fn get_all_transactions(auth_key: &str, table: &str) -> Result<Vec<Transaction>, Error> {
+    let client = get_supbase_client();
+
+    get_all(|| {
+        client
+            .from(table)
+            .auth(auth_key)
+            .select("*")
+            .order("date.desc,transaction_id")
+    })
+    .await
+}
+
+/// Every transaction in `window`, transfers included, as they left or reached each account
+pub async fn get_transactions(auth_key: &str, window: &Period) -> Result<Vec<Transaction>, Error> {
+    get_window(auth_key, window, true).await
+}
+
+/// Transactions in `window` that aren't transfers, with splits in place of their parents
+pub async fn get_spending(auth_key: &str, window: &Period) -> Result<Vec<Transaction>, Error> {
+    let transactions = get_window(auth_key, window, false).await?;
+
+    let ids: Vec<&str> = transactions
+        .iter()
+        .map(|t| t.transaction_id.as_str())
+        .collect();
+    let mut rows = Vec::new();
+    for chunk in ids.chunks(SPLITS_CHUNK) {
+        rows.extend(get_splits(auth_key, chunk).await?);
+    }
+
+    Ok(splits::expand(transactions, &splits::group(rows)))
+}
+
+async fn get_window(
+    auth_key: &str,
+    window: &Period,
+    transfers: bool,
+) -> Result<Vec<Transaction>, Error> {
+    let client = get_supbase_client();
+    let start = window.start.format("%Y-%m-%d").to_string();
+    let end = window.end.format("%Y-%m-%d").to_string();
+
+    get_all(|| {
+        let query = client.from("user_transactions").auth(auth_key).select("*");
+        let query = if transfers {
+            query
+        } else {
+            query.eq("is_transfer", "false")
+        };
+
+        query
+            .gte("date", &start)
+            .lt("date", &end)
+            .order("date.desc,transaction_id")
+    })
+    .await
+}
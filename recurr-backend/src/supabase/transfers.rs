@@ -0,0 +1,117 @@
+use std::collections::HashSet;
+
+use chrono::{Duration, NaiveDate};
+use recurr_core::{
+    budgets::Period,
+    get_all, get_supbase_client,
+    transfers::{self, SchemaTransferLink},
+    Transaction,
+};
+use serde::Deserialize;
+
+use super::{
+    transactions::{get_all_transactions, get_transactions},
+    Error,
+};
+
+async fn get_account_ids(auth_key: &str) -> Result<HashSet<String>, Error> {
+    #[derive(Deserialize)]
+    struct Row {
+        account_id: String,
+    }
+
+    let rows: Vec<Row> = get_supbase_client()
+        .from("plaid_accounts")
+        .auth(auth_key)
+        .select("account_id")
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    Ok(rows.into_iter().map(|r| r.account_id).collect())
+}
+
+async fn get_links(auth_key: &str) -> Result<Vec<SchemaTransferLink>, Error> {
+    let client = get_supbase_client();
+
+    get_all(|| {
+        client
+            .from("transfer_links")
+            .auth(auth_key)
+            .select("*")
+            .order("id")
+    })
+    .await
+}
+
+/// Links transfers among `transactions` and stores them, returns how many were found
+async fn link_transfers(auth_key: &str, transactions: &[Transaction]) -> Result<usize, Error> {
+    let account_ids = get_account_ids(auth_key).await?;
+    let accounts: HashSet<&str> = account_ids.iter().map(String::as_str).collect();
+    let links = get_links(auth_key).await?;
+
+    let matched = transfers::match_transfers(transactions, &accounts, &links);
+    if matched.is_empty() {
+        return Ok(0);
+    }
+
+    let _ = get_supbase_client()
+        .from("transfer_links")
+        .auth(auth_key)
+        .insert(serde_json::to_string(&matched)?)
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    Ok(matched.len())
+}
+
+/// Looks for transfers around the dates of transactions a sync added or modified
+pub async fn match_synced(auth_key: &str, changed: &[Transaction]) -> Result<usize, Error> {
+    let dates: Vec<NaiveDate> = changed
+        .iter()
+        .filter_map(|t| NaiveDate::parse_from_str(&t.date, "%Y-%m-%d").ok())
+        .collect();
+    let (Some(first), Some(last)) = (dates.iter().min(), dates.iter().max()) else {
+        return Ok(0);
+    };
+
+    let window = Period {
+        start: *first - Duration::days(transfers::MATCH_DAYS),
+        end: *last + Duration::days(transfers::MATCH_DAYS + 1),
+    };
+    let transactions = get_transactions(auth_key, &window).await?;
+
+    link_transfers(auth_key, &transactions).await
+}
+
+/// Drops links to transactions Plaid removed
+pub async fn delete_links(auth_key: &str, transaction_ids: &[String]) -> Result<(), Error> {
+    if transaction_ids.is_empty() {
+        return Ok(());
+    }
+
+    let ids = transaction_ids.join(",");
+    let _ = get_supbase_client()
+        .from("transfer_links")
+        .auth(auth_key)
+        .or(format!("outflow_id.in.({ids}),inflow_id.in.({ids})"))
+        .delete()
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn detect_transfers(auth_key: &str) -> Result<usize, Error> {
+    let transactions = get_all_transactions(auth_key, "user_transactions").await?;
+    link_transfers(auth_key, &transactions).await
+}
@@ -0,0 +1,193 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::{budgets::Period, categories::Taxonomy, currency::format_amount, SchemaBudget};
+
+/// Percentages of a budget's max that alert unless the user picks their own
+pub const DEFAULT_THRESHOLDS: [u32; 2] = [80, 100];
+
+/// A budget crossing one of the user's thresholds, stored once per period
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SchemaBudgetAlert {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    pub user_id: String,
+    pub category_id: String,
+    pub period_start: String,
+    pub period_end: String,
+    pub threshold: u32,
+    pub spent: f64,
+    pub max: f64,
+    #[serde(default)]
+    pub read: bool,
+    #[serde(skip_serializing)]
+    pub created_at: Option<String>,
+}
+
+impl SchemaBudgetAlert {
+    pub fn title(&self, taxonomy: &Taxonomy) -> String {
+        let category = taxonomy.label(&self.category_id);
+        match self.threshold {
+            t if t < 100 => format!("{category} is at {t}% of its budget"),
+            100 => format!("{category} has used its whole budget"),
+            t => format!("{category} is {}% over budget", t - 100),
+        }
+    }
+
+    pub fn body(&self, currency: &str) -> String {
+        let spent = format!(
+            "{} of {} spent",
+            format_amount(self.spent, currency),
+            format_amount(self.max, currency)
+        );
+
+        match self.period() {
+            Some(period) => format!("{spent}, {}", period.label()),
+            None => spent,
+        }
+    }
+
+    pub fn period(&self) -> Option<Period> {
+        let date = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok();
+        Some(Period {
+            start: date(&self.period_start)?,
+            end: date(&self.period_end)?,
+        })
+    }
+}
+
+/// Alerts for the thresholds `spent` has reached in the budget's `period` that
+/// aren't among the ones already `fired`, lowest threshold first
+pub fn due(
+    budget: &SchemaBudget,
+    period: &Period,
+    spent: f64,
+    thresholds: &[u32],
+    fired: &[SchemaBudgetAlert],
+) -> Vec<SchemaBudgetAlert> {
+    if budget.max <= 0.0 {
+        return Vec::new();
+    }
+
+    let period_start = period.start.format("%Y-%m-%d").to_string();
+    let mut thresholds: Vec<u32> = thresholds
+        .iter()
+        .copied()
+        .filter(|t| spent >= budget.max * *t as f64 / 100.0)
+        .filter(|t| {
+            !fired.iter().any(|a| {
+                a.category_id == budget.category_id
+                    && a.period_start == period_start
+                    && a.threshold == *t
+            })
+        })
+        .collect();
+    thresholds.sort_unstable();
+    thresholds.dedup();
+
+    thresholds
+        .into_iter()
+        .map(|threshold| SchemaBudgetAlert {
+            id: None,
+            user_id: budget.user_id.clone(),
+            category_id: budget.category_id.clone(),
+            period_start: period_start.clone(),
+            period_end: period.end.format("%Y-%m-%d").to_string(),
+            threshold,
+            spent: (spent * 100.0).round() / 100.0,
+            max: budget.max,
+            read: false,
+            created_at: None,
+        })
+        .collect()
+}
+
+/// Reads a list like "80, 100" into thresholds, ignoring anything that isn't a
+/// positive percentage
+pub fn parse_thresholds(thresholds: &str) -> Vec<u32> {
+    let mut thresholds: Vec<u32> = thresholds
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter_map(|t| t.trim_end_matches('%').parse().ok())
+        .filter(|t| *t > 0)
+        .collect();
+    thresholds.sort_unstable();
+    thresholds.dedup();
+
+    thresholds
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::budgets::{BudgetKind, BudgetPeriod};
+
+    use super::*;
+
+    fn budget() -> SchemaBudget {
+        SchemaBudget {
+            user_id: "user".to_string(),
+            category_id: "FOOD_AND_DRINK".to_string(),
+            max: 200.0,
+            period: BudgetPeriod::Monthly,
+            start_day: 1,
+            anchor_date: None,
+            rollover: false,
+            kind: BudgetKind::Spending,
+        }
+    }
+
+    fn period() -> Period {
+        Period {
+            start: NaiveDate::from_ymd_opt(2026, 10, 1).unwrap(),
+            end: NaiveDate::from_ymd_opt(2026, 11, 1).unwrap(),
+        }
+    }
+
+    fn thresholds(alerts: &[SchemaBudgetAlert]) -> Vec<u32> {
+        alerts.iter().map(|a| a.threshold).collect()
+    }
+
+    #[test]
+    fn thresholds_fire_once_per_period() {
+        let budget = budget();
+        let fired = due(&budget, &period(), 170.0, &DEFAULT_THRESHOLDS, &[]);
+        assert_eq!(thresholds(&fired), vec![80]);
+        assert_eq!(fired[0].period_start, "2026-10-01");
+        assert_eq!(fired[0].period_end, "2026-11-01");
+
+        // Already fired this period
+        assert!(due(&budget, &period(), 180.0, &DEFAULT_THRESHOLDS, &fired).is_empty());
+
+        // Fired last period
+        let mut last_period = fired.clone();
+        last_period[0].period_start = "2026-09-01".to_string();
+        let fired = due(&budget, &period(), 180.0, &DEFAULT_THRESHOLDS, &last_period);
+        assert_eq!(thresholds(&fired), vec![80]);
+    }
+
+    #[test]
+    fn highest_crossed_threshold_comes_last() {
+        let budget = budget();
+        let fired = due(&budget, &period(), 260.0, &[120, 80, 100, 150], &[]);
+        assert_eq!(thresholds(&fired), vec![80, 100, 120]);
+        assert_eq!(fired.last().map(|a| a.threshold), Some(120));
+
+        // Only the ones not fired yet are left
+        let later = due(&budget, &period(), 320.0, &[120, 80, 100, 150], &fired);
+        assert_eq!(thresholds(&later), vec![150]);
+    }
+
+    #[test]
+    fn budgets_without_a_max_never_alert() {
+        let mut budget = budget();
+        budget.max = 0.0;
+        assert!(due(&budget, &period(), 50.0, &DEFAULT_THRESHOLDS, &[]).is_empty());
+    }
+
+    #[test]
+    fn malformed_thresholds_are_ignored() {
+        assert_eq!(parse_thresholds("80, 100"), vec![80, 100]);
+        assert_eq!(parse_thresholds("100% 80%\t120"), vec![80, 100, 120]);
+        assert_eq!(parse_thresholds("90,,abc, -5, 0, 12.5, 90"), vec![90]);
+        assert!(parse_thresholds("").is_empty());
+    }
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+/// Storage bucket receipts are uploaded to, objects live under the user's id
+pub const ATTACHMENTS_BUCKET: &str = "receipts";
+
+/// Notes and tags a user added to a transaction, kept apart from "transactions"
+/// so sync's upserts don't clobber them
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SchemaTransactionAnnotation {
+    pub transaction_id: String,
+    pub user_id: String,
+    pub notes: Option<String>,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SchemaTransactionAttachment {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    pub user_id: String,
+    pub transaction_id: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub storage_path: String,
+}
+
+impl SchemaTransactionAttachment {
+    pub fn is_image(&self) -> bool {
+        self.content_type.starts_with("image/")
+    }
+}
+
+/// Splits free-form input like "work, travel #reimburse" into unique tags
+pub fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in input.split(|c: char| c == ',' || c.is_whitespace()) {
+        let tag = tag.trim().trim_start_matches('#').to_lowercase();
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+
+    tags
+}
+
+/// Content types accepted for receipts, by file extension
+pub fn content_type(file_name: &str) -> Option<&'static str> {
+    let extension = file_name.rsplit_once('.')?.1.to_lowercase();
+    match extension.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        "heic" => Some("image/heic"),
+        "pdf" => Some("application/pdf"),
+        _ => None,
+    }
+}
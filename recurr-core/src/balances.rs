@@ -0,0 +1,234 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{currency::ExchangeRates, Account};
+
+/// How an account counts towards net worth, by Plaid account type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AccountClass {
+    Cash,
+    Credit,
+    Investments,
+    Loans,
+    Other,
+}
+
+impl AccountClass {
+    /// Plaid's "other" and manual accounts for property like a home or car are
+    /// both assets
+    pub fn of(account_type: &str) -> Option<Self> {
+        match account_type {
+            "depository" => Some(Self::Cash),
+            "credit" => Some(Self::Credit),
+            "investment" | "brokerage" => Some(Self::Investments),
+            "loan" => Some(Self::Loans),
+            "other" => Some(Self::Other),
+            _ => None,
+        }
+    }
+
+    /// Balances of liabilities are what's owed, so they're subtracted from net worth
+    pub fn is_liability(&self) -> bool {
+        matches!(self, Self::Credit | Self::Loans)
+    }
+}
+
+/// An account's balance as it was on a given day, one row per account per day
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SchemaBalanceSnapshot {
+    pub user_id: String,
+    pub account_id: String,
+    pub date: String,
+    pub account_type: String,
+    pub current: f64,
+    pub iso_currency_code: Option<String>,
+}
+
+impl SchemaBalanceSnapshot {
+    pub fn from_account(user_id: &str, account: &Account, date: &str) -> Self {
+        Self {
+            user_id: user_id.to_string(),
+            account_id: account.account_id.clone(),
+            date: date.to_string(),
+            account_type: account.account_type.clone(),
+            current: account.balances.current.unwrap_or_default(),
+            iso_currency_code: account.balances.iso_currency_code.clone(),
+        }
+    }
+}
+
+/// Totals per account class on a given day, in the base currency
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct NetWorthPoint {
+    pub date: String,
+    pub cash: f64,
+    pub credit: f64,
+    pub investments: f64,
+    pub loans: f64,
+    pub other: f64,
+}
+
+impl NetWorthPoint {
+    pub fn net_worth(&self) -> f64 {
+        self.cash + self.investments + self.other - self.credit - self.loans
+    }
+
+    fn add(&mut self, class: AccountClass, amount: f64) {
+        match class {
+            AccountClass::Cash => self.cash += amount,
+            AccountClass::Credit => self.credit += amount,
+            AccountClass::Investments => self.investments += amount,
+            AccountClass::Loans => self.loans += amount,
+            AccountClass::Other => self.other += amount,
+        }
+    }
+}
+
+/// One point per day with a snapshot, accounts without a snapshot that day
+/// carry their last known balance forward. Accounts missing from `open` were
+/// unlinked or deleted, so they stop counting after their last snapshot.
+/// Dates are ISO formatted so they compare as strings
+pub fn net_worth_series(
+    snapshots: &[SchemaBalanceSnapshot],
+    rates: &ExchangeRates,
+    since: Option<&str>,
+    open: &HashSet<&str>,
+) -> Vec<NetWorthPoint> {
+    let mut snapshots: Vec<&SchemaBalanceSnapshot> = snapshots.iter().collect();
+    snapshots.sort_by(|a, b| a.date.cmp(&b.date));
+
+    let mut removed: HashMap<&str, &str> = HashMap::new();
+    for s in &snapshots {
+        if !open.contains(s.account_id.as_str()) {
+            removed.insert(s.account_id.as_str(), s.date.as_str());
+        }
+    }
+
+    let mut latest: HashMap<&str, (AccountClass, f64)> = HashMap::new();
+    let mut series: Vec<NetWorthPoint> = Vec::new();
+
+    let mut i = 0;
+    while i < snapshots.len() {
+        let date = snapshots[i].date.as_str();
+        while i < snapshots.len() && snapshots[i].date == date {
+            let s = snapshots[i];
+            let class = AccountClass::of(&s.account_type);
+            let amount = rates.to_base(s.current, s.iso_currency_code.as_deref());
+            if let (Some(class), Some(amount)) = (class, amount) {
+                latest.insert(s.account_id.as_str(), (class, amount));
+            }
+            i += 1;
+        }
+        latest.retain(|account_id, _| removed.get(account_id).is_none_or(|last| date <= *last));
+
+        if since.is_some_and(|since| date < since) {
+            continue;
+        }
+
+        let mut point = NetWorthPoint {
+            date: date.to_string(),
+            ..Default::default()
+        };
+        for (class, amount) in latest.values() {
+            point.add(*class, *amount);
+        }
+        series.push(point);
+    }
+
+    series
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn property_counts_towards_net_worth() {
+        let class = AccountClass::of("other").unwrap();
+        assert_eq!(class, AccountClass::Other);
+        assert!(!class.is_liability());
+
+        let mut point = NetWorthPoint::default();
+        point.add(AccountClass::Cash, 1000.0);
+        point.add(class, 250000.0);
+        point.add(AccountClass::Loans, 200000.0);
+        assert_eq!(point.net_worth(), 51000.0);
+    }
+
+    fn snapshot(
+        account_id: &str,
+        date: &str,
+        account_type: &str,
+        current: f64,
+    ) -> SchemaBalanceSnapshot {
+        SchemaBalanceSnapshot {
+            user_id: "user".to_string(),
+            account_id: account_id.to_string(),
+            date: date.to_string(),
+            account_type: account_type.to_string(),
+            current,
+            iso_currency_code: Some("USD".to_string()),
+        }
+    }
+
+    fn net_worths(series: &[NetWorthPoint]) -> Vec<(&str, f64)> {
+        series
+            .iter()
+            .map(|p| (p.date.as_str(), p.net_worth()))
+            .collect()
+    }
+
+    #[test]
+    fn balances_carry_forward_between_snapshots() {
+        let snapshots = vec![
+            snapshot("checking", "2026-10-01", "depository", 1000.0),
+            snapshot("card", "2026-10-01", "credit", 200.0),
+            snapshot("checking", "2026-10-02", "depository", 1100.0),
+            snapshot("card", "2026-10-03", "credit", 300.0),
+        ];
+        let open = HashSet::from(["checking", "card"]);
+
+        let series = net_worth_series(&snapshots, &ExchangeRates::default(), None, &open);
+        assert_eq!(
+            net_worths(&series),
+            vec![
+                ("2026-10-01", 800.0),
+                ("2026-10-02", 900.0),
+                ("2026-10-03", 800.0)
+            ]
+        );
+
+        let series = net_worth_series(
+            &snapshots,
+            &ExchangeRates::default(),
+            Some("2026-10-02"),
+            &open,
+        );
+        assert_eq!(
+            net_worths(&series),
+            vec![("2026-10-02", 900.0), ("2026-10-03", 800.0)]
+        );
+    }
+
+    #[test]
+    fn removed_accounts_stop_after_their_last_snapshot() {
+        let snapshots = vec![
+            snapshot("checking", "2026-10-01", "depository", 1000.0),
+            snapshot("old-savings", "2026-10-01", "depository", 5000.0),
+            snapshot("old-savings", "2026-10-02", "depository", 5000.0),
+            snapshot("checking", "2026-10-03", "depository", 1000.0),
+        ];
+        let open = HashSet::from(["checking"]);
+
+        let series = net_worth_series(&snapshots, &ExchangeRates::default(), None, &open);
+        assert_eq!(
+            net_worths(&series),
+            vec![
+                ("2026-10-01", 6000.0),
+                ("2026-10-02", 6000.0),
+                ("2026-10-03", 1000.0)
+            ]
+        );
+    }
+}
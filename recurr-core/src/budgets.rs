@@ -0,0 +1,727 @@
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::{categories::Taxonomy, pfc, SchemaBudget, Transaction};
+
+/// How often a budget resets
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetPeriod {
+    Weekly,
+    Biweekly,
+    #[default]
+    Monthly,
+    Quarterly,
+    Yearly,
+}
+
+impl BudgetPeriod {
+    pub const ALL: [BudgetPeriod; 5] = [
+        BudgetPeriod::Weekly,
+        BudgetPeriod::Biweekly,
+        BudgetPeriod::Monthly,
+        BudgetPeriod::Quarterly,
+        BudgetPeriod::Yearly,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BudgetPeriod::Weekly => "weekly",
+            BudgetPeriod::Biweekly => "biweekly",
+            BudgetPeriod::Monthly => "monthly",
+            BudgetPeriod::Quarterly => "quarterly",
+            BudgetPeriod::Yearly => "yearly",
+        }
+    }
+
+    pub fn parse(period: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.as_str() == period)
+    }
+
+    /// The period containing `today` shifted by `offset` periods. Monthly and longer periods
+    /// start on `start_day`, weekly ones are counted from `anchor`
+    pub fn period(
+        &self,
+        start_day: u32,
+        anchor: Option<&str>,
+        today: NaiveDate,
+        offset: i64,
+    ) -> Period {
+        let step = self.step();
+
+        match self {
+            BudgetPeriod::Weekly | BudgetPeriod::Biweekly => {
+                let anchor = anchor
+                    .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+                    .unwrap_or_else(default_anchor);
+
+                let n = (today - anchor).num_days().div_euclid(step) + offset;
+                let start = anchor + Duration::days(n * step);
+                Period {
+                    start,
+                    end: start + Duration::days(step),
+                }
+            }
+            BudgetPeriod::Monthly | BudgetPeriod::Quarterly | BudgetPeriod::Yearly => {
+                let day = start_day.clamp(1, 28);
+
+                let mut months = today.year() as i64 * 12 + today.month0() as i64;
+                if today.day() < day {
+                    months -= 1;
+                }
+                let months = months - months.rem_euclid(step) + offset * step;
+
+                Period {
+                    start: month_start(months, day),
+                    end: month_start(months + step, day),
+                }
+            }
+        }
+    }
+
+    /// How many of these periods start within `window`, to compare budgets with
+    /// different periods over the same stretch of time
+    pub fn starts_within(&self, start_day: u32, anchor: Option<&str>, window: &Period) -> i64 {
+        let mut period = self.period(start_day, anchor, window.start, 0);
+        if period.start < window.start {
+            period = self.period(start_day, anchor, period.start, 1);
+        }
+
+        let mut count = 0;
+        while period.start < window.end {
+            count += 1;
+            period = self.period(start_day, anchor, period.start, 1);
+        }
+
+        count
+    }
+
+    /// Length in days of the week based periods, months for the rest
+    fn step(&self) -> i64 {
+        match self {
+            BudgetPeriod::Weekly => 7,
+            BudgetPeriod::Biweekly => 14,
+            BudgetPeriod::Monthly => 1,
+            BudgetPeriod::Quarterly => 3,
+            BudgetPeriod::Yearly => 12,
+        }
+    }
+}
+
+pub(crate) fn default_start_day() -> u32 {
+    1
+}
+
+/// Weeks start on Mondays unless a budget has its own anchor, this one was a Monday
+fn default_anchor() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2023, 1, 2).expect("Valid date")
+}
+
+/// What a budget's money is for. Zero-based budgeting assigns income to all three
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetKind {
+    #[default]
+    Spending,
+    Goal,
+    Savings,
+}
+
+impl BudgetKind {
+    pub const ALL: [BudgetKind; 3] = [BudgetKind::Spending, BudgetKind::Goal, BudgetKind::Savings];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BudgetKind::Spending => "spending",
+            BudgetKind::Goal => "goal",
+            BudgetKind::Savings => "savings",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.as_str() == kind)
+    }
+}
+
+/// A budget period from `start` up to, but not including, `end`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Period {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl Period {
+    /// Whether an ISO formatted date falls within the period
+    pub fn contains(&self, date: &str) -> bool {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok_and(|d| self.start <= d && d < self.end)
+    }
+
+    /// The last day of the period
+    pub fn last_day(&self) -> NaiveDate {
+        self.end - Duration::days(1)
+    }
+
+    pub fn label(&self) -> String {
+        let last = self.last_day();
+        if self.start.year() == last.year() {
+            format!(
+                "{} – {}",
+                self.start.format("%b %-d"),
+                last.format("%b %-d, %Y")
+            )
+        } else {
+            format!(
+                "{} – {}",
+                self.start.format("%b %-d, %Y"),
+                last.format("%b %-d, %Y")
+            )
+        }
+    }
+}
+
+/// The date `day` in the month `months` after year 0
+fn month_start(months: i64, day: u32) -> NaiveDate {
+    let year = months.div_euclid(12) as i32;
+    let month = months.rem_euclid(12) as u32 + 1;
+    NaiveDate::from_ymd_opt(year, month, day).expect("Start day is at most 28")
+}
+
+impl SchemaBudget {
+    /// The period containing `today`, or `offset` periods before (negative) or after it
+    pub fn period(&self, today: NaiveDate, offset: i64) -> Period {
+        self.period
+            .period(self.start_day, self.anchor_date.as_deref(), today, offset)
+    }
+
+    /// Every period from the one containing `since` through the one containing `until`
+    pub fn periods_between(&self, since: NaiveDate, until: NaiveDate) -> Vec<Period> {
+        let mut periods = vec![self.period(since, 0)];
+        while let Some(last) = periods.last().filter(|p| p.end <= until) {
+            periods.push(self.period(last.start, 1));
+        }
+
+        periods
+    }
+}
+
+/// Money assigned to or moved between budgets in zero-based mode. A missing
+/// side is the period's unassigned income
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SchemaBudgetAllocation {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    pub user_id: String,
+    pub period_start: String,
+    pub from_category: Option<String>,
+    pub to_category: Option<String>,
+    pub amount: f64,
+    #[serde(skip_serializing)]
+    pub created_at: Option<String>,
+}
+
+/// One period of a rollover budget in the envelope ledger
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SchemaBudgetPeriod {
+    pub user_id: String,
+    pub category_id: String,
+    pub period_start: String,
+    pub allocated: f64,
+    pub carried: f64,
+    pub spent: f64,
+}
+
+impl SchemaBudgetPeriod {
+    /// What's left in the envelope, negative when overspent
+    pub fn available(&self) -> f64 {
+        self.allocated + self.carried - self.spent
+    }
+}
+
+/// Recomputes what each period carries in from the one before it. Rows must
+/// be one budget's consecutive periods in order, the first carries nothing in
+pub fn carry_forward(ledger: &mut [SchemaBudgetPeriod]) {
+    let mut carried = 0.0;
+    for row in ledger.iter_mut() {
+        row.carried = carried;
+        carried = row.available();
+    }
+}
+
+/// What a budget spent in its period
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Rollup {
+    /// Transactions no more specific budget claimed
+    pub own: f64,
+    /// Everything under the budget's category, its own and its sub-budgets'
+    pub total: f64,
+}
+
+/// Assigns each transaction to the most specific budget whose category and
+/// period cover it, so child budgets consume before their parents. Equally
+/// specific budgets go by lowest `category_id`, the order of `budgets` never
+/// matters. Returns the index of each transaction's budget
+pub fn allocate(
+    taxonomy: &Taxonomy,
+    budgets: &[(&SchemaBudget, Period)],
+    transactions: &[Transaction],
+) -> Vec<Option<usize>> {
+    transactions
+        .iter()
+        .map(|t| {
+            budgets
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, period))| period.contains(&t.date))
+                .filter_map(|(i, (b, _))| {
+                    taxonomy
+                        .match_depth(&b.category_id, t)
+                        .map(|depth| (i, depth, &b.category_id))
+                })
+                .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.2.cmp(a.2)))
+                .map(|(i, _, _)| i)
+        })
+        .collect()
+}
+
+/// Each budget's own and total spending from the budgets [`allocate`] picked
+pub fn rollup(
+    taxonomy: &Taxonomy,
+    budgets: &[(&SchemaBudget, Period)],
+    transactions: &[Transaction],
+    owners: &[Option<usize>],
+    amount: impl Fn(&Transaction) -> f64,
+) -> Vec<Rollup> {
+    let mut rollups = vec![Rollup::default(); budgets.len()];
+
+    for (t, owner) in transactions.iter().zip(owners) {
+        let Some(owner) = owner else {
+            continue;
+        };
+
+        let amount = amount(t);
+        rollups[*owner].own += amount;
+        for (rollup, (b, period)) in rollups.iter_mut().zip(budgets) {
+            if period.contains(&t.date) && taxonomy.match_depth(&b.category_id, t).is_some() {
+                rollup.total += amount;
+            }
+        }
+    }
+
+    rollups
+}
+
+/// The columns `daily_category_totals` groups by, an order that keeps pages of
+/// its rows from overlapping
+pub const DAILY_TOTALS_ORDER: &str =
+    "date,category,pfc_primary,pfc_detailed,payer,iso_currency_code,amount";
+
+/// Spending summed per day, category, currency and direction by the
+/// `daily_category_totals` function. Only inflows keep their payer, for matching
+/// income targets
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SchemaDailyCategoryTotal {
+    pub date: String,
+    pub category: Option<Vec<String>>,
+    pub pfc_primary: Option<String>,
+    pub pfc_detailed: Option<String>,
+    pub payer: Option<String>,
+    pub iso_currency_code: Option<String>,
+    pub amount: f64,
+    pub transactions: i64,
+}
+
+impl SchemaDailyCategoryTotal {
+    /// The total as one transaction, so [`allocate`] and [`rollup`] treat it
+    /// like the transactions it sums
+    pub fn to_transaction(&self) -> Transaction {
+        Transaction {
+            transaction_id: String::new(),
+            account_id: String::new(),
+            amount: self.amount,
+            name: self.payer.clone().unwrap_or_default(),
+            date: self.date.clone(),
+            category: self.category.clone(),
+            category_id: None,
+            merchant_name: self.payer.clone(),
+            pending: false,
+            pending_transaction_id: None,
+            iso_currency_code: self.iso_currency_code.clone(),
+            unofficial_currency_code: None,
+            personal_finance_category: self.pfc_primary.clone().map(|primary| {
+                pfc::PersonalFinanceCategory {
+                    detailed: self.pfc_detailed.clone().unwrap_or_else(|| primary.clone()),
+                    primary,
+                    confidence_level: None,
+                }
+            }),
+            is_transfer: None,
+            notes: None,
+            tags: None,
+            manual: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{income, splits, Category};
+
+    fn taxonomy() -> Taxonomy {
+        let category = |hierarchy: &[&str]| Category {
+            category_id: hierarchy.join("/"),
+            group: "place".to_string(),
+            hierarchy: hierarchy.iter().map(|s| s.to_string()).collect(),
+        };
+
+        Taxonomy::new(
+            &[
+                category(&["Food and Drink"]),
+                category(&["Food and Drink", "Restaurants"]),
+                category(&["Food and Drink", "Restaurants", "Coffee Shop"]),
+                category(&["Shops"]),
+            ],
+            Vec::new(),
+        )
+    }
+
+    fn transaction(id: &str, category: &[&str], amount: f64, date: &str) -> Transaction {
+        serde_json::from_value(serde_json::json!({
+            "transaction_id": id,
+            "account_id": "account",
+            "amount": amount,
+            "name": id,
+            "date": date,
+            "category": category,
+            "pending": false,
+        }))
+        .expect("Valid transaction")
+    }
+
+    fn budget(category_id: &str, period: BudgetPeriod) -> SchemaBudget {
+        SchemaBudget {
+            user_id: "user".to_string(),
+            category_id: category_id.to_string(),
+            max: 100.0,
+            period,
+            start_day: 1,
+            anchor_date: None,
+            rollover: false,
+            kind: BudgetKind::Spending,
+        }
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, 19).unwrap()
+    }
+
+    fn transactions() -> Vec<Transaction> {
+        vec![
+            transaction(
+                "dinner",
+                &["Food and Drink", "Restaurants"],
+                40.0,
+                "2026-10-02",
+            ),
+            transaction(
+                "latte",
+                &["Food and Drink", "Restaurants", "Coffee Shop"],
+                5.0,
+                "2026-10-19",
+            ),
+            transaction("groceries", &["Food and Drink"], 60.0, "2026-10-10"),
+            transaction("lamp", &["Shops"], 30.0, "2026-10-11"),
+        ]
+    }
+
+    #[test]
+    fn children_consume_before_parents() {
+        let taxonomy = taxonomy();
+        let food = budget("Food and Drink", BudgetPeriod::Monthly);
+        let restaurants = budget("Restaurants", BudgetPeriod::Monthly);
+        let budgets = [
+            (&food, food.period(today(), 0)),
+            (&restaurants, restaurants.period(today(), 0)),
+        ];
+        let transactions = transactions();
+
+        let owners = allocate(&taxonomy, &budgets, &transactions);
+        assert_eq!(owners, vec![Some(1), Some(1), Some(0), None]);
+
+        let rollups = rollup(&taxonomy, &budgets, &transactions, &owners, |t| t.amount);
+        assert_eq!(
+            rollups[0],
+            Rollup {
+                own: 60.0,
+                total: 105.0
+            }
+        );
+        assert_eq!(
+            rollups[1],
+            Rollup {
+                own: 45.0,
+                total: 45.0
+            }
+        );
+    }
+
+    #[test]
+    fn order_of_budgets_does_not_matter() {
+        let taxonomy = taxonomy();
+        let food = budget("Food and Drink", BudgetPeriod::Monthly);
+        let restaurants = budget("Restaurants", BudgetPeriod::Monthly);
+        let coffee = budget("Coffee Shop", BudgetPeriod::Monthly);
+        let transactions = transactions();
+
+        let forward = [
+            (&food, food.period(today(), 0)),
+            (&restaurants, restaurants.period(today(), 0)),
+            (&coffee, coffee.period(today(), 0)),
+        ];
+        let backward = [forward[2], forward[1], forward[0]];
+
+        let owners = allocate(&taxonomy, &forward, &transactions);
+        let reversed = allocate(&taxonomy, &backward, &transactions);
+        let name = |budgets: &[(&SchemaBudget, Period)], owner: &Option<usize>| {
+            owner.map(|i| budgets[i].0.category_id.clone())
+        };
+
+        for (a, b) in owners.iter().zip(&reversed) {
+            assert_eq!(name(&forward, a), name(&backward, b));
+        }
+        assert_eq!(name(&forward, &owners[1]), Some("Coffee Shop".to_string()));
+
+        let totals = rollup(&taxonomy, &forward, &transactions, &owners, |t| t.amount);
+        assert_eq!(totals[0].total, 105.0);
+        assert_eq!(
+            totals[1],
+            Rollup {
+                own: 40.0,
+                total: 45.0
+            }
+        );
+        assert_eq!(
+            totals[2],
+            Rollup {
+                own: 5.0,
+                total: 5.0
+            }
+        );
+    }
+
+    #[test]
+    fn parent_claims_what_falls_outside_a_child_period() {
+        let taxonomy = taxonomy();
+        let food = budget("Food and Drink", BudgetPeriod::Monthly);
+        let restaurants = budget("Restaurants", BudgetPeriod::Weekly);
+        let budgets = [
+            (&food, food.period(today(), 0)),
+            (&restaurants, restaurants.period(today(), 0)),
+        ];
+        let transactions = transactions();
+
+        // Only the latte is in the week of Oct 19, the dinner falls back to the monthly parent
+        let owners = allocate(&taxonomy, &budgets, &transactions);
+        assert_eq!(owners, vec![Some(0), Some(1), Some(0), None]);
+
+        let rollups = rollup(&taxonomy, &budgets, &transactions, &owners, |t| t.amount);
+        assert_eq!(
+            rollups[0],
+            Rollup {
+                own: 100.0,
+                total: 105.0
+            }
+        );
+        assert_eq!(
+            rollups[1],
+            Rollup {
+                own: 5.0,
+                total: 5.0
+            }
+        );
+    }
+
+    #[test]
+    fn equally_specific_budgets_go_to_the_lowest_key() {
+        let taxonomy = taxonomy();
+        let pfc = budget("FOOD_AND_DRINK", BudgetPeriod::Monthly);
+        let named = budget("Food and Drink", BudgetPeriod::Monthly);
+        let transactions = vec![transaction(
+            "groceries",
+            &["Food and Drink"],
+            60.0,
+            "2026-10-10",
+        )];
+
+        let budgets = [
+            (&named, named.period(today(), 0)),
+            (&pfc, pfc.period(today(), 0)),
+        ];
+        assert_eq!(allocate(&taxonomy, &budgets, &transactions), vec![Some(1)]);
+
+        let budgets = [budgets[1], budgets[0]];
+        assert_eq!(allocate(&taxonomy, &budgets, &transactions), vec![Some(0)]);
+    }
+
+    /// What `daily_category_totals` returns for [`fixture`], checked against the
+    /// database in supabase/tests/database/daily_category_totals.test.sql
+    fn daily_totals() -> Vec<Transaction> {
+        let row = |date: &str, category: &[&str], payer: Option<&str>, amount: f64| {
+            SchemaDailyCategoryTotal {
+                date: date.to_string(),
+                category: Some(category.iter().map(|c| c.to_string()).collect()),
+                pfc_primary: None,
+                pfc_detailed: None,
+                payer: payer.map(str::to_string),
+                iso_currency_code: None,
+                amount,
+                transactions: 1,
+            }
+        };
+        let market = SchemaDailyCategoryTotal {
+            pfc_primary: Some("FOOD_AND_DRINK".to_string()),
+            pfc_detailed: Some("FOOD_AND_DRINK_GROCERIES".to_string()),
+            iso_currency_code: Some("USD".to_string()),
+            ..row("2026-10-19", &["Shops"], None, 80.0)
+        };
+
+        [
+            row(
+                "2026-10-01",
+                &["Transfer", "Payroll"],
+                Some("salary"),
+                -2000.0,
+            ),
+            SchemaDailyCategoryTotal {
+                transactions: 2,
+                ..row("2026-10-02", &["Food and Drink", "Restaurants"], None, 65.0)
+            },
+            row("2026-10-10", &["Food and Drink"], None, 60.0),
+            row("2026-10-11", &["Food and Drink"], None, 10.0),
+            row("2026-10-11", &["Shops"], Some("refund"), -10.0),
+            row("2026-10-11", &["Shops"], None, 20.0),
+            row(
+                "2026-10-19",
+                &["Food and Drink", "Restaurants", "Coffee Shop"],
+                None,
+                5.0,
+            ),
+            market,
+        ]
+        .iter()
+        .map(SchemaDailyCategoryTotal::to_transaction)
+        .collect()
+    }
+
+    fn fixture() -> Vec<Transaction> {
+        let mut transactions = transactions();
+        transactions.extend([
+            transaction("refund", &["Shops"], -10.0, "2026-10-11"),
+            transaction(
+                "brunch",
+                &["Food and Drink", "Restaurants"],
+                25.0,
+                "2026-10-02",
+            ),
+            transaction("salary", &["Transfer", "Payroll"], -2000.0, "2026-10-01"),
+            serde_json::from_value(serde_json::json!({
+                "transaction_id": "market",
+                "account_id": "account",
+                "amount": 80.0,
+                "name": "market",
+                "date": "2026-10-19",
+                "category": ["Shops"],
+                "pending": true,
+                "iso_currency_code": "USD",
+                "personal_finance_category": {
+                    "primary": "FOOD_AND_DRINK",
+                    "detailed": "FOOD_AND_DRINK_GROCERIES",
+                },
+            }))
+            .expect("Valid transaction"),
+        ]);
+
+        let split = |category: &str, amount: f64| splits::SchemaTransactionSplit {
+            id: None,
+            transaction_id: "lamp".to_string(),
+            user_id: "user".to_string(),
+            category: vec![category.to_string()],
+            amount,
+            memo: None,
+        };
+        let splits = HashMap::from([(
+            "lamp".to_string(),
+            vec![split("Shops", 20.0), split("Food and Drink", 10.0)],
+        )]);
+
+        splits::expand(transactions, &splits)
+    }
+
+    #[test]
+    fn daily_totals_roll_up_like_transactions() {
+        let taxonomy = taxonomy();
+        let food = budget("Food and Drink", BudgetPeriod::Monthly);
+        let restaurants = budget("Restaurants", BudgetPeriod::Weekly);
+        let groceries = budget("FOOD_AND_DRINK_GROCERIES", BudgetPeriod::Monthly);
+        let shops = budget("Shops", BudgetPeriod::Biweekly);
+        let budgets = [
+            (&food, food.period(today(), 0)),
+            (&restaurants, restaurants.period(today(), 0)),
+            (&groceries, groceries.period(today(), 0)),
+            (&shops, shops.period(today(), 0)),
+        ];
+
+        let transactions = fixture();
+        let totals = daily_totals();
+        assert!(totals.len() < transactions.len());
+
+        let owners = allocate(&taxonomy, &budgets, &transactions);
+        let expected = rollup(&taxonomy, &budgets, &transactions, &owners, |t| t.amount);
+        let owners = allocate(&taxonomy, &budgets, &totals);
+        let actual = rollup(&taxonomy, &budgets, &totals, &owners, |t| t.amount);
+        assert_eq!(actual, expected);
+        assert_eq!(actual[2].own, 80.0);
+    }
+
+    #[test]
+    fn daily_totals_keep_income_apart() {
+        let taxonomy = taxonomy();
+        let transactions = fixture();
+        let totals = daily_totals();
+
+        let flows = |transactions: &[Transaction]| {
+            transactions
+                .iter()
+                .fold((0.0, 0.0), |(income, spending), t| {
+                    if income::is_income(t) {
+                        (income - t.amount, spending)
+                    } else {
+                        (income, spending + t.amount)
+                    }
+                })
+        };
+        assert_eq!(flows(&totals), flows(&transactions));
+        assert_eq!(flows(&totals), (2010.0, 240.0));
+
+        let target = income::SchemaIncomeTarget {
+            id: None,
+            user_id: "user".to_string(),
+            source: "SALARY".to_string(),
+            expected: 2000.0,
+            period: BudgetPeriod::Monthly,
+            start_day: 1,
+            anchor_date: None,
+        };
+        let received = |transactions: &[Transaction]| -> f64 {
+            transactions
+                .iter()
+                .filter(|t| target.matches(&taxonomy, t))
+                .map(|t| -t.amount)
+                .sum()
+        };
+        assert_eq!(received(&totals), received(&transactions));
+        assert_eq!(received(&totals), 2000.0);
+    }
+}
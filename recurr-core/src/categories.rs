@@ -0,0 +1,494 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{get_supbase_client, pfc, Category, Error, Transaction};
+
+/// Guards against merge cycles, a category can't be merged more times than this
+const MAX_MERGE_DEPTH: usize = 16;
+
+/// Joins the names in a category key
+const KEY_SEPARATOR: &str = " > ";
+
+/// The budget or filter key for a category. Keys hold the path the category
+/// was created with rather than its display name, so renames keep budgets,
+/// filters and income targets attached
+pub fn key(path: &[String]) -> String {
+    path.join(KEY_SEPARATOR)
+}
+
+/// A user edit to the category taxonomy. Rows are keyed by the path the
+/// category was created with, either a Plaid hierarchy or a custom one, so
+/// renames never invalidate references to it
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SchemaUserCategory {
+    pub user_id: String,
+    pub path: Vec<String>,
+    pub custom: bool,
+    pub name: Option<String>,
+    pub merged_into: Option<Vec<String>>,
+    pub hidden: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CategoryNode {
+    pub name: String,
+    pub path: Vec<String>,
+    pub custom: bool,
+    pub hidden: bool,
+    pub children: Vec<CategoryNode>,
+}
+
+/// Plaid's categories merged with the user's own
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Taxonomy {
+    roots: Vec<CategoryNode>,
+    rows: HashMap<Vec<String>, SchemaUserCategory>,
+    /// Every path a transaction can be stored with, before merges
+    paths: Vec<Vec<String>>,
+}
+
+impl Taxonomy {
+    pub fn new(plaid: &[Category], user: Vec<SchemaUserCategory>) -> Self {
+        let mut paths: Vec<(Vec<String>, bool)> =
+            plaid.iter().map(|c| (c.hierarchy.clone(), false)).collect();
+        paths.extend(
+            user.iter()
+                .filter(|c| c.custom)
+                .map(|c| (c.path.clone(), true)),
+        );
+
+        let mut taxonomy = Self {
+            roots: Vec::new(),
+            rows: user.into_iter().map(|c| (c.path.clone(), c)).collect(),
+            paths: paths.iter().map(|(p, _)| p.clone()).collect(),
+        };
+
+        let hidden: HashSet<Vec<String>> = taxonomy
+            .rows
+            .values()
+            .filter(|r| r.hidden)
+            .map(|r| r.path.clone())
+            .collect();
+
+        for (path, custom) in paths {
+            if path.is_empty() || taxonomy.merged_into(&path).is_some() {
+                continue;
+            }
+
+            let path = taxonomy.resolve(&path);
+            taxonomy.insert(&path, custom, &hidden);
+        }
+
+        sort_nodes(&mut taxonomy.roots);
+        taxonomy
+    }
+
+    pub fn roots(&self) -> &[CategoryNode] {
+        &self.roots
+    }
+
+    /// The stored edit for a category, or an empty one to start editing from
+    pub fn row(&self, user_id: &str, path: &[String]) -> SchemaUserCategory {
+        self.rows
+            .get(path)
+            .cloned()
+            .unwrap_or_else(|| SchemaUserCategory {
+                user_id: user_id.to_string(),
+                path: path.to_vec(),
+                custom: false,
+                name: None,
+                merged_into: None,
+                hidden: false,
+            })
+    }
+
+    /// Categories that were merged away, with the category they now belong to
+    pub fn merges(&self) -> Vec<(Vec<String>, Vec<String>)> {
+        let mut merges: Vec<(Vec<String>, Vec<String>)> = self
+            .rows
+            .values()
+            .filter_map(|r| r.merged_into.clone().map(|m| (r.path.clone(), m)))
+            .collect();
+        merges.sort();
+        merges
+    }
+
+    fn merged_into(&self, path: &[String]) -> Option<&Vec<String>> {
+        self.rows
+            .get(path)
+            .and_then(|r| r.merged_into.as_ref())
+            .filter(|m| m.as_slice() != path)
+    }
+
+    /// Follows merges so a path points at the category it now belongs to
+    pub fn resolve(&self, path: &[String]) -> Vec<String> {
+        let mut path = path.to_vec();
+
+        for _ in 0..MAX_MERGE_DEPTH {
+            let merged = (1..=path.len())
+                .rev()
+                .find_map(|len| self.merged_into(&path[..len]).map(|m| (len, m.clone())));
+
+            match merged {
+                Some((len, mut target)) => {
+                    target.extend_from_slice(&path[len..]);
+                    path = target;
+                }
+                None => break,
+            }
+        }
+
+        path
+    }
+
+    pub fn display_name(&self, path: &[String]) -> String {
+        self.rows
+            .get(path)
+            .and_then(|r| r.name.clone())
+            .or_else(|| path.last().cloned())
+            .unwrap_or_default()
+    }
+
+    /// The names a category path is shown with once merges and renames are applied
+    pub fn display_path(&self, path: &[String]) -> Vec<String> {
+        let path = self.resolve(path);
+        (1..=path.len())
+            .map(|len| self.display_name(&path[..len]))
+            .collect()
+    }
+
+    /// The stored path a non-PFC key refers to. Keys saved before categories
+    /// were keyed by path hold a Plaid category's original name instead
+    pub fn path_of(&self, key: &str) -> Vec<String> {
+        let path: Vec<String> = key.split(KEY_SEPARATOR).map(str::to_string).collect();
+        if path.len() > 1 || self.paths.iter().any(|p| p.starts_with(&path)) {
+            return path;
+        }
+
+        self.paths
+            .iter()
+            .filter(|p| p.last().map(String::as_str) == Some(key))
+            .min_by_key(|p| p.len())
+            .cloned()
+            .unwrap_or(path)
+    }
+
+    /// The key of the visible category shown as `name`, ignoring case, for
+    /// searches that name a category
+    pub fn find(&self, name: &str) -> Option<String> {
+        if pfc::is_pfc_key(name) {
+            return Some(name.to_string());
+        }
+
+        self.flatten()
+            .into_iter()
+            .find(|n| n.name.eq_ignore_ascii_case(name))
+            .map(|n| key(&n.path))
+    }
+
+    /// How a budget or filter key is shown, with renames and merges applied
+    pub fn label(&self, key: &str) -> String {
+        if pfc::is_pfc_key(key) {
+            pfc::display_name(key)
+        } else {
+            self.display_path(&self.path_of(key)).join(KEY_SEPARATOR)
+        }
+    }
+
+    /// Whether the category `key` refers to is the category of `path` or one
+    /// of its ancestors, once both are resolved through merges
+    pub fn contains(&self, key: &str, path: &[String]) -> bool {
+        let category = self.resolve(&self.path_of(key));
+        self.resolve(path).starts_with(&category)
+    }
+
+    /// The stored paths a budget or filter key covers, descendants and merged
+    /// categories included, so the key can be matched by the database. PFC
+    /// keys cover the legacy paths that map to them
+    pub fn paths_in(&self, key: &str) -> Vec<Vec<String>> {
+        self.paths
+            .iter()
+            .filter(|path| {
+                if pfc::is_pfc_key(key) {
+                    pfc::legacy_matches(key, path)
+                } else {
+                    self.contains(key, path)
+                }
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Whether a transaction belongs to a budget or filter key, either a PFC
+    /// category or a name in the user's taxonomy
+    pub fn in_category(&self, key: &str, transaction: &Transaction) -> bool {
+        if pfc::is_pfc_key(key) {
+            pfc::matches(key, transaction)
+        } else {
+            self.contains(key, transaction.category.as_deref().unwrap_or_default())
+        }
+    }
+
+    /// How deep in its tree a budget key matches a transaction, 1 for a top
+    /// level category, or `None` when it doesn't match at all
+    pub fn match_depth(&self, key: &str, transaction: &Transaction) -> Option<usize> {
+        if pfc::is_pfc_key(key) {
+            if !pfc::matches(key, transaction) {
+                return None;
+            }
+
+            let detailed = transaction
+                .personal_finance_category
+                .as_ref()
+                .is_some_and(|p| p.detailed == key && p.primary != key);
+            Some(if detailed { 2 } else { 1 })
+        } else {
+            self.contains(key, transaction.category.as_deref().unwrap_or_default())
+                .then(|| self.resolve(&self.path_of(key)).len())
+        }
+    }
+
+    /// Every visible category in tree order, for pickers
+    pub fn flatten(&self) -> Vec<&CategoryNode> {
+        fn walk<'a>(nodes: &'a [CategoryNode], out: &mut Vec<&'a CategoryNode>) {
+            for node in nodes.iter().filter(|n| !n.hidden) {
+                out.push(node);
+                walk(&node.children, out);
+            }
+        }
+
+        let mut out = Vec::new();
+        walk(&self.roots, &mut out);
+        out
+    }
+
+    fn insert(&mut self, path: &[String], custom: bool, hidden: &HashSet<Vec<String>>) {
+        let mut nodes = &mut self.roots;
+        for len in 1..=path.len() {
+            let prefix = &path[..len];
+
+            let index = match nodes.iter().position(|n| n.path == prefix) {
+                Some(index) => index,
+                None => {
+                    nodes.push(CategoryNode {
+                        name: self
+                            .rows
+                            .get(prefix)
+                            .and_then(|r| r.name.clone())
+                            .unwrap_or(prefix[len - 1].clone()),
+                        path: prefix.to_vec(),
+                        custom: false,
+                        hidden: hidden.contains(prefix),
+                        children: Vec::new(),
+                    });
+                    nodes.len() - 1
+                }
+            };
+
+            if len == path.len() {
+                nodes[index].custom |= custom;
+            }
+
+            nodes = &mut nodes[index].children;
+        }
+    }
+}
+
+fn sort_nodes(nodes: &mut [CategoryNode]) {
+    nodes.sort_by(|a, b| a.name.cmp(&b.name));
+    for node in nodes {
+        sort_nodes(&mut node.children);
+    }
+}
+
+/// Plaid's categories with the user's renames, merges and custom categories applied
+pub async fn get_taxonomy(auth_key: &str, plaid: &[Category]) -> Result<Taxonomy, Error> {
+    let user: Vec<SchemaUserCategory> = get_supbase_client()
+        .from("user_categories")
+        .auth(auth_key)
+        .select("*")
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    Ok(Taxonomy::new(plaid, user))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    fn category(names: &[&str]) -> Category {
+        Category {
+            category_id: names.join("/"),
+            group: "place".to_string(),
+            hierarchy: path(names),
+        }
+    }
+
+    fn edit(names: &[&str]) -> SchemaUserCategory {
+        SchemaUserCategory {
+            user_id: "user".to_string(),
+            path: path(names),
+            custom: false,
+            name: None,
+            merged_into: None,
+            hidden: false,
+        }
+    }
+
+    fn transaction(category: &[&str]) -> Transaction {
+        Transaction {
+            transaction_id: "t".to_string(),
+            account_id: "checking".to_string(),
+            amount: 20.0,
+            name: "Dinner".to_string(),
+            date: "2026-10-01".to_string(),
+            category: Some(path(category)),
+            category_id: None,
+            merchant_name: None,
+            pending: false,
+            pending_transaction_id: None,
+            iso_currency_code: Some("USD".to_string()),
+            unofficial_currency_code: None,
+            personal_finance_category: None,
+            is_transfer: None,
+            notes: None,
+            tags: None,
+            manual: None,
+        }
+    }
+
+    #[test]
+    fn paths_in_follow_hierarchy_and_merges() {
+        let taxonomy = Taxonomy::new(
+            &[
+                category(&["Food and Drink"]),
+                category(&["Food and Drink", "Restaurants"]),
+                category(&["Shops"]),
+                category(&["Shops", "Supermarkets and Groceries"]),
+            ],
+            vec![SchemaUserCategory {
+                merged_into: Some(path(&["Food and Drink", "Groceries"])),
+                ..edit(&["Shops", "Supermarkets and Groceries"])
+            }],
+        );
+
+        assert_eq!(
+            taxonomy.paths_in("Food and Drink"),
+            vec![
+                path(&["Food and Drink"]),
+                path(&["Food and Drink", "Restaurants"]),
+                path(&["Shops", "Supermarkets and Groceries"]),
+            ]
+        );
+        assert_eq!(taxonomy.paths_in("Shops"), vec![path(&["Shops"])]);
+        assert_eq!(
+            taxonomy.paths_in("FOOD_AND_DRINK"),
+            taxonomy.paths_in("Food and Drink")
+        );
+    }
+
+    #[test]
+    fn keys_survive_renames() {
+        let plaid = [
+            category(&["Food and Drink"]),
+            category(&["Food and Drink", "Restaurants"]),
+        ];
+        let coffee = SchemaUserCategory {
+            custom: true,
+            ..edit(&["Food and Drink", "Coffee"])
+        };
+        let before = Taxonomy::new(&plaid, vec![coffee.clone()]);
+        let key = key(&path(&["Food and Drink", "Coffee"]));
+        assert_eq!(before.label(&key), "Food and Drink > Coffee");
+
+        let renamed = Taxonomy::new(
+            &plaid,
+            vec![
+                SchemaUserCategory {
+                    name: Some("Cafés".to_string()),
+                    ..coffee
+                },
+                SchemaUserCategory {
+                    name: Some("Eating Out".to_string()),
+                    ..edit(&["Food and Drink"])
+                },
+            ],
+        );
+        let latte = transaction(&["Food and Drink", "Coffee"]);
+        assert!(renamed.in_category(&key, &latte));
+        assert!(renamed.in_category("Food and Drink", &latte));
+        assert_eq!(renamed.label(&key), "Eating Out > Cafés");
+        assert_eq!(renamed.find("cafés"), Some(key.clone()));
+        assert_eq!(renamed.find("Coffee"), None);
+    }
+
+    #[test]
+    fn keys_saved_as_names_still_match() {
+        let taxonomy = Taxonomy::new(
+            &[
+                category(&["Food and Drink"]),
+                category(&["Food and Drink", "Restaurants"]),
+                category(&["Travel", "Restaurants"]),
+                category(&["Travel", "Restaurants", "Airport"]),
+            ],
+            Vec::new(),
+        );
+
+        // Original Plaid names resolve to the shortest path with that name
+        assert_eq!(
+            taxonomy.path_of("Restaurants"),
+            path(&["Food and Drink", "Restaurants"])
+        );
+        assert_eq!(
+            taxonomy.path_of("Food and Drink"),
+            path(&["Food and Drink"])
+        );
+
+        let dinner = transaction(&["Food and Drink", "Restaurants"]);
+        assert_eq!(taxonomy.match_depth("Restaurants", &dinner), Some(2));
+        assert_eq!(taxonomy.match_depth("Food and Drink", &dinner), Some(1));
+        assert_eq!(taxonomy.match_depth("Travel", &dinner), None);
+        assert_eq!(
+            taxonomy.match_depth(
+                "Travel > Restaurants",
+                &transaction(&["Travel", "Restaurants", "Airport"])
+            ),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn merged_categories_match_their_target() {
+        let taxonomy = Taxonomy::new(
+            &[
+                category(&["Shops"]),
+                category(&["Shops", "Supermarkets and Groceries"]),
+                category(&["Food and Drink"]),
+            ],
+            vec![SchemaUserCategory {
+                merged_into: Some(path(&["Food and Drink", "Groceries"])),
+                ..edit(&["Shops", "Supermarkets and Groceries"])
+            }],
+        );
+        let groceries = transaction(&["Shops", "Supermarkets and Groceries"]);
+
+        assert!(taxonomy.in_category("Food and Drink", &groceries));
+        assert!(taxonomy.in_category("Food and Drink > Groceries", &groceries));
+        assert!(!taxonomy.in_category("Shops", &groceries));
+        assert_eq!(
+            taxonomy.label("Shops > Supermarkets and Groceries"),
+            "Food and Drink > Groceries"
+        );
+    }
+}
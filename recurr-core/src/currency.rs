@@ -0,0 +1,332 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{get_supbase_client, Error};
+
+pub const DEFAULT_CURRENCY: &str = "USD";
+
+/// Active ISO 4217 currency codes
+pub const ISO_CURRENCIES: &[&str] = &[
+    "AED", "AFN", "ALL", "AMD", "ANG", "AOA", "ARS", "AUD", "AWG", "AZN", "BAM", "BBD", "BDT",
+    "BGN", "BHD", "BIF", "BMD", "BND", "BOB", "BRL", "BSD", "BTN", "BWP", "BYN", "BZD", "CAD",
+    "CDF", "CHF", "CLP", "CNY", "COP", "CRC", "CUP", "CVE", "CZK", "DJF", "DKK", "DOP", "DZD",
+    "EGP", "ERN", "ETB", "EUR", "FJD", "FKP", "GBP", "GEL", "GHS", "GIP", "GMD", "GNF", "GTQ",
+    "GYD", "HKD", "HNL", "HTG", "HUF", "IDR", "ILS", "INR", "IQD", "IRR", "ISK", "JMD", "JOD",
+    "JPY", "KES", "KGS", "KHR", "KMF", "KPW", "KRW", "KWD", "KYD", "KZT", "LAK", "LBP", "LKR",
+    "LRD", "LSL", "LYD", "MAD", "MDL", "MGA", "MKD", "MMK", "MNT", "MOP", "MRU", "MUR", "MVR",
+    "MWK", "MXN", "MYR", "MZN", "NAD", "NGN", "NIO", "NOK", "NPR", "NZD", "OMR", "PAB", "PEN",
+    "PGK", "PHP", "PKR", "PLN", "PYG", "QAR", "RON", "RSD", "RUB", "RWF", "SAR", "SBD", "SCR",
+    "SDG", "SEK", "SGD", "SHP", "SLE", "SOS", "SRD", "SSP", "STN", "SVC", "SYP", "SZL", "THB",
+    "TJS", "TMT", "TND", "TOP", "TRY", "TTD", "TWD", "TZS", "UAH", "UGX", "USD", "UYU", "UZS",
+    "VES", "VND", "VUV", "WST", "XAF", "XCD", "XOF", "XPF", "YER", "ZAR", "ZMW", "ZWL",
+];
+
+/// Checks a base currency the user entered, returning it uppercased
+pub fn parse_currency(input: &str) -> Result<String, Error> {
+    let currency = input.trim().to_uppercase();
+    if currency.is_empty() {
+        return Err(Error::Other("Enter a currency code".to_string()));
+    }
+    if !ISO_CURRENCIES.contains(&currency.as_str()) {
+        return Err(Error::Other(format!(
+            "{currency} isn't an ISO 4217 currency code"
+        )));
+    }
+
+    Ok(currency)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SchemaUserSettings {
+    pub user_id: String,
+    pub base_currency: String,
+}
+
+/// A rate is quoted as units of `currency` per one unit of the user's base currency
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SchemaExchangeRate {
+    pub user_id: String,
+    pub currency: String,
+    pub rate: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExchangeRates {
+    base: String,
+    rates: HashMap<String, f64>,
+}
+
+impl Default for ExchangeRates {
+    fn default() -> Self {
+        Self::new(DEFAULT_CURRENCY, Vec::new())
+    }
+}
+
+impl ExchangeRates {
+    pub fn new(base: &str, rates: Vec<SchemaExchangeRate>) -> Self {
+        let mut rates: HashMap<String, f64> = rates
+            .into_iter()
+            .filter(|r| r.rate > 0.0)
+            .map(|r| (r.currency, r.rate))
+            .collect();
+        rates.insert(base.to_string(), 1.0);
+
+        Self {
+            base: base.to_string(),
+            rates,
+        }
+    }
+
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    pub fn rate(&self, currency: &str) -> Option<f64> {
+        self.rates.get(currency).copied()
+    }
+
+    /// Whether amounts in `currency` can be converted, amounts without a
+    /// currency are taken to be in the base currency
+    pub fn converts(&self, currency: Option<&str>) -> bool {
+        currency.is_none_or(|c| self.rates.contains_key(c))
+    }
+
+    /// Converts an amount into the base currency, `None` when there's no rate
+    /// for its currency. Totals leave those amounts out and list the currency
+    /// with [`Self::unconverted`]
+    pub fn to_base(&self, amount: f64, currency: Option<&str>) -> Option<f64> {
+        match currency {
+            Some(currency) => self.rate(currency).map(|rate| amount / rate),
+            None => Some(amount),
+        }
+    }
+
+    /// The currencies without a rate, sorted and without duplicates
+    pub fn unconverted<'a>(
+        &self,
+        currencies: impl IntoIterator<Item = Option<&'a str>>,
+    ) -> Vec<String> {
+        let mut missing: Vec<String> = currencies
+            .into_iter()
+            .flatten()
+            .filter(|c| !self.rates.contains_key(*c))
+            .map(str::to_string)
+            .collect();
+        missing.sort();
+        missing.dedup();
+        missing
+    }
+
+    /// Requotes every rate against a new base currency
+    pub fn rebase(&self, base: &str) -> Option<Self> {
+        let new_base_rate = self.rate(base)?;
+
+        let rates = self
+            .rates
+            .iter()
+            .map(|(currency, rate)| (currency.clone(), rate / new_base_rate))
+            .collect();
+
+        Some(Self {
+            base: base.to_string(),
+            rates,
+        })
+    }
+
+    pub fn to_schema(&self, user_id: &str) -> Vec<SchemaExchangeRate> {
+        let mut rows: Vec<SchemaExchangeRate> = self
+            .rates
+            .iter()
+            .filter(|(currency, _)| currency.as_str() != self.base)
+            .map(|(currency, rate)| SchemaExchangeRate {
+                user_id: user_id.to_string(),
+                currency: currency.clone(),
+                rate: *rate,
+            })
+            .collect();
+        rows.sort_by(|a, b| a.currency.cmp(&b.currency));
+        rows
+    }
+}
+
+/// Parses the ECB reference rates file (eurofxref-daily.xml), rates are quoted against EUR
+pub fn parse_ecb_xml(xml: &str) -> Result<ExchangeRates, Error> {
+    let mut rates = Vec::new();
+
+    for cube in xml.split("<Cube").skip(1) {
+        let currency = xml_attribute(cube, "currency");
+        let rate = xml_attribute(cube, "rate");
+
+        if let (Some(currency), Some(rate)) = (currency, rate) {
+            let rate = rate
+                .parse()
+                .map_err(|_| Error::Other(format!("Invalid rate for {currency}: {rate}")))?;
+
+            rates.push(SchemaExchangeRate {
+                user_id: String::new(),
+                currency: currency.to_string(),
+                rate,
+            });
+        }
+    }
+
+    if rates.is_empty() {
+        return Err(Error::Other("No exchange rates found".to_string()));
+    }
+
+    Ok(ExchangeRates::new("EUR", rates))
+}
+
+fn xml_attribute<'a>(element: &'a str, name: &str) -> Option<&'a str> {
+    let element = &element[..element.find('>').unwrap_or(element.len())];
+
+    for quote in ['\'', '"'] {
+        let key = format!("{name}={quote}");
+        if let Some(start) = element.find(&key) {
+            let value = &element[start + key.len()..];
+            return value.find(quote).map(|end| &value[..end]);
+        }
+    }
+
+    None
+}
+
+pub fn currency_symbol(currency: &str) -> Option<&'static str> {
+    match currency {
+        "USD" => Some("$"),
+        "CAD" => Some("CA$"),
+        "AUD" => Some("A$"),
+        "EUR" => Some("€"),
+        "GBP" => Some("£"),
+        "JPY" => Some("¥"),
+        "INR" => Some("₹"),
+        _ => None,
+    }
+}
+
+pub fn format_amount(amount: f64, currency: &str) -> String {
+    let sign = if amount < 0.0 { "-" } else { "" };
+    let decimals = if currency == "JPY" { 0 } else { 2 };
+    let amount = amount.abs();
+
+    match currency_symbol(currency) {
+        Some(symbol) => format!("{sign}{symbol}{amount:.decimals$}"),
+        None => format!("{sign}{amount:.decimals$} {currency}"),
+    }
+}
+
+/// The user's base currency with the rates saved against it
+pub async fn get_exchange_rates(auth_key: &str, user_id: &str) -> Result<ExchangeRates, Error> {
+    let client = get_supbase_client();
+
+    let settings: Vec<SchemaUserSettings> = client
+        .from("user_settings")
+        .auth(auth_key)
+        .select("*")
+        .eq("user_id", user_id)
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    let base = settings
+        .first()
+        .map_or(DEFAULT_CURRENCY.to_string(), |s| s.base_currency.clone());
+
+    let rates: Vec<SchemaExchangeRate> = client
+        .from("exchange_rates")
+        .auth(auth_key)
+        .select("*")
+        .eq("user_id", user_id)
+        .execute()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::Request(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| Error::Request(e.to_string()))?;
+
+    Ok(ExchangeRates::new(&base, rates))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rates(base: &str, rates: &[(&str, f64)]) -> ExchangeRates {
+        let rates = rates
+            .iter()
+            .map(|(currency, rate)| SchemaExchangeRate {
+                user_id: "user".to_string(),
+                currency: currency.to_string(),
+                rate: *rate,
+            })
+            .collect();
+        ExchangeRates::new(base, rates)
+    }
+
+    #[test]
+    fn parses_ecb_reference_rates() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gesmes:Envelope xmlns:gesmes="http://www.gesmes.org/xml/2002-08-01">
+    <Cube>
+        <Cube time='2026-10-16'>
+            <Cube currency='USD' rate='1.1600'/>
+            <Cube currency="JPY" rate="171.20"/>
+        </Cube>
+    </Cube>
+</gesmes:Envelope>"#;
+
+        let parsed = parse_ecb_xml(xml).unwrap();
+        assert_eq!(parsed.base(), "EUR");
+        assert_eq!(parsed.rate("USD"), Some(1.16));
+        assert_eq!(parsed.rate("JPY"), Some(171.2));
+        assert_eq!(parsed.rate("EUR"), Some(1.0));
+
+        assert!(parse_ecb_xml("<Cube><Cube time='2026-10-16'/></Cube>").is_err());
+        assert!(parse_ecb_xml("<Cube currency='USD' rate='abc'/>").is_err());
+    }
+
+    #[test]
+    fn rebase_requotes_against_the_new_base() {
+        let eur = rates("EUR", &[("USD", 1.25), ("GBP", 0.5)]);
+
+        let usd = eur.rebase("USD").unwrap();
+        assert_eq!(usd.base(), "USD");
+        assert_eq!(usd.rate("USD"), Some(1.0));
+        assert_eq!(usd.rate("EUR"), Some(0.8));
+        assert_eq!(usd.rate("GBP"), Some(0.4));
+
+        assert_eq!(eur.rebase("CAD"), None);
+    }
+
+    #[test]
+    fn to_base_leaves_out_currencies_without_a_rate() {
+        let usd = rates("USD", &[("EUR", 0.8)]);
+
+        assert_eq!(usd.to_base(8.0, Some("EUR")), Some(10.0));
+        assert_eq!(usd.to_base(8.0, Some("USD")), Some(8.0));
+        assert_eq!(usd.to_base(8.0, None), Some(8.0));
+        assert_eq!(usd.to_base(8.0, Some("CAD")), None);
+
+        assert!(usd.converts(None) && usd.converts(Some("EUR")));
+        assert!(!usd.converts(Some("CAD")));
+        assert_eq!(
+            usd.unconverted([Some("CAD"), None, Some("EUR"), Some("AUD"), Some("CAD")]),
+            vec!["AUD", "CAD"]
+        );
+    }
+
+    #[test]
+    fn base_currency_must_be_an_iso_code() {
+        assert_eq!(parse_currency(" eur ").unwrap(), "EUR");
+        assert!(parse_currency("").is_err());
+        assert!(parse_currency("   ").is_err());
+        assert!(parse_currency("XYZ").is_err());
+        assert!(parse_currency("US").is_err());
+    }
+}
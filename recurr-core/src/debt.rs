@@ -0,0 +1,353 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::{budgets::BudgetPeriod, Error};
+
+/// Plans that haven't paid everything off by then never will at that payment
+pub const MAX_MONTHS: usize = 600;
+
+/// Interest and minimum payment for a credit or loan account, entered by hand
+/// or imported from Plaid's liabilities
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SchemaDebtTerms {
+    pub user_id: String,
+    pub account_id: String,
+    /// Yearly rate as a percentage
+    pub apr: f64,
+    pub minimum_payment: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Debt {
+    pub account_id: String,
+    pub name: String,
+    pub balance: f64,
+    pub apr: f64,
+    pub minimum_payment: f64,
+}
+
+/// Which debt gets whatever is left after every minimum is paid
+#[derive(Debug, Clone, PartialEq)]
+pub enum Strategy {
+    /// Highest APR first, the least interest overall
+    Avalanche,
+    /// Smallest balance first, the quickest payoffs
+    Snowball,
+    /// In the order of these account ids, any others after them by APR
+    Custom(Vec<String>),
+}
+
+impl Strategy {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Strategy::Avalanche => "Avalanche",
+            Strategy::Snowball => "Snowball",
+            Strategy::Custom(_) => "Custom",
+        }
+    }
+
+    /// Indexes of `debts` in the order extra payments go to them
+    fn order(&self, debts: &[Debt]) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..debts.len()).collect();
+        let by_apr = |a: &usize, b: &usize| {
+            debts[*b]
+                .apr
+                .total_cmp(&debts[*a].apr)
+                .then_with(|| debts[*a].balance.total_cmp(&debts[*b].balance))
+        };
+
+        match self {
+            Strategy::Avalanche => order.sort_by(by_apr),
+            Strategy::Snowball => order.sort_by(|a, b| {
+                debts[*a]
+                    .balance
+                    .total_cmp(&debts[*b].balance)
+                    .then_with(|| by_apr(a, b))
+            }),
+            Strategy::Custom(ids) => {
+                let position = |i: &usize| {
+                    ids.iter()
+                        .position(|id| *id == debts[*i].account_id)
+                        .unwrap_or(usize::MAX)
+                };
+                order.sort_by(|a, b| position(a).cmp(&position(b)).then_with(|| by_apr(a, b)));
+            }
+        }
+
+        order
+    }
+}
+
+/// What happened to one debt in one month
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduleLine {
+    pub account_id: String,
+    pub interest: f64,
+    pub payment: f64,
+    /// Left owing after the payment
+    pub balance: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduleMonth {
+    pub date: NaiveDate,
+    pub lines: Vec<ScheduleLine>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Payoff {
+    pub account_id: String,
+    /// The month the debt is paid off, `None` if it isn't within [`MAX_MONTHS`]
+    pub date: Option<NaiveDate>,
+    pub interest: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plan {
+    pub schedule: Vec<ScheduleMonth>,
+    /// In the order of the debts given
+    pub payoffs: Vec<Payoff>,
+    pub total_interest: f64,
+    pub total_paid: f64,
+}
+
+impl Plan {
+    /// When the last debt is paid off
+    pub fn debt_free(&self) -> Option<NaiveDate> {
+        self.payoffs
+            .iter()
+            .map(|p| p.date)
+            .collect::<Option<Vec<NaiveDate>>>()?
+            .into_iter()
+            .max()
+    }
+}
+
+/// Pays `monthly_payment` towards `debts` each month from `start`. Interest
+/// accrues monthly at APR / 12, then every minimum is paid and the rest goes to
+/// debts in the strategy's order. Money freed by a paid off debt rolls over to the next
+pub fn simulate(
+    debts: &[Debt],
+    monthly_payment: f64,
+    strategy: &Strategy,
+    start: NaiveDate,
+) -> Result<Plan, Error> {
+    let minimums: f64 = debts
+        .iter()
+        .filter(|d| d.balance > 0.0)
+        .map(|d| d.minimum_payment)
+        .sum();
+    if monthly_payment + 0.005 < minimums {
+        return Err(Error::Other(format!(
+            "A monthly payment of {monthly_payment:.2} doesn't cover the minimums of {minimums:.2}"
+        )));
+    }
+
+    let order = strategy.order(debts);
+    let mut balances: Vec<f64> = debts.iter().map(|d| d.balance.max(0.0)).collect();
+    let mut payoffs: Vec<Payoff> = debts
+        .iter()
+        .zip(&balances)
+        .map(|(d, balance)| Payoff {
+            account_id: d.account_id.clone(),
+            date: (*balance <= 0.0).then_some(start),
+            interest: 0.0,
+        })
+        .collect();
+
+    let mut schedule = Vec::new();
+    let mut total_paid = 0.0;
+
+    for month in 0..MAX_MONTHS {
+        if balances.iter().all(|b| *b <= 0.0) {
+            break;
+        }
+
+        let date = BudgetPeriod::Monthly
+            .period(1, None, start, month as i64)
+            .start;
+        let mut lines: Vec<ScheduleLine> = debts
+            .iter()
+            .map(|d| ScheduleLine {
+                account_id: d.account_id.clone(),
+                interest: 0.0,
+                payment: 0.0,
+                balance: 0.0,
+            })
+            .collect();
+
+        for (i, d) in debts.iter().enumerate() {
+            if balances[i] <= 0.0 {
+                continue;
+            }
+
+            let interest = round(balances[i] * d.apr / 100.0 / 12.0);
+            balances[i] += interest;
+            lines[i].interest = interest;
+            payoffs[i].interest += interest;
+        }
+
+        let mut available = monthly_payment;
+        for (i, d) in debts.iter().enumerate() {
+            let payment = d.minimum_payment.min(balances[i]).min(available);
+            pay(&mut balances[i], &mut lines[i], &mut available, payment);
+        }
+        for i in &order {
+            let payment = balances[*i].min(available);
+            pay(&mut balances[*i], &mut lines[*i], &mut available, payment);
+        }
+
+        for (i, line) in lines.iter_mut().enumerate() {
+            line.balance = balances[i];
+            total_paid += line.payment;
+            if balances[i] <= 0.0 && payoffs[i].date.is_none() {
+                payoffs[i].date = Some(date);
+            }
+        }
+
+        schedule.push(ScheduleMonth { date, lines });
+    }
+
+    let total_interest = payoffs.iter().map(|p| p.interest).sum();
+
+    Ok(Plan {
+        schedule,
+        payoffs,
+        total_interest: round(total_interest),
+        total_paid: round(total_paid),
+    })
+}
+
+fn pay(balance: &mut f64, line: &mut ScheduleLine, available: &mut f64, payment: f64) {
+    if payment <= 0.0 {
+        return;
+    }
+
+    *balance = round(*balance - payment);
+    *available = round(*available - payment);
+    line.payment = round(line.payment + payment);
+}
+
+fn round(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn debt(id: &str, balance: f64, apr: f64, minimum_payment: f64) -> Debt {
+        Debt {
+            account_id: id.to_string(),
+            name: id.to_string(),
+            balance,
+            apr,
+            minimum_payment,
+        }
+    }
+
+    fn start() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 11, 1).unwrap()
+    }
+
+    fn date(y: i32, m: u32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(y, m, 1)
+    }
+
+    #[test]
+    fn interest_free_debt_pays_down_evenly() {
+        let plan = simulate(
+            &[debt("a", 300.0, 0.0, 50.0)],
+            100.0,
+            &Strategy::Avalanche,
+            start(),
+        )
+        .unwrap();
+
+        assert_eq!(plan.schedule.len(), 3);
+        assert_eq!(plan.payoffs[0].date, date(2027, 1));
+        assert_eq!(plan.total_interest, 0.0);
+        assert_eq!(plan.total_paid, 300.0);
+        assert_eq!(plan.schedule[1].lines[0].balance, 100.0);
+    }
+
+    #[test]
+    fn interest_accrues_monthly() {
+        let plan = simulate(
+            &[debt("a", 1200.0, 12.0, 10.0)],
+            1212.0,
+            &Strategy::Avalanche,
+            start(),
+        )
+        .unwrap();
+
+        assert_eq!(plan.schedule.len(), 1);
+        assert_eq!(plan.schedule[0].lines[0].interest, 12.0);
+        assert_eq!(plan.total_paid, 1212.0);
+    }
+
+    #[test]
+    fn avalanche_targets_highest_apr_and_snowball_smallest_balance() {
+        let debts = [
+            debt("card", 2000.0, 24.0, 25.0),
+            debt("loan", 500.0, 5.0, 25.0),
+        ];
+
+        let avalanche = simulate(&debts, 300.0, &Strategy::Avalanche, start()).unwrap();
+        assert_eq!(avalanche.schedule[0].lines[0].payment, 275.0);
+        assert_eq!(avalanche.schedule[0].lines[1].payment, 25.0);
+
+        let snowball = simulate(&debts, 300.0, &Strategy::Snowball, start()).unwrap();
+        assert_eq!(snowball.schedule[0].lines[0].payment, 25.0);
+        assert_eq!(snowball.schedule[0].lines[1].payment, 275.0);
+
+        assert!(snowball.payoffs[1].date < avalanche.payoffs[1].date);
+        assert!(avalanche.total_interest < snowball.total_interest);
+    }
+
+    #[test]
+    fn custom_order_goes_first_then_by_apr() {
+        let debts = [
+            debt("a", 1000.0, 20.0, 0.0),
+            debt("b", 1000.0, 10.0, 0.0),
+            debt("c", 1000.0, 15.0, 0.0),
+        ];
+        let strategy = Strategy::Custom(vec!["b".to_string()]);
+
+        assert_eq!(strategy.order(&debts), vec![1, 0, 2]);
+    }
+
+    #[test]
+    fn freed_payments_roll_over() {
+        let debts = [debt("a", 100.0, 0.0, 50.0), debt("b", 1000.0, 0.0, 50.0)];
+        let plan = simulate(&debts, 200.0, &Strategy::Snowball, start()).unwrap();
+
+        // "a" is paid off in the first month and the rest goes to "b"
+        assert_eq!(plan.schedule[0].lines[0].payment, 100.0);
+        assert_eq!(plan.schedule[0].lines[1].payment, 100.0);
+        assert_eq!(plan.schedule[1].lines[1].payment, 200.0);
+        assert_eq!(plan.debt_free(), date(2027, 4));
+    }
+
+    #[test]
+    fn payment_must_cover_minimums() {
+        let debts = [debt("a", 1000.0, 10.0, 60.0), debt("b", 1000.0, 10.0, 60.0)];
+
+        assert!(simulate(&debts, 100.0, &Strategy::Avalanche, start()).is_err());
+    }
+
+    #[test]
+    fn payments_below_interest_never_finish() {
+        let plan = simulate(
+            &[debt("a", 10000.0, 30.0, 100.0)],
+            100.0,
+            &Strategy::Avalanche,
+            start(),
+        )
+        .unwrap();
+
+        assert_eq!(plan.schedule.len(), MAX_MONTHS);
+        assert_eq!(plan.payoffs[0].date, None);
+        assert_eq!(plan.debt_free(), None);
+    }
+}
@@ -0,0 +1,239 @@
+use postgrest::{Builder, Postgrest};
+use serde::{Deserialize, Serialize};
+
+use crate::{categories::Taxonomy, pfc, search::Query, to_pg_array, Error};
+
+/// Which transactions to show, kept with the transactions tab and stored by
+/// saved filters, which reports also use as scopes
+#[derive(Debug, Default, Deserialize, PartialEq, Clone, Serialize)]
+pub struct Filter {
+    /// First day shown, inclusive
+    pub start_date: Option<String>,
+    /// Last day shown, inclusive
+    pub end_date: Option<String>,
+    pub category: Option<String>,
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub account_id: Option<String>,
+    #[serde(default)]
+    pub min_amount: Option<f64>,
+    #[serde(default)]
+    pub max_amount: Option<f64>,
+    #[serde(default)]
+    pub merchant: Option<String>,
+    #[serde(default)]
+    pub pending: Option<bool>,
+    /// A search like `merchant:starbucks amount>20 -pending`, see [`Query`]
+    #[serde(default)]
+    pub search: Option<String>,
+    /// Only transactions without a legacy or PFC category
+    #[serde(default)]
+    pub uncategorized: bool,
+}
+
+impl Filter {
+    /// The category key searched for, from the filters or the search
+    fn category(&self, taxonomy: &Taxonomy, search: &Query) -> Result<Option<String>, Error> {
+        // Searches name a category the way it's shown
+        let searched = match search.category() {
+            Some(name) => Some(
+                taxonomy
+                    .find(name)
+                    .ok_or_else(|| Error::Other(format!("No category named {name}")))?,
+            ),
+            None => None,
+        };
+
+        match (self.category.as_deref(), searched.as_deref()) {
+            (Some(a), Some(b)) if taxonomy.path_of(a) != taxonomy.path_of(b) => Err(Error::Other(
+                "Pick a category in the filters or the search, not both".to_string(),
+            )),
+            (category, searched) => Ok(category.or(searched).map(str::to_string)),
+        }
+    }
+
+    /// The filter's transactions, newest first. Callers add auth and a range
+    pub fn query(&self, client: &Postgrest, taxonomy: &Taxonomy) -> Result<Builder, Error> {
+        let search = Query::parse(self.search.as_deref().unwrap_or_default())?;
+        let category = self.category(taxonomy, &search)?;
+
+        // Categories are matched by `transactions_in_category` so pages and
+        // counts only ever hold matching transactions
+        let mut query = match category.as_deref() {
+            Some(category) => {
+                let params = serde_json::json!({
+                    "paths": taxonomy.paths_in(category),
+                    "pfc": pfc::is_pfc_key(category).then_some(category),
+                });
+                client.rpc("transactions_in_category", params.to_string())
+            }
+            None => client.from("user_transactions").select("*"),
+        }
+        .order("date.desc,transaction_id");
+
+        if let Some(start_date) = &self.start_date {
+            query = query.gte("date", start_date);
+        }
+
+        if let Some(end_date) = &self.end_date {
+            query = query.lte("date", end_date);
+        }
+
+        if let Some(tag) = &self.tag {
+            query = query.cs("tags", to_pg_array(std::slice::from_ref(tag)));
+        }
+
+        if let Some(account_id) = &self.account_id {
+            query = query.eq("account_id", account_id);
+        }
+
+        if let Some(min) = self.min_amount {
+            query = query.gte("amount", min.to_string());
+        }
+
+        if let Some(max) = self.max_amount {
+            query = query.lte("amount", max.to_string());
+        }
+
+        if let Some(merchant) = &self.merchant {
+            query = query.ilike("search_merchant", format!("%{merchant}%"));
+        }
+
+        if let Some(pending) = self.pending {
+            query = query.eq("pending", pending.to_string());
+        }
+
+        if self.uncategorized {
+            query = query
+                .is("personal_finance_category", "null")
+                .or("category.is.null,category.eq.{}");
+        }
+
+        if let Some(filters) = search.filters() {
+            query = query.and(filters);
+        }
+
+        Ok(query)
+    }
+
+    /// Arguments for the `filter_totals` and `filter_spending_summary` functions,
+    /// which apply the same conditions as [`Filter::query`] in `filter_transactions`
+    pub fn params(&self, taxonomy: &Taxonomy) -> Result<String, Error> {
+        let search = Query::parse(self.search.as_deref().unwrap_or_default())?;
+        let category = self.category(taxonomy, &search)?;
+
+        Ok(serde_json::json!({
+            "filter": {
+                "paths": category.as_deref().map(|c| taxonomy.paths_in(c)),
+                "pfc": category.as_deref().filter(|c| pfc::is_pfc_key(c)),
+                "start_date": self.start_date,
+                "end_date": self.end_date,
+                "tag": self.tag,
+                "account_id": self.account_id,
+                "min_amount": self.min_amount,
+                "max_amount": self.max_amount,
+                "merchant": self.merchant,
+                "pending": self.pending,
+                "uncategorized": self.uncategorized,
+                "terms": search.terms(),
+            }
+        })
+        .to_string())
+    }
+
+    /// A copy narrowed to at most `start..=end`, for reports scoped by a saved filter
+    pub fn within(&self, start: &str, end: &str) -> Self {
+        let mut filter = self.clone();
+        filter.start_date = Some(
+            self.start_date
+                .clone()
+                .filter(|s| s.as_str() > start)
+                .unwrap_or_else(|| start.to_string()),
+        );
+        filter.end_date = Some(
+            self.end_date
+                .clone()
+                .filter(|e| e.as_str() < end)
+                .unwrap_or_else(|| end.to_string()),
+        );
+        filter
+    }
+}
+
+/// A named filter shown in the sidebar
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SchemaSavedFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    pub user_id: String,
+    pub name: String,
+    pub filter: Filter,
+}
+
+/// The total in a PostgREST `content-range` header like "0-24/311" or "*/311",
+/// sent when a query asks for an exact count
+pub fn range_total(content_range: &str) -> Option<u64> {
+    content_range.rsplit_once('/')?.1.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_saved_before_new_fields_still_load() {
+        let filter: Filter = serde_json::from_value(serde_json::json!({
+            "start_date": "2026-01-01",
+            "end_date": null,
+            "category": "Shops",
+            "tag": null,
+        }))
+        .unwrap();
+
+        assert_eq!(filter.category.as_deref(), Some("Shops"));
+        assert!(!filter.uncategorized);
+        assert_eq!(filter.search, None);
+    }
+
+    #[test]
+    fn within_keeps_the_narrower_range() {
+        let filter = Filter {
+            start_date: Some("2026-03-01".to_string()),
+            ..Default::default()
+        };
+
+        let scoped = filter.within("2026-01-01", "2026-06-30");
+        assert_eq!(scoped.start_date.as_deref(), Some("2026-03-01"));
+        assert_eq!(scoped.end_date.as_deref(), Some("2026-06-30"));
+
+        let scoped = filter.within("2026-04-01", "2026-04-30");
+        assert_eq!(scoped.start_date.as_deref(), Some("2026-04-01"));
+    }
+
+    #[test]
+    fn params_carry_the_filters_and_search() {
+        let filter = Filter {
+            start_date: Some("2026-01-01".to_string()),
+            uncategorized: true,
+            search: Some("coffee -tag:work".to_string()),
+            ..Default::default()
+        };
+
+        let params: serde_json::Value =
+            serde_json::from_str(&filter.params(&Taxonomy::default()).unwrap()).unwrap();
+        let filter = &params["filter"];
+        assert_eq!(filter["start_date"], "2026-01-01");
+        assert_eq!(filter["paths"], serde_json::Value::Null);
+        assert_eq!(filter["uncategorized"], true);
+        assert_eq!(filter["terms"][0]["kind"], "text");
+        assert_eq!(filter["terms"][1]["negated"], true);
+    }
+
+    #[test]
+    fn totals_come_from_the_content_range() {
+        assert_eq!(range_total("0-24/311"), Some(311));
+        assert_eq!(range_total("*/0"), Some(0));
+        assert_eq!(range_total("0-24/*"), None);
+        assert_eq!(range_total(""), None);
+    }
+}
@@ -0,0 +1,700 @@
+use std::collections::HashMap;
+
+use chrono::{Duration, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    balances::AccountClass,
+    budgets::{BudgetKind, BudgetPeriod, Period},
+    categories::Taxonomy,
+    currency::ExchangeRates,
+    income::payer,
+    suggestions::quantile,
+    Account, SchemaBudget, Transaction,
+};
+
+/// Horizons the forecast is offered for, in days
+pub const HORIZONS: [usize; 3] = [30, 90, 365];
+/// How much history recurring streams are detected from
+pub const HISTORY_DAYS: i64 = 400;
+
+/// How far off its schedule or usual amount a payment can be and still count
+const TOLERANCE: f64 = 0.25;
+
+/// A payer's payments to one account, inflows and outflows kept apart
+type Payments<'a> = HashMap<(&'a str, String, bool), Vec<(NaiveDate, f64, &'a Transaction)>>;
+
+/// A payment that repeats on a schedule, detected from an account's history
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RecurringStream {
+    pub name: String,
+    /// In the base currency, positive for money leaving the account like Plaid's amounts
+    pub amount: f64,
+    pub frequency: BudgetPeriod,
+    /// The latest payment, its account and category stand for the stream's
+    pub last: Transaction,
+}
+
+impl RecurringStream {
+    pub fn account_id(&self) -> &str {
+        &self.last.account_id
+    }
+
+    /// Expected dates within `window`. The latest payment that's late is
+    /// expected on the window's first day
+    pub fn occurrences(&self, window: &Period) -> Vec<NaiveDate> {
+        let Ok(last) = NaiveDate::parse_from_str(&self.last.date, "%Y-%m-%d") else {
+            return Vec::new();
+        };
+
+        let mut dates = schedule(last, Some(self.frequency), None, window.end);
+        dates.retain(|d| *d > last);
+        if let Some(late) = dates.iter().rposition(|d| *d < window.start) {
+            dates.drain(..late);
+            dates[0] = window.start;
+        }
+
+        dates
+    }
+}
+
+/// Streams of at least three payments from the same payer to the same account
+/// at a steady interval and amount. Yearly ones need only two. Streams that
+/// have missed more than one payment are treated as ended
+pub fn detect_recurring(
+    transactions: &[Transaction],
+    rates: &ExchangeRates,
+    today: NaiveDate,
+) -> Vec<RecurringStream> {
+    let mut groups: Payments = HashMap::new();
+    for t in transactions.iter().filter(|t| !t.pending) {
+        let Ok(date) = NaiveDate::parse_from_str(&t.date, "%Y-%m-%d") else {
+            continue;
+        };
+        let Some(amount) = rates.to_base(t.amount, t.iso_currency_code.as_deref()) else {
+            continue;
+        };
+
+        groups
+            .entry((t.account_id.as_str(), payer(t).to_lowercase(), amount < 0.0))
+            .or_default()
+            .push((date, amount, t));
+    }
+
+    let mut streams: Vec<RecurringStream> = groups
+        .into_values()
+        .filter_map(|mut payments| {
+            payments.sort_by_key(|p| p.0);
+
+            let gaps: Vec<i64> = payments
+                .windows(2)
+                .map(|w| (w[1].0 - w[0].0).num_days())
+                .collect();
+            let frequency = frequency(median(gaps.iter().map(|g| *g as f64).collect())?)?;
+            let days = nominal_days(frequency);
+
+            let needed = if frequency == BudgetPeriod::Yearly {
+                2
+            } else {
+                3
+            };
+            if payments.len() < needed
+                || gaps
+                    .iter()
+                    .any(|g| (*g as f64 - days).abs() > days * TOLERANCE)
+            {
+                return None;
+            }
+
+            let recent: Vec<f64> = payments.iter().rev().take(3).map(|p| p.1).collect();
+            let amount = median(payments.iter().map(|p| p.1).collect())?;
+            if recent
+                .iter()
+                .any(|a| (a - amount).abs() > amount.abs() * TOLERANCE)
+            {
+                return None;
+            }
+
+            let (last_date, _, last) = payments.last()?;
+            if (today - *last_date).num_days() as f64 > days * (2.0 + TOLERANCE) {
+                return None;
+            }
+
+            Some(RecurringStream {
+                name: payer(last).to_string(),
+                amount: (median(recent)? * 100.0).round() / 100.0,
+                frequency,
+                last: (*last).clone(),
+            })
+        })
+        .collect();
+    streams.sort_by(|a, b| {
+        a.name
+            .cmp(&b.name)
+            .then_with(|| a.last.date.cmp(&b.last.date))
+    });
+
+    streams
+}
+
+/// A one-off or repeating payment the user expects, like a bill that isn't
+/// paid from a linked account yet or an upcoming tax refund
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SchemaScheduledItem {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    pub user_id: String,
+    pub account_id: String,
+    pub name: String,
+    /// Positive for money leaving the account like Plaid's amounts
+    pub amount: f64,
+    pub iso_currency_code: Option<String>,
+    /// The first payment
+    pub date: String,
+    /// `None` for a one-off payment
+    pub frequency: Option<BudgetPeriod>,
+    pub end_date: Option<String>,
+}
+
+impl SchemaScheduledItem {
+    pub fn occurrences(&self, window: &Period) -> Vec<NaiveDate> {
+        let date = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok();
+        let Some(first) = date(&self.date) else {
+            return Vec::new();
+        };
+
+        let mut dates = schedule(
+            first,
+            self.frequency,
+            self.end_date.as_deref().and_then(date),
+            window.end,
+        );
+        dates.retain(|d| *d >= window.start);
+
+        dates
+    }
+}
+
+/// Expected spending each day of `window` from spending budgets, the part of
+/// each budget's max that recurring payments in its category don't already
+/// cover, spread evenly over the rest of its period. Budgets nested in another
+/// budget's category are left to their parent. `budgets` pairs each budget
+/// with what it has spent in its current period
+pub fn budget_burn(
+    taxonomy: &Taxonomy,
+    budgets: &[(&SchemaBudget, f64)],
+    streams: &[RecurringStream],
+    window: &Period,
+) -> Vec<f64> {
+    let days = (window.end - window.start).num_days().max(0) as usize;
+    let mut burn = vec![0.0; days];
+
+    let spending: Vec<&(&SchemaBudget, f64)> = budgets
+        .iter()
+        .filter(|(b, _)| b.kind == BudgetKind::Spending && b.max > 0.0)
+        .collect();
+
+    for (budget, spent) in &spending {
+        if spending
+            .iter()
+            .any(|(other, _)| nested(taxonomy, &budget.category_id, &other.category_id))
+        {
+            continue;
+        }
+
+        let recurring: Vec<&RecurringStream> = streams
+            .iter()
+            .filter(|s| s.amount > 0.0 && taxonomy.in_category(&budget.category_id, &s.last))
+            .collect();
+
+        let last_day = window.end - Duration::days(1);
+        for period in budget.periods_between(window.start, last_day) {
+            let from = period.start.max(window.start);
+            let to = period.end.min(window.end);
+            if from >= to {
+                continue;
+            }
+
+            let rest = Period {
+                start: from,
+                end: period.end,
+            };
+            let expected: f64 = recurring
+                .iter()
+                .map(|s| s.amount * s.occurrences(&rest).len() as f64)
+                .sum();
+            let spent = if period.start <= window.start {
+                *spent
+            } else {
+                0.0
+            };
+
+            let daily =
+                (budget.max - spent - expected).max(0.0) / (period.end - from).num_days() as f64;
+            for day in (from - window.start).num_days()..(to - window.start).num_days() {
+                burn[day as usize] += daily;
+            }
+        }
+    }
+
+    burn
+}
+
+/// A payment expected on a day of the forecast
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ForecastEvent {
+    pub date: String,
+    pub account_id: String,
+    pub name: String,
+    pub amount: f64,
+    /// Entered by the user rather than detected
+    pub scheduled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ForecastAccount {
+    pub account_id: String,
+    pub name: String,
+    /// End of day balance for each of the forecast's dates, in the base currency
+    pub balances: Vec<f64>,
+}
+
+/// Projected end of day balances of cash accounts from today on
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct Forecast {
+    pub dates: Vec<String>,
+    pub accounts: Vec<ForecastAccount>,
+    /// Budgeted spending so far, it isn't tied to an account so only the total includes it
+    pub budgeted: Vec<f64>,
+    pub total: Vec<f64>,
+    pub events: Vec<ForecastEvent>,
+}
+
+impl Forecast {
+    /// Indexes of the days the total drops below `threshold`
+    pub fn low_days(&self, threshold: f64) -> Vec<usize> {
+        self.total
+            .iter()
+            .enumerate()
+            .filter(|(_, balance)| **balance < threshold)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// The day with the lowest total and its balance
+    pub fn lowest(&self) -> Option<(&str, f64)> {
+        self.dates
+            .iter()
+            .zip(&self.total)
+            .min_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(date, balance)| (date.as_str(), *balance))
+    }
+}
+
+/// Projects each cash account's balance `days` days from `start` with the
+/// recurring and scheduled payments expected on it, taking `burn` from
+/// [`budget_burn`] off the total. Accounts in a currency without a rate are
+/// left out
+pub fn forecast(
+    accounts: &[Account],
+    streams: &[RecurringStream],
+    scheduled: &[SchemaScheduledItem],
+    burn: &[f64],
+    rates: &ExchangeRates,
+    start: NaiveDate,
+    days: usize,
+) -> Forecast {
+    let window = Period {
+        start,
+        end: start + Duration::days(days as i64),
+    };
+
+    let cash: Vec<&Account> = accounts
+        .iter()
+        .filter(|a| AccountClass::of(&a.account_type) == Some(AccountClass::Cash))
+        .filter(|a| rates.converts(a.balances.iso_currency_code.as_deref()))
+        .collect();
+    let is_cash = |id: &str| cash.iter().any(|a| a.account_id == id);
+
+    let mut events: Vec<(NaiveDate, ForecastEvent)> = Vec::new();
+    for s in streams.iter().filter(|s| is_cash(s.account_id())) {
+        for date in s.occurrences(&window) {
+            events.push((
+                date,
+                ForecastEvent {
+                    date: date.format("%Y-%m-%d").to_string(),
+                    account_id: s.account_id().to_string(),
+                    name: s.name.clone(),
+                    amount: s.amount,
+                    scheduled: false,
+                },
+            ));
+        }
+    }
+    for item in scheduled.iter().filter(|i| is_cash(&i.account_id)) {
+        let Some(amount) = rates.to_base(item.amount, item.iso_currency_code.as_deref()) else {
+            continue;
+        };
+        for date in item.occurrences(&window) {
+            events.push((
+                date,
+                ForecastEvent {
+                    date: date.format("%Y-%m-%d").to_string(),
+                    account_id: item.account_id.clone(),
+                    name: item.name.clone(),
+                    amount,
+                    scheduled: true,
+                },
+            ));
+        }
+    }
+    events.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.name.cmp(&b.1.name)));
+
+    let mut balances: Vec<f64> = cash
+        .iter()
+        .map(|a| {
+            rates
+                .to_base(
+                    a.balances.current.unwrap_or_default(),
+                    a.balances.iso_currency_code.as_deref(),
+                )
+                .unwrap_or_default()
+        })
+        .collect();
+    let mut forecast = Forecast {
+        accounts: cash
+            .iter()
+            .map(|a| ForecastAccount {
+                account_id: a.account_id.clone(),
+                name: a.name.clone(),
+                balances: Vec::with_capacity(days),
+            })
+            .collect(),
+        ..Default::default()
+    };
+
+    let mut budgeted = 0.0;
+    let mut pending = events.iter().peekable();
+    for day in 0..days {
+        let date = start + Duration::days(day as i64);
+        while let Some((_, event)) = pending.next_if(|(d, _)| *d == date) {
+            if let Some(i) = cash.iter().position(|a| a.account_id == event.account_id) {
+                balances[i] -= event.amount;
+            }
+        }
+        budgeted += burn.get(day).copied().unwrap_or_default();
+
+        for (account, balance) in forecast.accounts.iter_mut().zip(&balances) {
+            account.balances.push(round(*balance));
+        }
+        forecast.dates.push(date.format("%Y-%m-%d").to_string());
+        forecast.budgeted.push(round(budgeted));
+        forecast
+            .total
+            .push(round(balances.iter().sum::<f64>() - budgeted));
+    }
+    forecast.events = events.into_iter().map(|(_, e)| e).collect();
+
+    forecast
+}
+
+/// Dates from `first` every `frequency` until `end` (exclusive) or `until` (inclusive)
+fn schedule(
+    first: NaiveDate,
+    frequency: Option<BudgetPeriod>,
+    until: Option<NaiveDate>,
+    end: NaiveDate,
+) -> Vec<NaiveDate> {
+    let mut dates = Vec::new();
+    let mut n = 0;
+    while let Some(date) = advance(first, frequency, n) {
+        if date >= end || until.is_some_and(|u| date > u) {
+            break;
+        }
+
+        dates.push(date);
+        if frequency.is_none() {
+            break;
+        }
+        n += 1;
+    }
+
+    dates
+}
+
+/// `first` moved `n` periods on, months past the end of a shorter month land on its last day
+fn advance(first: NaiveDate, frequency: Option<BudgetPeriod>, n: u32) -> Option<NaiveDate> {
+    match frequency {
+        None => Some(first),
+        Some(BudgetPeriod::Weekly) => Some(first + Duration::weeks(n as i64)),
+        Some(BudgetPeriod::Biweekly) => Some(first + Duration::weeks(2 * n as i64)),
+        Some(BudgetPeriod::Monthly) => first.checked_add_months(Months::new(n)),
+        Some(BudgetPeriod::Quarterly) => first.checked_add_months(Months::new(3 * n)),
+        Some(BudgetPeriod::Yearly) => first.checked_add_months(Months::new(12 * n)),
+    }
+}
+
+fn nominal_days(frequency: BudgetPeriod) -> f64 {
+    match frequency {
+        BudgetPeriod::Weekly => 7.0,
+        BudgetPeriod::Biweekly => 14.0,
+        BudgetPeriod::Monthly => 365.25 / 12.0,
+        BudgetPeriod::Quarterly => 365.25 / 4.0,
+        BudgetPeriod::Yearly => 365.25,
+    }
+}
+
+/// The schedule a typical gap between payments is closest to, if any is close enough
+fn frequency(gap: f64) -> Option<BudgetPeriod> {
+    BudgetPeriod::ALL
+        .into_iter()
+        .find(|f| (gap - nominal_days(*f)).abs() <= nominal_days(*f) * TOLERANCE)
+}
+
+/// Whether budget `key` sits under the `parent` budget's category
+fn nested(taxonomy: &Taxonomy, key: &str, parent: &str) -> bool {
+    if key == parent {
+        return false;
+    }
+
+    if crate::pfc::is_pfc_key(key) {
+        key.strip_prefix(parent)
+            .is_some_and(|detail| detail.starts_with('_'))
+    } else {
+        let path = taxonomy.path_of(key);
+        path != taxonomy.path_of(parent) && taxonomy.contains(parent, &path)
+    }
+}
+
+fn median(mut values: Vec<f64>) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+
+    values.sort_by(f64::total_cmp);
+    Some(quantile(&values, 0.5))
+}
+
+fn round(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Balances;
+
+    fn transaction(name: &str, amount: f64, date: &str) -> Transaction {
+        serde_json::from_value(serde_json::json!({
+            "transaction_id": format!("{name}-{date}"),
+            "account_id": "checking",
+            "amount": amount,
+            "name": name,
+            "date": date,
+            "category": ["Service"],
+            "pending": false,
+        }))
+        .expect("Valid transaction")
+    }
+
+    fn account(id: &str, account_type: &str, current: f64) -> Account {
+        Account {
+            account_id: id.to_string(),
+            balances: Balances {
+                available: None,
+                current: Some(current),
+                limit: None,
+                iso_currency_code: None,
+                unofficial_currency_code: None,
+                last_updated_datetime: None,
+            },
+            mask: None,
+            name: id.to_string(),
+            official_name: None,
+            account_type: account_type.to_string(),
+            subtype: String::new(),
+        }
+    }
+
+    fn date(d: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap()
+    }
+
+    fn today() -> NaiveDate {
+        date("2026-10-19")
+    }
+
+    fn rent() -> Vec<Transaction> {
+        vec![
+            transaction("Rent", 1500.0, "2026-07-01"),
+            transaction("Rent", 1500.0, "2026-08-01"),
+            transaction("Rent", 1500.0, "2026-09-02"),
+            transaction("Rent", 1500.0, "2026-10-01"),
+        ]
+    }
+
+    #[test]
+    fn detects_monthly_payments() {
+        let streams = detect_recurring(&rent(), &ExchangeRates::default(), today());
+
+        assert_eq!(streams.len(), 1);
+        assert_eq!(streams[0].frequency, BudgetPeriod::Monthly);
+        assert_eq!(streams[0].amount, 1500.0);
+        assert_eq!(streams[0].last.date, "2026-10-01");
+    }
+
+    #[test]
+    fn detects_biweekly_income() {
+        let pay: Vec<Transaction> = (0..5)
+            .map(|n| {
+                let d = date("2026-08-07") + Duration::weeks(2 * n);
+                transaction("Payroll", -2000.0, &d.format("%Y-%m-%d").to_string())
+            })
+            .collect();
+        let streams = detect_recurring(&pay, &ExchangeRates::default(), today());
+
+        assert_eq!(streams.len(), 1);
+        assert_eq!(streams[0].frequency, BudgetPeriod::Biweekly);
+        assert_eq!(streams[0].amount, -2000.0);
+    }
+
+    #[test]
+    fn ignores_irregular_and_ended_payments() {
+        let irregular = vec![
+            transaction("Hardware store", 40.0, "2026-07-03"),
+            transaction("Hardware store", 35.0, "2026-07-20"),
+            transaction("Hardware store", 50.0, "2026-09-28"),
+        ];
+        assert!(detect_recurring(&irregular, &ExchangeRates::default(), today()).is_empty());
+
+        let varying = vec![
+            transaction("Gym", 30.0, "2026-08-15"),
+            transaction("Gym", 90.0, "2026-09-15"),
+            transaction("Gym", 30.0, "2026-10-15"),
+        ];
+        assert!(detect_recurring(&varying, &ExchangeRates::default(), today()).is_empty());
+
+        let ended = detect_recurring(&rent(), &ExchangeRates::default(), date("2027-02-01"));
+        assert!(ended.is_empty());
+    }
+
+    #[test]
+    fn late_payment_is_expected_today() {
+        let stream = &detect_recurring(&rent(), &ExchangeRates::default(), today())[0];
+        let window = Period {
+            start: date("2026-11-05"),
+            end: date("2027-01-01"),
+        };
+
+        assert_eq!(
+            stream.occurrences(&window),
+            vec![date("2026-11-05"), date("2026-12-01")]
+        );
+    }
+
+    #[test]
+    fn scheduled_items_repeat_until_their_end_date() {
+        let item = SchemaScheduledItem {
+            id: None,
+            user_id: "user".to_string(),
+            account_id: "checking".to_string(),
+            name: "Car payment".to_string(),
+            amount: 300.0,
+            iso_currency_code: None,
+            date: "2026-08-31".to_string(),
+            frequency: Some(BudgetPeriod::Monthly),
+            end_date: Some("2026-12-31".to_string()),
+        };
+        let window = Period {
+            start: today(),
+            end: date("2027-06-01"),
+        };
+
+        assert_eq!(
+            item.occurrences(&window),
+            vec![date("2026-10-31"), date("2026-11-30"), date("2026-12-31")]
+        );
+
+        let once = SchemaScheduledItem {
+            frequency: None,
+            date: "2026-11-15".to_string(),
+            ..item
+        };
+        assert_eq!(once.occurrences(&window), vec![date("2026-11-15")]);
+    }
+
+    #[test]
+    fn forecast_applies_payments_to_cash_accounts() {
+        let rates = ExchangeRates::default();
+        let streams = detect_recurring(&rent(), &rates, today());
+        let accounts = [
+            account("checking", "depository", 2000.0),
+            account("card", "credit", 500.0),
+        ];
+        let burn = vec![10.0; 20];
+
+        let forecast = forecast(&accounts, &streams, &[], &burn, &rates, today(), 20);
+
+        assert_eq!(forecast.accounts.len(), 1);
+        assert_eq!(forecast.dates.len(), 20);
+        assert_eq!(forecast.events.len(), 1);
+        assert_eq!(forecast.events[0].date, "2026-11-01");
+
+        // Rent leaves on the 13th day
+        assert_eq!(forecast.accounts[0].balances[12], 2000.0);
+        assert_eq!(forecast.accounts[0].balances[13], 500.0);
+        assert_eq!(forecast.total[13], 500.0 - 140.0);
+        assert_eq!(forecast.low_days(400.0), vec![13, 14, 15, 16, 17, 18, 19]);
+        assert_eq!(forecast.lowest(), Some(("2026-11-07", 300.0)));
+    }
+
+    #[test]
+    fn budget_burn_spreads_what_recurring_payments_leave() {
+        let rates = ExchangeRates::default();
+        let taxonomy = Taxonomy::default();
+        let rent_budget = SchemaBudget {
+            user_id: "user".to_string(),
+            category_id: "RENT_AND_UTILITIES".to_string(),
+            max: 1600.0,
+            period: BudgetPeriod::Monthly,
+            start_day: 1,
+            anchor_date: None,
+            rollover: false,
+            kind: BudgetKind::Spending,
+        };
+        let food = SchemaBudget {
+            category_id: "FOOD_AND_DRINK".to_string(),
+            max: 310.0,
+            ..rent_budget.clone()
+        };
+        let groceries = SchemaBudget {
+            category_id: "FOOD_AND_DRINK_GROCERIES".to_string(),
+            max: 200.0,
+            ..rent_budget.clone()
+        };
+
+        let mut rent = rent();
+        for t in &mut rent {
+            t.category = Some(vec!["Payment".to_string(), "Rent".to_string()]);
+        }
+        let streams = detect_recurring(&rent, &rates, today());
+        let window = Period {
+            start: date("2026-10-20"),
+            end: date("2026-11-11"),
+        };
+
+        let burn = budget_burn(
+            &taxonomy,
+            &[(&rent_budget, 1500.0), (&food, 190.0), (&groceries, 150.0)],
+            &streams,
+            &window,
+        );
+
+        assert_eq!(burn.len(), 22);
+        // October has 12 days for what's left of food and of rent once it's paid,
+        // groceries is part of food
+        assert!((burn[0] - (120.0 + 100.0) / 12.0).abs() < 1e-9);
+        // November's rent leaves 100 of its budget, food gets its whole 310
+        assert!((burn[12] - (100.0 + 310.0) / 30.0).abs() < 1e-9);
+    }
+}
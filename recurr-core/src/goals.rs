@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    balances::SchemaBalanceSnapshot, budgets::BudgetKind, currency::ExchangeRates, Account,
+    SchemaBudget, Transaction,
+};
+
+/// How far back contributions are averaged over
+pub const LOOKBACK_DAYS: i64 = 91;
+const DAYS_PER_MONTH: f64 = 365.25 / 12.0;
+
+/// Something the user is saving towards. Progress is the balance of the linked
+/// accounts, or without any, a virtual sub-balance of `saved` plus transfers tagged `tag`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SchemaGoal {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    pub user_id: String,
+    pub name: String,
+    pub target_amount: f64,
+    pub target_date: Option<String>,
+    #[serde(default)]
+    pub account_ids: Vec<String>,
+    pub tag: Option<String>,
+    /// Set aside by hand towards a goal without linked accounts
+    #[serde(default)]
+    pub saved: f64,
+    /// Whether a goal budget of the same name gets money for it each month
+    #[serde(default)]
+    pub budgeted: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GoalStatus {
+    Complete,
+    OnTrack,
+    Behind,
+    Overdue,
+    /// Without a target date there's nothing to be behind on
+    NoTargetDate,
+}
+
+impl GoalStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            GoalStatus::Complete => "Complete",
+            GoalStatus::OnTrack => "On track",
+            GoalStatus::Behind => "Behind",
+            GoalStatus::Overdue => "Overdue",
+            GoalStatus::NoTargetDate => "No target date",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoalProgress {
+    pub saved: f64,
+    /// Average contributed per month recently
+    pub monthly: f64,
+    /// What's needed each month from now to reach the target by its date
+    pub required_monthly: Option<f64>,
+    /// When the goal is reached at the recent pace
+    pub projected: Option<NaiveDate>,
+    pub status: GoalStatus,
+}
+
+impl SchemaGoal {
+    pub fn target_date(&self) -> Option<NaiveDate> {
+        self.target_date
+            .as_deref()
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+    }
+
+    /// Linked account balances or the virtual sub-balance, in the base currency
+    pub fn saved(
+        &self,
+        accounts: &[Account],
+        tagged: &[Transaction],
+        rates: &ExchangeRates,
+    ) -> f64 {
+        if self.account_ids.is_empty() {
+            self.saved + contributions(tagged, rates)
+        } else {
+            accounts
+                .iter()
+                .filter(|a| self.account_ids.contains(&a.account_id))
+                .filter_map(|a| {
+                    rates.to_base(
+                        a.balances.current.unwrap_or_default(),
+                        a.balances.iso_currency_code.as_deref(),
+                    )
+                })
+                .sum()
+        }
+    }
+
+    /// Progress so far with contributions taken from balance history for linked
+    /// accounts, or from `tagged` transfers otherwise
+    pub fn progress(
+        &self,
+        accounts: &[Account],
+        snapshots: &[SchemaBalanceSnapshot],
+        tagged: &[Transaction],
+        rates: &ExchangeRates,
+        today: NaiveDate,
+    ) -> GoalProgress {
+        let saved = self.saved(accounts, tagged, rates);
+        let since = today - Duration::days(LOOKBACK_DAYS);
+
+        let monthly = if self.account_ids.is_empty() {
+            let since = since.format("%Y-%m-%d").to_string();
+            let recent = tagged.iter().filter(|t| t.date >= since);
+            contributions(recent, rates) / (LOOKBACK_DAYS as f64 / DAYS_PER_MONTH)
+        } else {
+            self.monthly_from_history(saved, snapshots, rates, since, today)
+        };
+
+        let remaining = self.target_amount - saved;
+        let target_date = self.target_date();
+        let required_monthly = target_date
+            .filter(|date| *date > today)
+            .map(|date| remaining.max(0.0) / months_between(today, date).max(1) as f64);
+        let projected = (remaining > 0.0 && monthly > 0.0)
+            .then(|| today + Duration::days((remaining / monthly * DAYS_PER_MONTH).ceil() as i64));
+
+        let status = if remaining <= 0.0 {
+            GoalStatus::Complete
+        } else {
+            match (target_date, required_monthly) {
+                (None, _) => GoalStatus::NoTargetDate,
+                (Some(_), None) => GoalStatus::Overdue,
+                (Some(_), Some(required)) if monthly + 0.005 >= required => GoalStatus::OnTrack,
+                (Some(_), Some(_)) => GoalStatus::Behind,
+            }
+        };
+
+        GoalProgress {
+            saved,
+            monthly,
+            required_monthly,
+            projected,
+            status,
+        }
+    }
+
+    /// The goal budget that gets money towards this goal each month
+    pub fn budget(&self, required_monthly: f64) -> SchemaBudget {
+        SchemaBudget {
+            user_id: self.user_id.clone(),
+            category_id: self.name.clone(),
+            max: (required_monthly * 100.0).ceil() / 100.0,
+            period: Default::default(),
+            start_day: 1,
+            anchor_date: None,
+            rollover: true,
+            kind: BudgetKind::Goal,
+        }
+    }
+
+    /// Change in the linked balances since `since` per month, from the last
+    /// snapshot on or before it, or the first one after when history is shorter
+    fn monthly_from_history(
+        &self,
+        saved: f64,
+        snapshots: &[SchemaBalanceSnapshot],
+        rates: &ExchangeRates,
+        since: NaiveDate,
+        today: NaiveDate,
+    ) -> f64 {
+        let mut linked: Vec<&SchemaBalanceSnapshot> = snapshots
+            .iter()
+            .filter(|s| self.account_ids.contains(&s.account_id))
+            .collect();
+        linked.sort_by(|a, b| a.date.cmp(&b.date));
+
+        let Some(first) = linked.first() else {
+            return 0.0;
+        };
+        let since = since.format("%Y-%m-%d").to_string();
+        let start = if first.date > since {
+            first.date.clone()
+        } else {
+            since
+        };
+
+        let mut balances: HashMap<&str, f64> = HashMap::new();
+        for s in linked.iter().filter(|s| s.date <= start) {
+            if let Some(current) = rates.to_base(s.current, s.iso_currency_code.as_deref()) {
+                balances.insert(&s.account_id, current);
+            }
+        }
+
+        let Ok(start) = NaiveDate::parse_from_str(&start, "%Y-%m-%d") else {
+            return 0.0;
+        };
+        let days = (today - start).num_days();
+        if days < 7 {
+            return 0.0;
+        }
+
+        (saved - balances.values().sum::<f64>()) / (days as f64 / DAYS_PER_MONTH)
+    }
+}
+
+/// Tagged transfers leave the spending account, so positive amounts are contributions
+fn contributions<'a>(
+    tagged: impl IntoIterator<Item = &'a Transaction>,
+    rates: &ExchangeRates,
+) -> f64 {
+    tagged
+        .into_iter()
+        .filter_map(|t| rates.to_base(t.amount, t.iso_currency_code.as_deref()))
+        .sum()
+}
+
+/// Calendar months from `from` until `to`, counting a partial month as one
+fn months_between(from: NaiveDate, to: NaiveDate) -> i64 {
+    let months = (to.year() - from.year()) as i64 * 12 + to.month() as i64 - from.month() as i64;
+    if to.day() > from.day() {
+        months + 1
+    } else {
+        months
+    }
+}
@@ -0,0 +1,52 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    budgets::{default_start_day, BudgetPeriod, Period},
+    categories::Taxonomy,
+    Transaction,
+};
+
+/// Plaid amounts are positive for money leaving an account, so inflows are negative
+pub fn is_income(transaction: &Transaction) -> bool {
+    transaction.amount < 0.0
+}
+
+/// Who a transaction came from, the merchant when Plaid knows it
+pub fn payer(transaction: &Transaction) -> &str {
+    transaction
+        .merchant_name
+        .as_deref()
+        .unwrap_or(&transaction.name)
+}
+
+/// Income the user expects each period from a payer or category
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SchemaIncomeTarget {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    pub user_id: String,
+    /// A payer's name, or a category key like budgets use
+    pub source: String,
+    pub expected: f64,
+    #[serde(default)]
+    pub period: BudgetPeriod,
+    #[serde(default = "default_start_day")]
+    pub start_day: u32,
+    #[serde(default)]
+    pub anchor_date: Option<String>,
+}
+
+impl SchemaIncomeTarget {
+    /// The period containing `today`, or `offset` periods before (negative) or after it
+    pub fn period(&self, today: NaiveDate, offset: i64) -> Period {
+        self.period
+            .period(self.start_day, self.anchor_date.as_deref(), today, offset)
+    }
+
+    pub fn matches(&self, taxonomy: &Taxonomy, transaction: &Transaction) -> bool {
+        is_income(transaction)
+            && (payer(transaction).eq_ignore_ascii_case(&self.source)
+                || taxonomy.in_category(&self.source, transaction))
+    }
+}
@@ -1,8 +1,28 @@
-use postgrest::Postgrest;
-use serde::{Deserialize, Serialize};
+use postgrest::{Builder, Postgrest};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::fmt::Display;
 
+pub mod alerts;
+pub mod annotations;
+pub mod balances;
+pub mod budgets;
+pub mod categories;
+pub mod currency;
+pub mod debt;
+pub mod filters;
+pub mod forecast;
+pub mod goals;
+pub mod income;
+pub mod manual;
+pub mod pending;
+pub mod pfc;
 pub mod plaid;
+pub mod reports;
+pub mod rules;
+pub mod search;
+pub mod splits;
+pub mod suggestions;
+pub mod transfers;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum Event {
@@ -136,6 +156,20 @@ pub struct Transaction {
     pub merchant_name: Option<String>,
     pub pending: bool,
     pub pending_transaction_id: Option<String>,
+    pub iso_currency_code: Option<String>,
+    pub unofficial_currency_code: Option<String>,
+    #[serde(default)]
+    pub personal_finance_category: Option<pfc::PersonalFinanceCategory>,
+
+    /// Only present when read from the `user_transactions` view
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_transfer: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manual: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -152,6 +186,19 @@ pub struct SchemaBudget {
     pub user_id: String,
     pub category_id: String,
     pub max: f64,
+    #[serde(default)]
+    pub period: budgets::BudgetPeriod,
+    /// Day of the month monthly, quarterly and yearly periods start on, to line up with paydays
+    #[serde(default = "budgets::default_start_day")]
+    pub start_day: u32,
+    /// A day a weekly or biweekly period starts on
+    #[serde(default)]
+    pub anchor_date: Option<String>,
+    /// Carry what's left, or overspent, into the next period
+    #[serde(default)]
+    pub rollover: bool,
+    #[serde(default)]
+    pub kind: budgets::BudgetKind,
 }
 
 impl std::hash::Hash for SchemaBudget {
@@ -187,7 +234,49 @@ pub struct SchemaPlaidAccount {
     pub access_token_id: i32,
 }
 
+/// Formats values as a Postgres array literal for PostgREST filters
+pub fn to_pg_array(values: &[String]) -> String {
+    let values: Vec<String> = values
+        .iter()
+        .map(|v| format!("\"{}\"", v.replace('\\', "\\\\").replace('"', "\\\"")))
+        .collect();
+
+    format!("{{{}}}", values.join(","))
+}
+
 pub fn get_supbase_client() -> Postgrest {
     Postgrest::new(env!("SUPABASE_URL").to_owned() + "/rest/v1")
         .insert_header("apikey", env!("SUPABASE_KEY"))
 }
+
+/// PostgREST's max rows, responses are cut off past it
+pub const PAGE_SIZE: usize = 1000;
+
+/// Reads every row `query` matches a page at a time. `query` is built again for each
+/// page and needs a stable order so pages don't overlap
+pub async fn get_all<T, F>(query: F) -> Result<Vec<T>, Error>
+where
+    T: DeserializeOwned,
+    F: Fn() -> Builder,
+{
+    let mut rows = Vec::new();
+    loop {
+        let offset = rows.len();
+        let page: Vec<T> = query()
+            .range(offset, offset + PAGE_SIZE - 1)
+            .execute()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| Error::Request(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| Error::Request(e.to_string()))?;
+
+        let last_page = page.len() < PAGE_SIZE;
+        rows.extend(page);
+
+        if last_page {
+            return Ok(rows);
+        }
+    }
+}
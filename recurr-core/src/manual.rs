@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Account, Balances, Institution};
+
+/// Account types a manual account can have, "other" covers property like a home or car
+pub const MANUAL_ACCOUNT_TYPES: &[&str] = &["depository", "credit", "investment", "loan", "other"];
+
+/// Stands in for Plaid's institution when manual accounts are grouped with linked ones
+pub fn manual_institution() -> Institution {
+    Institution {
+        institution_id: "manual".to_string(),
+        name: "Manual accounts".to_string(),
+    }
+}
+
+/// An account the user tracks by hand, for cash, property or institutions Plaid doesn't support
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SchemaManualAccount {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<String>,
+    pub user_id: String,
+    pub name: String,
+    pub account_type: String,
+    pub subtype: Option<String>,
+    pub iso_currency_code: Option<String>,
+    pub current_balance: f64,
+}
+
+impl SchemaManualAccount {
+    /// The account in Plaid's shape so views can treat both the same
+    pub fn to_account(&self) -> Account {
+        Account {
+            account_id: self.account_id.clone().unwrap_or_default(),
+            balances: Balances {
+                available: Some(self.current_balance),
+                current: Some(self.current_balance),
+                limit: None,
+                iso_currency_code: self.iso_currency_code.clone(),
+                unofficial_currency_code: None,
+                last_updated_datetime: None,
+            },
+            mask: None,
+            name: self.name.clone(),
+            official_name: Some(self.name.clone()),
+            account_type: self.account_type.clone(),
+            subtype: self.subtype.clone().unwrap_or_default(),
+        }
+    }
+}
+
+/// A balance the user recorded for a manual account on a given date
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SchemaManualBalance {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    pub user_id: String,
+    pub account_id: String,
+    pub balance: f64,
+    pub date: String,
+}
+
+/// A transaction entered by hand against a manual account
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SchemaManualTransaction {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_id: Option<String>,
+    pub user_id: String,
+    pub account_id: String,
+    pub amount: f64,
+    pub name: String,
+    pub date: String,
+    pub category: Option<Vec<String>>,
+    pub merchant_name: Option<String>,
+    pub iso_currency_code: Option<String>,
+}
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+
+use crate::{annotations::SchemaTransactionAnnotation, Transaction};
+
+/// Pending transaction ids and the posted transactions replacing them
+pub fn successors(added: &[Transaction]) -> HashMap<&str, &str> {
+    added
+        .iter()
+        .filter(|t| !t.pending)
+        .filter_map(|t| {
+            Some((
+                t.pending_transaction_id.as_deref()?,
+                t.transaction_id.as_str(),
+            ))
+        })
+        .collect()
+}
+
+/// Annotations for posted transactions that take over their pending
+/// transaction's notes and tags. Notes already on the posted transaction are
+/// kept, tags from both are combined
+pub fn carry_annotations(
+    successors: &HashMap<&str, &str>,
+    annotations: &[SchemaTransactionAnnotation],
+) -> Vec<SchemaTransactionAnnotation> {
+    let existing: HashMap<&str, &SchemaTransactionAnnotation> = annotations
+        .iter()
+        .map(|a| (a.transaction_id.as_str(), a))
+        .collect();
+
+    annotations
+        .iter()
+        .filter_map(|pending| {
+            let posted_id = *successors.get(pending.transaction_id.as_str())?;
+            let mut carried = match existing.get(posted_id) {
+                Some(posted) => (*posted).clone(),
+                None => SchemaTransactionAnnotation {
+                    transaction_id: posted_id.to_string(),
+                    user_id: pending.user_id.clone(),
+                    notes: None,
+                    tags: Vec::new(),
+                },
+            };
+
+            if carried.notes.as_deref().is_none_or(str::is_empty) {
+                carried.notes = pending.notes.clone();
+            }
+            for tag in &pending.tags {
+                if !carried.tags.contains(tag) {
+                    carried.tags.push(tag.clone());
+                }
+            }
+
+            Some(carried)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(id: &str, pending: bool, pending_id: Option<&str>) -> Transaction {
+        Transaction {
+            transaction_id: id.to_string(),
+            account_id: "checking".to_string(),
+            amount: 12.5,
+            name: "Coffee".to_string(),
+            date: "2026-10-01".to_string(),
+            category: None,
+            category_id: None,
+            merchant_name: None,
+            pending,
+            pending_transaction_id: pending_id.map(str::to_string),
+            iso_currency_code: Some("USD".to_string()),
+            unofficial_currency_code: None,
+            personal_finance_category: None,
+            is_transfer: None,
+            notes: None,
+            tags: None,
+            manual: None,
+        }
+    }
+
+    fn annotation(id: &str, notes: Option<&str>, tags: &[&str]) -> SchemaTransactionAnnotation {
+        SchemaTransactionAnnotation {
+            transaction_id: id.to_string(),
+            user_id: "user".to_string(),
+            notes: notes.map(str::to_string),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn posted_transactions_succeed_their_pending_ones() {
+        let added = vec![
+            transaction("posted", false, Some("pending")),
+            transaction("fresh", false, None),
+            transaction("still-pending", true, None),
+        ];
+
+        assert_eq!(successors(&added), HashMap::from([("pending", "posted")]));
+    }
+
+    #[test]
+    fn annotations_move_to_the_posted_transaction() {
+        let successors = HashMap::from([("a-pending", "a-posted"), ("b-pending", "b-posted")]);
+        let annotations = vec![
+            annotation("a-pending", Some("Team lunch"), &["work"]),
+            annotation("b-pending", Some("Old note"), &["work", "travel"]),
+            annotation("b-posted", Some("New note"), &["travel"]),
+            annotation("unrelated", Some("Keep"), &[]),
+        ];
+
+        let carried = carry_annotations(&successors, &annotations);
+        assert_eq!(
+            carried,
+            vec![
+                annotation("a-posted", Some("Team lunch"), &["work"]),
+                annotation("b-posted", Some("New note"), &["travel", "work"]),
+            ]
+        );
+    }
+}
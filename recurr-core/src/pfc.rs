@@ -0,0 +1,234 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Transaction;
+
+/// Plaid's personal finance category, replaces the legacy `category` hierarchy
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PersonalFinanceCategory {
+    pub primary: String,
+    pub detailed: String,
+    pub confidence_level: Option<String>,
+}
+
+pub const PRIMARY_CATEGORIES: &[&str] = &[
+    "INCOME",
+    "TRANSFER_IN",
+    "TRANSFER_OUT",
+    "LOAN_PAYMENTS",
+    "BANK_FEES",
+    "ENTERTAINMENT",
+    "FOOD_AND_DRINK",
+    "GENERAL_MERCHANDISE",
+    "HOME_IMPROVEMENT",
+    "MEDICAL",
+    "PERSONAL_CARE",
+    "GENERAL_SERVICES",
+    "GOVERNMENT_AND_NON_PROFIT",
+    "TRANSPORTATION",
+    "TRAVEL",
+    "RENT_AND_UTILITIES",
+];
+
+/// Legacy category names and the PFC category they correspond to, detailed
+/// where Plaid has one so budgets keep their scope. Subcategories are listed
+/// when they belong somewhere narrower than their parent. Legacy budgets were
+/// moved once with a copy, in the `rekey_legacy_budgets_once` migration
+pub const LEGACY_TO_PFC: &[(&str, &str)] = &[
+    ("Bank Fees", "BANK_FEES"),
+    ("Community", "GOVERNMENT_AND_NON_PROFIT"),
+    ("Food and Drink", "FOOD_AND_DRINK"),
+    ("Restaurants", "FOOD_AND_DRINK_RESTAURANT"),
+    ("Coffee Shop", "FOOD_AND_DRINK_COFFEE"),
+    ("Fast Food", "FOOD_AND_DRINK_FAST_FOOD"),
+    ("Bar", "FOOD_AND_DRINK_BEER_WINE_AND_LIQUOR"),
+    ("Healthcare", "MEDICAL"),
+    ("Interest", "INCOME"),
+    ("Interest Earned", "INCOME_INTEREST_EARNED"),
+    ("Payment", "LOAN_PAYMENTS"),
+    ("Credit Card", "LOAN_PAYMENTS_CREDIT_CARD_PAYMENT"),
+    ("Rent", "RENT_AND_UTILITIES_RENT"),
+    ("Recreation", "ENTERTAINMENT"),
+    (
+        "Gyms and Fitness Centers",
+        "PERSONAL_CARE_GYMS_AND_FITNESS_CENTERS",
+    ),
+    ("Service", "GENERAL_SERVICES"),
+    ("Utilities", "RENT_AND_UTILITIES"),
+    ("Personal Care", "PERSONAL_CARE"),
+    ("Home Improvement", "HOME_IMPROVEMENT"),
+    ("Hardware Store", "HOME_IMPROVEMENT_HARDWARE"),
+    ("Shops", "GENERAL_MERCHANDISE"),
+    ("Supermarkets and Groceries", "FOOD_AND_DRINK_GROCERIES"),
+    ("Pharmacies", "MEDICAL_PHARMACIES_AND_SUPPLEMENTS"),
+    ("Tax", "GOVERNMENT_AND_NON_PROFIT_TAX_PAYMENT"),
+    ("Transfer", "TRANSFER_OUT"),
+    ("Deposit", "TRANSFER_IN"),
+    ("Payroll", "INCOME_WAGES"),
+    ("Travel", "TRAVEL"),
+    ("Gas Stations", "TRANSPORTATION_GAS"),
+    (
+        "Public Transportation Services",
+        "TRANSPORTATION_PUBLIC_TRANSIT",
+    ),
+    ("Taxi", "TRANSPORTATION_TAXIS_AND_RIDE_SHARES"),
+    ("Car Service", "TRANSPORTATION_TAXIS_AND_RIDE_SHARES"),
+    ("Parking", "TRANSPORTATION_PARKING"),
+];
+
+/// The PFC key for a legacy hierarchy, the most specific mapped name wins
+pub fn from_legacy(hierarchy: &[String]) -> Option<&'static str> {
+    hierarchy.iter().rev().find_map(|name| {
+        LEGACY_TO_PFC
+            .iter()
+            .find(|(legacy, _)| legacy == name)
+            .map(|(_, pfc)| *pfc)
+    })
+}
+
+/// The primary a PFC primary or detailed key falls under
+pub fn primary_of(key: &str) -> Option<&'static str> {
+    PRIMARY_CATEGORIES
+        .iter()
+        .find(|p| key == **p || key.strip_prefix(**p).is_some_and(|d| d.starts_with('_')))
+        .copied()
+}
+
+/// Whether a budget or filter key is a PFC primary or detailed category
+pub fn is_pfc_key(key: &str) -> bool {
+    primary_of(key).is_some()
+}
+
+/// Whether a legacy hierarchy maps onto a PFC primary or detailed key
+pub fn legacy_matches(key: &str, hierarchy: &[String]) -> bool {
+    from_legacy(hierarchy).is_some_and(|mapped| mapped == key || primary_of(mapped) == Some(key))
+}
+
+/// The transaction's PFC primary, mapped from its legacy category when Plaid didn't send one
+pub fn primary(transaction: &Transaction) -> Option<String> {
+    match &transaction.personal_finance_category {
+        Some(pfc) => Some(pfc.primary.clone()),
+        None => from_legacy(transaction.category.as_deref().unwrap_or_default())
+            .and_then(primary_of)
+            .map(str::to_string),
+    }
+}
+
+/// Whether a transaction falls under a PFC primary or detailed key
+pub fn matches(key: &str, transaction: &Transaction) -> bool {
+    match &transaction.personal_finance_category {
+        Some(pfc) => pfc.primary == key || pfc.detailed == key,
+        None => legacy_matches(key, transaction.category.as_deref().unwrap_or_default()),
+    }
+}
+
+/// "FOOD_AND_DRINK" becomes "Food and Drink"
+pub fn display_name(key: &str) -> String {
+    key.split('_')
+        .enumerate()
+        .map(|(i, word)| {
+            let word = word.to_lowercase();
+            match word.as_str() {
+                "and" | "or" | "of" if i > 0 => word,
+                _ => {
+                    let mut chars = word.chars();
+                    chars
+                        .next()
+                        .map_or(String::new(), |c| c.to_uppercase().chain(chars).collect())
+                }
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// How a report key is shown, budget and filter keys can name categories in
+/// the user's taxonomy and are shown with [`crate::categories::Taxonomy::label`]
+pub fn label(key: &str) -> String {
+    if is_pfc_key(key) {
+        display_name(key)
+    } else {
+        key.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    fn legacy(category: &[&str]) -> Transaction {
+        Transaction {
+            transaction_id: "t".to_string(),
+            account_id: "checking".to_string(),
+            amount: 40.0,
+            name: "Market".to_string(),
+            date: "2026-10-01".to_string(),
+            category: Some(path(category)),
+            category_id: None,
+            merchant_name: None,
+            pending: false,
+            pending_transaction_id: None,
+            iso_currency_code: Some("USD".to_string()),
+            unofficial_currency_code: None,
+            personal_finance_category: None,
+            is_transfer: None,
+            notes: None,
+            tags: None,
+            manual: None,
+        }
+    }
+
+    #[test]
+    fn legacy_subcategories_map_to_detailed_keys() {
+        assert_eq!(
+            from_legacy(&path(&["Shops", "Supermarkets and Groceries"])),
+            Some("FOOD_AND_DRINK_GROCERIES")
+        );
+        assert_eq!(
+            from_legacy(&path(&["Travel", "Gas Stations"])),
+            Some("TRANSPORTATION_GAS")
+        );
+        assert_eq!(from_legacy(&path(&["Shops"])), Some("GENERAL_MERCHANDISE"));
+        assert_eq!(from_legacy(&path(&["Unknown"])), None);
+
+        // Every mapped key is one Plaid sends
+        for (_, key) in LEGACY_TO_PFC {
+            assert!(is_pfc_key(key), "{key}");
+        }
+    }
+
+    #[test]
+    fn keys_fall_under_their_primary() {
+        assert_eq!(primary_of("FOOD_AND_DRINK"), Some("FOOD_AND_DRINK"));
+        assert_eq!(
+            primary_of("FOOD_AND_DRINK_GROCERIES"),
+            Some("FOOD_AND_DRINK")
+        );
+        assert_eq!(primary_of("TRANSFER_IN_DEPOSIT"), Some("TRANSFER_IN"));
+        assert_eq!(primary_of("FOOD_AND_DRINKS"), None);
+        assert!(!is_pfc_key("Food and Drink"));
+    }
+
+    #[test]
+    fn legacy_transactions_match_primary_and_detailed_keys() {
+        let groceries = legacy(&["Shops", "Supermarkets and Groceries"]);
+
+        assert_eq!(primary(&groceries).as_deref(), Some("FOOD_AND_DRINK"));
+        assert!(matches("FOOD_AND_DRINK", &groceries));
+        assert!(matches("FOOD_AND_DRINK_GROCERIES", &groceries));
+        assert!(!matches("FOOD_AND_DRINK_RESTAURANT", &groceries));
+        assert!(!matches("GENERAL_MERCHANDISE", &groceries));
+    }
+
+    #[test]
+    fn keys_display_in_title_case() {
+        assert_eq!(display_name("FOOD_AND_DRINK"), "Food and Drink");
+        assert_eq!(
+            display_name("GOVERNMENT_AND_NON_PROFIT"),
+            "Government and Non Profit"
+        );
+    }
+}
@@ -0,0 +1,326 @@
+use std::collections::HashMap;
+
+use chrono::{Datelike, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::{budgets::Period, currency::ExchangeRates, pfc, suggestions::EXCLUDED};
+
+/// Key for spending without a category Plaid or the legacy mapping knows
+pub const UNCATEGORIZED: &str = "OTHER";
+
+/// Spending summed per month, category, merchant and currency by the
+/// `spending_summary` function
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SchemaSpendingRow {
+    /// "2026-10"
+    pub month: String,
+    pub category: Option<Vec<String>>,
+    pub pfc_primary: Option<String>,
+    pub merchant: String,
+    pub iso_currency_code: Option<String>,
+    pub amount: f64,
+    pub transactions: i64,
+}
+
+impl SchemaSpendingRow {
+    /// The PFC primary the row's spending counts under, mapped from its legacy
+    /// category when Plaid didn't send one
+    pub fn category_key(&self) -> String {
+        self.pfc_primary
+            .clone()
+            .or_else(|| {
+                pfc::from_legacy(self.category.as_deref().unwrap_or_default())
+                    .and_then(pfc::primary_of)
+                    .map(str::to_string)
+            })
+            .unwrap_or_else(|| UNCATEGORIZED.to_string())
+    }
+
+    pub fn is_spending(&self) -> bool {
+        !EXCLUDED.contains(&self.category_key().as_str())
+    }
+}
+
+/// The columns `spending_summary` groups by, an order that keeps pages of its
+/// rows from overlapping
+pub const SUMMARY_ORDER: &str = "month,category,pfc_primary,merchant,iso_currency_code";
+
+/// Arguments for the `spending_summary` and `daily_category_totals` functions
+pub fn summary_params(window: &Period) -> String {
+    serde_json::json!({
+        "start_date": window.start.format("%Y-%m-%d").to_string(),
+        "end_date": window.end.format("%Y-%m-%d").to_string(),
+    })
+    .to_string()
+}
+
+/// Spending under one key, in the base currency. Refunds count against it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Total {
+    pub key: String,
+    pub amount: f64,
+    pub transactions: i64,
+}
+
+/// How a key's spending changed between two ranges
+#[derive(Debug, Clone, PartialEq)]
+pub struct Delta {
+    pub key: String,
+    pub current: f64,
+    pub previous: f64,
+}
+
+impl Delta {
+    pub fn change(&self) -> f64 {
+        self.current - self.previous
+    }
+
+    /// `None` when there was nothing to compare against
+    pub fn percent(&self) -> Option<f64> {
+        (self.previous.abs() > 0.005).then(|| self.change() / self.previous.abs() * 100.0)
+    }
+}
+
+/// Spending per category, largest first
+pub fn by_category<'a>(
+    rows: impl IntoIterator<Item = &'a SchemaSpendingRow>,
+    rates: &ExchangeRates,
+) -> Vec<Total> {
+    totals(rows, rates, SchemaSpendingRow::category_key)
+}
+
+/// Spending per merchant, largest first
+pub fn by_merchant<'a>(
+    rows: impl IntoIterator<Item = &'a SchemaSpendingRow>,
+    rates: &ExchangeRates,
+) -> Vec<Total> {
+    totals(rows, rates, |r| r.merchant.clone())
+}
+
+/// Spending in every month of `window` in order, months without any included
+pub fn by_month<'a>(
+    rows: impl IntoIterator<Item = &'a SchemaSpendingRow>,
+    rates: &ExchangeRates,
+    window: &Period,
+) -> Vec<Total> {
+    let found = totals(rows, rates, |r| r.month.clone());
+
+    months(window)
+        .into_iter()
+        .map(|month| {
+            found
+                .iter()
+                .find(|t| t.key == month)
+                .cloned()
+                .unwrap_or(Total {
+                    key: month,
+                    amount: 0.0,
+                    transactions: 0,
+                })
+        })
+        .collect()
+}
+
+/// Each key's change from `previous` to `current`, biggest changes first.
+/// Keys missing from one side count as nothing spent there
+pub fn compare(current: &[Total], previous: &[Total]) -> Vec<Delta> {
+    let mut deltas: Vec<Delta> = current
+        .iter()
+        .map(|c| Delta {
+            key: c.key.clone(),
+            current: c.amount,
+            previous: previous
+                .iter()
+                .find(|p| p.key == c.key)
+                .map_or(0.0, |p| p.amount),
+        })
+        .collect();
+    deltas.extend(
+        previous
+            .iter()
+            .filter(|p| !current.iter().any(|c| c.key == p.key))
+            .map(|p| Delta {
+                key: p.key.clone(),
+                current: 0.0,
+                previous: p.amount,
+            }),
+    );
+
+    deltas.sort_by(|a, b| {
+        b.change()
+            .abs()
+            .total_cmp(&a.change().abs())
+            .then_with(|| a.key.cmp(&b.key))
+    });
+    deltas
+}
+
+/// The same range a year earlier, for year-over-year comparisons
+pub fn year_before(window: &Period) -> Period {
+    let shift = |date: NaiveDate| {
+        date.with_year(date.year() - 1)
+            .or_else(|| NaiveDate::from_ymd_opt(date.year() - 1, date.month(), date.day() - 1))
+            .unwrap_or(date)
+    };
+
+    Period {
+        start: shift(window.start),
+        end: shift(window.end),
+    }
+}
+
+/// "2026-10" style keys of every month `window` touches
+pub fn months(window: &Period) -> Vec<String> {
+    let mut months = Vec::new();
+    let mut month = NaiveDate::from_ymd_opt(window.start.year(), window.start.month(), 1);
+    while let Some(m) = month.filter(|m| *m < window.end) {
+        months.push(m.format("%Y-%m").to_string());
+        month = m.checked_add_months(Months::new(1));
+    }
+
+    months
+}
+
+fn totals<'a>(
+    rows: impl IntoIterator<Item = &'a SchemaSpendingRow>,
+    rates: &ExchangeRates,
+    key: impl Fn(&SchemaSpendingRow) -> String,
+) -> Vec<Total> {
+    let mut totals: HashMap<String, (f64, i64)> = HashMap::new();
+    for row in rows.into_iter().filter(|r| r.is_spending()) {
+        let Some(amount) = rates.to_base(row.amount, row.iso_currency_code.as_deref()) else {
+            continue;
+        };
+        let total = totals.entry(key(row)).or_default();
+        total.0 += amount;
+        total.1 += row.transactions;
+    }
+
+    let mut totals: Vec<Total> = totals
+        .into_iter()
+        .map(|(key, (amount, transactions))| Total {
+            key,
+            amount: (amount * 100.0).round() / 100.0,
+            transactions,
+        })
+        .collect();
+    totals.sort_by(|a, b| {
+        b.amount
+            .total_cmp(&a.amount)
+            .then_with(|| a.key.cmp(&b.key))
+    });
+
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(month: &str, pfc_primary: &str, merchant: &str, amount: f64) -> SchemaSpendingRow {
+        SchemaSpendingRow {
+            month: month.to_string(),
+            category: None,
+            pfc_primary: Some(pfc_primary.to_string()),
+            merchant: merchant.to_string(),
+            iso_currency_code: None,
+            amount,
+            transactions: 1,
+        }
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn rows() -> Vec<SchemaSpendingRow> {
+        vec![
+            row("2026-08", "FOOD_AND_DRINK", "Grocer", 200.0),
+            row("2026-09", "FOOD_AND_DRINK", "Grocer", 250.0),
+            row("2026-09", "FOOD_AND_DRINK", "Cafe", 30.0),
+            row("2026-09", "ENTERTAINMENT", "Cinema", 40.0),
+            row("2026-09", "ENTERTAINMENT", "Cinema", -10.0),
+            row("2026-09", "INCOME", "Employer", -3000.0),
+        ]
+    }
+
+    #[test]
+    fn totals_skip_income_and_net_refunds() {
+        let rates = ExchangeRates::default();
+
+        let categories = by_category(&rows(), &rates);
+        assert_eq!(categories.len(), 2);
+        assert_eq!(categories[0].key, "FOOD_AND_DRINK");
+        assert_eq!(categories[0].amount, 480.0);
+        assert_eq!(categories[1].amount, 30.0);
+        assert_eq!(categories[1].transactions, 2);
+
+        let merchants = by_merchant(&rows(), &rates);
+        assert_eq!(merchants[0].key, "Grocer");
+        assert_eq!(merchants[0].amount, 450.0);
+    }
+
+    #[test]
+    fn legacy_categories_map_to_pfc() {
+        let legacy = SchemaSpendingRow {
+            category: Some(vec![
+                "Food and Drink".to_string(),
+                "Restaurants".to_string(),
+            ]),
+            pfc_primary: None,
+            ..row("2026-09", "", "Diner", 20.0)
+        };
+        assert_eq!(legacy.category_key(), "FOOD_AND_DRINK");
+
+        let unknown = SchemaSpendingRow {
+            category: None,
+            ..legacy
+        };
+        assert_eq!(unknown.category_key(), UNCATEGORIZED);
+    }
+
+    #[test]
+    fn months_without_spending_are_kept() {
+        let window = Period {
+            start: date(2026, 7, 15),
+            end: date(2026, 10, 1),
+        };
+        let months = by_month(&rows(), &ExchangeRates::default(), &window);
+
+        let keys: Vec<&str> = months.iter().map(|m| m.key.as_str()).collect();
+        assert_eq!(keys, vec!["2026-07", "2026-08", "2026-09"]);
+        assert_eq!(months[0].amount, 0.0);
+        assert_eq!(months[2].amount, 310.0);
+    }
+
+    #[test]
+    fn month_over_month_includes_keys_on_either_side() {
+        let rates = ExchangeRates::default();
+        let all = rows();
+        let august = by_category(all.iter().filter(|r| r.month == "2026-08"), &rates);
+        let september = by_category(all.iter().filter(|r| r.month == "2026-09"), &rates);
+
+        let deltas = compare(&september, &august);
+        assert_eq!(deltas[0].key, "FOOD_AND_DRINK");
+        assert_eq!(deltas[0].change(), 80.0);
+        assert_eq!(deltas[0].percent(), Some(40.0));
+        assert_eq!(deltas[1].key, "ENTERTAINMENT");
+        assert_eq!(deltas[1].percent(), None);
+    }
+
+    #[test]
+    fn year_before_handles_leap_days() {
+        let window = Period {
+            start: date(2028, 2, 29),
+            end: date(2028, 3, 1),
+        };
+
+        assert_eq!(
+            year_before(&window),
+            Period {
+                start: date(2027, 2, 28),
+                end: date(2027, 3, 1),
+            }
+        );
+    }
+}
@@ -0,0 +1,246 @@
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Transaction};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SchemaRule {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    pub user_id: String,
+    pub name: String,
+    pub priority: i32,
+
+    /// Regex matched against both the merchant name and the transaction name
+    pub name_pattern: Option<String>,
+    pub amount_min: Option<f64>,
+    pub amount_max: Option<f64>,
+    pub account_id: Option<String>,
+
+    pub set_category: Option<Vec<String>>,
+    pub rename_merchant: Option<String>,
+    pub mark_transfer: bool,
+}
+
+/// User metadata layered over a Plaid transaction, a missing `rule_id` means it was set by hand
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SchemaTransactionOverride {
+    pub transaction_id: String,
+    pub user_id: String,
+    pub category: Option<Vec<String>>,
+    pub merchant_name: Option<String>,
+    pub is_transfer: Option<bool>,
+    pub rule_id: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub schema: SchemaRule,
+    pattern: Option<Regex>,
+}
+
+impl Rule {
+    pub fn new(schema: SchemaRule) -> Result<Self, Error> {
+        let pattern = match &schema.name_pattern {
+            Some(pattern) if !pattern.is_empty() => Some(
+                RegexBuilder::new(pattern)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|e| Error::Other(format!("Invalid pattern {pattern}: {e}")))?,
+            ),
+            _ => None,
+        };
+
+        Ok(Self { schema, pattern })
+    }
+
+    pub fn matches(&self, transaction: &Transaction) -> bool {
+        if let Some(pattern) = &self.pattern {
+            let merchant = transaction.merchant_name.as_deref().unwrap_or_default();
+            if !pattern.is_match(merchant) && !pattern.is_match(&transaction.name) {
+                return false;
+            }
+        }
+
+        if let Some(min) = self.schema.amount_min {
+            if transaction.amount < min {
+                return false;
+            }
+        }
+
+        if let Some(max) = self.schema.amount_max {
+            if transaction.amount > max {
+                return false;
+            }
+        }
+
+        if let Some(account_id) = &self.schema.account_id {
+            if &transaction.account_id != account_id {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+pub struct RuleSet {
+    rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    /// Rules are evaluated from the highest priority down, ties broken by
+    /// creation order. Rules that fail to compile are left out and returned
+    /// with their errors, so one bad pattern doesn't stop the rest
+    pub fn new(mut rules: Vec<SchemaRule>) -> (Self, Vec<(SchemaRule, Error)>) {
+        rules.sort_by(|a, b| b.priority.cmp(&a.priority).then(a.id.cmp(&b.id)));
+
+        let mut valid = Vec::new();
+        let mut invalid = Vec::new();
+        for schema in rules {
+            match Rule::new(schema.clone()) {
+                Ok(rule) => valid.push(rule),
+                Err(e) => invalid.push((schema, e)),
+            }
+        }
+
+        (Self { rules: valid }, invalid)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Each action is taken from the first matching rule that sets it, the
+    /// override is attributed to the first rule that matched
+    pub fn apply(&self, transaction: &Transaction) -> Option<SchemaTransactionOverride> {
+        let mut result: Option<SchemaTransactionOverride> = None;
+
+        for rule in self.rules.iter().filter(|r| r.matches(transaction)) {
+            let o = result.get_or_insert_with(|| SchemaTransactionOverride {
+                transaction_id: transaction.transaction_id.clone(),
+                user_id: rule.schema.user_id.clone(),
+                category: None,
+                merchant_name: None,
+                is_transfer: None,
+                rule_id: rule.schema.id,
+            });
+
+            if o.category.is_none() {
+                o.category = rule.schema.set_category.clone();
+            }
+
+            if o.merchant_name.is_none() {
+                o.merchant_name = rule.schema.rename_merchant.clone();
+            }
+
+            if o.is_transfer.is_none() && rule.schema.mark_transfer {
+                o.is_transfer = Some(true);
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(id: i64, priority: i32, pattern: &str) -> SchemaRule {
+        SchemaRule {
+            id: Some(id),
+            user_id: "user".to_string(),
+            name: format!("rule {id}"),
+            priority,
+            name_pattern: Some(pattern.to_string()),
+            amount_min: None,
+            amount_max: None,
+            account_id: None,
+            set_category: None,
+            rename_merchant: None,
+            mark_transfer: false,
+        }
+    }
+
+    fn transaction(name: &str, amount: f64) -> Transaction {
+        Transaction {
+            transaction_id: "t".to_string(),
+            account_id: "checking".to_string(),
+            amount,
+            name: name.to_string(),
+            date: "2026-10-01".to_string(),
+            category: None,
+            category_id: None,
+            merchant_name: None,
+            pending: false,
+            pending_transaction_id: None,
+            iso_currency_code: Some("USD".to_string()),
+            unofficial_currency_code: None,
+            personal_finance_category: None,
+            is_transfer: None,
+            notes: None,
+            tags: None,
+            manual: None,
+        }
+    }
+
+    #[test]
+    fn higher_priority_rules_win() {
+        let low = SchemaRule {
+            rename_merchant: Some("Coffee".to_string()),
+            set_category: Some(vec!["Food and Drink".to_string()]),
+            ..rule(1, 0, "starbucks")
+        };
+        let high = SchemaRule {
+            rename_merchant: Some("Starbucks".to_string()),
+            ..rule(2, 10, "star")
+        };
+        let (rules, invalid) = RuleSet::new(vec![low, high]);
+        assert!(invalid.is_empty());
+
+        let o = rules.apply(&transaction("STARBUCKS #123", 4.5)).unwrap();
+        assert_eq!(o.rule_id, Some(2));
+        assert_eq!(o.merchant_name.as_deref(), Some("Starbucks"));
+        // Actions the first rule leaves unset come from the next match
+        assert_eq!(o.category, Some(vec!["Food and Drink".to_string()]));
+    }
+
+    #[test]
+    fn ties_go_to_the_first_rule_created() {
+        let first = SchemaRule {
+            rename_merchant: Some("First".to_string()),
+            ..rule(1, 5, "shop")
+        };
+        let second = SchemaRule {
+            rename_merchant: Some("Second".to_string()),
+            ..rule(2, 5, "shop")
+        };
+        let (rules, _) = RuleSet::new(vec![second, first]);
+
+        let o = rules.apply(&transaction("Corner Shop", 10.0)).unwrap();
+        assert_eq!(
+            (o.rule_id, o.merchant_name.as_deref()),
+            (Some(1), Some("First"))
+        );
+
+        assert!(rules.apply(&transaction("Gas station", 10.0)).is_none());
+    }
+
+    #[test]
+    fn invalid_patterns_are_left_out() {
+        let valid = SchemaRule {
+            amount_min: Some(100.0),
+            mark_transfer: true,
+            ..rule(1, 0, "")
+        };
+        let (rules, invalid) = RuleSet::new(vec![rule(2, 10, "(unclosed"), valid]);
+
+        assert_eq!(invalid.len(), 1);
+        assert_eq!(invalid[0].0.id, Some(2));
+
+        let o = rules.apply(&transaction("Transfer", 150.0)).unwrap();
+        assert_eq!((o.rule_id, o.is_transfer), (Some(1), Some(true)));
+        assert!(rules.apply(&transaction("Transfer", 50.0)).is_none());
+    }
+}
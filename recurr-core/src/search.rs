@@ -0,0 +1,346 @@
+use chrono::NaiveDate;
+
+use crate::Error;
+
+/// How an `amount` term compares
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl Comparison {
+    /// The PostgREST operator
+    fn operator(&self) -> &'static str {
+        match self {
+            Comparison::Less => "lt",
+            Comparison::LessOrEqual => "lte",
+            Comparison::Equal => "eq",
+            Comparison::GreaterOrEqual => "gte",
+            Comparison::Greater => "gt",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Term {
+    /// Bare words and quoted phrases, matched against name, merchant and notes
+    Text(String),
+    Merchant(String),
+    Name(String),
+    Category(String),
+    Tag(String),
+    Amount(Comparison, f64),
+    Before(NaiveDate),
+    After(NaiveDate),
+    On(NaiveDate),
+    Pending,
+}
+
+/// A term, or a term that must not match when negated with a leading `-`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clause {
+    pub negated: bool,
+    pub term: Term,
+}
+
+/// A parsed search like `merchant:starbucks amount>20 -pending cat:"Food and Drink"`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Query {
+    pub clauses: Vec<Clause>,
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        let mut clauses = Vec::new();
+
+        for token in tokenize(input)? {
+            let (negated, token) = match token.strip_prefix('-') {
+                Some(rest) if !rest.is_empty() => (true, rest.to_string()),
+                _ => (false, token),
+            };
+            clauses.push(Clause {
+                negated,
+                term: parse_term(&token)?,
+            });
+        }
+
+        let categories = clauses
+            .iter()
+            .filter(|c| matches!(c.term, Term::Category(_)))
+            .count();
+        if clauses
+            .iter()
+            .any(|c| c.negated && matches!(c.term, Term::Category(_)))
+        {
+            return Err(Error::Other("Categories can't be excluded".to_string()));
+        }
+        if categories > 1 {
+            return Err(Error::Other(
+                "Only one category can be searched at a time".to_string(),
+            ));
+        }
+
+        Ok(Self { clauses })
+    }
+
+    /// The category key to filter by, categories are matched with their
+    /// descendants and splits by `transactions_in_category`
+    pub fn category(&self) -> Option<&str> {
+        self.clauses.iter().find_map(|c| match &c.term {
+            Term::Category(category) => Some(category.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Every other term as a PostgREST logic tree for `and`, `None` when there
+    /// are none
+    pub fn filters(&self) -> Option<String> {
+        let filters: Vec<String> = self
+            .clauses
+            .iter()
+            .filter_map(|c| {
+                let filter = filter(&c.term)?;
+                Some(if c.negated {
+                    negate(&c.term, filter)
+                } else {
+                    filter
+                })
+            })
+            .collect();
+
+        (!filters.is_empty()).then(|| filters.join(","))
+    }
+
+    /// Every term but the category for the `filter_transactions` function, as
+    /// objects with the term's `kind`, `value` and comparison `op` for amounts
+    pub fn terms(&self) -> Vec<serde_json::Value> {
+        self.clauses
+            .iter()
+            .filter_map(|c| {
+                let date = |date: &NaiveDate| date.format("%Y-%m-%d").to_string();
+                let (kind, value) = match &c.term {
+                    Term::Text(text) => ("text", serde_json::json!(text)),
+                    Term::Merchant(merchant) => ("merchant", serde_json::json!(merchant)),
+                    Term::Name(name) => ("name", serde_json::json!(name)),
+                    Term::Category(_) => return None,
+                    Term::Tag(tag) => ("tag", serde_json::json!(tag)),
+                    Term::Amount(_, amount) => ("amount", serde_json::json!(amount)),
+                    Term::Before(d) => ("before", serde_json::json!(date(d))),
+                    Term::After(d) => ("after", serde_json::json!(date(d))),
+                    Term::On(d) => ("on", serde_json::json!(date(d))),
+                    Term::Pending => ("pending", serde_json::Value::Null),
+                };
+                let op = match &c.term {
+                    Term::Amount(comparison, _) => Some(comparison.operator()),
+                    _ => None,
+                };
+
+                Some(serde_json::json!({
+                    "negated": c.negated,
+                    "kind": kind,
+                    "value": value,
+                    "op": op,
+                }))
+            })
+            .collect()
+    }
+}
+
+/// Splits on whitespace outside of double quotes, quotes are dropped
+fn tokenize(input: &str) -> Result<Vec<String>, Error> {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut quoted = false;
+
+    for c in input.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !token.is_empty() {
+                    tokens.push(std::mem::take(&mut token));
+                }
+            }
+            c => token.push(c),
+        }
+    }
+
+    if quoted {
+        return Err(Error::Other("Unclosed quote in search".to_string()));
+    }
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+fn parse_term(token: &str) -> Result<Term, Error> {
+    if token.eq_ignore_ascii_case("pending") {
+        return Ok(Term::Pending);
+    }
+
+    if let Some(rest) = token.strip_prefix("amount") {
+        let (comparison, value) = if let Some(value) = rest.strip_prefix(">=") {
+            (Comparison::GreaterOrEqual, value)
+        } else if let Some(value) = rest.strip_prefix("<=") {
+            (Comparison::LessOrEqual, value)
+        } else if let Some(value) = rest.strip_prefix('>') {
+            (Comparison::Greater, value)
+        } else if let Some(value) = rest.strip_prefix('<') {
+            (Comparison::Less, value)
+        } else if let Some(value) = rest.strip_prefix('=').or_else(|| rest.strip_prefix(':')) {
+            (Comparison::Equal, value)
+        } else {
+            return Ok(Term::Text(token.to_string()));
+        };
+
+        let amount = value
+            .parse::<f64>()
+            .ok()
+            .filter(|a| a.is_finite())
+            .ok_or_else(|| Error::Other(format!("Invalid amount `{value}`")))?;
+        return Ok(Term::Amount(comparison, amount));
+    }
+
+    let Some((key, value)) = token.split_once(':').filter(|(_, v)| !v.is_empty()) else {
+        return Ok(Term::Text(token.to_string()));
+    };
+    let date = || {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map_err(|_| Error::Other(format!("Invalid date `{value}`, use YYYY-MM-DD")))
+    };
+
+    Ok(match key.to_lowercase().as_str() {
+        "merchant" => Term::Merchant(value.to_string()),
+        "name" => Term::Name(value.to_string()),
+        "cat" | "category" => Term::Category(value.to_string()),
+        "tag" => Term::Tag(value.trim_start_matches('#').to_lowercase()),
+        "before" => Term::Before(date()?),
+        "after" => Term::After(date()?),
+        "on" => Term::On(date()?),
+        _ => Term::Text(token.to_string()),
+    })
+}
+
+/// Quotes a value for a logic tree, where commas and parentheses are reserved
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn contains(column: &str, value: &str) -> String {
+    format!("{column}.ilike.{}", quote(&format!("*{value}*")))
+}
+
+fn filter(term: &Term) -> Option<String> {
+    let date = |date: &NaiveDate| date.format("%Y-%m-%d").to_string();
+
+    Some(match term {
+        Term::Text(text) => contains("search_text", text),
+        Term::Merchant(merchant) => contains("search_merchant", merchant),
+        Term::Name(name) => contains("name", name),
+        Term::Category(_) => return None,
+        Term::Tag(tag) => format!("tags.cs.{{{}}}", quote(tag)),
+        Term::Amount(comparison, amount) => format!("amount.{}.{amount}", comparison.operator()),
+        Term::Before(d) => format!("date.lt.{}", date(d)),
+        Term::After(d) => format!("date.gt.{}", date(d)),
+        Term::On(d) => format!("date.eq.{}", date(d)),
+        Term::Pending => "pending.is.true".to_string(),
+    })
+}
+
+/// Negated text matches keep rows where the column is null, which `not`
+/// alone would drop
+fn negate(term: &Term, filter: String) -> String {
+    match term {
+        Term::Text(text) => format!(
+            "or(search_text.is.null,search_text.not.ilike.{})",
+            quote(&format!("*{text}*"))
+        ),
+        Term::Merchant(merchant) => format!(
+            "or(search_merchant.is.null,search_merchant.not.ilike.{})",
+            quote(&format!("*{merchant}*"))
+        ),
+        _ => format!("not.{filter}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_example_query() {
+        let query = Query::parse(
+            r#"merchant:starbucks amount>20 -pending cat:"Food and Drink" before:2024-01-01"#,
+        )
+        .unwrap();
+
+        assert_eq!(query.category(), Some("Food and Drink"));
+        assert_eq!(
+            query.filters().unwrap(),
+            r#"search_merchant.ilike."*starbucks*",amount.gt.20,not.pending.is.true,date.lt.2024-01-01"#
+        );
+    }
+
+    #[test]
+    fn bare_words_search_text() {
+        let query = Query::parse(r#"coffee "corner, shop" -refund"#).unwrap();
+
+        assert_eq!(query.clauses.len(), 3);
+        assert_eq!(query.category(), None);
+        assert_eq!(
+            query.filters().unwrap(),
+            r#"search_text.ilike."*coffee*",search_text.ilike."*corner, shop*",or(search_text.is.null,search_text.not.ilike."*refund*")"#
+        );
+    }
+
+    #[test]
+    fn amounts_and_tags() {
+        let query = Query::parse("amount<=9.5 amount:3 tag:#Work").unwrap();
+
+        assert_eq!(
+            query.clauses[0].term,
+            Term::Amount(Comparison::LessOrEqual, 9.5)
+        );
+        assert_eq!(
+            query.filters().unwrap(),
+            r#"amount.lte.9.5,amount.eq.3,tags.cs.{"work"}"#
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_queries() {
+        assert!(Query::parse("before:yesterday").is_err());
+        assert!(Query::parse("amount>lots").is_err());
+        assert!(Query::parse("amount>NaN").is_err());
+        assert!(Query::parse("amount<inf").is_err());
+        assert!(Query::parse("amount:-infinity").is_err());
+        assert!(Query::parse(r#"merchant:"open"#).is_err());
+        assert!(Query::parse("-cat:Shops").is_err());
+        assert!(Query::parse("cat:Shops cat:Travel").is_err());
+    }
+
+    #[test]
+    fn terms_leave_out_the_category() {
+        let query = Query::parse(r#"-merchant:"blue bottle" amount>20 cat:Shops pending"#).unwrap();
+
+        assert_eq!(
+            serde_json::Value::from(query.terms()),
+            serde_json::json!([
+                {"negated": true, "kind": "merchant", "value": "blue bottle", "op": null},
+                {"negated": false, "kind": "amount", "value": 20.0, "op": "gt"},
+                {"negated": false, "kind": "pending", "value": null, "op": null},
+            ])
+        );
+    }
+
+    #[test]
+    fn empty_queries_have_no_filters() {
+        assert_eq!(Query::parse("  ").unwrap().filters(), None);
+        assert_eq!(Query::parse("cat:Shops").unwrap().filters(), None);
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Transaction};
+
+/// Splits keyed by their parent's transaction id
+pub type SplitMap = HashMap<String, Vec<SchemaTransactionSplit>>;
+
+/// Splits may be off from the parent amount by rounding, but not by a cent
+const TOLERANCE: f64 = 0.005;
+
+/// A share of a transaction's amount assigned to its own category. Splits are
+/// stored apart from "transactions" so Plaid updates to the parent keep them
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SchemaTransactionSplit {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    pub transaction_id: String,
+    pub user_id: String,
+    pub category: Vec<String>,
+    pub amount: f64,
+    pub memo: Option<String>,
+}
+
+impl SchemaTransactionSplit {
+    /// The parent transaction narrowed down to this split
+    pub fn to_transaction(&self, parent: &Transaction) -> Transaction {
+        let mut transaction = parent.clone();
+        transaction.category = Some(self.category.clone());
+        transaction.amount = self.amount;
+        transaction.personal_finance_category = None;
+        transaction
+    }
+}
+
+pub fn validate(amount: f64, splits: &[SchemaTransactionSplit]) -> Result<(), Error> {
+    if splits.iter().any(|s| s.category.is_empty()) {
+        return Err(Error::Other("Every split needs a category".to_string()));
+    }
+
+    let total: f64 = splits.iter().map(|s| s.amount).sum();
+    if (total - amount).abs() > TOLERANCE {
+        return Err(Error::Other(format!(
+            "Splits add up to {total:.2} but the transaction is {amount:.2}"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Scales splits to a new parent amount keeping their proportions, for when
+/// Plaid changes the amount of a transaction that was already split, like a
+/// tip added once it posts. Rounding cents land on the last split. Returns
+/// whether anything changed
+pub fn rebalance(amount: f64, splits: &mut [SchemaTransactionSplit]) -> bool {
+    let total: f64 = splits.iter().map(|s| s.amount).sum();
+    if splits.is_empty() || (amount - total).abs() <= TOLERANCE {
+        return false;
+    }
+
+    // Splits cancelling out have no proportions to keep
+    let count = splits.len() as f64;
+    for split in splits.iter_mut() {
+        let share = if total.abs() > TOLERANCE {
+            split.amount / total
+        } else {
+            1.0 / count
+        };
+        split.amount = (amount * share * 100.0).round() / 100.0;
+    }
+
+    let rounded: f64 = splits.iter().map(|s| s.amount).sum();
+    if let Some(last) = splits.last_mut() {
+        last.amount = ((last.amount + amount - rounded) * 100.0).round() / 100.0;
+    }
+
+    true
+}
+
+pub fn group(splits: Vec<SchemaTransactionSplit>) -> SplitMap {
+    let mut grouped = SplitMap::new();
+    for split in splits {
+        grouped
+            .entry(split.transaction_id.clone())
+            .or_default()
+            .push(split);
+    }
+
+    grouped
+}
+
+/// Replaces split transactions with one transaction per split
+pub fn expand(transactions: Vec<Transaction>, splits: &SplitMap) -> Vec<Transaction> {
+    transactions
+        .into_iter()
+        .flat_map(|t| match splits.get(&t.transaction_id) {
+            Some(splits) if !splits.is_empty() => {
+                splits.iter().map(|s| s.to_transaction(&t)).collect()
+            }
+            _ => vec![t],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(category: &str, amount: f64) -> SchemaTransactionSplit {
+        SchemaTransactionSplit {
+            id: None,
+            transaction_id: "parent".to_string(),
+            user_id: "user".to_string(),
+            category: vec![category.to_string()],
+            amount,
+            memo: None,
+        }
+    }
+
+    fn amounts(splits: &[SchemaTransactionSplit]) -> Vec<f64> {
+        splits.iter().map(|s| s.amount).collect()
+    }
+
+    fn parent(amount: f64) -> Transaction {
+        Transaction {
+            transaction_id: "parent".to_string(),
+            account_id: "checking".to_string(),
+            amount,
+            name: "Costco".to_string(),
+            date: "2026-10-01".to_string(),
+            category: Some(vec!["Shops".to_string()]),
+            category_id: None,
+            merchant_name: None,
+            pending: false,
+            pending_transaction_id: None,
+            iso_currency_code: Some("USD".to_string()),
+            unofficial_currency_code: None,
+            personal_finance_category: None,
+            is_transfer: None,
+            notes: None,
+            tags: None,
+            manual: None,
+        }
+    }
+
+    #[test]
+    fn splits_must_add_up_and_have_categories() {
+        let splits = vec![split("Groceries", 60.0), split("Home", 40.004)];
+        assert!(validate(100.0, &splits).is_ok());
+        assert!(validate(100.02, &splits).is_err());
+
+        let mut uncategorized = split("Home", 40.0);
+        uncategorized.category.clear();
+        assert!(validate(100.0, &[split("Groceries", 60.0), uncategorized]).is_err());
+    }
+
+    #[test]
+    fn rebalance_keeps_proportions() {
+        let mut splits = vec![split("Food", 30.0), split("Drinks", 10.0)];
+        assert!(rebalance(48.0, &mut splits));
+        assert_eq!(amounts(&splits), vec![36.0, 12.0]);
+
+        // Already adding up leaves them alone
+        assert!(!rebalance(48.0, &mut splits));
+    }
+
+    #[test]
+    fn rebalance_puts_rounding_on_the_last_split() {
+        let mut splits = vec![split("A", 10.0), split("B", 10.0), split("C", 10.0)];
+        assert!(rebalance(10.0, &mut splits));
+        assert_eq!(amounts(&splits), vec![3.33, 3.33, 3.34]);
+        assert!(validate(10.0, &splits).is_ok());
+
+        // A refund and a purchase cancelling out are shared evenly
+        let mut splits = vec![split("A", 5.0), split("B", -5.0)];
+        assert!(rebalance(8.0, &mut splits));
+        assert_eq!(amounts(&splits), vec![4.0, 4.0]);
+    }
+
+    #[test]
+    fn expand_replaces_split_parents() {
+        let splits = group(vec![split("Groceries", 60.0), split("Home", 40.0)]);
+        let mut other = parent(12.0);
+        other.transaction_id = "other".to_string();
+
+        let expanded = expand(vec![parent(100.0), other], &splits);
+        let rows: Vec<(&str, f64, Option<&[String]>)> = expanded
+            .iter()
+            .map(|t| (t.transaction_id.as_str(), t.amount, t.category.as_deref()))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("parent", 60.0, Some(&["Groceries".to_string()][..])),
+                ("parent", 40.0, Some(&["Home".to_string()][..])),
+                ("other", 12.0, Some(&["Shops".to_string()][..])),
+            ]
+        );
+    }
+}
@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    budgets::{BudgetPeriod, Period},
+    currency::ExchangeRates,
+    pfc, Transaction,
+};
+
+/// Money moving between the user's own accounts or coming in isn't spending
+pub(crate) const EXCLUDED: &[&str] = &["INCOME", "TRANSFER_IN", "TRANSFER_OUT"];
+
+/// A monthly budget proposed from a category's past spending
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BudgetSuggestion {
+    pub category_id: String,
+    /// Spending in each month analysed, oldest first
+    pub months: Vec<f64>,
+    pub median: f64,
+    /// Mean of the months left after dropping outliers
+    pub mean: f64,
+    /// Change per month of the spending left after dropping outliers
+    pub trend: f64,
+    pub outliers: usize,
+    pub suggested: f64,
+}
+
+impl BudgetSuggestion {
+    /// The suggested amount scaled from a month to one `period`
+    pub fn for_period(&self, period: BudgetPeriod) -> f64 {
+        let scaled = match period {
+            BudgetPeriod::Weekly => self.suggested * 12.0 / 52.0,
+            BudgetPeriod::Biweekly => self.suggested * 12.0 / 26.0,
+            BudgetPeriod::Monthly => self.suggested,
+            BudgetPeriod::Quarterly => self.suggested * 3.0,
+            BudgetPeriod::Yearly => self.suggested * 12.0,
+        };
+
+        (scaled * 100.0).round() / 100.0
+    }
+}
+
+/// The `months` whole calendar months before the one containing `today`
+pub fn window(today: NaiveDate, months: u32) -> Period {
+    let current = BudgetPeriod::Monthly.period(1, None, today, 0);
+    Period {
+        start: BudgetPeriod::Monthly
+            .period(1, None, today, -(months as i64))
+            .start,
+        end: current.start,
+    }
+}
+
+/// Proposes a monthly budget for every PFC primary with spending in `window`. Transfers and
+/// income are skipped, months far outside the rest are left out of the mean and trend
+pub fn suggest(
+    transactions: &[Transaction],
+    rates: &ExchangeRates,
+    window: &Period,
+) -> Vec<BudgetSuggestion> {
+    let mut months = vec![BudgetPeriod::Monthly.period(1, None, window.start, 0)];
+    while let Some(last) = months.last().filter(|m| m.end < window.end) {
+        months.push(BudgetPeriod::Monthly.period(1, None, last.start, 1));
+    }
+
+    let mut totals: HashMap<String, Vec<f64>> = HashMap::new();
+    for t in transactions.iter().filter(|t| t.is_transfer != Some(true)) {
+        let Some(category) = pfc::primary(t).filter(|c| !EXCLUDED.contains(&c.as_str())) else {
+            continue;
+        };
+        let Some(month) = months.iter().position(|m| m.contains(&t.date)) else {
+            continue;
+        };
+        let Some(amount) = rates.to_base(t.amount, t.iso_currency_code.as_deref()) else {
+            continue;
+        };
+
+        totals
+            .entry(category)
+            .or_insert_with(|| vec![0.0; months.len()])[month] += amount;
+    }
+
+    let mut suggestions: Vec<BudgetSuggestion> = totals
+        .into_iter()
+        .filter_map(|(category_id, months)| {
+            // Refunds can outweigh a month's purchases
+            let months: Vec<f64> = months
+                .into_iter()
+                .map(|m| (m.max(0.0) * 100.0).round() / 100.0)
+                .collect();
+            suggestion(category_id, months)
+        })
+        .collect();
+
+    suggestions.sort_by(|a, b| {
+        b.suggested
+            .total_cmp(&a.suggested)
+            .then_with(|| a.category_id.cmp(&b.category_id))
+    });
+    suggestions
+}
+
+fn suggestion(category_id: String, months: Vec<f64>) -> Option<BudgetSuggestion> {
+    let mut sorted = months.clone();
+    sorted.sort_by(f64::total_cmp);
+    let median = quantile(&sorted, 0.5);
+
+    // Tukey's fences, too few months to tell what's unusual otherwise
+    let (low, high) = if sorted.len() >= 4 {
+        let (q1, q3) = (quantile(&sorted, 0.25), quantile(&sorted, 0.75));
+        let iqr = q3 - q1;
+        (q1 - 1.5 * iqr, q3 + 1.5 * iqr)
+    } else {
+        (f64::MIN, f64::MAX)
+    };
+
+    let kept: Vec<(f64, f64)> = months
+        .iter()
+        .enumerate()
+        .filter(|(_, m)| (low..=high).contains(*m))
+        .map(|(i, m)| (i as f64, *m))
+        .collect();
+
+    let n = kept.len() as f64;
+    let mean_x = kept.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean = kept.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+    let variance: f64 = kept.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    let trend = if variance > 0.0 {
+        kept.iter()
+            .map(|(x, y)| (x - mean_x) * (y - mean))
+            .sum::<f64>()
+            / variance
+    } else {
+        0.0
+    };
+
+    // Where the trend puts next month, never below a typical month
+    let projected = mean + trend * (months.len() as f64 - mean_x);
+    let suggested = (median.max(projected) / 5.0).ceil() * 5.0;
+    if suggested <= 0.0 {
+        return None;
+    }
+
+    Some(BudgetSuggestion {
+        category_id,
+        outliers: months.len() - kept.len(),
+        months,
+        median,
+        mean: (mean * 100.0).round() / 100.0,
+        trend: (trend * 100.0).round() / 100.0,
+        suggested,
+    })
+}
+
+/// Linearly interpolated quantile of sorted values
+pub(crate) fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = (sorted.len() - 1) as f64 * q;
+    let (lower, upper) = (position.floor() as usize, position.ceil() as usize);
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(primary: &str, amount: f64, date: &str) -> Transaction {
+        serde_json::from_value(serde_json::json!({
+            "transaction_id": format!("{primary}-{date}"),
+            "account_id": "account",
+            "amount": amount,
+            "name": primary,
+            "date": date,
+            "pending": false,
+            "personal_finance_category": {
+                "primary": primary,
+                "detailed": format!("{primary}_OTHER"),
+            },
+        }))
+        .expect("Valid transaction")
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, 19).unwrap()
+    }
+
+    #[test]
+    fn no_history_suggests_nothing() {
+        let window = window(today(), 6);
+        assert!(suggest(&[], &ExchangeRates::default(), &window).is_empty());
+
+        // Only income and transfers
+        let transactions = vec![
+            transaction("INCOME", -2000.0, "2026-09-01"),
+            transaction("TRANSFER_OUT", 500.0, "2026-09-02"),
+        ];
+        assert!(suggest(&transactions, &ExchangeRates::default(), &window).is_empty());
+    }
+
+    #[test]
+    fn one_month_suggests_its_spending() {
+        let transactions = vec![
+            transaction("FOOD_AND_DRINK", 60.0, "2026-09-03"),
+            transaction("FOOD_AND_DRINK", 37.0, "2026-09-20"),
+            // Outside the window
+            transaction("FOOD_AND_DRINK", 500.0, "2026-10-02"),
+        ];
+
+        let suggestions = suggest(
+            &transactions,
+            &ExchangeRates::default(),
+            &window(today(), 1),
+        );
+        assert_eq!(suggestions.len(), 1);
+
+        let food = &suggestions[0];
+        assert_eq!(food.months, vec![97.0]);
+        assert_eq!(food.trend, 0.0);
+        assert_eq!(food.outliers, 0);
+        assert_eq!(food.suggested, 100.0);
+    }
+
+    #[test]
+    fn outlying_months_are_left_out() {
+        let transactions: Vec<Transaction> = [
+            ("2026-04-10", 100.0),
+            ("2026-05-10", 100.0),
+            ("2026-06-10", 900.0),
+            ("2026-07-10", 100.0),
+            ("2026-08-10", 100.0),
+            ("2026-09-10", 100.0),
+        ]
+        .into_iter()
+        .map(|(date, amount)| transaction("TRAVEL", amount, date))
+        .collect();
+
+        let suggestions = suggest(
+            &transactions,
+            &ExchangeRates::default(),
+            &window(today(), 6),
+        );
+        let travel = &suggestions[0];
+        assert_eq!(
+            travel.months,
+            vec![100.0, 100.0, 900.0, 100.0, 100.0, 100.0]
+        );
+        assert_eq!(travel.outliers, 1);
+        assert_eq!(travel.mean, 100.0);
+        assert_eq!(travel.trend, 0.0);
+        assert_eq!(travel.suggested, 100.0);
+        assert_eq!(travel.for_period(BudgetPeriod::Yearly), 1200.0);
+    }
+}
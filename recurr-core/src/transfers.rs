@@ -0,0 +1,269 @@
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::{pfc, Transaction};
+
+/// Days apart the two sides of a transfer may post, card payments take a few
+/// business days to clear
+pub const MATCH_DAYS: i64 = 5;
+
+/// PFC primaries Plaid gives money moved between accounts, legacy `Transfer`,
+/// `Deposit` and `Payment` categories map onto them
+const TRANSFER_PRIMARIES: &[&str] = &["TRANSFER_IN", "TRANSFER_OUT", "LOAN_PAYMENTS"];
+
+/// Two transactions moving the same money between the user's own accounts,
+/// both are left out of spending and income while `linked`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SchemaTransferLink {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    /// Left to the database's default when matched during a sync
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    /// The side money left, with a positive amount
+    pub outflow_id: String,
+    pub inflow_id: String,
+    /// Linked by hand rather than found by [`match_transfers`]
+    pub manual: bool,
+    /// False once the user unlinks a matched pair, so it isn't matched again
+    pub linked: bool,
+}
+
+impl SchemaTransferLink {
+    pub fn contains(&self, transaction_id: &str) -> bool {
+        self.outflow_id == transaction_id || self.inflow_id == transaction_id
+    }
+
+    /// The other side of the transfer from `transaction_id`
+    pub fn other(&self, transaction_id: &str) -> &str {
+        if self.outflow_id == transaction_id {
+            &self.inflow_id
+        } else {
+            &self.outflow_id
+        }
+    }
+}
+
+/// A transaction with its parsed date
+type Dated<'a> = (&'a Transaction, NaiveDate);
+
+fn is_hinted(transaction: &Transaction) -> bool {
+    pfc::primary(transaction).is_some_and(|p| TRANSFER_PRIMARIES.contains(&p.as_str()))
+}
+
+/// Amounts in cents with their currency, sides of a transfer share one
+fn amount_key(transaction: &Transaction) -> (i64, Option<&str>) {
+    let currency = transaction
+        .iso_currency_code
+        .as_deref()
+        .or(transaction.unofficial_currency_code.as_deref());
+    ((transaction.amount.abs() * 100.0).round() as i64, currency)
+}
+
+/// Pairs opposite-signed transactions of equal amount on two different
+/// `accounts` that posted within [`MATCH_DAYS`] of each other. At least one
+/// side must carry a Plaid transfer category, pairs with both hinted and then
+/// the closest dates are linked first. Transactions already `linked`, and
+/// pairs the user unlinked, are left alone
+pub fn match_transfers(
+    transactions: &[Transaction],
+    accounts: &HashSet<&str>,
+    links: &[SchemaTransferLink],
+) -> Vec<SchemaTransferLink> {
+    let mut used: HashSet<&str> = links
+        .iter()
+        .filter(|l| l.linked)
+        .flat_map(|l| [l.outflow_id.as_str(), l.inflow_id.as_str()])
+        .collect();
+    let unlinked: HashSet<(&str, &str)> = links
+        .iter()
+        .filter(|l| !l.linked)
+        .map(|l| (l.outflow_id.as_str(), l.inflow_id.as_str()))
+        .collect();
+
+    let candidates: Vec<Dated> = transactions
+        .iter()
+        .filter(|t| !t.pending && t.amount != 0.0)
+        .filter(|t| accounts.contains(t.account_id.as_str()))
+        .filter(|t| !used.contains(t.transaction_id.as_str()))
+        .filter_map(|t| Some((t, NaiveDate::parse_from_str(&t.date, "%Y-%m-%d").ok()?)))
+        .collect();
+
+    let mut inflows: HashMap<(i64, Option<&str>), Vec<Dated>> = HashMap::new();
+    for (t, date) in candidates.iter().filter(|(t, _)| t.amount < 0.0) {
+        inflows.entry(amount_key(t)).or_default().push((t, *date));
+    }
+
+    let mut pairs = Vec::new();
+    for (outflow, out_date) in candidates.iter().filter(|(t, _)| t.amount > 0.0) {
+        for (inflow, in_date) in inflows.get(&amount_key(outflow)).into_iter().flatten() {
+            let days = (*in_date - *out_date).num_days().abs();
+            let hints = is_hinted(outflow) as u8 + is_hinted(inflow) as u8;
+            if outflow.account_id == inflow.account_id
+                || days > MATCH_DAYS
+                || hints == 0
+                || unlinked.contains(&(
+                    outflow.transaction_id.as_str(),
+                    inflow.transaction_id.as_str(),
+                ))
+            {
+                continue;
+            }
+
+            pairs.push((Reverse(hints), days, *outflow, *inflow));
+        }
+    }
+    pairs.sort_by(|a, b| {
+        (a.0, a.1, &a.2.transaction_id, &a.3.transaction_id).cmp(&(
+            b.0,
+            b.1,
+            &b.2.transaction_id,
+            &b.3.transaction_id,
+        ))
+    });
+
+    let mut matched = Vec::new();
+    for (_, _, outflow, inflow) in pairs {
+        if used.contains(outflow.transaction_id.as_str())
+            || used.contains(inflow.transaction_id.as_str())
+        {
+            continue;
+        }
+        used.insert(&outflow.transaction_id);
+        used.insert(&inflow.transaction_id);
+
+        matched.push(SchemaTransferLink {
+            id: None,
+            user_id: None,
+            outflow_id: outflow.transaction_id.clone(),
+            inflow_id: inflow.transaction_id.clone(),
+            manual: false,
+            linked: true,
+        });
+    }
+
+    matched
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pfc::PersonalFinanceCategory;
+
+    fn transaction(id: &str, account: &str, amount: f64, date: &str, primary: &str) -> Transaction {
+        Transaction {
+            transaction_id: id.to_string(),
+            account_id: account.to_string(),
+            amount,
+            name: id.to_string(),
+            date: date.to_string(),
+            category: None,
+            category_id: None,
+            merchant_name: None,
+            pending: false,
+            pending_transaction_id: None,
+            iso_currency_code: Some("USD".to_string()),
+            unofficial_currency_code: None,
+            personal_finance_category: Some(PersonalFinanceCategory {
+                primary: primary.to_string(),
+                detailed: format!("{primary}_OTHER"),
+                confidence_level: None,
+            }),
+            is_transfer: None,
+            notes: None,
+            tags: None,
+            manual: None,
+        }
+    }
+
+    fn pairs(links: &[SchemaTransferLink]) -> Vec<(&str, &str)> {
+        links
+            .iter()
+            .map(|l| (l.outflow_id.as_str(), l.inflow_id.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn matches_card_payments_between_own_accounts() {
+        let transactions = vec![
+            transaction("payment", "checking", 500.0, "2026-10-01", "LOAN_PAYMENTS"),
+            transaction("received", "card", -500.0, "2026-10-03", "TRANSFER_IN"),
+            // Same amounts that aren't transfers between the user's accounts
+            transaction(
+                "refund",
+                "checking",
+                -500.0,
+                "2026-10-02",
+                "GENERAL_MERCHANDISE",
+            ),
+            transaction("cash", "wallet", -500.0, "2026-10-01", "TRANSFER_IN"),
+            transaction("late", "savings", -500.0, "2026-10-09", "TRANSFER_IN"),
+        ];
+        let accounts = HashSet::from(["checking", "card", "savings"]);
+
+        let links = match_transfers(&transactions, &accounts, &[]);
+        assert_eq!(pairs(&links), vec![("payment", "received")]);
+        assert!(links[0].linked && !links[0].manual);
+    }
+
+    #[test]
+    fn needs_a_hint_and_prefers_the_closest_match() {
+        let transactions = vec![
+            transaction(
+                "rent",
+                "checking",
+                1200.0,
+                "2026-10-01",
+                "RENT_AND_UTILITIES",
+            ),
+            transaction(
+                "sale",
+                "savings",
+                -1200.0,
+                "2026-10-01",
+                "GENERAL_MERCHANDISE",
+            ),
+            transaction("out", "checking", 80.0, "2026-10-05", "TRANSFER_OUT"),
+            transaction("far", "savings", -80.0, "2026-10-09", "TRANSFER_IN"),
+            transaction("near", "card", -80.0, "2026-10-06", "TRANSFER_IN"),
+        ];
+        let accounts = HashSet::from(["checking", "card", "savings"]);
+
+        let links = match_transfers(&transactions, &accounts, &[]);
+        assert_eq!(pairs(&links), vec![("out", "near")]);
+    }
+
+    #[test]
+    fn leaves_linked_and_unlinked_pairs_alone() {
+        let transactions = vec![
+            transaction("out", "checking", 80.0, "2026-10-05", "TRANSFER_OUT"),
+            transaction("near", "card", -80.0, "2026-10-06", "TRANSFER_IN"),
+            transaction("far", "savings", -80.0, "2026-10-08", "TRANSFER_IN"),
+        ];
+        let accounts = HashSet::from(["checking", "card", "savings"]);
+        let unlinked = SchemaTransferLink {
+            id: Some(1),
+            user_id: None,
+            outflow_id: "out".to_string(),
+            inflow_id: "near".to_string(),
+            manual: false,
+            linked: false,
+        };
+
+        let links = match_transfers(&transactions, &accounts, std::slice::from_ref(&unlinked));
+        assert_eq!(pairs(&links), vec![("out", "far")]);
+
+        let linked = SchemaTransferLink {
+            inflow_id: "far".to_string(),
+            manual: true,
+            linked: true,
+            ..unlinked.clone()
+        };
+        assert!(match_transfers(&transactions, &accounts, &[unlinked, linked]).is_empty());
+    }
+}
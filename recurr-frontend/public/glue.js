@@ -90,6 +90,98 @@ export async function invokeTransactionsSync(auth_key, access_token) {
     });
 }
 
+export async function invokeImportExchangeRates(
+    auth_key,
+    user_id,
+    base_currency,
+    path,
+) {
+    return await invoke("import_exchange_rates", {
+        authKey: auth_key,
+        userId: user_id,
+        baseCurrency: base_currency,
+        path: path,
+    });
+}
+
+export async function invokePreviewRule(auth_key, rule) {
+    return await invoke("preview_rule", {
+        authKey: auth_key,
+        rule: rule,
+    });
+}
+
+export async function invokeApplyRules(auth_key) {
+    return await invoke("apply_rules", {
+        authKey: auth_key,
+    });
+}
+
+export async function invokeDetectTransfers(auth_key) {
+    return await invoke("detect_transfers", {
+        authKey: auth_key,
+    });
+}
+
+export async function invokeForecastCashFlow(auth_key, user_id, days) {
+    return await invoke("forecast_cash_flow", {
+        authKey: auth_key,
+        userId: user_id,
+        days: days,
+    });
+}
+
+export async function invokeImportLiabilities(auth_key, user_id) {
+    return await invoke("import_liabilities", {
+        authKey: auth_key,
+        userId: user_id,
+    });
+}
+
+export async function invokeSaveSplits(auth_key, transaction_id, splits) {
+    return await invoke("save_splits", {
+        authKey: auth_key,
+        transactionId: transaction_id,
+        splits: splits,
+    });
+}
+
+export async function invokeSuggestBudgets(auth_key, user_id, months) {
+    return await invoke("suggest_budgets", {
+        authKey: auth_key,
+        userId: user_id,
+        months: months,
+    });
+}
+
+export async function invokeUploadAttachment(
+    auth_key,
+    user_id,
+    transaction_id,
+    path,
+) {
+    return await invoke("upload_attachment", {
+        authKey: auth_key,
+        userId: user_id,
+        transactionId: transaction_id,
+        path: path,
+    });
+}
+
+export async function invokeAttachmentUrl(auth_key, storage_path) {
+    return await invoke("attachment_url", {
+        authKey: auth_key,
+        storagePath: storage_path,
+    });
+}
+
+export async function invokeDeleteAttachment(auth_key, attachment) {
+    return await invoke("delete_attachment", {
+        authKey: auth_key,
+        attachment: attachment,
+    });
+}
+
 export function linkStart(link_token, callback) {
     Plaid.create({
         token: link_token,
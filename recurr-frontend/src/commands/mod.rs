@@ -1,4 +1,8 @@
-use recurr_core::{Account, Category, Institution, Item};
+use recurr_core::{
+    annotations::SchemaTransactionAttachment, currency::SchemaExchangeRate, debt::SchemaDebtTerms,
+    forecast::Forecast, rules::SchemaRule, splits::SchemaTransactionSplit,
+    suggestions::BudgetSuggestion, Account, Category, Institution, Item, Transaction,
+};
 use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
 
 pub mod link;
@@ -61,6 +65,68 @@ extern "C" {
         auth_token: &str,
         access_token: &str,
     ) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(catch)]
+    pub async fn invokeImportExchangeRates(
+        auth_key: &str,
+        user_id: &str,
+        base_currency: &str,
+        path: &str,
+    ) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch)]
+    pub async fn invokePreviewRule(auth_key: &str, rule: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch)]
+    pub async fn invokeApplyRules(auth_key: &str) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch)]
+    pub async fn invokeDetectTransfers(auth_key: &str) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch)]
+    pub async fn invokeSaveSplits(
+        auth_key: &str,
+        transaction_id: &str,
+        splits: JsValue,
+    ) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch)]
+    pub async fn invokeForecastCashFlow(
+        auth_key: &str,
+        user_id: &str,
+        days: usize,
+    ) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch)]
+    pub async fn invokeImportLiabilities(auth_key: &str, user_id: &str)
+        -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch)]
+    pub async fn invokeSuggestBudgets(
+        auth_key: &str,
+        user_id: &str,
+        months: u32,
+    ) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch)]
+    pub async fn invokeUploadAttachment(
+        auth_key: &str,
+        user_id: &str,
+        transaction_id: &str,
+        path: &str,
+    ) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch)]
+    pub async fn invokeAttachmentUrl(
+        auth_key: &str,
+        storage_path: &str,
+    ) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch)]
+    pub async fn invokeDeleteAttachment(
+        auth_key: &str,
+        attachment: JsValue,
+    ) -> Result<JsValue, JsValue>;
 }
 
 pub async fn get_accounts(
@@ -117,3 +183,124 @@ pub async fn get_balances(auth_token: &str, user_id: &str) -> Result<Vec<Account
         }
     }
 }
+
+pub async fn import_exchange_rates(
+    auth_key: &str,
+    user_id: &str,
+    base_currency: &str,
+    path: &str,
+) -> Result<Vec<SchemaExchangeRate>, String> {
+    let res = invokeImportExchangeRates(auth_key, user_id, base_currency, path).await;
+    match res {
+        Ok(json) => Ok(serde_wasm_bindgen::from_value(json).map_err(|e| e.to_string())?),
+        Err(e) => Err(format!("{:?}", e)),
+    }
+}
+
+pub async fn preview_rule(auth_key: &str, rule: &SchemaRule) -> Result<Vec<Transaction>, String> {
+    let rule = serde_wasm_bindgen::to_value(rule).expect("failed to serialize");
+
+    let res = invokePreviewRule(auth_key, rule).await;
+    match res {
+        Ok(json) => Ok(serde_wasm_bindgen::from_value(json).map_err(|e| e.to_string())?),
+        Err(e) => Err(format!("{:?}", e)),
+    }
+}
+
+pub async fn apply_rules(auth_key: &str) -> Result<usize, String> {
+    let res = invokeApplyRules(auth_key).await;
+    match res {
+        Ok(json) => Ok(serde_wasm_bindgen::from_value(json).map_err(|e| e.to_string())?),
+        Err(e) => Err(format!("{:?}", e)),
+    }
+}
+
+pub async fn detect_transfers(auth_key: &str) -> Result<usize, String> {
+    let res = invokeDetectTransfers(auth_key).await;
+    match res {
+        Ok(json) => Ok(serde_wasm_bindgen::from_value(json).map_err(|e| e.to_string())?),
+        Err(e) => Err(format!("{:?}", e)),
+    }
+}
+
+pub async fn save_splits(
+    auth_key: &str,
+    transaction_id: &str,
+    splits: &[SchemaTransactionSplit],
+) -> Result<Vec<SchemaTransactionSplit>, String> {
+    let splits = serde_wasm_bindgen::to_value(splits).expect("failed to serialize");
+
+    let res = invokeSaveSplits(auth_key, transaction_id, splits).await;
+    match res {
+        Ok(json) => Ok(serde_wasm_bindgen::from_value(json).map_err(|e| e.to_string())?),
+        Err(e) => Err(format!("{:?}", e)),
+    }
+}
+
+pub async fn forecast_cash_flow(
+    auth_key: &str,
+    user_id: &str,
+    days: usize,
+) -> Result<Forecast, String> {
+    let res = invokeForecastCashFlow(auth_key, user_id, days).await;
+    match res {
+        Ok(json) => Ok(serde_wasm_bindgen::from_value(json).map_err(|e| e.to_string())?),
+        Err(e) => Err(format!("{:?}", e)),
+    }
+}
+
+pub async fn import_liabilities(
+    auth_key: &str,
+    user_id: &str,
+) -> Result<Vec<SchemaDebtTerms>, String> {
+    let res = invokeImportLiabilities(auth_key, user_id).await;
+    match res {
+        Ok(json) => Ok(serde_wasm_bindgen::from_value(json).map_err(|e| e.to_string())?),
+        Err(e) => Err(format!("{:?}", e)),
+    }
+}
+
+pub async fn suggest_budgets(
+    auth_key: &str,
+    user_id: &str,
+    months: u32,
+) -> Result<Vec<BudgetSuggestion>, String> {
+    let res = invokeSuggestBudgets(auth_key, user_id, months).await;
+    match res {
+        Ok(json) => Ok(serde_wasm_bindgen::from_value(json).map_err(|e| e.to_string())?),
+        Err(e) => Err(format!("{:?}", e)),
+    }
+}
+
+pub async fn upload_attachment(
+    auth_key: &str,
+    user_id: &str,
+    transaction_id: &str,
+    path: &str,
+) -> Result<SchemaTransactionAttachment, String> {
+    let res = invokeUploadAttachment(auth_key, user_id, transaction_id, path).await;
+    match res {
+        Ok(json) => Ok(serde_wasm_bindgen::from_value(json).map_err(|e| e.to_string())?),
+        Err(e) => Err(format!("{:?}", e)),
+    }
+}
+
+pub async fn attachment_url(auth_key: &str, storage_path: &str) -> Result<String, String> {
+    let res = invokeAttachmentUrl(auth_key, storage_path).await;
+    match res {
+        Ok(json) => Ok(serde_wasm_bindgen::from_value(json).map_err(|e| e.to_string())?),
+        Err(e) => Err(format!("{:?}", e)),
+    }
+}
+
+pub async fn delete_attachment(
+    auth_key: &str,
+    attachment: &SchemaTransactionAttachment,
+) -> Result<(), String> {
+    let attachment = serde_wasm_bindgen::to_value(attachment).expect("failed to serialize");
+
+    invokeDeleteAttachment(auth_key, attachment)
+        .await
+        .map(|_| ())
+        .map_err(|e| format!("{:?}", e))
+}
@@ -1 +1,2 @@
 pub mod pagination;
+pub mod unconverted;
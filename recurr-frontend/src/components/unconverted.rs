@@ -0,0 +1,24 @@
+use yew::{function_component, html, Html, Properties};
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    /// Currencies without an exchange rate, from `ExchangeRates::unconverted`
+    pub currencies: Vec<String>,
+}
+
+/// Warns that amounts in currencies without a rate are left out of totals
+#[function_component(Unconverted)]
+pub fn unconverted(props: &Props) -> Html {
+    if props.currencies.is_empty() {
+        return html! {};
+    }
+
+    html! {
+        <p class="notification is-warning is-light p-2 m-3">
+            {format!(
+                "No exchange rate for {}, amounts in it are left out of these totals. Add one in Settings",
+                props.currencies.join(", ")
+            )}
+        </p>
+    }
+}
@@ -1,10 +1,13 @@
 use crate::supabase;
+use recurr_core::{categories::Taxonomy, currency::ExchangeRates};
 use std::rc::Rc;
 use supabase_js_rs::SupabaseClient;
 use yew::prelude::*;
 
 pub enum ContextUpdate {
     Session(Option<supabase::Session>),
+    ExchangeRates(ExchangeRates),
+    Taxonomy(Taxonomy),
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -12,6 +15,8 @@ pub struct Session {
     pub anon_key: String,
     pub supabase_client: SupabaseClient,
     pub supabase_session: Option<supabase::Session>,
+    pub exchange_rates: ExchangeRates,
+    pub taxonomy: Taxonomy,
 }
 
 impl Reducible for Session {
@@ -22,6 +27,8 @@ impl Reducible for Session {
 
         match action {
             ContextUpdate::Session(session) => s.supabase_session = session,
+            ContextUpdate::ExchangeRates(rates) => s.exchange_rates = rates,
+            ContextUpdate::Taxonomy(taxonomy) => s.taxonomy = taxonomy,
         }
 
         s.into()
@@ -46,6 +53,8 @@ pub fn SessionProvider(props: &SessionProviderProps) -> Html {
         supabase_client: (*supabase_client).clone(),
         supabase_session: None,
         anon_key: String::default(),
+        exchange_rates: ExchangeRates::default(),
+        taxonomy: Taxonomy::default(),
     });
 
     html! {
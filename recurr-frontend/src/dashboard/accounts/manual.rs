@@ -0,0 +1,437 @@
+use chrono::Local;
+use recurr_core::{
+    balances::SchemaBalanceSnapshot,
+    currency::format_amount,
+    get_supbase_client,
+    manual::{SchemaManualAccount, SchemaManualBalance, MANUAL_ACCOUNT_TYPES},
+};
+use web_sys::{HtmlInputElement, HtmlSelectElement, MouseEvent, SubmitEvent};
+use yew::{html, Component, Context, Html, NodeRef, Properties};
+
+use crate::context::SessionContext;
+
+use super::manual_transactions::ManualTransactions;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub context: SessionContext,
+}
+
+pub enum Msg {
+    GetAccounts,
+    GotAccounts(Vec<SchemaManualAccount>),
+
+    Add,
+    EditBalance(Option<String>),
+    SaveBalance(String),
+    ShowHistory(Option<String>),
+    GotHistory(String, Vec<SchemaManualBalance>),
+    ShowTransactions(Option<SchemaManualAccount>),
+    Delete(String),
+
+    Error(String),
+}
+
+pub struct ManualAccounts {
+    accounts: Vec<SchemaManualAccount>,
+    editing_balance: Option<String>,
+    history: Option<(String, Vec<SchemaManualBalance>)>,
+    transactions_for: Option<SchemaManualAccount>,
+    error: Option<String>,
+
+    name_ref: NodeRef,
+    type_ref: NodeRef,
+    currency_ref: NodeRef,
+    balance_ref: NodeRef,
+    new_balance_ref: NodeRef,
+}
+
+impl ManualAccounts {
+    fn session(ctx: &Context<Self>) -> (String, String) {
+        let session = ctx
+            .props()
+            .context
+            .supabase_session
+            .clone()
+            .expect("Needs session");
+        (session.auth_key, session.user.id)
+    }
+
+    fn input_value(node: &NodeRef) -> String {
+        node.cast::<HtmlInputElement>()
+            .expect("Not an input element")
+            .value()
+    }
+}
+
+impl Component for ManualAccounts {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        ctx.link().send_message(Msg::GetAccounts);
+
+        Self {
+            accounts: Vec::new(),
+            editing_balance: None,
+            history: None,
+            transactions_for: None,
+            error: None,
+
+            name_ref: NodeRef::default(),
+            type_ref: NodeRef::default(),
+            currency_ref: NodeRef::default(),
+            balance_ref: NodeRef::default(),
+            new_balance_ref: NodeRef::default(),
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let base_currency = ctx.props().context.exchange_rates.base().to_string();
+
+        let add = ctx.link().callback(|e: SubmitEvent| {
+            e.prevent_default();
+            Msg::Add
+        });
+        let close_transactions = ctx.link().callback(|_| Msg::ShowTransactions(None));
+
+        html! {
+            <div class="m-3 card">
+                <div class="card-header">
+                    <h1 class="card-header-title">{"Manual accounts"}</h1>
+                </div>
+
+                <div class="card-content">
+                    if let Some(e) = &self.error {
+                        <p class="help is-danger">{e}</p>
+                    }
+
+                    if let Some(account) = &self.transactions_for {
+                        <ManualTransactions account={account.clone()} on_close={close_transactions} context={ctx.props().context.clone()}/>
+                    }
+
+                    {
+                        self.accounts.iter().map(|a| {
+                            let account_id = a.account_id.clone().unwrap_or_default();
+                            let currency = a.iso_currency_code.clone().unwrap_or(base_currency.clone());
+
+                            let edit_balance = {
+                                let id = account_id.clone();
+                                ctx.link().callback(move |_| Msg::EditBalance(Some(id.clone())))
+                            };
+                            let save_balance = {
+                                let id = account_id.clone();
+                                ctx.link().callback(move |e: SubmitEvent| {
+                                    e.prevent_default();
+                                    Msg::SaveBalance(id.clone())
+                                })
+                            };
+                            let show_history = {
+                                let id = account_id.clone();
+                                let open = self.history.as_ref().map_or(false, |(h, _)| h == &id);
+                                ctx.link().callback(move |_| Msg::ShowHistory(if open { None } else { Some(id.clone()) }))
+                            };
+                            let show_transactions = {
+                                let a = a.clone();
+                                ctx.link().callback(move |_: MouseEvent| Msg::ShowTransactions(Some(a.clone())))
+                            };
+                            let delete = {
+                                let id = account_id.clone();
+                                ctx.link().callback(move |_| Msg::Delete(id.clone()))
+                            };
+
+                            html!{
+                                <div class="mb-3">
+                                    <div class="is-flex is-justify-content-space-between">
+                                        <span>{format!("{} ({})", a.name, a.account_type)}</span>
+                                        <span>{format_amount(a.current_balance, &currency)}</span>
+                                    </div>
+                                    <div class="is-size-7">
+                                        <a class="mr-3" onclick={edit_balance}>{"Update balance"}</a>
+                                        <a class="mr-3" onclick={show_history}>{"History"}</a>
+                                        <a class="mr-3" onclick={show_transactions}>{"Transactions"}</a>
+                                        <a class="has-text-danger" onclick={delete}>{"Delete"}</a>
+                                    </div>
+
+                                    if self.editing_balance.as_ref() == Some(&account_id) {
+                                        <form class="field has-addons mt-2" onsubmit={save_balance}>
+                                            <div class="control">
+                                                <input ref={self.new_balance_ref.clone()} class="input is-small" type="number" step="0.01" value={format!("{:.2}", a.current_balance)}/>
+                                            </div>
+                                            <div class="control">
+                                                <button class="button is-small is-success" type="submit">{"Save"}</button>
+                                            </div>
+                                        </form>
+                                    }
+
+                                    if let Some((_, history)) = self.history.as_ref().filter(|(h, _)| h == &account_id) {
+                                        <table class="table is-narrow is-size-7 mt-2">
+                                            <tbody>
+                                            {
+                                                history.iter().map(|b| html!{
+                                                    <tr>
+                                                        <td>{b.date.clone()}</td>
+                                                        <td>{format_amount(b.balance, &currency)}</td>
+                                                    </tr>
+                                                }).collect::<Html>()
+                                            }
+                                            </tbody>
+                                        </table>
+                                    }
+                                </div>
+                            }
+                        }).collect::<Html>()
+                    }
+
+                    <form onsubmit={add}>
+                        <div class="field has-addons">
+                            <div class="control is-expanded">
+                                <input ref={self.name_ref.clone()} class="input is-small" type="text" placeholder="Cash, House, Car..."/>
+                            </div>
+                            <div class="control">
+                                <div class="select is-small">
+                                    <select ref={self.type_ref.clone()}>
+                                    {
+                                        MANUAL_ACCOUNT_TYPES.iter().map(|t| html!{<option value={t.to_string()}>{t.to_string()}</option>}).collect::<Html>()
+                                    }
+                                    </select>
+                                </div>
+                            </div>
+                            <div class="control">
+                                <input ref={self.currency_ref.clone()} class="input is-small" type="text" maxlength="3" placeholder={base_currency.clone()}/>
+                            </div>
+                            <div class="control">
+                                <input ref={self.balance_ref.clone()} class="input is-small" type="number" step="0.01" placeholder="Balance"/>
+                            </div>
+                            <div class="control">
+                                <button class="button is-small is-success" type="submit">{"Add account"}</button>
+                            </div>
+                        </div>
+                    </form>
+                </div>
+            </div>
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        self.error = None;
+        let (auth_key, user_id) = Self::session(ctx);
+
+        match msg {
+            Msg::GetAccounts => {
+                ctx.link().send_future(async move {
+                    match get_manual_accounts(&auth_key).await {
+                        Ok(accounts) => Msg::GotAccounts(accounts),
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::GotAccounts(accounts) => self.accounts = accounts,
+            Msg::Add => {
+                let name = Self::input_value(&self.name_ref);
+                if name.is_empty() {
+                    self.error = Some("Enter a name for the account".to_string());
+                    return true;
+                }
+
+                let currency = Self::input_value(&self.currency_ref).to_uppercase();
+                let account = SchemaManualAccount {
+                    account_id: None,
+                    user_id: user_id.clone(),
+                    name,
+                    account_type: self
+                        .type_ref
+                        .cast::<HtmlSelectElement>()
+                        .expect("Type ref not a select element")
+                        .value(),
+                    subtype: None,
+                    iso_currency_code: if currency.is_empty() {
+                        None
+                    } else {
+                        Some(currency)
+                    },
+                    current_balance: Self::input_value(&self.balance_ref)
+                        .parse()
+                        .unwrap_or_default(),
+                };
+
+                ctx.link().send_future(async move {
+                    match add_account(&auth_key, &user_id, account).await {
+                        Ok(_) => Msg::GetAccounts,
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::EditBalance(account_id) => self.editing_balance = account_id,
+            Msg::SaveBalance(account_id) => {
+                let Some(account) = self
+                    .accounts
+                    .iter()
+                    .find(|a| a.account_id.as_ref() == Some(&account_id))
+                    .cloned()
+                else {
+                    return false;
+                };
+                let balance = match Self::input_value(&self.new_balance_ref).parse::<f64>() {
+                    Ok(balance) => balance,
+                    Err(_) => {
+                        self.error = Some("Enter a balance".to_string());
+                        return true;
+                    }
+                };
+                self.editing_balance = None;
+
+                ctx.link().send_future(async move {
+                    match record_balance(&auth_key, &user_id, &account, balance).await {
+                        Ok(_) => Msg::GetAccounts,
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::ShowHistory(None) => self.history = None,
+            Msg::ShowHistory(Some(account_id)) => {
+                ctx.link().send_future(async move {
+                    let res = get_supbase_client()
+                        .from("manual_account_balances")
+                        .auth(&auth_key)
+                        .select("*")
+                        .eq("account_id", &account_id)
+                        .order("date.desc,id.desc")
+                        .execute()
+                        .await
+                        .and_then(|r| r.error_for_status());
+
+                    match res {
+                        Ok(res) => match res.json().await {
+                            Ok(history) => Msg::GotHistory(account_id, history),
+                            Err(e) => Msg::Error(e.to_string()),
+                        },
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::GotHistory(account_id, history) => self.history = Some((account_id, history)),
+            Msg::ShowTransactions(account) => self.transactions_for = account,
+            Msg::Delete(account_id) => {
+                ctx.link().send_future(async move {
+                    let res = get_supbase_client()
+                        .from("manual_accounts")
+                        .auth(&auth_key)
+                        .eq("account_id", account_id)
+                        .delete()
+                        .execute()
+                        .await
+                        .and_then(|r| r.error_for_status());
+
+                    match res {
+                        Ok(_) => Msg::GetAccounts,
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::Error(e) => {
+                log::error!("{e}");
+                self.error = Some(e);
+            }
+        }
+
+        true
+    }
+}
+
+pub async fn get_manual_accounts(
+    auth_key: &str,
+) -> Result<Vec<SchemaManualAccount>, recurr_core::Error> {
+    let res = get_supbase_client()
+        .from("manual_accounts")
+        .auth(auth_key)
+        .select("*")
+        .order("name")
+        .execute()
+        .await?
+        .error_for_status()?;
+
+    Ok(res.json().await?)
+}
+
+async fn add_account(
+    auth_key: &str,
+    user_id: &str,
+    account: SchemaManualAccount,
+) -> Result<(), recurr_core::Error> {
+    let balance = account.current_balance;
+
+    let mut inserted: Vec<SchemaManualAccount> = get_supbase_client()
+        .from("manual_accounts")
+        .auth(auth_key)
+        .insert(serde_json::to_string(&account)?)
+        .execute()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    let account = inserted
+        .pop()
+        .filter(|a| a.account_id.is_some())
+        .ok_or_else(|| recurr_core::Error::Other("Account wasn't saved".to_string()))?;
+
+    record_balance(auth_key, user_id, &account, balance).await
+}
+
+/// Sets an account's balance and records it in the account's history and
+/// today's net worth snapshot
+async fn record_balance(
+    auth_key: &str,
+    user_id: &str,
+    account: &SchemaManualAccount,
+    balance: f64,
+) -> Result<(), recurr_core::Error> {
+    let db_client = get_supbase_client();
+    let account_id = account.account_id.clone().unwrap_or_default();
+    let date = Local::now().format("%Y-%m-%d").to_string();
+
+    db_client
+        .from("manual_accounts")
+        .auth(auth_key)
+        .eq("account_id", &account_id)
+        .update(serde_json::json!({ "current_balance": balance }).to_string())
+        .execute()
+        .await?
+        .error_for_status()?;
+
+    let entry = SchemaManualBalance {
+        id: None,
+        user_id: user_id.to_string(),
+        account_id: account_id.clone(),
+        balance,
+        date: date.clone(),
+    };
+
+    db_client
+        .from("manual_account_balances")
+        .auth(auth_key)
+        .insert(serde_json::to_string(&entry)?)
+        .execute()
+        .await?
+        .error_for_status()?;
+
+    let snapshot = SchemaBalanceSnapshot {
+        user_id: user_id.to_string(),
+        account_id,
+        date,
+        account_type: account.account_type.clone(),
+        current: balance,
+        iso_currency_code: account.iso_currency_code.clone(),
+    };
+
+    db_client
+        .from("balance_snapshots")
+        .auth(auth_key)
+        .upsert(serde_json::to_string(&snapshot)?)
+        .execute()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
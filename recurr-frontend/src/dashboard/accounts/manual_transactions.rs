@@ -0,0 +1,294 @@
+use chrono::Local;
+use recurr_core::{
+    currency::format_amount,
+    get_supbase_client,
+    manual::{SchemaManualAccount, SchemaManualTransaction},
+};
+use web_sys::{HtmlInputElement, HtmlSelectElement, SubmitEvent};
+use yew::{html, Callback, Component, Context, Html, NodeRef, Properties};
+
+use crate::context::SessionContext;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub account: SchemaManualAccount,
+    pub on_close: Callback<()>,
+    pub context: SessionContext,
+}
+
+pub enum Msg {
+    GetTransactions,
+    GotTransactions(Vec<SchemaManualTransaction>),
+
+    Edit(Option<SchemaManualTransaction>),
+    Save,
+    Delete(String),
+
+    Error(String),
+}
+
+pub struct ManualTransactions {
+    transactions: Vec<SchemaManualTransaction>,
+    editing: Option<SchemaManualTransaction>,
+    error: Option<String>,
+
+    date_ref: NodeRef,
+    name_ref: NodeRef,
+    amount_ref: NodeRef,
+    category_ref: NodeRef,
+}
+
+impl ManualTransactions {
+    fn session(ctx: &Context<Self>) -> (String, String) {
+        let session = ctx
+            .props()
+            .context
+            .supabase_session
+            .clone()
+            .expect("Needs session");
+        (session.auth_key, session.user.id)
+    }
+
+    fn input_value(node: &NodeRef) -> String {
+        node.cast::<HtmlInputElement>()
+            .expect("Not an input element")
+            .value()
+    }
+
+    fn blank(ctx: &Context<Self>) -> SchemaManualTransaction {
+        let (_, user_id) = Self::session(ctx);
+        let account = &ctx.props().account;
+
+        SchemaManualTransaction {
+            transaction_id: None,
+            user_id,
+            account_id: account.account_id.clone().unwrap_or_default(),
+            amount: 0.0,
+            name: String::new(),
+            date: Local::now().format("%Y-%m-%d").to_string(),
+            category: None,
+            merchant_name: None,
+            iso_currency_code: account.iso_currency_code.clone(),
+        }
+    }
+}
+
+impl Component for ManualTransactions {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        ctx.link().send_message(Msg::GetTransactions);
+
+        Self {
+            transactions: Vec::new(),
+            editing: None,
+            error: None,
+
+            date_ref: NodeRef::default(),
+            name_ref: NodeRef::default(),
+            amount_ref: NodeRef::default(),
+            category_ref: NodeRef::default(),
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let account = &ctx.props().account;
+        let taxonomy = &ctx.props().context.taxonomy;
+        let currency = account
+            .iso_currency_code
+            .clone()
+            .unwrap_or(ctx.props().context.exchange_rates.base().to_string());
+
+        let close_modal = ctx.props().on_close.reform(|_| ());
+        let new_transaction = {
+            let blank = Self::blank(ctx);
+            ctx.link().callback(move |_| Msg::Edit(Some(blank.clone())))
+        };
+        let save = ctx.link().callback(|e: SubmitEvent| {
+            e.prevent_default();
+            Msg::Save
+        });
+        let cancel = ctx.link().callback(|_| Msg::Edit(None));
+
+        html! {
+            <div class="modal is-active">
+                <div class="modal-background" onclick={close_modal.clone()}></div>
+
+                <div class="modal-card">
+                    <header class="modal-card-head">
+                        <h1 class="modal-card-title">{format!("{} transactions", account.name)}</h1>
+                        <button class="delete" aria-label="close" onclick={close_modal}></button>
+                    </header>
+
+                    <section class="modal-card-body">
+                        if let Some(e) = &self.error {
+                            <p class="help is-danger">{e}</p>
+                        }
+
+                        if let Some(editing) = &self.editing {
+                            <form class="box" onsubmit={save}>
+                                <div class="field is-grouped">
+                                    <input ref={self.date_ref.clone()} class="input is-small" type="date" value={editing.date.clone()}/>
+                                    <input ref={self.name_ref.clone()} class="input is-small" type="text" placeholder="Description" value={editing.name.clone()}/>
+                                    <input ref={self.amount_ref.clone()} class="input is-small" type="number" step="0.01" value={format!("{:.2}", editing.amount)}/>
+                                </div>
+                                <div class="field">
+                                    <div class="select is-small">
+                                        <select ref={self.category_ref.clone()}>
+                                            <option value="">{"No category"}</option>
+                                            {
+                                                taxonomy.flatten().into_iter().map(|c| {
+                                                    let selected = editing.category.as_ref().map_or(false, |p| taxonomy.resolve(p) == c.path);
+                                                    html!{<option {selected} value={serde_json::to_string(&c.path).unwrap_or_default()}>{taxonomy.display_path(&c.path).join(" > ")}</option>}
+                                                }).collect::<Html>()
+                                            }
+                                        </select>
+                                    </div>
+                                </div>
+                                <button class="button is-small is-success mr-2" type="submit">{"Save"}</button>
+                                <button class="button is-small" type="button" onclick={cancel}>{"Cancel"}</button>
+                            </form>
+                        }
+
+                        <table class="table is-fullwidth is-hoverable">
+                            <tbody>
+                            {
+                                self.transactions.iter().map(|t| {
+                                    let edit = {
+                                        let t = t.clone();
+                                        ctx.link().callback(move |_| Msg::Edit(Some(t.clone())))
+                                    };
+                                    let delete = {
+                                        let id = t.transaction_id.clone().unwrap_or_default();
+                                        ctx.link().callback(move |_| Msg::Delete(id.clone()))
+                                    };
+
+                                    html!{
+                                        <tr>
+                                            <td>{t.date.clone()}</td>
+                                            <td>{t.name.clone()}</td>
+                                            <td>{taxonomy.display_path(t.category.as_deref().unwrap_or_default()).last().cloned().unwrap_or_default()}</td>
+                                            <td>{format_amount(t.amount, &currency)}</td>
+                                            <td class="is-size-7">
+                                                <a class="mr-2" onclick={edit}>{"Edit"}</a>
+                                                <a class="has-text-danger" onclick={delete}>{"Delete"}</a>
+                                            </td>
+                                        </tr>
+                                    }
+                                }).collect::<Html>()
+                            }
+                            </tbody>
+                        </table>
+                    </section>
+
+                    <footer class="modal-card-foot">
+                        <button class="button is-success" onclick={new_transaction}>{"Add transaction"}</button>
+                    </footer>
+                </div>
+            </div>
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        self.error = None;
+        let (auth_key, _) = Self::session(ctx);
+
+        match msg {
+            Msg::GetTransactions => {
+                let account_id = ctx.props().account.account_id.clone().unwrap_or_default();
+
+                ctx.link().send_future(async move {
+                    let res = get_supbase_client()
+                        .from("manual_transactions")
+                        .auth(&auth_key)
+                        .select("*")
+                        .eq("account_id", account_id)
+                        .order("date.desc")
+                        .execute()
+                        .await
+                        .and_then(|r| r.error_for_status());
+
+                    match res {
+                        Ok(res) => match res.json().await {
+                            Ok(transactions) => Msg::GotTransactions(transactions),
+                            Err(e) => Msg::Error(e.to_string()),
+                        },
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::GotTransactions(transactions) => self.transactions = transactions,
+            Msg::Edit(transaction) => self.editing = transaction,
+            Msg::Save => {
+                let Some(mut transaction) = self.editing.clone() else {
+                    return false;
+                };
+
+                transaction.date = Self::input_value(&self.date_ref);
+                transaction.name = Self::input_value(&self.name_ref);
+                transaction.amount = match Self::input_value(&self.amount_ref).parse() {
+                    Ok(amount) => amount,
+                    Err(_) => {
+                        self.error = Some("Enter an amount".to_string());
+                        return true;
+                    }
+                };
+                transaction.category = serde_json::from_str(
+                    &self
+                        .category_ref
+                        .cast::<HtmlSelectElement>()
+                        .expect("Category ref not a select element")
+                        .value(),
+                )
+                .ok();
+
+                if transaction.name.is_empty() || transaction.date.is_empty() {
+                    self.error = Some("Enter a date and description".to_string());
+                    return true;
+                }
+
+                self.editing = None;
+                let body = serde_json::to_string(&transaction).expect("Failed to serialize");
+
+                ctx.link().send_future(async move {
+                    let query = get_supbase_client()
+                        .from("manual_transactions")
+                        .auth(&auth_key);
+                    let query = match &transaction.transaction_id {
+                        Some(id) => query.eq("transaction_id", id).update(body),
+                        None => query.insert(body),
+                    };
+
+                    match query.execute().await.and_then(|r| r.error_for_status()) {
+                        Ok(_) => Msg::GetTransactions,
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::Delete(transaction_id) => {
+                ctx.link().send_future(async move {
+                    let res = get_supbase_client()
+                        .from("manual_transactions")
+                        .auth(&auth_key)
+                        .eq("transaction_id", transaction_id)
+                        .delete()
+                        .execute()
+                        .await
+                        .and_then(|r| r.error_for_status());
+
+                    match res {
+                        Ok(_) => Msg::GetTransactions,
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::Error(e) => {
+                log::error!("{e}");
+                self.error = Some(e);
+            }
+        }
+
+        true
+    }
+}
@@ -1,8 +1,11 @@
 mod link;
+pub mod manual;
+mod manual_transactions;
 
 use crate::{commands, context::Session};
 use futures::future;
 use link::Link;
+use manual::ManualAccounts;
 use recurr_core::{get_supbase_client, Account, Institution, SchemaAccessToken};
 use serde::Deserialize;
 use std::collections::HashMap;
@@ -83,6 +86,9 @@ impl Component for AccountsView {
                     }
                 }
                 </div>
+                <div class="p-2">
+                    <ManualAccounts context={ctx.props().context.clone()}/>
+                </div>
             </div>
         }
     }
@@ -1,8 +1,13 @@
-use recurr_core::{get_supbase_client, Category, SchemaBudget};
-use web_sys::{HtmlInputElement, SubmitEvent};
+use recurr_core::{
+    budgets::{BudgetKind, BudgetPeriod},
+    categories, get_supbase_client, pfc, SchemaBudget,
+};
+use web_sys::{HtmlInputElement, HtmlSelectElement, SubmitEvent};
 use yew::{html, Callback, Component, Context, Html, NodeRef, Properties, UseReducerHandle};
 
-use crate::{commands, context::Session};
+use crate::context::Session;
+
+use super::zero_based::record_allocation;
 
 #[derive(Debug, PartialEq)]
 pub enum ModalMsg {
@@ -12,7 +17,6 @@ pub enum ModalMsg {
 
 pub enum Msg {
     Error(String),
-    GotCategories(Vec<Category>),
     CloseModal,
     Delete,
     Submit,
@@ -26,39 +30,39 @@ pub struct Props {
     pub show: bool,
 
     pub detail: Option<SchemaBudget>,
+    /// Changes to how much a budget gets are recorded as allocations
+    pub zero_based: bool,
 }
 
 pub struct Modal {
     modal_ref: NodeRef,
-    categories: Vec<Category>,
 
     category_ref: NodeRef,
     amount_ref: NodeRef,
-}
-
-impl Modal {
-    async fn get_categories() -> Msg {
-        let categories = commands::get_categories()
-            .await
-            .expect("Failed to get categories");
-        //TODO Would be good to either sort this or group it
-        Msg::GotCategories(categories)
-    }
+    period_ref: NodeRef,
+    start_day_ref: NodeRef,
+    anchor_ref: NodeRef,
+    rollover_ref: NodeRef,
+    kind_ref: NodeRef,
+    name_ref: NodeRef,
 }
 
 impl Component for Modal {
     type Message = Msg;
     type Properties = Props;
 
-    fn create(ctx: &Context<Self>) -> Self {
-        ctx.link().send_future(Self::get_categories());
-
+    fn create(_ctx: &Context<Self>) -> Self {
         Self {
             modal_ref: NodeRef::default(),
-            categories: Vec::new(),
 
             category_ref: NodeRef::default(),
             amount_ref: NodeRef::default(),
+            period_ref: NodeRef::default(),
+            start_day_ref: NodeRef::default(),
+            anchor_ref: NodeRef::default(),
+            rollover_ref: NodeRef::default(),
+            kind_ref: NodeRef::default(),
+            name_ref: NodeRef::default(),
         }
     }
 
@@ -70,6 +74,22 @@ impl Component for Modal {
         });
 
         let delete = ctx.link().callback(|_| Msg::Delete);
+        let taxonomy = &ctx.props().session.taxonomy;
+        let detail = ctx.props().detail.clone();
+        let period = detail.as_ref().map(|d| d.period).unwrap_or_default();
+        let start_day = detail.as_ref().map_or(1, |d| d.start_day);
+        let anchor = detail
+            .as_ref()
+            .and_then(|d| d.anchor_date.clone())
+            .unwrap_or_default();
+        let rollover = detail.as_ref().map_or(false, |d| d.rollover);
+        let kind = detail.as_ref().map(|d| d.kind).unwrap_or_default();
+        // Goals and savings can be named freely rather than picked from categories
+        let name = detail
+            .as_ref()
+            .filter(|d| d.kind != BudgetKind::Spending)
+            .map(|d| d.category_id.clone())
+            .unwrap_or_default();
 
         if ctx.props().show {
             html! {
@@ -88,29 +108,89 @@ impl Component for Modal {
                         <button class="delete" aria-label="close" onclick={close_modal.clone()}></button>
                     </header>
                     {
-                        if !self.categories.is_empty() {
+                        if !taxonomy.roots().is_empty() {
                             html!{
                             <>
                                 <form onsubmit={on_submit}>
                                     <section class="modal-card-body">
                                         <div class="select is-info">
                                             <select placeholder="Choose a category" ref={self.category_ref.clone()}>
+                                                <optgroup label="Personal finance categories">
+                                                {
+                                                    pfc::PRIMARY_CATEGORIES.iter().map(|p| {
+                                                        let selected = ctx.props().detail.clone().map_or(false, |d| d.category_id == *p);
+                                                        html!{<option {selected} value={p.to_string()}>{pfc::display_name(p)}</option>}
+                                                    }).collect::<Html>()
+                                                }
+                                                </optgroup>
+                                                <optgroup label="Categories">
                                                 {
-                                                    self.categories.clone().iter().map(|c| {
-                                                        let cat = c.hierarchy.last().expect("Requires an option").clone();
-                                                        let selected = ctx.props().detail.clone().map_or(false, |d| d.category_id == cat);
-                                                        html!{<option {selected}>{cat}</option>}
+                                                    taxonomy.flatten().into_iter().map(|c| {
+                                                        let depth = "\u{a0}\u{a0}".repeat(c.path.len() - 1);
+                                                        let selected = ctx.props().detail.as_ref().is_some_and(|d| taxonomy.path_of(&d.category_id) == c.path);
+                                                        html!{<option {selected} value={categories::key(&c.path)}>{format!("{depth}{}", c.name)}</option>}
                                                     }).collect::<Html>()
                                                 }
+                                                </optgroup>
                                             </select>
                                         </div>
 
+                                        <div class="field has-addons mt-2">
+                                            <div class="control">
+                                                <div class="select is-small">
+                                                    <select ref={self.kind_ref.clone()}>
+                                                    {
+                                                        BudgetKind::ALL.iter().map(|k| {
+                                                            html!{<option selected={*k == kind} value={k.as_str()}>{k.as_str()}</option>}
+                                                        }).collect::<Html>()
+                                                    }
+                                                    </select>
+                                                </div>
+                                            </div>
+                                            <div class="control is-expanded">
+                                                <input class="input is-small" type="text" placeholder="Goal or savings name, instead of a category" value={name} ref={self.name_ref.clone()}/>
+                                            </div>
+                                        </div>
+
                                         <div class="field">
                                             <label class="label">{"How much"}</label>
                                             <div class="control">
                                                 <input class="input is-success" type="number" value={ctx.props().detail.clone().map_or(0.0, |d| d.max).to_string()} ref={self.amount_ref.clone()}/>
                                             </div>
                                         </div>
+
+                                        <div class="field">
+                                            <label class="label">{"Every"}</label>
+                                            <div class="select">
+                                                <select ref={self.period_ref.clone()}>
+                                                {
+                                                    BudgetPeriod::ALL.iter().map(|p| {
+                                                        html!{<option selected={*p == period} value={p.as_str()}>{p.as_str()}</option>}
+                                                    }).collect::<Html>()
+                                                }
+                                                </select>
+                                            </div>
+                                        </div>
+
+                                        <div class="field is-grouped">
+                                            <div class="control">
+                                                <label class="label is-small">{"Month starts on day"}</label>
+                                                <input class="input is-small" type="number" min="1" max="28" value={start_day.to_string()} ref={self.start_day_ref.clone()}/>
+                                                <p class="help">{"Monthly, quarterly and yearly budgets, e.g. your payday"}</p>
+                                            </div>
+                                            <div class="control">
+                                                <label class="label is-small">{"Weeks start from"}</label>
+                                                <input class="input is-small" type="date" value={anchor} ref={self.anchor_ref.clone()}/>
+                                                <p class="help">{"Weekly and biweekly budgets, defaults to Mondays"}</p>
+                                            </div>
+                                        </div>
+
+                                        <div class="field">
+                                            <label class="checkbox">
+                                                <input class="mr-2" type="checkbox" checked={rollover} ref={self.rollover_ref.clone()}/>
+                                                {"Roll over what's left, or overspent, into the next period"}
+                                            </label>
+                                        </div>
                                     </section>
                                     <footer class="modal-card-foot">
                                         <button class="button" onclick={close_modal.clone()}>{"Cancel"}</button>
@@ -137,7 +217,6 @@ impl Component for Modal {
 
     fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
-            Msg::GotCategories(categories) => self.categories = categories,
             Msg::CloseModal => ctx.props().on_change.emit(ModalMsg::Close),
             Msg::Submit => {
                 let amount = self
@@ -148,12 +227,40 @@ impl Component for Modal {
                     .parse()
                     .expect("Failed to parse amount");
 
-                let category_id = self
-                    .category_ref
+                let kind = BudgetKind::parse(
+                    &self
+                        .kind_ref
+                        .cast::<HtmlSelectElement>()
+                        .expect("Kind ref not a select element")
+                        .value(),
+                )
+                .unwrap_or_default();
+
+                let name = self
+                    .name_ref
                     .cast::<HtmlInputElement>()
-                    .expect("Category ref not an input element")
+                    .expect("Name ref not an input element")
                     .value();
 
+                let category_id = if kind != BudgetKind::Spending && !name.is_empty() {
+                    name
+                } else {
+                    let key = self
+                        .category_ref
+                        .cast::<HtmlInputElement>()
+                        .expect("Category ref not an input element")
+                        .value();
+
+                    // Budgets saved with a category's name keep it while they stay on that category
+                    let taxonomy = &ctx.props().session.taxonomy;
+                    match &ctx.props().detail {
+                        Some(d) if taxonomy.path_of(&d.category_id) == taxonomy.path_of(&key) => {
+                            d.category_id.clone()
+                        }
+                        _ => key,
+                    }
+                };
+
                 let session = ctx
                     .props()
                     .session
@@ -166,13 +273,60 @@ impl Component for Modal {
 
                 let db_client = get_supbase_client();
 
-                let schema = SchemaBudget {
-                    user_id,
+                let period = BudgetPeriod::parse(
+                    &self
+                        .period_ref
+                        .cast::<HtmlSelectElement>()
+                        .expect("Period ref not a select element")
+                        .value(),
+                )
+                .unwrap_or_default();
+
+                let start_day = self
+                    .start_day_ref
+                    .cast::<HtmlInputElement>()
+                    .expect("Start day ref not an input element")
+                    .value()
+                    .parse::<u32>()
+                    .unwrap_or(1)
+                    .clamp(1, 28);
+
+                let anchor_date = self
+                    .anchor_ref
+                    .cast::<HtmlInputElement>()
+                    .expect("Anchor ref not an input element")
+                    .value();
+
+                let rollover = self
+                    .rollover_ref
+                    .cast::<HtmlInputElement>()
+                    .expect("Rollover ref not an input element")
+                    .checked();
+
+                let budget = SchemaBudget {
+                    user_id: user_id.clone(),
                     category_id,
                     max: amount,
-                }
-                .to_string()
-                .expect("Failed to serialize");
+                    period,
+                    start_day,
+                    anchor_date: if anchor_date.is_empty() {
+                        None
+                    } else {
+                        Some(anchor_date)
+                    },
+                    rollover,
+                    kind,
+                };
+                let schema = budget.to_string().expect("Failed to serialize");
+
+                // What the budget had before, nothing if it's new or moved to another category
+                let previous = ctx
+                    .props()
+                    .detail
+                    .as_ref()
+                    .filter(|d| d.category_id == budget.category_id)
+                    .map_or(0.0, |d| d.max);
+                let zero_based = ctx.props().zero_based;
 
                 ctx.link().send_future(async move {
                     let res = db_client
@@ -184,10 +338,28 @@ impl Component for Modal {
                         .map(|r| r.error_for_status())
                         .flatten();
 
-                    match res {
-                        Ok(_) => Msg::Submitted,
-                        Err(e) => Msg::Error(e.to_string()),
+                    if let Err(e) = res {
+                        return Msg::Error(e.to_string());
+                    }
+
+                    let change = budget.max - previous;
+                    if zero_based && change.abs() > 0.005 {
+                        let category = Some(budget.category_id.clone());
+                        let (from, to) = if change > 0.0 {
+                            (None, category)
+                        } else {
+                            (category, None)
+                        };
+
+                        let res =
+                            record_allocation(&auth_key, &user_id, &budget, from, to, change.abs())
+                                .await;
+                        if let Err(e) = res {
+                            return Msg::Error(e.to_string());
+                        }
                     }
+
+                    Msg::Submitted
                 });
             }
             Msg::Delete => {
@@ -198,29 +370,41 @@ impl Component for Modal {
                     .as_ref()
                     .expect("Needs session");
                 let auth_key = session.auth_key.clone();
+                let user_id = session.user.id.clone();
 
                 let db_client = get_supbase_client();
 
-                let category = self
-                    .category_ref
-                    .cast::<HtmlInputElement>()
-                    .expect("Category ref not an input element")
-                    .value();
+                let Some(budget) = ctx.props().detail.clone() else {
+                    return false;
+                };
+                let zero_based = ctx.props().zero_based;
 
                 ctx.link().send_future(async move {
                     let res = db_client
                         .from("budgets")
                         .auth(&auth_key)
-                        .eq("category_id", category)
+                        .eq("category_id", &budget.category_id)
                         .delete()
                         .execute()
                         .await
                         .and_then(|r| r.error_for_status());
 
-                    match res {
-                        Ok(_) => Msg::Submitted,
-                        Err(e) => Msg::Error(e.to_string()),
+                    if let Err(e) = res {
+                        return Msg::Error(e.to_string());
                     }
+
+                    // Whatever the budget had goes back to being unassigned
+                    if zero_based && budget.max.abs() > 0.005 {
+                        let from = Some(budget.category_id.clone());
+                        let res =
+                            record_allocation(&auth_key, &user_id, &budget, from, None, budget.max)
+                                .await;
+                        if let Err(e) = res {
+                            return Msg::Error(e.to_string());
+                        }
+                    }
+
+                    Msg::Submitted
                 });
             }
             Msg::Submitted => {
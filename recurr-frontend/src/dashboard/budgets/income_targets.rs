@@ -0,0 +1,243 @@
+use recurr_core::{
+    budgets::{BudgetPeriod, Period},
+    currency::format_amount,
+    get_supbase_client,
+    income::SchemaIncomeTarget,
+    pfc,
+};
+use web_sys::{HtmlInputElement, HtmlSelectElement, SubmitEvent};
+use yew::{html, Callback, Component, Context, Html, NodeRef, Properties};
+
+use crate::context::SessionContext;
+
+/// An income target and what came in towards it in the period being shown
+#[derive(Clone, PartialEq)]
+pub struct IncomeLine {
+    pub target: SchemaIncomeTarget,
+    pub period: Period,
+    pub received: f64,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub lines: Vec<IncomeLine>,
+    /// Payers seen recently, suggested as sources
+    pub payers: Vec<String>,
+    pub currency: String,
+    pub on_change: Callback<()>,
+    pub context: SessionContext,
+}
+
+pub enum Msg {
+    Add,
+    Delete(i64),
+    Saved,
+    Error(String),
+}
+
+pub struct IncomeTargets {
+    error: Option<String>,
+
+    source_ref: NodeRef,
+    expected_ref: NodeRef,
+    period_ref: NodeRef,
+}
+
+impl Component for IncomeTargets {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            error: None,
+
+            source_ref: NodeRef::default(),
+            expected_ref: NodeRef::default(),
+            period_ref: NodeRef::default(),
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let currency = &ctx.props().currency;
+        let taxonomy = &ctx.props().context.taxonomy;
+        let add = ctx.link().callback(|e: SubmitEvent| {
+            e.prevent_default();
+            Msg::Add
+        });
+
+        html! {
+            <div>
+                if let Some(e) = &self.error {
+                    <p class="help is-danger">{e}</p>
+                }
+
+                {
+                    ctx.props().lines.iter().map(|line| {
+                        let delete = {
+                            let id = line.target.id.unwrap_or_default();
+                            ctx.link().callback(move |_| Msg::Delete(id))
+                        };
+                        let expected = line.target.expected;
+                        let class = if line.received >= expected { "progress m-0 is-success" } else { "progress m-0 is-info" };
+
+                        html!{
+                            <div>
+                                <div class="is-flex is-justify-content-space-between">
+                                    <span>{taxonomy.label(&line.target.source)}</span>
+                                    <span>{format!("{} of {}", format_amount(line.received, currency), format_amount(expected, currency))}</span>
+                                </div>
+                                <progress {class} value={format!("{:0.2}", (line.received / expected).min(1.0))} max="1"></progress>
+                                <div class="is-flex is-justify-content-space-between is-size-7">
+                                    <span class="has-text-grey">{format!("{} · {}", line.period.label(), line.target.period.as_str())}</span>
+                                    <a class="has-text-danger" onclick={delete}>{"Delete"}</a>
+                                </div>
+                            </div>
+                        }
+                    }).collect::<Html>()
+                }
+
+                <form class="field has-addons mt-2" onsubmit={add}>
+                    <div class="control is-expanded">
+                        <input ref={self.source_ref.clone()} class="input is-small" type="text" list="income-sources" placeholder="Employer or category"/>
+                        <datalist id="income-sources">
+                            <option value="INCOME">{pfc::display_name("INCOME")}</option>
+                            {
+                                ctx.props().payers.iter().map(|p| html!{<option value={p.clone()}/>}).collect::<Html>()
+                            }
+                        </datalist>
+                    </div>
+                    <div class="control">
+                        <input ref={self.expected_ref.clone()} class="input is-small" type="number" step="0.01" placeholder="Expected"/>
+                    </div>
+                    <div class="control">
+                        <div class="select is-small">
+                            <select ref={self.period_ref.clone()}>
+                            {
+                                BudgetPeriod::ALL.iter().map(|p| {
+                                    html!{<option selected={*p == BudgetPeriod::Monthly} value={p.as_str()}>{p.as_str()}</option>}
+                                }).collect::<Html>()
+                            }
+                            </select>
+                        </div>
+                    </div>
+                    <div class="control">
+                        <button class="button is-small is-success" type="submit">{"Expect income"}</button>
+                    </div>
+                </form>
+            </div>
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        self.error = None;
+        let session = ctx
+            .props()
+            .context
+            .supabase_session
+            .clone()
+            .expect("Needs session");
+        let auth_key = session.auth_key;
+
+        match msg {
+            Msg::Add => {
+                let source = self
+                    .source_ref
+                    .cast::<HtmlInputElement>()
+                    .expect("Source ref not an input element")
+                    .value();
+                let expected = self
+                    .expected_ref
+                    .cast::<HtmlInputElement>()
+                    .expect("Expected ref not an input element")
+                    .value()
+                    .parse::<f64>();
+                let period = BudgetPeriod::parse(
+                    &self
+                        .period_ref
+                        .cast::<HtmlSelectElement>()
+                        .expect("Period ref not a select element")
+                        .value(),
+                )
+                .unwrap_or_default();
+
+                let expected = match expected {
+                    Ok(expected) if !source.is_empty() => expected,
+                    _ => {
+                        self.error = Some("Enter a source and the amount expected".to_string());
+                        return true;
+                    }
+                };
+
+                let target = SchemaIncomeTarget {
+                    id: None,
+                    user_id: session.user.id,
+                    source,
+                    expected,
+                    period,
+                    start_day: 1,
+                    anchor_date: None,
+                };
+
+                ctx.link().send_future(async move {
+                    let body = match serde_json::to_string(&target) {
+                        Ok(body) => body,
+                        Err(e) => return Msg::Error(e.to_string()),
+                    };
+
+                    let res = get_supbase_client()
+                        .from("income_targets")
+                        .auth(&auth_key)
+                        .upsert(body)
+                        .on_conflict("user_id,source")
+                        .execute()
+                        .await
+                        .and_then(|r| r.error_for_status());
+
+                    match res {
+                        Ok(_) => Msg::Saved,
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::Delete(id) => {
+                ctx.link().send_future(async move {
+                    let res = get_supbase_client()
+                        .from("income_targets")
+                        .auth(&auth_key)
+                        .eq("id", id.to_string())
+                        .delete()
+                        .execute()
+                        .await
+                        .and_then(|r| r.error_for_status());
+
+                    match res {
+                        Ok(_) => Msg::Saved,
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::Saved => ctx.props().on_change.emit(()),
+            Msg::Error(e) => {
+                log::error!("{e}");
+                self.error = Some(e);
+            }
+        }
+
+        true
+    }
+}
+
+pub async fn get_income_targets(
+    auth_key: &str,
+) -> Result<Vec<SchemaIncomeTarget>, recurr_core::Error> {
+    let res = get_supbase_client()
+        .from("income_targets")
+        .auth(auth_key)
+        .select("*")
+        .order("source")
+        .execute()
+        .await?
+        .error_for_status()?;
+
+    Ok(res.json().await?)
+}
@@ -1,26 +1,73 @@
 use std::collections::HashMap;
 
-use chrono::Local;
-use now::DateTimeNow;
-use recurr_core::{get_supbase_client, SchemaBudget, Transaction};
-use wasm_bindgen::JsCast;
+use chrono::{Local, NaiveDate};
+use recurr_core::{
+    budgets::{
+        allocate, rollup, BudgetPeriod, Period, SchemaDailyCategoryTotal, DAILY_TOTALS_ORDER,
+    },
+    currency::format_amount,
+    get_all, get_supbase_client, income, reports, SchemaBudget, Transaction,
+};
 use web_sys::{HtmlElement, MouseEvent};
 use yew::{
     html, Callback, Component, Context, ContextHandle, Html, Properties, TargetCast,
     UseReducerHandle,
 };
 
-use crate::context::{Session, SessionContext};
+use crate::{
+    components::unconverted::Unconverted,
+    context::{Session, SessionContext},
+};
 
 use super::{transactions::Filter, DashboardTab};
 
 mod edit_modal;
+mod income_targets;
+mod rollover;
+mod suggestions;
+mod zero_based;
+
+use income_targets::{get_income_targets, IncomeLine, IncomeTargets};
+
+/// A budget's standing in the period being shown
+#[derive(Clone)]
+pub struct BudgetLine {
+    budget: SchemaBudget,
+    period: Period,
+    /// Everything under the budget's category, including its sub-budgets
+    spent: f64,
+    /// What no sub-budget claimed
+    own: f64,
+    allocated: f64,
+    /// What rolled over from the previous period, for rollover budgets
+    carried: Option<f64>,
+}
+
+impl BudgetLine {
+    fn available(&self) -> f64 {
+        self.allocated + self.carried.unwrap_or_default() - self.spent
+    }
+}
+
+/// Money in and out over the calendar month being shown
+pub struct CashFlow {
+    period: Period,
+    income: f64,
+    spending: f64,
+}
 
 #[derive(Default)]
 pub struct Transactions {
-    other_income: HashMap<String, f64>,
-    budgeted_spending: Vec<(SchemaBudget, f64)>,
+    expected_income: Vec<IncomeLine>,
+    /// Unexpected income as (category, payer, amount)
+    other_income: Vec<(String, String, f64)>,
+    budgeted_spending: Vec<BudgetLine>,
     other_spending: HashMap<String, f64>,
+    cash_flow: Option<CashFlow>,
+    /// Only when zero-based budgeting is on
+    zero_based: Option<zero_based::Summary>,
+    /// Currencies without a rate, left out of every total
+    unconverted: Vec<String>,
 }
 
 pub enum Msg {
@@ -29,9 +76,17 @@ pub enum Msg {
     ShowModal(Option<SchemaBudget>),
     HideModal,
 
+    ShowSuggestions,
+    /// Whether any suggested budgets were accepted
+    HideSuggestions(bool),
+
     GotTransactions(Transactions),
     GetTransactions,
 
+    /// Moves every budget this many periods forward, negative steps go back
+    StepPeriod(i64),
+    SetZeroBased(bool),
+
     Update,
 
     Error(String),
@@ -53,6 +108,10 @@ pub struct BudgetsView {
     budget_details: Option<SchemaBudget>,
 
     modal_show: bool,
+    suggestions_show: bool,
+
+    /// How many periods back from the current one is shown
+    offset: i64,
 }
 
 impl BudgetsView {
@@ -66,57 +125,226 @@ impl BudgetsView {
             .expect("Needs session");
         let auth_key = session.auth_key;
         let user_id = session.user.id;
+        let rates = ctx.props().context.exchange_rates.clone();
+        let taxonomy = ctx.props().context.taxonomy.clone();
+        let offset = self.offset;
 
         ctx.link().send_future(async move {
-            let transactions = get_transactions(&auth_key).await;
-            if let Err(e) = transactions {
-                return Msg::Error(e.to_string());
-            }
-            let transactions = transactions.unwrap();
-
             let budgets = get_budgets(&auth_key, &user_id).await;
             if let Err(e) = budgets {
                 return Msg::Error(e.to_string());
             }
             let budgets = budgets.unwrap();
 
-            let _income: Vec<Transaction> = Vec::new();
-            let mut spending: Vec<Transaction> = transactions;
+            let targets = match get_income_targets(&auth_key).await {
+                Ok(targets) => targets,
+                Err(e) => return Msg::Error(e.to_string()),
+            };
+
+            let is_zero_based = match zero_based::get_zero_based(&auth_key, &user_id).await {
+                Ok(is_zero_based) => is_zero_based,
+                Err(e) => return Msg::Error(e.to_string()),
+            };
+
+            let today = Local::now().date_naive();
+            let month = BudgetPeriod::Monthly.period(1, None, today, offset);
+            let periods: Vec<Period> = budgets.iter().map(|b| b.period(today, offset)).collect();
+            let target_periods: Vec<Period> =
+                targets.iter().map(|t| t.period(today, offset)).collect();
+
+            let zero_based = if is_zero_based {
+                let expected: f64 = targets
+                    .iter()
+                    .map(|t| {
+                        let n =
+                            t.period
+                                .starts_within(t.start_day, t.anchor_date.as_deref(), &month);
+                        t.expected * n as f64
+                    })
+                    .sum();
+                let assigned: f64 = budgets
+                    .iter()
+                    .map(|b| {
+                        let n =
+                            b.period
+                                .starts_within(b.start_day, b.anchor_date.as_deref(), &month);
+                        b.max * n as f64
+                    })
+                    .sum();
+
+                match zero_based::get_allocations(&auth_key, &month).await {
+                    Ok(allocations) => Some(zero_based::Summary {
+                        period: month,
+                        expected,
+                        assigned,
+                        allocations,
+                    }),
+                    Err(e) => return Msg::Error(e.to_string()),
+                }
+            } else {
+                None
+            };
+
+            let mut ledger = if budgets.iter().any(|b| b.rollover) {
+                match rollover::get_ledger(&auth_key).await {
+                    Ok(ledger) => ledger,
+                    Err(e) => return Msg::Error(e.to_string()),
+                }
+            } else {
+                HashMap::new()
+            };
+            let refresh_from = budgets.iter().filter(|b| b.rollover).map(|b| {
+                let rows = ledger.get(&b.category_id).map_or(&[][..], |r| r.as_slice());
+                b.period(rollover::refresh_from(rows, today), 0).start
+            });
+
+            let start = periods
+                .iter()
+                .chain(&target_periods)
+                .map(|p| p.start)
+                .chain(refresh_from)
+                .fold(month.start, NaiveDate::min);
+            let end = periods
+                .iter()
+                .chain(&target_periods)
+                .map(|p| p.end)
+                .fold(month.end, NaiveDate::max);
+
+            let transactions = match get_totals(&auth_key, Period { start, end }).await {
+                Ok(totals) => totals
+                    .iter()
+                    .map(SchemaDailyCategoryTotal::to_transaction)
+                    .collect::<Vec<Transaction>>(),
+                Err(e) => return Msg::Error(e.to_string()),
+            };
+            let unconverted =
+                rates.unconverted(transactions.iter().map(|t| t.iso_currency_code.as_deref()));
+            let to_base = |t: &Transaction| {
+                rates
+                    .to_base(t.amount, t.iso_currency_code.as_deref())
+                    .unwrap_or_default()
+            };
+
+            for b in budgets.iter().filter(|b| b.rollover) {
+                let spent = |period: &Period| {
+                    transactions
+                        .iter()
+                        .filter(|t| {
+                            period.contains(&t.date) && taxonomy.in_category(&b.category_id, t)
+                        })
+                        .map(to_base)
+                        .sum::<f64>()
+                };
+
+                let rows = ledger.entry(b.category_id.clone()).or_default();
+                if let Err(e) =
+                    rollover::update_ledger(&auth_key, &user_id, b, rows, today, spent).await
+                {
+                    return Msg::Error(e.to_string());
+                }
+            }
+
+            let mut cash_flow = CashFlow {
+                period: month,
+                income: 0.0,
+                spending: 0.0,
+            };
+            for t in transactions.iter().filter(|t| month.contains(&t.date)) {
+                let amount = to_base(t);
+                if income::is_income(t) {
+                    cash_flow.income -= amount;
+                } else {
+                    cash_flow.spending += amount;
+                }
+            }
+
+            let covered: Vec<(&SchemaBudget, Period)> = budgets.iter().zip(periods).collect();
+            let owners = allocate(&taxonomy, &covered, &transactions);
+            let rollups = rollup(&taxonomy, &covered, &transactions, &owners, to_base);
+            let periods: Vec<Period> = covered.into_iter().map(|(_, p)| p).collect();
+
+            let mut spending: Vec<Transaction> = transactions
+                .into_iter()
+                .zip(owners)
+                .filter(|(_, owner)| owner.is_none())
+                .map(|(t, _)| t)
+                .collect();
 
             let mut budgeted_spending = Vec::new();
-            for b in budgets {
-                let mut amount = 0.0;
-
-                let budgeted: Vec<Transaction> = spending
-                    .drain_filter(|t| t.category.as_ref().unwrap().contains(&b.category_id))
-                    .collect();
-                budgeted.into_iter().for_each(|t| {
-                    amount += t.amount;
+            for ((b, period), spent) in budgets.into_iter().zip(periods).zip(rollups) {
+                let period_start = period.start.format("%Y-%m-%d").to_string();
+                let row = ledger
+                    .get(&b.category_id)
+                    .and_then(|rows| rows.iter().find(|r| r.period_start == period_start))
+                    .filter(|_| b.rollover);
+
+                budgeted_spending.push(BudgetLine {
+                    allocated: row.map_or(b.max, |r| r.allocated),
+                    carried: row.map(|r| r.carried),
+                    budget: b,
+                    period,
+                    spent: spent.total,
+                    own: spent.own,
                 });
+            }
+            budgeted_spending.sort_by(|a, b| a.budget.category_id.cmp(&b.budget.category_id));
+
+            let mut expected_income = Vec::new();
+            for (target, period) in targets.into_iter().zip(target_periods) {
+                let received: f64 = spending
+                    .drain_filter(|t| period.contains(&t.date) && target.matches(&taxonomy, t))
+                    .map(|t| -to_base(&t))
+                    .sum();
+
+                expected_income.push(IncomeLine {
+                    target,
+                    period,
+                    received,
+                });
+            }
 
-                budgeted_spending.push((b.clone(), amount));
+            let mut other_income: HashMap<(String, String), f64> = HashMap::new();
+            for t in spending.drain_filter(|t| income::is_income(t) && month.contains(&t.date)) {
+                let category = taxonomy
+                    .display_path(t.category.as_deref().unwrap_or_default())
+                    .first()
+                    .cloned()
+                    .unwrap_or_else(|| "Uncategorized".to_string());
+
+                *other_income
+                    .entry((category, income::payer(&t).to_string()))
+                    .or_default() -= to_base(&t);
             }
-            budgeted_spending.sort_by(|a, b| a.0.category_id.cmp(&b.0.category_id));
+            let mut other_income: Vec<(String, String, f64)> = other_income
+                .into_iter()
+                .map(|((category, payer), amount)| (category, payer, amount))
+                .collect();
+            other_income.sort_by(|a, b| a.0.cmp(&b.0).then(b.2.total_cmp(&a.2)));
 
             let mut other_spending: HashMap<String, f64> = HashMap::new();
-            for t in spending {
-                let general_category = t.category.as_ref().unwrap().first();
+            for t in spending.into_iter().filter(|t| month.contains(&t.date)) {
+                let category = taxonomy.display_path(t.category.as_deref().unwrap_or_default());
+                let general_category = category.first();
                 if let Some(category) = general_category {
                     if other_spending.contains_key(category) {
                         let v = other_spending.get_mut(category);
                         if let Some(v) = v {
-                            *v += t.amount;
+                            *v += to_base(&t);
                         }
                     } else {
-                        other_spending.insert(category.to_string(), t.amount);
+                        other_spending.insert(category.to_string(), to_base(&t));
                     }
                 }
             }
 
             Msg::GotTransactions(Transactions {
-                other_income: HashMap::new(),
+                expected_income,
+                other_income,
                 budgeted_spending,
-                other_spending: HashMap::new(),
+                other_spending,
+                cash_flow: Some(cash_flow),
+                zero_based,
+                unconverted,
             })
         });
     }
@@ -139,6 +367,8 @@ impl Component for BudgetsView {
             error: None,
             budget_details: None,
             modal_show: false,
+            suggestions_show: false,
+            offset: 0,
             context,
             _context_listener: context_listener,
         }
@@ -146,51 +376,51 @@ impl Component for BudgetsView {
 
     fn view(&self, ctx: &yew::Context<Self>) -> Html {
         let session = ctx.props().context.clone();
+        let currency = ctx.props().context.exchange_rates.base().to_string();
+        let taxonomy = &ctx.props().context.taxonomy;
 
         let modal_cb = ctx.link().callback(|e: edit_modal::ModalMsg| match e {
             edit_modal::ModalMsg::Close => Msg::HideModal,
             edit_modal::ModalMsg::Save => Msg::Update,
         });
 
-        let edit_budget = ctx.link().callback(move |e: MouseEvent| {
-            let target = e.target();
-
-            let t = target.and_then(|t| t.dyn_into::<HtmlElement>().ok());
-
-            if let Some(t) = t {
-                let category = t.get_attribute("data-category");
-                let max = t.get_attribute("data-amount");
-
-                if let (Some(category_id), Some(max)) = (category, max) {
-                    let max: f64 = max.parse().expect("Failed to parse budget max");
-
-                    let b = SchemaBudget {
-                        user_id: "".to_string(),
-                        category_id,
-                        max,
-                    };
-
-                    Msg::ShowModal(Some(b))
-                } else {
-                    Msg::ShowModal(None)
-                }
-            } else {
-                Msg::Error("Failed to edit modal".to_string())
-            }
-        });
+        let add_budget = ctx.link().callback(|_| Msg::ShowModal(None));
+        let suggest_budgets = ctx.link().callback(|_| Msg::ShowSuggestions);
+        let income_changed = ctx.link().callback(|_| Msg::Update);
+        let is_zero_based = self.transactions.zero_based.is_some();
+        let toggle_zero_based = ctx
+            .link()
+            .callback(move |_| Msg::SetZeroBased(!is_zero_based));
+        let budgets: Vec<SchemaBudget> = self
+            .transactions
+            .budgeted_spending
+            .iter()
+            .map(|l| l.budget.clone())
+            .collect();
+        let mut payers: Vec<String> = self
+            .transactions
+            .other_income
+            .iter()
+            .map(|(_, payer, _)| payer.clone())
+            .collect();
+        payers.sort();
+        payers.dedup();
+        let previous_period = ctx.link().callback(|_| Msg::StepPeriod(-1));
+        let next_period = ctx.link().callback(|_| Msg::StepPeriod(1));
 
         let goto_transactions = {
             let switch_tabs = ctx.props().switch_tab.clone();
             Callback::from(move |e: MouseEvent| {
                 let cat_element = e.target_dyn_into::<HtmlElement>().unwrap();
                 let cat = cat_element.get_attribute("data-category").unwrap();
+                let start_date = cat_element.get_attribute("data-start");
+                let end_date = cat_element.get_attribute("data-end");
 
-                let start_date = Local::now().beginning_of_month();
-                let end_date = Local::now().end_of_month();
                 switch_tabs.emit(DashboardTab::Transaction(Filter {
-                    start_date: Some(start_date.naive_local().format("%Y-%m-%d").to_string()),
-                    end_date: Some(end_date.naive_local().format("%Y-%m-%d").to_string()),
+                    start_date,
+                    end_date,
                     category: Some(cat),
+                    ..Default::default()
                 }));
             })
         };
@@ -202,14 +432,54 @@ impl Component for BudgetsView {
                     <h1 class="title">{"Budgets"}</h1>
                 </div>
 
-                <button class="button is-success" onclick={edit_budget.clone()}>{"Add Budget"}</button>
-                <edit_modal::Modal on_change={modal_cb} {session} show={self.modal_show} detail={self.budget_details.clone()}/>
+                <div class="is-flex is-justify-content-space-between is-align-items-center">
+                    <div class="buttons mb-0">
+                        <button class="button is-success mb-0" onclick={add_budget}>{"Add Budget"}</button>
+                        <button class="button is-light mb-0" onclick={suggest_budgets}>{"Suggest budgets"}</button>
+                        <button class={if is_zero_based { "button is-info mb-0" } else { "button is-light mb-0" }} onclick={toggle_zero_based}>{"Zero-based"}</button>
+                    </div>
+                    <div class="buttons has-addons mb-0">
+                        <button class="button is-small mb-0" onclick={previous_period}>{"‹"}</button>
+                        <span class="button is-small is-static mb-0">
+                        {
+                            match self.offset {
+                                0 => "Current period".to_string(),
+                                -1 => "Previous period".to_string(),
+                                n => format!("{} periods ago", -n),
+                            }
+                        }
+                        </span>
+                        <button class="button is-small mb-0" disabled={self.offset >= 0} onclick={next_period}>{"›"}</button>
+                    </div>
+                </div>
+                <edit_modal::Modal on_change={modal_cb} {session} show={self.modal_show} detail={self.budget_details.clone()} zero_based={is_zero_based}/>
+                if self.suggestions_show {
+                    <suggestions::Suggestions budgets={budgets.clone()} zero_based={is_zero_based} on_close={ctx.link().callback(Msg::HideSuggestions)} context={ctx.props().context.clone()}/>
+                }
 
                 <div class="columns m-1">
                     <div class="column is-half is-flex is-flex-direction-column">
+                        <Unconverted currencies={self.transactions.unconverted.clone()}/>
+
+                        if let Some(summary) = &self.transactions.zero_based {
+                            <zero_based::ZeroBased summary={summary.clone()} {budgets} currency={currency.clone()} on_change={ctx.link().callback(|_| Msg::Update)} context={ctx.props().context.clone()}/>
+                        }
+
+                        if let Some(flow) = &self.transactions.cash_flow {
+                            <div class="box">
+                                <p class="is-size-7 has-text-grey">{format!("Cash flow · {}", flow.period.label())}</p>
+                                <div class="is-flex is-justify-content-space-between">
+                                    <span>{format!("{} in − {} out", format_amount(flow.income, &currency), format_amount(flow.spending, &currency))}</span>
+                                    <strong class={if flow.income >= flow.spending { "has-text-success" } else { "has-text-danger" }}>
+                                        {format_amount(flow.income - flow.spending, &currency)}
+                                    </strong>
+                                </div>
+                            </div>
+                        }
 
                         <div>
                             <h1 class="is-size-5">{"Income"}</h1>
+                            <IncomeTargets lines={self.transactions.expected_income.clone()} {payers} currency={currency.clone()} on_change={income_changed} context={ctx.props().context.clone()}/>
                             {
                                 if !self.transactions.other_income.is_empty() {
                                     html!{
@@ -220,12 +490,12 @@ impl Component for BudgetsView {
                                                 </thead>
                                                 <tbody>
                                                 {
-                                                    self.transactions.other_income.clone().into_iter().map(|(c, a)| {
+                                                    self.transactions.other_income.clone().into_iter().map(|(c, p, a)| {
                                                         html!{
                                                             <tr>
                                                                 <td>{c}</td>
-                                                                <td>{format!("${:0.2}", a.abs())}</td>
-                                                                // <td><button class="button">{"+"}</button></td>
+                                                                <td>{p}</td>
+                                                                <td>{format_amount(a, &currency)}</td>
                                                             </tr>
                                                         }
                                                     }).collect::<Html>()
@@ -248,16 +518,45 @@ impl Component for BudgetsView {
                             if !self.transactions.budgeted_spending.is_empty() {
                                 html!{
                                     {
-                                        self.transactions.budgeted_spending.clone().into_iter().map(|(c, a)| {
+                                        self.transactions.budgeted_spending.clone().into_iter().map(|line| {
+                                            let available = line.available();
+                                            let BudgetLine { budget: c, period, spent: a, own, allocated, carried } = line;
+                                            let total = allocated + carried.unwrap_or_default();
+
+                                            let edit_budget = {
+                                                let c = c.clone();
+                                                ctx.link().callback(move |_: MouseEvent| Msg::ShowModal(Some(c.clone())))
+                                            };
+                                            let filter_start = period.start.format("%Y-%m-%d").to_string();
+                                            let filter_end = period.last_day().format("%Y-%m-%d").to_string();
+                                            let progress_class = if available < 0.0 { "progress m-0 is-danger" } else { "progress m-0 is-success" };
+
                                             html!{
                                                 <div>
                                                     <div class="is-flex is-justify-content-space-between">
-                                                        <td><a class="has-hover-underline" data-category={c.category_id.clone()} onclick={goto_transactions.clone()}> {c.category_id.clone()} </a></td>
-                                                        <div>{format!("${:0.2} left", c.max - a)}</div>
+                                                        <td><a class="has-hover-underline" data-category={c.category_id.clone()} data-start={filter_start} data-end={filter_end} onclick={goto_transactions.clone()}> {taxonomy.label(&c.category_id)} </a></td>
+                                                        <div>
+                                                        {
+                                                            if available < 0.0 {
+                                                                format!("{} over", format_amount(-available, &currency))
+                                                            } else {
+                                                                format!("{} left", format_amount(available, &currency))
+                                                            }
+                                                        }
+                                                        </div>
                                                     </div>
-                                                    <progress class="progress m-0 is-success" value={format!("{:0.2}", a/c.max)} max="1">{format!("{:0.2}", a/c.max)}</progress>
-                                                    <div class="is-flex is-justify-content-flex-end">
-                                                        <a onclick={edit_budget.clone()} data-category={c.category_id} data-amount={format!("{:0.2}", c.max)}>{"Edit"}</a>
+                                                    <progress class={progress_class} value={format!("{:0.2}", (a/total).min(1.0))} max="1">{format!("{:0.2}", a/total)}</progress>
+                                                    <div class="is-flex is-justify-content-space-between is-size-7">
+                                                        <span class="has-text-grey">
+                                                            {format!("{} · {} of {}", period.label(), format_amount(a, &currency), format_amount(allocated, &currency))}
+                                                            if let Some(carried) = carried {
+                                                                {format!(" · {} carried in", format_amount(carried, &currency))}
+                                                            }
+                                                            if (a - own).abs() > 0.005 {
+                                                                {format!(" · {} in sub-budgets", format_amount(a - own, &currency))}
+                                                            }
+                                                        </span>
+                                                        <a onclick={edit_budget}>{"Edit"}</a>
                                                     </div>
                                                 </div>
                                             }
@@ -284,7 +583,7 @@ impl Component for BudgetsView {
                                                     html!{
                                                         <tr>
                                                             <td>{c}</td>
-                                                            <td>{format!("${a:0.2}")}</td>
+                                                            <td>{format_amount(a, &currency)}</td>
                                                             // <td><button class="button">{"+"}</button></td>
                                                         </tr>
                                                     }
@@ -315,6 +614,33 @@ impl Component for BudgetsView {
                 self.modal_show = true;
             }
             Msg::HideModal => self.modal_show = false,
+            Msg::ShowSuggestions => self.suggestions_show = true,
+            Msg::HideSuggestions(accepted) => {
+                self.suggestions_show = false;
+                if accepted {
+                    self.get_transaction(ctx);
+                }
+            }
+            Msg::SetZeroBased(on) => {
+                let session = ctx
+                    .props()
+                    .context
+                    .supabase_session
+                    .clone()
+                    .expect("Needs session");
+
+                ctx.link().send_future(async move {
+                    match zero_based::set_zero_based(&session.auth_key, &session.user.id, on).await
+                    {
+                        Ok(_) => Msg::Update,
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::StepPeriod(step) => {
+                self.offset = (self.offset + step).min(0);
+                self.get_transaction(ctx);
+            }
             Msg::Update => self.get_transaction(ctx),
             Msg::UpdatedContext(context) => self.context = context,
         }
@@ -323,37 +649,32 @@ impl Component for BudgetsView {
     }
 }
 
-async fn get_transactions(auth_key: &str) -> Result<Vec<Transaction>, recurr_core::Error> {
-    let db_client = get_supbase_client();
-
-    let start_date = Local::now().beginning_of_month();
-    let end_date = Local::now().end_of_month();
-
-    let res = db_client
-        .from("transactions")
-        .auth(&auth_key)
-        .select("*")
-        .gt("date", start_date.to_string())
-        .lt("date", end_date.to_string())
-        .order("date.desc")
-        .execute()
-        .await?
-        .error_for_status()?;
-
-    Ok(res.json().await?)
+/// Spending per day and category, see `SchemaDailyCategoryTotal`
+async fn get_totals(
+    auth_key: &str,
+    window: Period,
+) -> Result<Vec<SchemaDailyCategoryTotal>, recurr_core::Error> {
+    let client = get_supbase_client();
+    let params = reports::summary_params(&window);
+
+    get_all(|| {
+        client
+            .rpc("daily_category_totals", &params)
+            .auth(auth_key)
+            .order(DAILY_TOTALS_ORDER)
+    })
+    .await
 }
 
 async fn get_budgets(
     auth_key: &str,
     user_id: &str,
 ) -> Result<Vec<SchemaBudget>, recurr_core::Error> {
-    let db_client = get_supbase_client();
-
-    let res = db_client
+    let res = get_supbase_client()
         .from("budgets")
-        .auth(&auth_key)
+        .auth(auth_key)
         .select("*")
-        .eq("user_id", &user_id)
+        .eq("user_id", user_id)
         .execute()
         .await?
         .error_for_status()?;
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use recurr_core::{
+    budgets::{carry_forward, Period, SchemaBudgetPeriod},
+    get_all, get_supbase_client, SchemaBudget,
+};
+
+/// Ledger rows of every rollover budget, keyed by category and in period order
+pub async fn get_ledger(
+    auth_key: &str,
+) -> Result<HashMap<String, Vec<SchemaBudgetPeriod>>, recurr_core::Error> {
+    let client = get_supbase_client();
+    let rows: Vec<SchemaBudgetPeriod> = get_all(|| {
+        client
+            .from("budget_periods")
+            .auth(auth_key)
+            .select("*")
+            .order("category_id,period_start")
+    })
+    .await?;
+
+    let mut ledger: HashMap<String, Vec<SchemaBudgetPeriod>> = HashMap::new();
+    for row in rows {
+        ledger.entry(row.category_id.clone()).or_default().push(row);
+    }
+
+    Ok(ledger)
+}
+
+/// The day from which a ledger's spending is refreshed, the latest period it
+/// has since transactions can still post late, or today for a new ledger
+pub fn refresh_from(rows: &[SchemaBudgetPeriod], today: NaiveDate) -> NaiveDate {
+    rows.last()
+        .and_then(|r| NaiveDate::parse_from_str(&r.period_start, "%Y-%m-%d").ok())
+        .map_or(today, |start| start.min(today))
+}
+
+/// Brings a budget's ledger up to the current period with `spent` in each, recomputes what
+/// carries over and saves the periods that changed
+pub async fn update_ledger(
+    auth_key: &str,
+    user_id: &str,
+    budget: &SchemaBudget,
+    rows: &mut Vec<SchemaBudgetPeriod>,
+    today: NaiveDate,
+    spent: impl Fn(&Period) -> f64,
+) -> Result<(), recurr_core::Error> {
+    let current = budget.period(today, 0).start.format("%Y-%m-%d").to_string();
+    let saved = rows.clone();
+
+    for period in budget.periods_between(refresh_from(rows, today), today) {
+        let period_start = period.start.format("%Y-%m-%d").to_string();
+        let spent = spent(&period);
+
+        match rows.iter_mut().find(|r| r.period_start == period_start) {
+            Some(row) => row.spent = spent,
+            None => rows.push(SchemaBudgetPeriod {
+                user_id: user_id.to_string(),
+                category_id: budget.category_id.clone(),
+                period_start,
+                allocated: budget.max,
+                carried: 0.0,
+                spent,
+            }),
+        }
+    }
+
+    // Past allocations stay as they were, the current one follows the budget
+    if let Some(row) = rows.iter_mut().find(|r| r.period_start == current) {
+        row.allocated = budget.max;
+    }
+
+    carry_forward(rows);
+
+    let changed: Vec<&SchemaBudgetPeriod> = rows.iter().filter(|r| !saved.contains(r)).collect();
+    if changed.is_empty() {
+        return Ok(());
+    }
+
+    get_supbase_client()
+        .from("budget_periods")
+        .auth(auth_key)
+        .upsert(serde_json::to_string(&changed)?)
+        .execute()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
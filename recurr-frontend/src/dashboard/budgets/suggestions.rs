@@ -0,0 +1,366 @@
+use recurr_core::{
+    budgets::BudgetPeriod, currency::format_amount, get_supbase_client, pfc,
+    suggestions::BudgetSuggestion, SchemaBudget,
+};
+use web_sys::{Event, HtmlInputElement, HtmlSelectElement, InputEvent};
+use yew::{html, Callback, Component, Context, Html, Properties, TargetCast};
+
+use crate::{commands, context::SessionContext};
+
+use super::zero_based::record_allocation;
+
+const MONTHS: [u32; 4] = [3, 6, 12, 24];
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub budgets: Vec<SchemaBudget>,
+    /// Changes to how much a budget gets are recorded as allocations
+    pub zero_based: bool,
+
+    /// Emits whether any budgets were saved
+    pub on_close: Callback<bool>,
+    pub context: SessionContext,
+}
+
+pub enum Msg {
+    SetMonths(u32),
+    GotSuggestions(Vec<BudgetSuggestion>),
+
+    Toggle(usize),
+    SetAmount(usize, String),
+
+    Accept,
+    Accepted,
+
+    Error(String),
+}
+
+struct Row {
+    suggestion: BudgetSuggestion,
+    /// The budget already set for the category
+    existing: Option<SchemaBudget>,
+    selected: bool,
+    amount: String,
+}
+
+/// Proposes budgets from past spending, to be accepted together or tweaked first
+pub struct Suggestions {
+    months: u32,
+    rows: Option<Vec<Row>>,
+    error: Option<String>,
+}
+
+impl Suggestions {
+    fn fetch(&self, ctx: &Context<Self>) {
+        let session = ctx
+            .props()
+            .context
+            .supabase_session
+            .clone()
+            .expect("Needs session");
+        let months = self.months;
+
+        ctx.link().send_future(async move {
+            match commands::suggest_budgets(&session.auth_key, &session.user.id, months).await {
+                Ok(suggestions) => Msg::GotSuggestions(suggestions),
+                Err(e) => Msg::Error(e),
+            }
+        });
+    }
+
+    fn read_budgets(&self, ctx: &Context<Self>) -> Result<Vec<SchemaBudget>, String> {
+        let user_id = ctx
+            .props()
+            .context
+            .supabase_session
+            .as_ref()
+            .expect("Needs session")
+            .user
+            .id
+            .clone();
+
+        self.rows
+            .iter()
+            .flatten()
+            .filter(|r| r.selected)
+            .map(|r| {
+                let max = r
+                    .amount
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|a| *a >= 0.0)
+                    .ok_or_else(|| {
+                        format!(
+                            "Invalid amount for {}",
+                            pfc::label(&r.suggestion.category_id)
+                        )
+                    })?;
+
+                Ok(match &r.existing {
+                    Some(budget) => SchemaBudget {
+                        max,
+                        ..budget.clone()
+                    },
+                    None => SchemaBudget {
+                        user_id: user_id.clone(),
+                        category_id: r.suggestion.category_id.clone(),
+                        max,
+                        period: BudgetPeriod::Monthly,
+                        start_day: 1,
+                        anchor_date: None,
+                        rollover: false,
+                        kind: Default::default(),
+                    },
+                })
+            })
+            .collect()
+    }
+}
+
+impl Component for Suggestions {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let suggestions = Self {
+            months: 6,
+            rows: None,
+            error: None,
+        };
+        suggestions.fetch(ctx);
+
+        suggestions
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let currency = ctx.props().context.exchange_rates.base().to_string();
+        let close_modal = ctx.props().on_close.reform(|_| false);
+        let accept = ctx.link().callback(|_| Msg::Accept);
+        let set_months = ctx.link().callback(|e: Event| {
+            Msg::SetMonths(
+                e.target_unchecked_into::<HtmlSelectElement>()
+                    .value()
+                    .parse()
+                    .unwrap_or(6),
+            )
+        });
+        let selected = self.rows.iter().flatten().filter(|r| r.selected).count();
+
+        html! {
+            <div class="modal is-active">
+                <div class="modal-background" onclick={close_modal.clone()}></div>
+                <div class="modal-card">
+                    <header class="modal-card-head">
+                        <p class="modal-card-title">{"Suggested budgets"}</p>
+                        <button class="delete" aria-label="close" onclick={close_modal.clone()}></button>
+                    </header>
+
+                    <section class="modal-card-body">
+                        <div class="field is-flex is-align-items-center">
+                            <span class="mr-2">{"From the last"}</span>
+                            <div class="select is-small">
+                                <select onchange={set_months}>
+                                {
+                                    MONTHS.iter().map(|m| {
+                                        html!{<option selected={*m == self.months} value={m.to_string()}>{format!("{m} months")}</option>}
+                                    }).collect::<Html>()
+                                }
+                                </select>
+                            </div>
+                        </div>
+
+                        if let Some(e) = &self.error {
+                            <p class="help is-danger">{e}</p>
+                        }
+
+                        {
+                            match &self.rows {
+                                None => html!{<progress class="progress is-small is-info" max="100"></progress>},
+                                Some(rows) if rows.is_empty() => html!{<p>{"No spending to suggest budgets from"}</p>},
+                                Some(rows) => html!{
+                                    <table class="table is-fullwidth is-narrow">
+                                        <thead>
+                                            <th></th>
+                                            <th>{"Category"}</th>
+                                            <th>{"Median"}</th>
+                                            <th>{"Mean"}</th>
+                                            <th>{"Trend"}</th>
+                                            <th>{"Budget"}</th>
+                                        </thead>
+                                        <tbody>
+                                        {
+                                            rows.iter().enumerate().map(|(i, r)| {
+                                                let s = &r.suggestion;
+                                                let toggle = ctx.link().callback(move |_| Msg::Toggle(i));
+                                                let set_amount = ctx.link().callback(move |e: InputEvent| {
+                                                    Msg::SetAmount(i, e.target_unchecked_into::<HtmlInputElement>().value())
+                                                });
+
+                                                html!{
+                                                    <tr>
+                                                        <td><input type="checkbox" checked={r.selected} onclick={toggle}/></td>
+                                                        <td>
+                                                            {pfc::label(&s.category_id)}
+                                                            if s.outliers > 0 {
+                                                                <p class="is-size-7 has-text-grey">{format!("{} unusual month(s) left out", s.outliers)}</p>
+                                                            }
+                                                        </td>
+                                                        <td>{format_amount(s.median, &currency)}</td>
+                                                        <td>{format_amount(s.mean, &currency)}</td>
+                                                        <td>{format!("{}{}/mo", if s.trend >= 0.0 { "+" } else { "−" }, format_amount(s.trend.abs(), &currency))}</td>
+                                                        <td>
+                                                            <input class="input is-small" type="number" step="0.01" value={r.amount.clone()} oninput={set_amount}/>
+                                                            if let Some(b) = &r.existing {
+                                                                <p class="is-size-7 has-text-grey">{format!("Now {} {}", format_amount(b.max, &currency), b.period.as_str())}</p>
+                                                            }
+                                                        </td>
+                                                    </tr>
+                                                }
+                                            }).collect::<Html>()
+                                        }
+                                        </tbody>
+                                    </table>
+                                },
+                            }
+                        }
+                    </section>
+
+                    <footer class="modal-card-foot">
+                        <button class="button" onclick={close_modal}>{"Cancel"}</button>
+                        <button class="button is-success" disabled={selected == 0} onclick={accept}>{format!("Accept {selected}")}</button>
+                    </footer>
+                </div>
+            </div>
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        self.error = None;
+
+        match msg {
+            Msg::SetMonths(months) => {
+                self.months = months;
+                self.rows = None;
+                self.fetch(ctx);
+            }
+            Msg::GotSuggestions(suggestions) => {
+                self.rows = Some(
+                    suggestions
+                        .into_iter()
+                        .map(|suggestion| {
+                            let existing = ctx
+                                .props()
+                                .budgets
+                                .iter()
+                                .find(|b| b.category_id == suggestion.category_id)
+                                .cloned();
+                            let period = existing.as_ref().map(|b| b.period).unwrap_or_default();
+
+                            Row {
+                                amount: format!("{:.2}", suggestion.for_period(period)),
+                                selected: existing.is_none(),
+                                suggestion,
+                                existing,
+                            }
+                        })
+                        .collect(),
+                )
+            }
+            Msg::Toggle(i) => {
+                if let Some(row) = self.rows.iter_mut().flatten().nth(i) {
+                    row.selected = !row.selected;
+                }
+            }
+            Msg::SetAmount(i, amount) => {
+                if let Some(row) = self.rows.iter_mut().flatten().nth(i) {
+                    row.amount = amount;
+                }
+            }
+            Msg::Accept => {
+                let budgets = match self.read_budgets(ctx) {
+                    Ok(budgets) => budgets,
+                    Err(e) => {
+                        self.error = Some(e);
+                        return true;
+                    }
+                };
+                let previous: Vec<f64> = budgets
+                    .iter()
+                    .map(|b| {
+                        ctx.props()
+                            .budgets
+                            .iter()
+                            .find(|p| p.category_id == b.category_id)
+                            .map_or(0.0, |p| p.max)
+                    })
+                    .collect();
+
+                let session = ctx
+                    .props()
+                    .context
+                    .supabase_session
+                    .clone()
+                    .expect("Needs session");
+                let zero_based = ctx.props().zero_based;
+
+                ctx.link().send_future(async move {
+                    let schema = match serde_json::to_string(&budgets) {
+                        Ok(schema) => schema,
+                        Err(e) => return Msg::Error(e.to_string()),
+                    };
+
+                    let res = get_supbase_client()
+                        .from("budgets")
+                        .auth(&session.auth_key)
+                        .upsert(schema)
+                        .execute()
+                        .await
+                        .and_then(|r| r.error_for_status());
+                    if let Err(e) = res {
+                        return Msg::Error(e.to_string());
+                    }
+
+                    if !zero_based {
+                        return Msg::Accepted;
+                    }
+
+                    for (budget, previous) in budgets.iter().zip(previous) {
+                        let change = budget.max - previous;
+                        if change.abs() <= 0.005 {
+                            continue;
+                        }
+
+                        let category = Some(budget.category_id.clone());
+                        let (from, to) = if change > 0.0 {
+                            (None, category)
+                        } else {
+                            (category, None)
+                        };
+
+                        let res = record_allocation(
+                            &session.auth_key,
+                            &session.user.id,
+                            budget,
+                            from,
+                            to,
+                            change.abs(),
+                        )
+                        .await;
+                        if let Err(e) = res {
+                            return Msg::Error(e.to_string());
+                        }
+                    }
+
+                    Msg::Accepted
+                });
+            }
+            Msg::Accepted => ctx.props().on_close.emit(true),
+            Msg::Error(e) => {
+                log::error!("{e}");
+                self.error = Some(e);
+            }
+        }
+
+        true
+    }
+}
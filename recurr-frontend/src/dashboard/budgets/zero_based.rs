@@ -0,0 +1,356 @@
+use chrono::Local;
+use recurr_core::{
+    budgets::{Period, SchemaBudgetAllocation},
+    currency::format_amount,
+    get_supbase_client, SchemaBudget,
+};
+use serde::Deserialize;
+use web_sys::{HtmlInputElement, HtmlSelectElement, SubmitEvent};
+use yew::{html, Callback, Component, Context, Html, NodeRef, Properties};
+
+use crate::context::SessionContext;
+
+/// Income against assignments over the calendar month being shown
+#[derive(Clone, PartialEq)]
+pub struct Summary {
+    pub period: Period,
+    pub expected: f64,
+    pub assigned: f64,
+    pub allocations: Vec<SchemaBudgetAllocation>,
+}
+
+impl Summary {
+    pub fn unassigned(&self) -> f64 {
+        self.expected - self.assigned
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub summary: Summary,
+    pub budgets: Vec<SchemaBudget>,
+    pub currency: String,
+    pub on_change: Callback<()>,
+    pub context: SessionContext,
+}
+
+pub enum Msg {
+    Move,
+    Moved,
+    Error(String),
+}
+
+/// The unassigned amount with a form to move money between budgets
+pub struct ZeroBased {
+    error: Option<String>,
+
+    from_ref: NodeRef,
+    to_ref: NodeRef,
+    amount_ref: NodeRef,
+}
+
+impl Component for ZeroBased {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            error: None,
+
+            from_ref: NodeRef::default(),
+            to_ref: NodeRef::default(),
+            amount_ref: NodeRef::default(),
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let summary = &ctx.props().summary;
+        let currency = &ctx.props().currency;
+        let taxonomy = &ctx.props().context.taxonomy;
+        let unassigned = summary.unassigned();
+
+        let move_money = ctx.link().callback(|e: SubmitEvent| {
+            e.prevent_default();
+            Msg::Move
+        });
+        let options = html! {
+            <>
+                <option value="">{"Unassigned"}</option>
+                {
+                    ctx.props().budgets.iter().map(|b| {
+                        html!{<option value={b.category_id.clone()}>{taxonomy.label(&b.category_id)}</option>}
+                    }).collect::<Html>()
+                }
+            </>
+        };
+
+        html! {
+            <div class="box">
+                <p class="is-size-7 has-text-grey">{format!("Zero-based · {}", summary.period.label())}</p>
+                <div class="is-flex is-justify-content-space-between">
+                    <span>{format!("{} expected − {} assigned", format_amount(summary.expected, currency), format_amount(summary.assigned, currency))}</span>
+                    <strong>{format!("{} unassigned", format_amount(unassigned, currency))}</strong>
+                </div>
+
+                if unassigned < -0.005 {
+                    <p class="notification is-danger is-light p-2 mt-2">
+                        {format!("Assignments exceed expected income by {}", format_amount(-unassigned, currency))}
+                    </p>
+                } else if unassigned > 0.005 {
+                    <p class="help">{"Assign the rest to budgets, goals or savings"}</p>
+                }
+
+                if let Some(e) = &self.error {
+                    <p class="help is-danger">{e}</p>
+                }
+
+                <form class="field has-addons mt-2" onsubmit={move_money}>
+                    <div class="control">
+                        <div class="select is-small">
+                            <select ref={self.from_ref.clone()}>{options.clone()}</select>
+                        </div>
+                    </div>
+                    <div class="control">
+                        <span class="button is-small is-static">{"→"}</span>
+                    </div>
+                    <div class="control">
+                        <div class="select is-small">
+                            <select ref={self.to_ref.clone()}>{options}</select>
+                        </div>
+                    </div>
+                    <div class="control">
+                        <input ref={self.amount_ref.clone()} class="input is-small" type="number" step="0.01" placeholder="Amount"/>
+                    </div>
+                    <div class="control">
+                        <button class="button is-small is-success" type="submit">{"Move money"}</button>
+                    </div>
+                </form>
+
+                if !summary.allocations.is_empty() {
+                    <table class="table is-narrow is-size-7 is-fullwidth">
+                        <tbody>
+                        {
+                            summary.allocations.iter().map(|a| {
+                                let side = |c: &Option<String>| c.as_deref().map_or("Unassigned".to_string(), |c| taxonomy.label(c));
+                                html!{
+                                    <tr>
+                                        <td>{a.created_at.clone().unwrap_or_default().chars().take(10).collect::<String>()}</td>
+                                        <td>{format!("{} → {}", side(&a.from_category), side(&a.to_category))}</td>
+                                        <td>{format_amount(a.amount, currency)}</td>
+                                    </tr>
+                                }
+                            }).collect::<Html>()
+                        }
+                        </tbody>
+                    </table>
+                }
+            </div>
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        self.error = None;
+
+        match msg {
+            Msg::Move => {
+                let select_value = |node: &NodeRef| {
+                    let value = node
+                        .cast::<HtmlSelectElement>()
+                        .expect("Not a select element")
+                        .value();
+                    if value.is_empty() {
+                        None
+                    } else {
+                        Some(value)
+                    }
+                };
+                let from = select_value(&self.from_ref);
+                let to = select_value(&self.to_ref);
+                let amount = self
+                    .amount_ref
+                    .cast::<HtmlInputElement>()
+                    .expect("Amount ref not an input element")
+                    .value()
+                    .parse::<f64>();
+
+                let amount = match amount {
+                    Ok(amount) if amount > 0.0 && from != to => amount,
+                    _ => {
+                        self.error = Some("Pick two different budgets and an amount".to_string());
+                        return true;
+                    }
+                };
+
+                let find = |c: &Option<String>| {
+                    c.as_ref().and_then(|c| {
+                        ctx.props()
+                            .budgets
+                            .iter()
+                            .find(|b| &b.category_id == c)
+                            .cloned()
+                    })
+                };
+                let (from, to) = (find(&from), find(&to));
+
+                let session = ctx
+                    .props()
+                    .context
+                    .supabase_session
+                    .clone()
+                    .expect("Needs session");
+
+                ctx.link().send_future(async move {
+                    let res = move_money(
+                        &session.auth_key,
+                        &session.user.id,
+                        from.as_ref(),
+                        to.as_ref(),
+                        amount,
+                    )
+                    .await;
+
+                    match res {
+                        Ok(_) => Msg::Moved,
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::Moved => ctx.props().on_change.emit(()),
+            Msg::Error(e) => {
+                log::error!("{e}");
+                self.error = Some(e);
+            }
+        }
+
+        true
+    }
+}
+
+pub async fn get_zero_based(auth_key: &str, user_id: &str) -> Result<bool, recurr_core::Error> {
+    #[derive(Deserialize)]
+    struct Row {
+        zero_based: bool,
+    }
+
+    let rows: Vec<Row> = get_supbase_client()
+        .from("user_settings")
+        .auth(auth_key)
+        .select("zero_based")
+        .eq("user_id", user_id)
+        .execute()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(rows.first().map_or(false, |r| r.zero_based))
+}
+
+pub async fn set_zero_based(
+    auth_key: &str,
+    user_id: &str,
+    zero_based: bool,
+) -> Result<(), recurr_core::Error> {
+    get_supbase_client()
+        .from("user_settings")
+        .auth(auth_key)
+        .upsert(serde_json::json!({ "user_id": user_id, "zero_based": zero_based }).to_string())
+        .execute()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
+/// Allocation events recorded for periods starting within `period`, newest first
+pub async fn get_allocations(
+    auth_key: &str,
+    period: &Period,
+) -> Result<Vec<SchemaBudgetAllocation>, recurr_core::Error> {
+    let res = get_supbase_client()
+        .from("budget_allocations")
+        .auth(auth_key)
+        .select("*")
+        .gte("period_start", period.start.format("%Y-%m-%d").to_string())
+        .lt("period_start", period.end.format("%Y-%m-%d").to_string())
+        .order("created_at.desc")
+        .execute()
+        .await?
+        .error_for_status()?;
+
+    Ok(res.json().await?)
+}
+
+/// Records `amount` being assigned from one side to the other in the budget's current period
+pub async fn record_allocation(
+    auth_key: &str,
+    user_id: &str,
+    budget: &SchemaBudget,
+    from_category: Option<String>,
+    to_category: Option<String>,
+    amount: f64,
+) -> Result<(), recurr_core::Error> {
+    let allocation = SchemaBudgetAllocation {
+        id: None,
+        user_id: user_id.to_string(),
+        period_start: budget
+            .period(Local::now().date_naive(), 0)
+            .start
+            .format("%Y-%m-%d")
+            .to_string(),
+        from_category,
+        to_category,
+        amount,
+        created_at: None,
+    };
+
+    get_supbase_client()
+        .from("budget_allocations")
+        .auth(auth_key)
+        .insert(serde_json::to_string(&allocation)?)
+        .execute()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
+/// Moves money between two budgets, either side may be unassigned income
+async fn move_money(
+    auth_key: &str,
+    user_id: &str,
+    from: Option<&SchemaBudget>,
+    to: Option<&SchemaBudget>,
+    amount: f64,
+) -> Result<(), recurr_core::Error> {
+    let db_client = get_supbase_client();
+
+    for (budget, change) in [(from, -amount), (to, amount)] {
+        let Some(budget) = budget else {
+            continue;
+        };
+
+        db_client
+            .from("budgets")
+            .auth(auth_key)
+            .eq("category_id", &budget.category_id)
+            .update(serde_json::json!({ "max": budget.max + change }).to_string())
+            .execute()
+            .await?
+            .error_for_status()?;
+    }
+
+    let Some(budget) = to.or(from) else {
+        return Ok(());
+    };
+
+    record_allocation(
+        auth_key,
+        user_id,
+        budget,
+        from.map(|b| b.category_id.clone()),
+        to.map(|b| b.category_id.clone()),
+        amount,
+    )
+    .await
+}
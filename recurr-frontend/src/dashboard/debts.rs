@@ -0,0 +1,433 @@
+use chrono::{Datelike, Local};
+use recurr_core::{
+    balances::AccountClass,
+    currency::format_amount,
+    debt::{simulate, Debt, Plan, SchemaDebtTerms, Strategy},
+    get_supbase_client,
+};
+use web_sys::{Event, HtmlInputElement, HtmlSelectElement};
+use yew::{html, Component, Context, Html, Properties, UseReducerHandle};
+
+use crate::{commands, components::unconverted::Unconverted, context::Session};
+
+use super::accounts::manual::get_manual_accounts;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub context: UseReducerHandle<Session>,
+}
+
+pub enum Msg {
+    GotDebts(Vec<Debt>, Vec<String>),
+    SetApr(usize, String),
+    SetMinimum(usize, String),
+    SetPayment(String),
+    SetStrategy(String),
+    MoveUp(usize),
+    SaveTerms,
+    Import,
+    Update,
+
+    Error(String),
+}
+
+/// Plans paying off credit cards and loans, comparing the order extra money goes to them
+pub struct DebtsView {
+    debts: Option<Vec<Debt>>,
+    /// `None` until the user picks one, meaning just the minimums
+    payment: Option<f64>,
+    strategy: Strategy,
+    /// Currencies of debts left out of the plan
+    unconverted: Vec<String>,
+    error: Option<String>,
+}
+
+impl DebtsView {
+    fn reload(&self, ctx: &Context<Self>) {
+        let session = ctx
+            .props()
+            .context
+            .supabase_session
+            .clone()
+            .expect("Needs session");
+        let rates = ctx.props().context.exchange_rates.clone();
+
+        ctx.link().send_future(async move {
+            let mut accounts =
+                match commands::get_balances(&session.auth_key, &session.user.id).await {
+                    Ok(accounts) => accounts,
+                    Err(e) => return Msg::Error(e),
+                };
+            match get_manual_accounts(&session.auth_key).await {
+                Ok(manual) => accounts.extend(manual.iter().map(|a| a.to_account())),
+                Err(e) => return Msg::Error(e.to_string()),
+            }
+            let terms = match get_terms(&session.auth_key).await {
+                Ok(terms) => terms,
+                Err(e) => return Msg::Error(e.to_string()),
+            };
+
+            accounts
+                .retain(|a| AccountClass::of(&a.account_type).is_some_and(|c| c.is_liability()));
+            let unconverted = rates.unconverted(
+                accounts
+                    .iter()
+                    .map(|a| a.balances.iso_currency_code.as_deref()),
+            );
+
+            let debts = accounts
+                .into_iter()
+                .filter_map(|a| {
+                    let balance = rates.to_base(
+                        a.balances.current.unwrap_or_default(),
+                        a.balances.iso_currency_code.as_deref(),
+                    )?;
+                    let terms = terms.iter().find(|t| t.account_id == a.account_id);
+                    Some(Debt {
+                        balance,
+                        apr: terms.map_or(0.0, |t| t.apr),
+                        minimum_payment: terms.map_or(0.0, |t| t.minimum_payment),
+                        account_id: a.account_id,
+                        name: a.name,
+                    })
+                })
+                .collect();
+
+            Msg::GotDebts(debts, unconverted)
+        });
+    }
+
+    fn minimums(&self) -> f64 {
+        self.debts
+            .iter()
+            .flatten()
+            .filter(|d| d.balance > 0.0)
+            .map(|d| d.minimum_payment)
+            .sum()
+    }
+
+    /// The custom strategy in the order the debts are listed
+    fn custom(&self) -> Strategy {
+        Strategy::Custom(
+            self.debts
+                .iter()
+                .flatten()
+                .map(|d| d.account_id.clone())
+                .collect(),
+        )
+    }
+
+    fn schedule(&self, debts: &[Debt], plan: &Plan, currency: &str) -> Html {
+        html! {
+            <div class="box">
+                <h2 class="is-size-5">{"Schedule"}</h2>
+                <div class="table-container">
+                    <table class="table is-fullwidth is-narrow is-size-7">
+                        <thead>
+                            <tr>
+                                <th>{"Month"}</th>
+                                {
+                                    debts.iter().map(|d| html!{<th>{d.name.clone()}</th>}).collect::<Html>()
+                                }
+                            </tr>
+                        </thead>
+                        <tbody>
+                        {
+                            plan.schedule.iter().map(|month| html!{
+                                <tr>
+                                    <td>{month.date.format("%b %Y").to_string()}</td>
+                                    {
+                                        month.lines.iter().map(|line| html!{
+                                            <td title={format!("{} interest", format_amount(line.interest, currency))}>
+                                                if line.payment > 0.0 {
+                                                    {format!("{} → {}", format_amount(line.payment, currency), format_amount(line.balance, currency))}
+                                                }
+                                            </td>
+                                        }).collect::<Html>()
+                                    }
+                                </tr>
+                            }).collect::<Html>()
+                        }
+                        </tbody>
+                    </table>
+                </div>
+            </div>
+        }
+    }
+}
+
+impl Component for DebtsView {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let view = Self {
+            debts: None,
+            payment: None,
+            strategy: Strategy::Avalanche,
+            unconverted: Vec::new(),
+            error: None,
+        };
+        view.reload(ctx);
+
+        view
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let currency = ctx.props().context.exchange_rates.base().to_string();
+
+        let Some(debts) = &self.debts else {
+            return html! {
+                <div class="column">
+                    <h1 class="is-size-3">{"Debts"}</h1>
+                    if let Some(e) = &self.error {
+                        <p class="help is-danger">{e}</p>
+                    }
+                    <progress class="progress is-small is-primary" max="100"></progress>
+                </div>
+            };
+        };
+
+        let payment = self.payment.unwrap_or_else(|| self.minimums());
+        let today = Local::now().date_naive();
+        let start = today.with_day(1).unwrap_or(today);
+        let plans: Vec<(Strategy, Result<Plan, recurr_core::Error>)> =
+            [Strategy::Avalanche, Strategy::Snowball, self.custom()]
+                .into_iter()
+                .map(|s| {
+                    let plan = simulate(debts, payment, &s, start);
+                    (s, plan)
+                })
+                .collect();
+
+        let set_payment = ctx.link().callback(|e: Event| {
+            Msg::SetPayment(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let set_strategy = ctx.link().callback(|e: Event| {
+            Msg::SetStrategy(e.target_unchecked_into::<HtmlSelectElement>().value())
+        });
+        let save = ctx.link().callback(|_| Msg::SaveTerms);
+        let import = ctx.link().callback(|_| Msg::Import);
+
+        html! {
+            <div class="column">
+                <div class="is-flex is-justify-content-space-between is-align-items-center">
+                    <h1 class="is-size-3">{"Debts"}</h1>
+                    <div class="buttons">
+                        <button class="button is-small" onclick={import}>{"Import from Plaid"}</button>
+                        <button class="button is-small is-primary" onclick={save}>{"Save terms"}</button>
+                    </div>
+                </div>
+
+                if let Some(e) = &self.error {
+                    <p class="help is-danger">{e}</p>
+                }
+                <Unconverted currencies={self.unconverted.clone()}/>
+
+                if debts.is_empty() {
+                    <p>{"No credit or loan accounts to pay off"}</p>
+                } else {
+                    <div class="box">
+                        <table class="table is-fullwidth is-narrow">
+                            <thead>
+                                <tr>
+                                    <th>{"Account"}</th>
+                                    <th>{"Balance"}</th>
+                                    <th>{"APR %"}</th>
+                                    <th>{"Minimum"}</th>
+                                    <th title="Order for the custom strategy"></th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                            {
+                                debts.iter().enumerate().map(|(i, debt)| {
+                                    let set_apr = ctx.link().callback(move |e: Event| {
+                                        Msg::SetApr(i, e.target_unchecked_into::<HtmlInputElement>().value())
+                                    });
+                                    let set_minimum = ctx.link().callback(move |e: Event| {
+                                        Msg::SetMinimum(i, e.target_unchecked_into::<HtmlInputElement>().value())
+                                    });
+                                    let move_up = ctx.link().callback(move |_| Msg::MoveUp(i));
+
+                                    html!{
+                                        <tr>
+                                            <td>{debt.name.clone()}</td>
+                                            <td>{format_amount(debt.balance, &currency)}</td>
+                                            <td><input class="input is-small" type="number" step="0.01" min="0" value={debt.apr.to_string()} onchange={set_apr}/></td>
+                                            <td><input class="input is-small" type="number" step="0.01" min="0" value={debt.minimum_payment.to_string()} onchange={set_minimum}/></td>
+                                            <td>
+                                                if i > 0 {
+                                                    <a onclick={move_up}>{"↑"}</a>
+                                                }
+                                            </td>
+                                        </tr>
+                                    }
+                                }).collect::<Html>()
+                            }
+                            </tbody>
+                        </table>
+
+                        <div class="field is-grouped">
+                            <div class="control">
+                                <label class="label is-small">{"Monthly payment"}</label>
+                                <input class="input is-small" type="number" step="0.01" min="0" value={format!("{payment:.2}")} onchange={set_payment}/>
+                            </div>
+                            <div class="control">
+                                <label class="label is-small">{"Schedule for"}</label>
+                                <div class="select is-small">
+                                    <select onchange={set_strategy}>
+                                    {
+                                        plans.iter().map(|(s, _)| {
+                                            html!{<option value={s.label()} selected={s.label() == self.strategy.label()}>{s.label()}</option>}
+                                        }).collect::<Html>()
+                                    }
+                                    </select>
+                                </div>
+                            </div>
+                        </div>
+                    </div>
+
+                    <div class="box">
+                        <table class="table is-fullwidth is-narrow">
+                            <thead>
+                                <tr>
+                                    <th>{"Strategy"}</th>
+                                    <th>{"Debt free"}</th>
+                                    <th>{"Total interest"}</th>
+                                    <th>{"Total paid"}</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                            {
+                                plans.iter().map(|(s, plan)| match plan {
+                                    Ok(plan) => html!{
+                                        <tr>
+                                            <td>{s.label()}</td>
+                                            <td>{plan.debt_free().map_or("Never at this payment".to_string(), |d| d.format("%b %Y").to_string())}</td>
+                                            <td>{format_amount(plan.total_interest, &currency)}</td>
+                                            <td>{format_amount(plan.total_paid, &currency)}</td>
+                                        </tr>
+                                    },
+                                    Err(e) => html!{
+                                        <tr>
+                                            <td>{s.label()}</td>
+                                            <td colspan="3" class="has-text-danger">{e.to_string()}</td>
+                                        </tr>
+                                    },
+                                }).collect::<Html>()
+                            }
+                            </tbody>
+                        </table>
+                    </div>
+
+                    {
+                        match plans.iter().find(|(s, _)| s.label() == self.strategy.label()) {
+                            Some((_, Ok(plan))) => self.schedule(debts, plan, &currency),
+                            _ => html!{},
+                        }
+                    }
+                }
+            </div>
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        self.error = None;
+
+        let session = ctx
+            .props()
+            .context
+            .supabase_session
+            .clone()
+            .expect("Needs session");
+
+        match msg {
+            Msg::GotDebts(debts, unconverted) => {
+                self.debts = Some(debts);
+                self.unconverted = unconverted;
+            }
+            Msg::SetApr(i, apr) => {
+                if let Some(debt) = self.debts.iter_mut().flatten().nth(i) {
+                    debt.apr = apr.parse().unwrap_or_default();
+                }
+            }
+            Msg::SetMinimum(i, minimum) => {
+                if let Some(debt) = self.debts.iter_mut().flatten().nth(i) {
+                    debt.minimum_payment = minimum.parse().unwrap_or_default();
+                }
+            }
+            Msg::SetPayment(payment) => self.payment = payment.parse().ok(),
+            Msg::SetStrategy(label) => {
+                self.strategy = match label.as_str() {
+                    "Snowball" => Strategy::Snowball,
+                    "Custom" => self.custom(),
+                    _ => Strategy::Avalanche,
+                }
+            }
+            Msg::MoveUp(i) => {
+                if let Some(debts) = self.debts.as_mut().filter(|_| i > 0) {
+                    debts.swap(i - 1, i);
+                }
+            }
+            Msg::SaveTerms => {
+                let terms: Vec<SchemaDebtTerms> = self
+                    .debts
+                    .iter()
+                    .flatten()
+                    .map(|d| SchemaDebtTerms {
+                        user_id: session.user.id.clone(),
+                        account_id: d.account_id.clone(),
+                        apr: d.apr,
+                        minimum_payment: d.minimum_payment,
+                    })
+                    .collect();
+
+                ctx.link().send_future(async move {
+                    match save_terms(&session.auth_key, &terms).await {
+                        Ok(_) => Msg::Update,
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::Import => {
+                ctx.link().send_future(async move {
+                    match commands::import_liabilities(&session.auth_key, &session.user.id).await {
+                        Ok(_) => Msg::Update,
+                        Err(e) => Msg::Error(e),
+                    }
+                });
+            }
+            Msg::Update => self.reload(ctx),
+            Msg::Error(e) => {
+                log::error!("{e}");
+                self.error = Some(e);
+            }
+        }
+
+        true
+    }
+}
+
+async fn get_terms(auth_key: &str) -> Result<Vec<SchemaDebtTerms>, recurr_core::Error> {
+    let res = get_supbase_client()
+        .from("debt_terms")
+        .auth(auth_key)
+        .select("*")
+        .execute()
+        .await?
+        .error_for_status()?;
+
+    Ok(res.json().await?)
+}
+
+async fn save_terms(auth_key: &str, terms: &[SchemaDebtTerms]) -> Result<(), recurr_core::Error> {
+    get_supbase_client()
+        .from("debt_terms")
+        .auth(auth_key)
+        .upsert(serde_json::to_string(terms)?)
+        .execute()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
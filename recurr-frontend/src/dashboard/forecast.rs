@@ -0,0 +1,578 @@
+use chrono::Local;
+use recurr_core::{
+    budgets::BudgetPeriod,
+    currency::format_amount,
+    forecast::{Forecast, SchemaScheduledItem, HORIZONS},
+    get_supbase_client,
+};
+use serde::Deserialize;
+use web_sys::{Event, HtmlInputElement, HtmlSelectElement, SubmitEvent};
+use yew::{html, Component, Context, Html, NodeRef, Properties, UseReducerHandle};
+
+use crate::{commands, context::Session};
+
+const WIDTH: f64 = 600.0;
+const HEIGHT: f64 = 200.0;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub context: UseReducerHandle<Session>,
+}
+
+pub enum Msg {
+    GotForecast(Forecast),
+    GotScheduled(Vec<SchemaScheduledItem>),
+    GotThreshold(f64),
+    SetDays(usize),
+    SetThreshold(String),
+    AddScheduled,
+    DeleteScheduled(i64),
+    Update,
+
+    Error(String),
+}
+
+/// Daily projected balances of cash accounts, with the days they run low
+pub struct ForecastView {
+    days: usize,
+    forecast: Option<Forecast>,
+    scheduled: Vec<SchemaScheduledItem>,
+    threshold: f64,
+    error: Option<String>,
+
+    account_ref: NodeRef,
+    name_ref: NodeRef,
+    amount_ref: NodeRef,
+    direction_ref: NodeRef,
+    date_ref: NodeRef,
+    frequency_ref: NodeRef,
+    end_date_ref: NodeRef,
+}
+
+impl ForecastView {
+    fn reload(&self, ctx: &Context<Self>) {
+        let session = ctx
+            .props()
+            .context
+            .supabase_session
+            .clone()
+            .expect("Needs session");
+        let days = self.days;
+
+        {
+            let session = session.clone();
+            ctx.link().send_future(async move {
+                match commands::forecast_cash_flow(&session.auth_key, &session.user.id, days).await
+                {
+                    Ok(forecast) => Msg::GotForecast(forecast),
+                    Err(e) => Msg::Error(e),
+                }
+            });
+        }
+
+        ctx.link().send_future(async move {
+            match get_scheduled(&session.auth_key).await {
+                Ok(scheduled) => Msg::GotScheduled(scheduled),
+                Err(e) => Msg::Error(e.to_string()),
+            }
+        });
+    }
+
+    fn chart(&self, forecast: &Forecast, currency: &str) -> Html {
+        let values = forecast
+            .total
+            .iter()
+            .chain(forecast.accounts.iter().flat_map(|a| a.balances.iter()))
+            .chain([&self.threshold]);
+        let min = values.clone().copied().fold(0.0_f64, f64::min);
+        let max = values.copied().fold(0.0_f64, f64::max);
+        let span = if max - min > 0.0 { max - min } else { 1.0 };
+
+        let x = |i: usize| {
+            if forecast.dates.len() > 1 {
+                i as f64 * WIDTH / (forecast.dates.len() - 1) as f64
+            } else {
+                WIDTH / 2.0
+            }
+        };
+        let y = |v: f64| HEIGHT - (v - min) / span * HEIGHT;
+        let points = |values: &[f64]| {
+            values
+                .iter()
+                .enumerate()
+                .map(|(i, v)| format!("{:.1},{:.1}", x(i), y(*v)))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        html! {
+            <>
+                <svg viewBox={format!("0 0 {WIDTH} {HEIGHT}")} width="100%" preserveAspectRatio="none">
+                    <line x1="0" x2={WIDTH.to_string()} y1={y(0.0).to_string()} y2={y(0.0).to_string()} stroke="#e7e7e7"/>
+                    <line x1="0" x2={WIDTH.to_string()} y1={y(self.threshold).to_string()} y2={y(self.threshold).to_string()} stroke="#f14668" stroke-dasharray="4"/>
+                    {
+                        forecast.accounts.iter().map(|a| {
+                            html!{<polyline points={points(&a.balances)} fill="none" stroke="#b5b5b5" stroke-width="1"/>}
+                        }).collect::<Html>()
+                    }
+                    <polyline points={points(&forecast.total)} fill="none" stroke="#1E3888" stroke-width="3"/>
+                    {
+                        forecast.low_days(self.threshold).into_iter().map(|i| {
+                            html!{<circle cx={format!("{:.1}", x(i))} cy={format!("{:.1}", y(forecast.total[i]))} r="3" fill="#f14668"/>}
+                        }).collect::<Html>()
+                    }
+                </svg>
+                <div class="is-flex is-justify-content-space-between is-size-7 has-text-grey">
+                    <span>{forecast.dates.first().cloned().unwrap_or_default()}</span>
+                    if let Some((date, lowest)) = forecast.lowest() {
+                        <span>{format!("Lowest {} on {date}", format_amount(lowest, currency))}</span>
+                    }
+                    <span>{forecast.dates.last().cloned().unwrap_or_default()}</span>
+                </div>
+            </>
+        }
+    }
+}
+
+impl Component for ForecastView {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let view = Self {
+            days: HORIZONS[0],
+            forecast: None,
+            scheduled: Vec::new(),
+            threshold: 0.0,
+            error: None,
+
+            account_ref: NodeRef::default(),
+            name_ref: NodeRef::default(),
+            amount_ref: NodeRef::default(),
+            direction_ref: NodeRef::default(),
+            date_ref: NodeRef::default(),
+            frequency_ref: NodeRef::default(),
+            end_date_ref: NodeRef::default(),
+        };
+        view.reload(ctx);
+
+        let auth_key = ctx
+            .props()
+            .context
+            .supabase_session
+            .as_ref()
+            .expect("Needs session")
+            .auth_key
+            .clone();
+        ctx.link().send_future(async move {
+            match get_threshold(&auth_key).await {
+                Ok(threshold) => Msg::GotThreshold(threshold),
+                Err(e) => Msg::Error(e.to_string()),
+            }
+        });
+
+        view
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let currency = ctx.props().context.exchange_rates.base().to_string();
+        let set_threshold = ctx.link().callback(|e: Event| {
+            Msg::SetThreshold(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let add = ctx.link().callback(|e: SubmitEvent| {
+            e.prevent_default();
+            Msg::AddScheduled
+        });
+
+        let account_name = |id: &str| {
+            self.forecast
+                .iter()
+                .flat_map(|f| f.accounts.iter())
+                .find(|a| a.account_id == id)
+                .map_or(id.to_string(), |a| a.name.clone())
+        };
+
+        html! {
+            <div class="column">
+                <div class="is-flex is-justify-content-space-between is-align-items-center">
+                    <h1 class="is-size-3">{"Cash flow forecast"}</h1>
+                    <div class="buttons has-addons">
+                    {
+                        HORIZONS.iter().map(|days| {
+                            let days = *days;
+                            let class = if days == self.days { "button is-small is-primary is-selected" } else { "button is-small" };
+                            let onclick = ctx.link().callback(move |_| Msg::SetDays(days));
+                            html!{<button {class} {onclick}>{format!("{days} days")}</button>}
+                        }).collect::<Html>()
+                    }
+                    </div>
+                </div>
+
+                if let Some(e) = &self.error {
+                    <p class="help is-danger">{e}</p>
+                }
+
+                <div class="field is-horizontal">
+                    <div class="field-label is-small">
+                        <label class="label">{"Low balance below"}</label>
+                    </div>
+                    <div class="field-body">
+                        <input class="input is-small" type="number" step="0.01" value={self.threshold.to_string()} onchange={set_threshold}/>
+                    </div>
+                </div>
+
+                {
+                    match &self.forecast {
+                        None => html!{<progress class="progress is-small is-primary" max="100"></progress>},
+                        Some(forecast) if forecast.accounts.is_empty() => html!{<p>{"No cash accounts to forecast"}</p>},
+                        Some(forecast) => {
+                            let low = forecast.low_days(self.threshold);
+
+                            html!{
+                                <>
+                                    <div class="box">
+                                        {self.chart(forecast, &currency)}
+                                        if !low.is_empty() {
+                                            <p class="help is-danger">
+                                                {format!("Below {} on {} days, first on {}", format_amount(self.threshold, &currency), low.len(), forecast.dates[low[0]])}
+                                            </p>
+                                        }
+                                    </div>
+
+                                    <div class="box">
+                                        <table class="table is-fullwidth is-narrow">
+                                            <thead>
+                                                <tr>
+                                                    <th>{"Account"}</th>
+                                                    <th>{"Today"}</th>
+                                                    <th>{"Lowest"}</th>
+                                                    <th>{format!("In {} days", self.days)}</th>
+                                                </tr>
+                                            </thead>
+                                            <tbody>
+                                            {
+                                                forecast.accounts.iter().map(|a| {
+                                                    let lowest = a.balances.iter().copied().fold(f64::INFINITY, f64::min);
+                                                    let class = if lowest < self.threshold { "has-text-danger" } else { "" };
+                                                    html!{
+                                                        <tr>
+                                                            <td>{a.name.clone()}</td>
+                                                            <td>{format_amount(a.balances.first().copied().unwrap_or_default(), &currency)}</td>
+                                                            <td {class}>{format_amount(lowest, &currency)}</td>
+                                                            <td>{format_amount(a.balances.last().copied().unwrap_or_default(), &currency)}</td>
+                                                        </tr>
+                                                    }
+                                                }).collect::<Html>()
+                                            }
+                                                <tr>
+                                                    <td>{"Budgeted spending"}</td>
+                                                    <td></td>
+                                                    <td></td>
+                                                    <td>{format_amount(-forecast.budgeted.last().copied().unwrap_or_default(), &currency)}</td>
+                                                </tr>
+                                            </tbody>
+                                        </table>
+                                    </div>
+
+                                    <div class="box">
+                                        <h2 class="is-size-5">{"Upcoming"}</h2>
+                                        <table class="table is-fullwidth is-narrow is-size-7">
+                                            <tbody>
+                                            {
+                                                forecast.events.iter().map(|event| {
+                                                    let day = forecast.dates.iter().position(|d| *d == event.date);
+                                                    let class = match day {
+                                                        Some(i) if forecast.total[i] < self.threshold => "has-background-danger-light",
+                                                        _ => "",
+                                                    };
+                                                    html!{
+                                                        <tr {class}>
+                                                            <td>{event.date.clone()}</td>
+                                                            <td>
+                                                                {event.name.clone()}
+                                                                if event.scheduled {
+                                                                    <span class="tag is-light ml-2">{"Scheduled"}</span>
+                                                                }
+                                                            </td>
+                                                            <td>{account_name(&event.account_id)}</td>
+                                                            <td class="has-text-right">{format_amount(-event.amount, &currency)}</td>
+                                                        </tr>
+                                                    }
+                                                }).collect::<Html>()
+                                            }
+                                            </tbody>
+                                        </table>
+                                    </div>
+                                </>
+                            }
+                        }
+                    }
+                }
+
+                <div class="box">
+                    <h2 class="is-size-5">{"Scheduled items"}</h2>
+                    {
+                        self.scheduled.iter().map(|item| {
+                            let delete = {
+                                let id = item.id.unwrap_or_default();
+                                ctx.link().callback(move |_| Msg::DeleteScheduled(id))
+                            };
+                            let repeats = item.frequency.map_or("once", |f| f.as_str());
+
+                            html!{
+                                <div class="is-flex is-justify-content-space-between is-size-7 mb-1">
+                                    <span>{format!("{} · {} · {} from {}", item.name, account_name(&item.account_id), repeats, item.date)}</span>
+                                    <span>
+                                        {format_amount(-item.amount, item.iso_currency_code.as_deref().unwrap_or(&currency))}
+                                        <a class="has-text-danger ml-2" onclick={delete}>{"Delete"}</a>
+                                    </span>
+                                </div>
+                            }
+                        }).collect::<Html>()
+                    }
+
+                    <form class="mt-2" onsubmit={add}>
+                        <div class="field has-addons">
+                            <div class="control is-expanded">
+                                <input ref={self.name_ref.clone()} class="input is-small" type="text" placeholder="Name"/>
+                            </div>
+                            <div class="control">
+                                <div class="select is-small">
+                                    <select ref={self.account_ref.clone()}>
+                                    {
+                                        self.forecast.iter().flat_map(|f| f.accounts.iter()).map(|a| {
+                                            html!{<option value={a.account_id.clone()}>{a.name.clone()}</option>}
+                                        }).collect::<Html>()
+                                    }
+                                    </select>
+                                </div>
+                            </div>
+                            <div class="control">
+                                <div class="select is-small">
+                                    <select ref={self.direction_ref.clone()}>
+                                        <option value="out">{"Out"}</option>
+                                        <option value="in">{"In"}</option>
+                                    </select>
+                                </div>
+                            </div>
+                            <div class="control">
+                                <input ref={self.amount_ref.clone()} class="input is-small" type="number" step="0.01" min="0" placeholder="Amount"/>
+                            </div>
+                        </div>
+                        <div class="field has-addons">
+                            <div class="control">
+                                <input ref={self.date_ref.clone()} class="input is-small" type="date"/>
+                            </div>
+                            <div class="control">
+                                <div class="select is-small">
+                                    <select ref={self.frequency_ref.clone()}>
+                                        <option value="">{"Once"}</option>
+                                        {
+                                            BudgetPeriod::ALL.iter().map(|p| {
+                                                html!{<option value={p.as_str()}>{p.as_str()}</option>}
+                                            }).collect::<Html>()
+                                        }
+                                    </select>
+                                </div>
+                            </div>
+                            <div class="control">
+                                <input ref={self.end_date_ref.clone()} class="input is-small" type="date" title="Last payment"/>
+                            </div>
+                            <div class="control">
+                                <button class="button is-small is-primary" type="submit">{"Add"}</button>
+                            </div>
+                        </div>
+                    </form>
+                </div>
+            </div>
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        self.error = None;
+
+        let session = ctx
+            .props()
+            .context
+            .supabase_session
+            .clone()
+            .expect("Needs session");
+
+        match msg {
+            Msg::GotForecast(forecast) => self.forecast = Some(forecast),
+            Msg::GotScheduled(scheduled) => self.scheduled = scheduled,
+            Msg::GotThreshold(threshold) => self.threshold = threshold,
+            Msg::SetDays(days) => {
+                self.days = days;
+                self.forecast = None;
+                self.reload(ctx);
+            }
+            Msg::SetThreshold(threshold) => {
+                let Ok(threshold) = threshold.parse::<f64>() else {
+                    self.error = Some("Enter an amount".to_string());
+                    return true;
+                };
+                self.threshold = threshold;
+
+                ctx.link().send_future(async move {
+                    match set_threshold(&session.auth_key, &session.user.id, threshold).await {
+                        Ok(_) => Msg::GotThreshold(threshold),
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::AddScheduled => {
+                let value = |node: &NodeRef| {
+                    node.cast::<HtmlInputElement>()
+                        .map(|i| i.value())
+                        .unwrap_or_default()
+                };
+                let select = |node: &NodeRef| {
+                    node.cast::<HtmlSelectElement>()
+                        .map(|s| s.value())
+                        .unwrap_or_default()
+                };
+
+                let name = value(&self.name_ref).trim().to_string();
+                let account_id = select(&self.account_ref);
+                let date = value(&self.date_ref);
+                let Ok(amount) = value(&self.amount_ref).parse::<f64>() else {
+                    self.error = Some("Enter an amount".to_string());
+                    return true;
+                };
+                if name.is_empty() || account_id.is_empty() || date.is_empty() {
+                    self.error = Some("A name, account and date are needed".to_string());
+                    return true;
+                }
+
+                let end_date = Some(value(&self.end_date_ref)).filter(|d| !d.is_empty());
+                let item = SchemaScheduledItem {
+                    id: None,
+                    user_id: session.user.id.clone(),
+                    account_id,
+                    name,
+                    amount: if select(&self.direction_ref) == "in" {
+                        -amount.abs()
+                    } else {
+                        amount.abs()
+                    },
+                    iso_currency_code: None,
+                    date,
+                    frequency: BudgetPeriod::parse(&select(&self.frequency_ref)),
+                    end_date,
+                };
+
+                ctx.link().send_future(async move {
+                    match add_scheduled(&session.auth_key, &item).await {
+                        Ok(_) => Msg::Update,
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::DeleteScheduled(id) => {
+                ctx.link().send_future(async move {
+                    match delete_scheduled(&session.auth_key, id).await {
+                        Ok(_) => Msg::Update,
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::Update => self.reload(ctx),
+            Msg::Error(e) => {
+                log::error!("{e}");
+                self.error = Some(e);
+            }
+        }
+
+        true
+    }
+}
+
+/// Items that still have payments to come
+async fn get_scheduled(auth_key: &str) -> Result<Vec<SchemaScheduledItem>, recurr_core::Error> {
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    let items: Vec<SchemaScheduledItem> = get_supbase_client()
+        .from("scheduled_items")
+        .auth(auth_key)
+        .select("*")
+        .order("date")
+        .execute()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(items
+        .into_iter()
+        .filter(|i| match (&i.frequency, &i.end_date) {
+            (None, _) => i.date >= today,
+            (Some(_), end_date) => end_date.as_ref().map_or(true, |d| *d >= today),
+        })
+        .collect())
+}
+
+async fn add_scheduled(
+    auth_key: &str,
+    item: &SchemaScheduledItem,
+) -> Result<(), recurr_core::Error> {
+    get_supbase_client()
+        .from("scheduled_items")
+        .auth(auth_key)
+        .insert(serde_json::to_string(item)?)
+        .execute()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
+async fn delete_scheduled(auth_key: &str, id: i64) -> Result<(), recurr_core::Error> {
+    get_supbase_client()
+        .from("scheduled_items")
+        .auth(auth_key)
+        .eq("id", id.to_string())
+        .delete()
+        .execute()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
+async fn get_threshold(auth_key: &str) -> Result<f64, recurr_core::Error> {
+    #[derive(Deserialize)]
+    struct Row {
+        low_balance_threshold: f64,
+    }
+
+    let rows: Vec<Row> = get_supbase_client()
+        .from("user_settings")
+        .auth(auth_key)
+        .select("low_balance_threshold")
+        .execute()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(rows.first().map_or(0.0, |r| r.low_balance_threshold))
+}
+
+async fn set_threshold(
+    auth_key: &str,
+    user_id: &str,
+    threshold: f64,
+) -> Result<(), recurr_core::Error> {
+    get_supbase_client()
+        .from("user_settings")
+        .auth(auth_key)
+        .upsert(
+            serde_json::json!({ "user_id": user_id, "low_balance_threshold": threshold })
+                .to_string(),
+        )
+        .execute()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
@@ -0,0 +1,401 @@
+use chrono::{Duration, Local, NaiveDate};
+use recurr_core::{
+    balances::SchemaBalanceSnapshot,
+    currency::format_amount,
+    get_all, get_supbase_client,
+    goals::{GoalProgress, GoalStatus, SchemaGoal, LOOKBACK_DAYS},
+    to_pg_array, Account, Transaction,
+};
+use web_sys::{HtmlInputElement, HtmlSelectElement, SubmitEvent};
+use yew::{html, Component, Context, Html, NodeRef, Properties, UseReducerHandle};
+
+use crate::context::Session;
+
+/// A month of balance history before the recent pace is measured from, for its starting balance
+const HISTORY_DAYS: i64 = LOOKBACK_DAYS + 30;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    /// Every account, to link goals to and read current balances from
+    pub accounts: Vec<Account>,
+    pub context: UseReducerHandle<Session>,
+}
+
+pub enum Msg {
+    GotGoals(Vec<(SchemaGoal, GoalProgress)>),
+    Add,
+    Delete(i64),
+    Saved,
+    Error(String),
+}
+
+/// Savings goals with how they're tracking against their target dates
+pub struct Goals {
+    goals: Option<Vec<(SchemaGoal, GoalProgress)>>,
+    error: Option<String>,
+
+    name_ref: NodeRef,
+    target_ref: NodeRef,
+    date_ref: NodeRef,
+    accounts_ref: NodeRef,
+    tag_ref: NodeRef,
+    saved_ref: NodeRef,
+    budgeted_ref: NodeRef,
+}
+
+impl Goals {
+    fn reload(&self, ctx: &Context<Self>) {
+        let auth_key = ctx
+            .props()
+            .context
+            .supabase_session
+            .as_ref()
+            .expect("Needs session")
+            .auth_key
+            .clone();
+        let accounts = ctx.props().accounts.clone();
+        let rates = ctx.props().context.exchange_rates.clone();
+
+        ctx.link().send_future(async move {
+            let today = Local::now().date_naive();
+
+            let goals = match get_goals(&auth_key).await {
+                Ok(goals) => goals,
+                Err(e) => return Msg::Error(e.to_string()),
+            };
+
+            let mut account_ids: Vec<String> =
+                goals.iter().flat_map(|g| g.account_ids.clone()).collect();
+            account_ids.sort();
+            account_ids.dedup();
+            let snapshots = match get_snapshots(&auth_key, &account_ids, today).await {
+                Ok(snapshots) => snapshots,
+                Err(e) => return Msg::Error(e.to_string()),
+            };
+
+            let mut lines = Vec::new();
+            for goal in goals {
+                let tagged = match goal.tag.as_deref().filter(|_| goal.account_ids.is_empty()) {
+                    Some(tag) => match get_tagged(&auth_key, tag).await {
+                        Ok(tagged) => tagged,
+                        Err(e) => return Msg::Error(e.to_string()),
+                    },
+                    None => Vec::new(),
+                };
+
+                let progress = goal.progress(&accounts, &snapshots, &tagged, &rates, today);
+                lines.push((goal, progress));
+            }
+
+            Msg::GotGoals(lines)
+        });
+    }
+}
+
+impl Component for Goals {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let goals = Self {
+            goals: None,
+            error: None,
+
+            name_ref: NodeRef::default(),
+            target_ref: NodeRef::default(),
+            date_ref: NodeRef::default(),
+            accounts_ref: NodeRef::default(),
+            tag_ref: NodeRef::default(),
+            saved_ref: NodeRef::default(),
+            budgeted_ref: NodeRef::default(),
+        };
+        goals.reload(ctx);
+
+        goals
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let currency = ctx.props().context.exchange_rates.base().to_string();
+        let add = ctx.link().callback(|e: SubmitEvent| {
+            e.prevent_default();
+            Msg::Add
+        });
+
+        html! {
+            <div class="box">
+                <h2 class="is-size-5">{"Goals"}</h2>
+
+                if let Some(e) = &self.error {
+                    <p class="help is-danger">{e}</p>
+                }
+
+                {
+                    self.goals.iter().flatten().map(|(goal, progress)| {
+                        let delete = {
+                            let id = goal.id.unwrap_or_default();
+                            ctx.link().callback(move |_| Msg::Delete(id))
+                        };
+                        let class = match progress.status {
+                            GoalStatus::Complete | GoalStatus::OnTrack => "tag is-success is-light",
+                            GoalStatus::Behind | GoalStatus::Overdue => "tag is-danger is-light",
+                            GoalStatus::NoTargetDate => "tag is-light",
+                        };
+
+                        html!{
+                            <div class="mb-3">
+                                <div class="is-flex is-justify-content-space-between">
+                                    <span>{goal.name.clone()}<span class={format!("{class} ml-2")}>{progress.status.label()}</span></span>
+                                    <span>{format!("{} of {}", format_amount(progress.saved, &currency), format_amount(goal.target_amount, &currency))}</span>
+                                </div>
+                                <progress class="progress m-0 is-info" value={format!("{:0.2}", (progress.saved / goal.target_amount).clamp(0.0, 1.0))} max="1"></progress>
+                                <div class="is-flex is-justify-content-space-between is-size-7">
+                                    <span class="has-text-grey">
+                                        {format!("{}/mo lately", format_amount(progress.monthly, &currency))}
+                                        if let Some(required) = progress.required_monthly {
+                                            {format!(" · {}/mo needed", format_amount(required, &currency))}
+                                        }
+                                        if let Some(date) = &goal.target_date {
+                                            {format!(" · by {date}")}
+                                        }
+                                        if let Some(projected) = progress.projected {
+                                            {format!(" · reached around {}", projected.format("%b %Y"))}
+                                        }
+                                    </span>
+                                    <a class="has-text-danger" onclick={delete}>{"Delete"}</a>
+                                </div>
+                            </div>
+                        }
+                    }).collect::<Html>()
+                }
+
+                <form class="mt-2" onsubmit={add}>
+                    <div class="field has-addons">
+                        <div class="control is-expanded">
+                            <input ref={self.name_ref.clone()} class="input is-small" type="text" placeholder="Goal"/>
+                        </div>
+                        <div class="control">
+                            <input ref={self.target_ref.clone()} class="input is-small" type="number" step="0.01" placeholder="Target"/>
+                        </div>
+                        <div class="control">
+                            <input ref={self.date_ref.clone()} class="input is-small" type="date"/>
+                        </div>
+                    </div>
+                    <div class="field has-addons">
+                        <div class="control">
+                            <div class="select is-small is-multiple">
+                                <select ref={self.accounts_ref.clone()} multiple={true} size="2">
+                                {
+                                    ctx.props().accounts.iter().map(|a| {
+                                        html!{<option value={a.account_id.clone()}>{a.name.clone()}</option>}
+                                    }).collect::<Html>()
+                                }
+                                </select>
+                            </div>
+                        </div>
+                        <div class="control">
+                            <input ref={self.tag_ref.clone()} class="input is-small" type="text" placeholder="Or a transfer tag"/>
+                        </div>
+                        <div class="control">
+                            <input ref={self.saved_ref.clone()} class="input is-small" type="number" step="0.01" placeholder="Already saved"/>
+                        </div>
+                    </div>
+                    <div class="field is-flex is-justify-content-space-between">
+                        <label class="checkbox is-size-7">
+                            <input class="mr-2" type="checkbox" ref={self.budgeted_ref.clone()}/>
+                            {"Budget what's needed each month"}
+                        </label>
+                        <button class="button is-small is-success" type="submit">{"Save goal"}</button>
+                    </div>
+                </form>
+            </div>
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        self.error = None;
+        let session = ctx
+            .props()
+            .context
+            .supabase_session
+            .clone()
+            .expect("Needs session");
+        let auth_key = session.auth_key;
+
+        match msg {
+            Msg::GotGoals(goals) => self.goals = Some(goals),
+            Msg::Add => {
+                let input = |node: &NodeRef| {
+                    node.cast::<HtmlInputElement>()
+                        .expect("Not an input element")
+                        .value()
+                };
+
+                let name = input(&self.name_ref);
+                let target_amount = input(&self.target_ref).parse::<f64>();
+                let target_date = input(&self.date_ref);
+                let tag = input(&self.tag_ref);
+                let saved = input(&self.saved_ref).parse::<f64>().unwrap_or_default();
+                let budgeted = self
+                    .budgeted_ref
+                    .cast::<HtmlInputElement>()
+                    .expect("Budgeted ref not an input element")
+                    .checked();
+
+                let options = self
+                    .accounts_ref
+                    .cast::<HtmlSelectElement>()
+                    .expect("Accounts ref not a select element")
+                    .selected_options();
+                let account_ids: Vec<String> = (0..options.length())
+                    .filter_map(|i| options.item(i))
+                    .filter_map(|o| o.get_attribute("value"))
+                    .collect();
+
+                let target_amount = match target_amount {
+                    Ok(target) if target > 0.0 && !name.is_empty() => target,
+                    _ => {
+                        self.error = Some("Enter a name and a target amount".to_string());
+                        return true;
+                    }
+                };
+
+                let goal = SchemaGoal {
+                    id: None,
+                    user_id: session.user.id,
+                    name,
+                    target_amount,
+                    target_date: (!target_date.is_empty()).then_some(target_date),
+                    account_ids,
+                    tag: (!tag.is_empty()).then_some(tag),
+                    saved,
+                    budgeted,
+                };
+
+                // Tagged transfers are counted once the goal is loaded again
+                let progress = goal.progress(
+                    &ctx.props().accounts,
+                    &[],
+                    &[],
+                    &ctx.props().context.exchange_rates,
+                    Local::now().date_naive(),
+                );
+                let budget = progress
+                    .required_monthly
+                    .filter(|_| budgeted)
+                    .map(|required| goal.budget(required));
+
+                ctx.link().send_future(async move {
+                    let res = save_goal(&auth_key, &goal).await;
+                    if let Err(e) = res {
+                        return Msg::Error(e.to_string());
+                    }
+
+                    if let Some(budget) = budget {
+                        let res = get_supbase_client()
+                            .from("budgets")
+                            .auth(&auth_key)
+                            .upsert(budget.to_string().expect("Failed to serialize"))
+                            .execute()
+                            .await
+                            .and_then(|r| r.error_for_status());
+                        if let Err(e) = res {
+                            return Msg::Error(e.to_string());
+                        }
+                    }
+
+                    Msg::Saved
+                });
+            }
+            Msg::Delete(id) => {
+                ctx.link().send_future(async move {
+                    let res = get_supbase_client()
+                        .from("goals")
+                        .auth(&auth_key)
+                        .eq("id", id.to_string())
+                        .delete()
+                        .execute()
+                        .await
+                        .and_then(|r| r.error_for_status());
+
+                    match res {
+                        Ok(_) => Msg::Saved,
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::Saved => self.reload(ctx),
+            Msg::Error(e) => {
+                log::error!("{e}");
+                self.error = Some(e);
+            }
+        }
+
+        true
+    }
+}
+
+async fn get_goals(auth_key: &str) -> Result<Vec<SchemaGoal>, recurr_core::Error> {
+    let res = get_supbase_client()
+        .from("goals")
+        .auth(auth_key)
+        .select("*")
+        .order("target_date.nullslast,name")
+        .execute()
+        .await?
+        .error_for_status()?;
+
+    Ok(res.json().await?)
+}
+
+async fn save_goal(auth_key: &str, goal: &SchemaGoal) -> Result<(), recurr_core::Error> {
+    get_supbase_client()
+        .from("goals")
+        .auth(auth_key)
+        .upsert(serde_json::to_string(goal)?)
+        .on_conflict("user_id,name")
+        .execute()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
+async fn get_snapshots(
+    auth_key: &str,
+    account_ids: &[String],
+    today: NaiveDate,
+) -> Result<Vec<SchemaBalanceSnapshot>, recurr_core::Error> {
+    if account_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let client = get_supbase_client();
+    let since = (today - Duration::days(HISTORY_DAYS))
+        .format("%Y-%m-%d")
+        .to_string();
+
+    get_all(|| {
+        client
+            .from("balance_snapshots")
+            .auth(auth_key)
+            .select("*")
+            .in_("account_id", account_ids)
+            .gte("date", &since)
+            .order("date,account_id")
+    })
+    .await
+}
+
+/// Transfers the user tagged towards a goal
+async fn get_tagged(auth_key: &str, tag: &str) -> Result<Vec<Transaction>, recurr_core::Error> {
+    let res = get_supbase_client()
+        .from("user_transactions")
+        .auth(auth_key)
+        .select("*")
+        .cs("tags", to_pg_array(&[tag.to_string()]))
+        .execute()
+        .await?
+        .error_for_status()?;
+
+    Ok(res.json().await?)
+}
@@ -2,12 +2,17 @@ use std::string::ToString;
 use std::{mem, str::FromStr};
 
 use crate::{
-    context::{Session, SessionContext},
+    context::{ContextUpdate, Session, SessionContext},
     dashboard::{
-        accounts::AccountsView, budgets::BudgetsView, summary::SummaryView,
-        transactions::TransactionsView,
+        accounts::AccountsView, budgets::BudgetsView, debts::DebtsView,
+        forecast::ForecastView, notifications::NotificationsView, reports::ReportsView,
+        settings::SettingsView, summary::SummaryView, transactions::TransactionsView,
     },
 };
+use recurr_core::{
+    categories::Taxonomy,
+    currency::{format_amount, get_exchange_rates, ExchangeRates},
+};
 use gloo_storage::{LocalStorage, Storage};
 use serde::{Deserialize, Serialize};
 use strum::{Display, EnumIter, EnumString, IntoEnumIterator};
@@ -18,10 +23,18 @@ use yew::{
     Properties, UseReducerHandle,
 };
 
-use self::transactions::Filter;
+use self::{saved_filters::SavedFilterLine, transactions::Filter};
 
 mod accounts;
 mod budgets;
+mod debts;
+mod forecast;
+mod goals;
+mod net_worth;
+mod notifications;
+mod reports;
+mod saved_filters;
+mod settings;
 mod summary;
 mod transactions;
 
@@ -29,20 +42,27 @@ mod transactions;
 pub enum DashboardTab {
     Summary,
     Budgets,
+    Forecast,
+    Reports,
     Transaction(Filter),
     Accounts,
+    Debts,
+    Notifications,
+    Settings,
 }
 
 #[derive(Properties, PartialEq)]
 struct SidebarProps {
     active_tab: DashboardTab,
     switch_tab: Callback<DashboardTab>,
+    saved_filters: Vec<SavedFilterLine>,
+    delete_filter: Callback<i64>,
 }
 
 #[function_component(Sidebar)]
 fn sidebar(props: &SidebarProps) -> Html {
     let context = use_context::<SessionContext>().unwrap();
-    let use_context = context;
+    let use_context = context.clone();
 
     let signout = move |_: MouseEvent| {
         let use_context = use_context.clone();
@@ -78,6 +98,45 @@ fn sidebar(props: &SidebarProps) -> Html {
                         }
                     }).collect::<Html>()
                 }
+                if !props.saved_filters.is_empty() {
+                    <p class="menu-label has-text-white mt-4">{"Saved filters"}</p>
+                    <ul class="menu-list">
+                    {
+                        props.saved_filters.iter().map(|line| {
+                            let filter = line.saved.filter.clone();
+                            let active = props.active_tab == DashboardTab::Transaction(filter.clone());
+                            let open = {
+                                let switch_tab = props.switch_tab.clone();
+                                Callback::from(move |_| switch_tab.emit(DashboardTab::Transaction(filter.clone())))
+                            };
+                            let delete = {
+                                let delete_filter = props.delete_filter.clone();
+                                let id = line.saved.id;
+                                Callback::from(move |e: MouseEvent| {
+                                    e.stop_propagation();
+                                    if let Some(id) = id {
+                                        delete_filter.emit(id);
+                                    }
+                                })
+                            };
+
+                            html!{
+                                <li>
+                                    <a class={if active { "is-active" } else { "has-text-white" }} onclick={open}>
+                                        <span class="is-flex is-justify-content-space-between">
+                                            <span>{line.saved.name.clone()}</span>
+                                            <span class="delete is-small" onclick={delete}></span>
+                                        </span>
+                                        <span class="is-size-7">
+                                            {format!("{} · {}", line.transactions, format_amount(line.total, context.exchange_rates.base()))}
+                                        </span>
+                                    </a>
+                                </li>
+                            }
+                        }).collect::<Html>()
+                    }
+                    </ul>
+                }
             </div>
             <div class="is-flex is-justify-content-center">
                 <button onclick={signout} class="button is-danger">{"Signout"}</button>
@@ -93,20 +152,99 @@ pub struct Props {
 
 pub enum Msg {
     SwitchTabs(DashboardTab),
+    GotSettings(ExchangeRates, Taxonomy),
+    /// The transactions tab applied a filter
+    SetFilter(Filter),
+
+    GetSavedFilters,
+    GotSavedFilters(Vec<SavedFilterLine>),
+    DeleteSavedFilter(i64),
 }
 
 pub struct Dashboard {
     active_tab: DashboardTab,
+    settings_loaded: bool,
+    /// The transactions tab's filter, restored when going back to it
+    last_filter: Filter,
+    saved_filters: Vec<SavedFilterLine>,
+}
+
+impl Dashboard {
+    fn get_saved_filters(
+        &self,
+        ctx: &yew::Context<Self>,
+        taxonomy: Taxonomy,
+        rates: ExchangeRates,
+    ) {
+        let auth_key = ctx
+            .props()
+            .context
+            .supabase_session
+            .as_ref()
+            .expect("Needs session")
+            .auth_key
+            .clone();
+
+        ctx.link().send_future(async move {
+            let lines = saved_filters::get_lines(&auth_key, &taxonomy, &rates)
+                .await
+                .unwrap_or_else(|e| {
+                    log::error!("Failed to get saved filters {e}");
+                    Vec::new()
+                });
+
+            Msg::GotSavedFilters(lines)
+        });
+    }
+
+    fn save_tab(&mut self, tab: DashboardTab) {
+        LocalStorage::set("SavedTab", &tab).expect("Failed to save tab to local storage");
+        self.active_tab = tab
+    }
 }
 
 impl Component for Dashboard {
     type Message = Msg;
     type Properties = Props;
 
-    fn create(_ctx: &yew::Context<Self>) -> Self {
+    fn create(ctx: &yew::Context<Self>) -> Self {
         let tab = LocalStorage::get("SavedTab").unwrap_or(DashboardTab::Summary);
 
-        Self { active_tab: tab }
+        let session = ctx
+            .props()
+            .context
+            .supabase_session
+            .clone()
+            .expect("Needs session");
+        ctx.link().send_future(async move {
+            let rates = get_exchange_rates(&session.auth_key, &session.user.id)
+                .await
+                .unwrap_or_else(|e| {
+                    log::error!("Failed to get exchange rates {e}");
+                    ExchangeRates::default()
+                });
+
+            let taxonomy = transactions::categories::get_taxonomy(&session.auth_key)
+                .await
+                .unwrap_or_else(|e| {
+                    log::error!("Failed to get categories {e}");
+                    Taxonomy::default()
+                });
+
+            Msg::GotSettings(rates, taxonomy)
+        });
+
+        let last_filter = match &tab {
+            DashboardTab::Transaction(filter) => filter.clone(),
+            _ => Filter::default(),
+        };
+
+        Self {
+            active_tab: tab,
+            settings_loaded: false,
+            last_filter,
+            saved_filters: Vec::new(),
+        }
     }
 
     fn view(&self, ctx: &yew::Context<Self>) -> Html {
@@ -114,17 +252,29 @@ impl Component for Dashboard {
 
         let active_tab = &self.active_tab;
         let switch_tab = ctx.link().callback(Msg::SwitchTabs);
+        let delete_filter = ctx.link().callback(Msg::DeleteSavedFilter);
+        let on_filter = ctx.link().callback(Msg::SetFilter);
+        let on_save = ctx.link().callback(|_| Msg::GetSavedFilters);
 
         html! {
             <div class="full-height columns m-0">
-                <Sidebar active_tab={active_tab.clone()} switch_tab={switch_tab.clone()} />
+                <Sidebar active_tab={active_tab.clone()} switch_tab={switch_tab.clone()} saved_filters={self.saved_filters.clone()} {delete_filter}/>
                 <div class="column has-background-light">
-                    {
-                        match &self.active_tab {
-                            DashboardTab::Summary => html!{<SummaryView context={context.clone()} />},
-                            DashboardTab::Budgets => html!{<BudgetsView context={context.clone()} {switch_tab}/>},
-                            DashboardTab::Transaction(filter) => html!{<TransactionsView context={context.clone()} filter={filter.clone()}/>},
-                            DashboardTab::Accounts => html!{<AccountsView context={context.clone()}/>},
+                    if !self.settings_loaded {
+                        <progress class="progress is-small is-primary" max="100">{"15%"}</progress>
+                    } else {
+                        {
+                            match &self.active_tab {
+                                DashboardTab::Summary => html!{<SummaryView context={context.clone()} />},
+                                DashboardTab::Budgets => html!{<BudgetsView context={context.clone()} {switch_tab}/>},
+                                DashboardTab::Forecast => html!{<ForecastView context={context.clone()}/>},
+                                DashboardTab::Reports => html!{<ReportsView context={context.clone()}/>},
+                                DashboardTab::Transaction(filter) => html!{<TransactionsView context={context.clone()} filter={filter.clone()} {on_filter} {on_save}/>},
+                                DashboardTab::Accounts => html!{<AccountsView context={context.clone()}/>},
+                                DashboardTab::Debts => html!{<DebtsView context={context.clone()}/>},
+                                DashboardTab::Notifications => html!{<NotificationsView context={context.clone()} {switch_tab}/>},
+                                DashboardTab::Settings => html!{<SettingsView context={context.clone()}/>},
+                            }
                         }
                     }
                 </div>
@@ -132,11 +282,60 @@ impl Component for Dashboard {
         }
     }
 
-    fn update(&mut self, _ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
         match msg {
             Msg::SwitchTabs(tab) => {
-                LocalStorage::set("SavedTab", &tab).expect("Failed to save tab to local storage");
-                self.active_tab = tab
+                // The sidebar opens transactions unfiltered, which brings back the last filter
+                let tab = match tab {
+                    DashboardTab::Transaction(filter) if filter == Filter::default() => {
+                        DashboardTab::Transaction(self.last_filter.clone())
+                    }
+                    DashboardTab::Transaction(filter) => {
+                        self.last_filter = filter.clone();
+                        DashboardTab::Transaction(filter)
+                    }
+                    tab => tab,
+                };
+                self.save_tab(tab);
+            }
+            Msg::GotSettings(rates, taxonomy) => {
+                self.get_saved_filters(ctx, taxonomy.clone(), rates.clone());
+
+                let context = &ctx.props().context;
+                context.dispatch(ContextUpdate::ExchangeRates(rates));
+                context.dispatch(ContextUpdate::Taxonomy(taxonomy));
+                self.settings_loaded = true;
+            }
+            Msg::SetFilter(filter) => {
+                self.last_filter = filter.clone();
+                self.save_tab(DashboardTab::Transaction(filter));
+            }
+            Msg::GetSavedFilters => {
+                let context = &ctx.props().context;
+                self.get_saved_filters(
+                    ctx,
+                    context.taxonomy.clone(),
+                    context.exchange_rates.clone(),
+                );
+                return false;
+            }
+            Msg::GotSavedFilters(lines) => self.saved_filters = lines,
+            Msg::DeleteSavedFilter(id) => {
+                let auth_key = ctx
+                    .props()
+                    .context
+                    .supabase_session
+                    .as_ref()
+                    .expect("Needs session")
+                    .auth_key
+                    .clone();
+                ctx.link().send_future(async move {
+                    if let Err(e) = saved_filters::delete_filter(&auth_key, id).await {
+                        log::error!("Failed to delete saved filter {e}");
+                    }
+                    Msg::GetSavedFilters
+                });
+                return false;
             }
         }
 
@@ -0,0 +1,248 @@
+use std::collections::HashSet;
+
+use chrono::{Duration, Local};
+use recurr_core::{
+    balances::{net_worth_series, NetWorthPoint, SchemaBalanceSnapshot},
+    currency::format_amount,
+    get_all, get_supbase_client,
+};
+use serde::Deserialize;
+use yew::{html, Component, Context, Html, Properties};
+
+use crate::context::SessionContext;
+
+const WIDTH: f64 = 600.0;
+const HEIGHT: f64 = 200.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Range {
+    Month,
+    HalfYear,
+    Year,
+    All,
+}
+
+impl Range {
+    const ALL: [Range; 4] = [Range::Month, Range::HalfYear, Range::Year, Range::All];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Range::Month => "1M",
+            Range::HalfYear => "6M",
+            Range::Year => "1Y",
+            Range::All => "All",
+        }
+    }
+
+    fn since(&self) -> Option<String> {
+        let days = match self {
+            Range::Month => 30,
+            Range::HalfYear => 182,
+            Range::Year => 365,
+            Range::All => return None,
+        };
+
+        Some(
+            (Local::now() - Duration::days(days))
+                .format("%Y-%m-%d")
+                .to_string(),
+        )
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub context: SessionContext,
+}
+
+pub enum Msg {
+    GotSnapshots(Vec<SchemaBalanceSnapshot>, Vec<String>),
+    SetRange(Range),
+    Error(String),
+}
+
+pub struct NetWorthChart {
+    snapshots: Option<Vec<SchemaBalanceSnapshot>>,
+    /// Linked and manual accounts that still exist
+    open: Vec<String>,
+    range: Range,
+}
+
+impl Component for NetWorthChart {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let auth_key = ctx
+            .props()
+            .context
+            .supabase_session
+            .clone()
+            .expect("Needs session")
+            .auth_key;
+
+        ctx.link().send_future(async move {
+            let snapshots = get_snapshots(&auth_key).await;
+            let open = get_open_accounts(&auth_key).await;
+            match (snapshots, open) {
+                (Ok(snapshots), Ok(open)) => Msg::GotSnapshots(snapshots, open),
+                (Err(e), _) | (_, Err(e)) => Msg::Error(e.to_string()),
+            }
+        });
+
+        Self {
+            snapshots: None,
+            open: Vec::new(),
+            range: Range::Year,
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let Some(snapshots) = &self.snapshots else {
+            return html! {};
+        };
+
+        let rates = &ctx.props().context.exchange_rates;
+        let currency = rates.base().to_string();
+        let open: HashSet<&str> = self.open.iter().map(String::as_str).collect();
+        let series = net_worth_series(snapshots, rates, self.range.since().as_deref(), &open);
+
+        let lines: [(&str, &str, fn(&NetWorthPoint) -> f64); 6] = [
+            ("Net worth", "#1E3888", NetWorthPoint::net_worth),
+            ("Cash", "#48c78e", |p| p.cash),
+            ("Credit Cards", "#f14668", |p| p.credit),
+            ("Investments", "#3e8ed0", |p| p.investments),
+            ("Loans", "#ffb70f", |p| p.loans),
+            ("Property & Other", "#b5b5b5", |p| p.other),
+        ];
+
+        let values = series
+            .iter()
+            .flat_map(|p| lines.iter().map(move |(_, _, value)| value(p)));
+        let min = values.clone().fold(0.0_f64, f64::min);
+        let max = values.fold(0.0_f64, f64::max);
+        let span = if max - min > 0.0 { max - min } else { 1.0 };
+
+        let x = |i: usize| {
+            if series.len() > 1 {
+                i as f64 * WIDTH / (series.len() - 1) as f64
+            } else {
+                WIDTH / 2.0
+            }
+        };
+        let y = |v: f64| HEIGHT - (v - min) / span * HEIGHT;
+
+        html! {
+            <div class="card m-3">
+                <header class="card-header">
+                    <div class="card-header-title is-flex is-justify-content-space-between">
+                        <p class="mr-4">{"Net worth"}</p>
+                        if let Some(last) = series.last() {
+                            <p>{format_amount(last.net_worth(), &currency)}</p>
+                        }
+                    </div>
+                </header>
+                <div class="card-content">
+                    <div class="buttons has-addons">
+                    {
+                        Range::ALL.iter().map(|range| {
+                            let range = *range;
+                            let class = if range == self.range { "button is-small is-primary is-selected" } else { "button is-small" };
+                            let onclick = ctx.link().callback(move |_| Msg::SetRange(range));
+                            html!{<button {class} {onclick}>{range.label()}</button>}
+                        }).collect::<Html>()
+                    }
+                    </div>
+
+                    if series.is_empty() {
+                        <p class="has-text-grey">{"No balance history yet, it's recorded each time balances are fetched."}</p>
+                    } else {
+                        <svg viewBox={format!("0 0 {WIDTH} {HEIGHT}")} width="100%" preserveAspectRatio="none">
+                            <line x1="0" x2={WIDTH.to_string()} y1={y(0.0).to_string()} y2={y(0.0).to_string()} stroke="#e7e7e7"/>
+                            {
+                                lines.iter().enumerate().map(|(n, (_, colour, value))| {
+                                    let points = series
+                                        .iter()
+                                        .enumerate()
+                                        .map(|(i, p)| format!("{:.1},{:.1}", x(i), y(value(p))))
+                                        .collect::<Vec<_>>()
+                                        .join(" ");
+                                    let width = if n == 0 { "3" } else { "1.5" };
+                                    html!{<polyline {points} fill="none" stroke={colour.to_string()} stroke-width={width}/>}
+                                }).collect::<Html>()
+                            }
+                        </svg>
+                        <div class="is-flex is-justify-content-space-between is-size-7 has-text-grey">
+                            <span>{series.first().map(|p| p.date.clone()).unwrap_or_default()}</span>
+                            <span>{series.last().map(|p| p.date.clone()).unwrap_or_default()}</span>
+                        </div>
+                        <div class="is-flex is-flex-wrap-wrap is-size-7 mt-2">
+                        {
+                            lines.iter().map(|(name, colour, value)| {
+                                let last = series.last().map(value).unwrap_or_default();
+                                html!{
+                                    <span class="mr-4">
+                                        <span style={format!("color: {colour}")}>{"● "}</span>
+                                        {format!("{name}: {}", format_amount(last, &currency))}
+                                    </span>
+                                }
+                            }).collect::<Html>()
+                        }
+                        </div>
+                    }
+                </div>
+            </div>
+        }
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::GotSnapshots(snapshots, open) => {
+                self.snapshots = Some(snapshots);
+                self.open = open;
+            }
+            Msg::SetRange(range) => self.range = range,
+            Msg::Error(e) => log::error!("{e}"),
+        }
+
+        true
+    }
+}
+
+/// Every snapshot the user has, PostgREST caps responses so they're fetched a page at a time
+async fn get_snapshots(auth_key: &str) -> Result<Vec<SchemaBalanceSnapshot>, recurr_core::Error> {
+    let client = get_supbase_client();
+
+    get_all(|| {
+        client
+            .from("balance_snapshots")
+            .auth(auth_key)
+            .select("*")
+            .order("date,account_id")
+    })
+    .await
+}
+
+/// Ids of the linked and manual accounts that haven't been removed
+async fn get_open_accounts(auth_key: &str) -> Result<Vec<String>, recurr_core::Error> {
+    #[derive(Deserialize)]
+    struct Row {
+        account_id: String,
+    }
+
+    let mut open = Vec::new();
+    for table in ["plaid_accounts", "manual_accounts"] {
+        let rows: Vec<Row> = get_supbase_client()
+            .from(table)
+            .auth(auth_key)
+            .select("account_id")
+            .execute()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        open.extend(rows.into_iter().map(|r| r.account_id));
+    }
+
+    Ok(open)
+}
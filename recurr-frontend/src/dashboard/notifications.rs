@@ -0,0 +1,250 @@
+use recurr_core::{
+    alerts::{SchemaBudgetAlert, DEFAULT_THRESHOLDS},
+    get_supbase_client,
+};
+use serde::Deserialize;
+use yew::{
+    html, platform::spawn_local, Callback, Component, Context, Html, Properties, UseReducerHandle,
+};
+
+use crate::context::Session;
+
+use super::{transactions::Filter, DashboardTab};
+
+/// How many past alerts are listed
+const HISTORY: usize = 100;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub context: UseReducerHandle<Session>,
+    pub switch_tab: Callback<DashboardTab>,
+}
+
+pub enum Msg {
+    GotAlerts(Vec<SchemaBudgetAlert>),
+    Open(SchemaBudgetAlert),
+    MarkAllRead,
+    Update,
+
+    Error(String),
+}
+
+/// Budget alerts sent after syncs, newest first
+pub struct NotificationsView {
+    alerts: Option<Vec<SchemaBudgetAlert>>,
+    error: Option<String>,
+}
+
+impl NotificationsView {
+    fn reload(&self, ctx: &Context<Self>) {
+        let auth_key = ctx
+            .props()
+            .context
+            .supabase_session
+            .as_ref()
+            .expect("Needs session")
+            .auth_key
+            .clone();
+
+        ctx.link().send_future(async move {
+            match get_alerts(&auth_key).await {
+                Ok(alerts) => Msg::GotAlerts(alerts),
+                Err(e) => Msg::Error(e.to_string()),
+            }
+        });
+    }
+}
+
+impl Component for NotificationsView {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let view = Self {
+            alerts: None,
+            error: None,
+        };
+        view.reload(ctx);
+
+        view
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let currency = ctx.props().context.exchange_rates.base().to_string();
+        let taxonomy = &ctx.props().context.taxonomy;
+        let mark_all_read = ctx.link().callback(|_| Msg::MarkAllRead);
+        let unread = self.alerts.iter().flatten().filter(|a| !a.read).count();
+
+        html! {
+            <div class="column">
+                <div class="is-flex is-justify-content-space-between is-align-items-center">
+                    <h1 class="is-size-3">{"Notifications"}</h1>
+                    <button class="button is-small" disabled={unread == 0} onclick={mark_all_read}>{"Mark all read"}</button>
+                </div>
+
+                if let Some(e) = &self.error {
+                    <p class="help is-danger">{e}</p>
+                }
+
+                {
+                    match &self.alerts {
+                        None => html!{<progress class="progress is-small is-primary" max="100"></progress>},
+                        Some(alerts) if alerts.is_empty() => html!{<p>{"No alerts yet, they appear here when a budget nears its limit"}</p>},
+                        Some(alerts) => alerts.iter().map(|alert| {
+                            let open = {
+                                let alert = alert.clone();
+                                ctx.link().callback(move |_| Msg::Open(alert.clone()))
+                            };
+                            let class = if alert.read { "box mb-2" } else { "box mb-2 has-background-info-light" };
+
+                            html!{
+                                <div {class}>
+                                    <div class="is-flex is-justify-content-space-between">
+                                        if alert.read {
+                                            <span>{alert.title(taxonomy)}</span>
+                                        } else {
+                                            <strong>{alert.title(taxonomy)}</strong>
+                                        }
+                                        <span class="is-size-7 has-text-grey">{alert.created_at.clone().unwrap_or_default().chars().take(10).collect::<String>()}</span>
+                                    </div>
+                                    <div class="is-flex is-justify-content-space-between is-size-7">
+                                        <span>{alert.body(&currency)}</span>
+                                        <a onclick={open}>{"View transactions"}</a>
+                                    </div>
+                                </div>
+                            }
+                        }).collect::<Html>(),
+                    }
+                }
+            </div>
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        self.error = None;
+
+        let auth_key = ctx
+            .props()
+            .context
+            .supabase_session
+            .as_ref()
+            .expect("Needs session")
+            .auth_key
+            .clone();
+
+        match msg {
+            Msg::GotAlerts(alerts) => self.alerts = Some(alerts),
+            Msg::Open(alert) => {
+                if !alert.read {
+                    let id = alert.id.unwrap_or_default();
+                    spawn_local(async move {
+                        if let Err(e) = mark_read(&auth_key, Some(id)).await {
+                            log::error!("{e}");
+                        }
+                    });
+                }
+
+                let period = alert.period();
+                ctx.props()
+                    .switch_tab
+                    .emit(DashboardTab::Transaction(Filter {
+                        start_date: period.map(|p| p.start.format("%Y-%m-%d").to_string()),
+                        end_date: period.map(|p| p.last_day().format("%Y-%m-%d").to_string()),
+                        category: Some(alert.category_id),
+                        ..Default::default()
+                    }));
+            }
+            Msg::MarkAllRead => {
+                ctx.link().send_future(async move {
+                    match mark_read(&auth_key, None).await {
+                        Ok(_) => Msg::Update,
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::Update => self.reload(ctx),
+            Msg::Error(e) => {
+                log::error!("{e}");
+                self.error = Some(e);
+            }
+        }
+
+        true
+    }
+}
+
+async fn get_alerts(auth_key: &str) -> Result<Vec<SchemaBudgetAlert>, recurr_core::Error> {
+    let res = get_supbase_client()
+        .from("budget_alerts")
+        .auth(auth_key)
+        .select("*")
+        .order("created_at.desc,threshold.desc")
+        .limit(HISTORY)
+        .execute()
+        .await?
+        .error_for_status()?;
+
+    Ok(res.json().await?)
+}
+
+/// Marks one alert read, or every unread one without an `id`
+async fn mark_read(auth_key: &str, id: Option<i64>) -> Result<(), recurr_core::Error> {
+    let query = get_supbase_client()
+        .from("budget_alerts")
+        .auth(auth_key)
+        .eq("read", "false");
+    let query = match id {
+        Some(id) => query.eq("id", id.to_string()),
+        None => query,
+    };
+
+    query
+        .update(serde_json::json!({ "read": true }).to_string())
+        .execute()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
+/// The percentages of a budget that send alerts
+pub async fn get_thresholds(auth_key: &str, user_id: &str) -> Result<Vec<u32>, recurr_core::Error> {
+    #[derive(Deserialize)]
+    struct Row {
+        alert_thresholds: Vec<u32>,
+    }
+
+    let rows: Vec<Row> = get_supbase_client()
+        .from("user_settings")
+        .auth(auth_key)
+        .select("alert_thresholds")
+        .eq("user_id", user_id)
+        .execute()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .next()
+        .map_or(DEFAULT_THRESHOLDS.to_vec(), |r| r.alert_thresholds))
+}
+
+pub async fn set_thresholds(
+    auth_key: &str,
+    user_id: &str,
+    thresholds: &[u32],
+) -> Result<(), recurr_core::Error> {
+    get_supbase_client()
+        .from("user_settings")
+        .auth(auth_key)
+        .upsert(
+            serde_json::json!({ "user_id": user_id, "alert_thresholds": thresholds }).to_string(),
+        )
+        .execute()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
@@ -0,0 +1,499 @@
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use recurr_core::{
+    budgets::{BudgetPeriod, Period},
+    categories::Taxonomy,
+    currency::format_amount,
+    filters::{Filter, SchemaSavedFilter},
+    get_all, get_supbase_client, pfc,
+    reports::{self, Delta, SchemaSpendingRow, Total},
+};
+use web_sys::{Event, HtmlInputElement, HtmlSelectElement};
+use yew::{html, Component, Context, Html, Properties, UseReducerHandle};
+
+use crate::{components::unconverted::Unconverted, context::Session};
+
+use super::saved_filters::{get_saved_filters, get_scoped_summary};
+
+const WIDTH: f64 = 600.0;
+const HEIGHT: f64 = 200.0;
+/// Categories and merchants shown in the bar charts
+const TOP: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Range {
+    ThisMonth,
+    ThreeMonths,
+    SixMonths,
+    Year,
+    YearToDate,
+}
+
+impl Range {
+    const ALL: [Range; 5] = [
+        Range::ThisMonth,
+        Range::ThreeMonths,
+        Range::SixMonths,
+        Range::Year,
+        Range::YearToDate,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Range::ThisMonth => "This month",
+            Range::ThreeMonths => "3M",
+            Range::SixMonths => "6M",
+            Range::Year => "12M",
+            Range::YearToDate => "YTD",
+        }
+    }
+
+    /// Whole months up to and including today's
+    fn window(&self, today: NaiveDate) -> Period {
+        let months = match self {
+            Range::ThisMonth => 1,
+            Range::ThreeMonths => 3,
+            Range::SixMonths => 6,
+            Range::Year => 12,
+            Range::YearToDate => today.month() as i64,
+        };
+
+        Period {
+            start: BudgetPeriod::Monthly
+                .period(1, None, today, 1 - months)
+                .start,
+            end: today + Duration::days(1),
+        }
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub context: UseReducerHandle<Session>,
+}
+
+pub enum Msg {
+    GotRows(Vec<SchemaSpendingRow>, Vec<SchemaSpendingRow>),
+    GotScopes(Vec<SchemaSavedFilter>),
+    /// A saved filter's id, or empty for all transactions
+    SetScope(String),
+    SetRange(Range),
+    SetStart(String),
+    SetEnd(String),
+
+    Error(String),
+}
+
+/// Spending by category, merchant and month over a range, against the month
+/// before and the same range a year earlier
+pub struct ReportsView {
+    range: Option<Range>,
+    window: Period,
+    /// The range with the month before it, for month-over-month changes
+    rows: Option<Vec<SchemaSpendingRow>>,
+    last_year: Vec<SchemaSpendingRow>,
+    scopes: Vec<SchemaSavedFilter>,
+    /// The saved filter reports are limited to
+    scope: Option<SchemaSavedFilter>,
+    error: Option<String>,
+}
+
+impl ReportsView {
+    fn reload(&self, ctx: &Context<Self>) {
+        let auth_key = ctx
+            .props()
+            .context
+            .supabase_session
+            .as_ref()
+            .expect("Needs session")
+            .auth_key
+            .clone();
+        let window = Period {
+            start: BudgetPeriod::Monthly
+                .period(1, None, self.window.start, -1)
+                .start,
+            end: self.window.end,
+        };
+        let last_year = reports::year_before(&self.window);
+        let taxonomy = ctx.props().context.taxonomy.clone();
+        let scope = self.scope.as_ref().map(|s| s.filter.clone());
+
+        ctx.link().send_future(async move {
+            let rows = match get_rows(&auth_key, &taxonomy, scope.as_ref(), &window).await {
+                Ok(rows) => rows,
+                Err(e) => return Msg::Error(e.to_string()),
+            };
+            match get_rows(&auth_key, &taxonomy, scope.as_ref(), &last_year).await {
+                Ok(last_year) => Msg::GotRows(rows, last_year),
+                Err(e) => Msg::Error(e.to_string()),
+            }
+        });
+    }
+
+    fn monthly_chart(&self, months: &[Total], last_year: &[Total], currency: &str) -> Html {
+        let max = months
+            .iter()
+            .chain(last_year)
+            .map(|m| m.amount)
+            .fold(0.0_f64, f64::max);
+        let max = if max > 0.0 { max } else { 1.0 };
+        let slot = WIDTH / months.len().max(1) as f64;
+        let bar = slot * 0.4;
+        let height = |amount: f64| amount.max(0.0) / max * (HEIGHT - 20.0);
+
+        html! {
+            <svg viewBox={format!("0 0 {WIDTH} {HEIGHT}")} width="100%">
+            {
+                months.iter().enumerate().map(|(i, month)| {
+                    let x = i as f64 * slot + slot * 0.1;
+                    let previous = last_year.get(i).map_or(0.0, |m| m.amount);
+                    let change = i
+                        .checked_sub(1)
+                        .and_then(|p| months.get(p))
+                        .map(|p| Delta { key: month.key.clone(), current: month.amount, previous: p.amount });
+                    let title = format!(
+                        "{}: {}, a year earlier {}{}",
+                        month.key,
+                        format_amount(month.amount, currency),
+                        format_amount(previous, currency),
+                        change.and_then(|c| c.percent()).map_or(String::new(), |p| format!(", {p:+.0}% on the month before")),
+                    );
+
+                    html!{
+                        <g>
+                            <title>{title}</title>
+                            <rect x={format!("{x:.1}")} y={format!("{:.1}", HEIGHT - 20.0 - height(previous))} width={format!("{bar:.1}")} height={format!("{:.1}", height(previous))} fill="#b5b5b5"/>
+                            <rect x={format!("{:.1}", x + bar)} y={format!("{:.1}", HEIGHT - 20.0 - height(month.amount))} width={format!("{bar:.1}")} height={format!("{:.1}", height(month.amount))} fill="#3e8ed0"/>
+                            <text x={format!("{:.1}", x + bar)} y={(HEIGHT - 5.0).to_string()} font-size="10" text-anchor="middle">{month.key.clone()}</text>
+                        </g>
+                    }
+                }).collect::<Html>()
+            }
+            </svg>
+        }
+    }
+
+    fn bars(&self, totals: &[Total], label: fn(&str) -> String, currency: &str) -> Html {
+        let shown: Vec<&Total> = totals.iter().take(TOP).collect();
+        let max = shown
+            .iter()
+            .map(|t| t.amount)
+            .fold(0.0_f64, f64::max)
+            .max(1.0);
+        let row = 22.0;
+        let height = row * shown.len() as f64;
+
+        html! {
+            <svg viewBox={format!("0 0 {WIDTH} {height}")} width="100%">
+            {
+                shown.iter().enumerate().map(|(i, total)| {
+                    let y = i as f64 * row;
+                    let width = total.amount.max(0.0) / max * (WIDTH * 0.5);
+                    html!{
+                        <g>
+                            <text x="0" y={format!("{:.1}", y + 15.0)} font-size="12">{label(&total.key)}</text>
+                            <rect x={(WIDTH * 0.3).to_string()} y={format!("{:.1}", y + 4.0)} width={format!("{width:.1}")} height={(row - 8.0).to_string()} fill="#48c78e"/>
+                            <text x={format!("{:.1}", WIDTH * 0.3 + width + 4.0)} y={format!("{:.1}", y + 15.0)} font-size="12">{format_amount(total.amount, currency)}</text>
+                        </g>
+                    }
+                }).collect::<Html>()
+            }
+            </svg>
+        }
+    }
+
+    fn deltas(&self, title: &str, deltas: &[Delta], currency: &str) -> Html {
+        html! {
+            <div class="box">
+                <h2 class="is-size-5">{title.to_string()}</h2>
+                if deltas.is_empty() {
+                    <p class="has-text-grey">{"Nothing to compare"}</p>
+                } else {
+                    <table class="table is-fullwidth is-narrow is-size-7">
+                        <thead>
+                            <tr>
+                                <th>{"Category"}</th>
+                                <th class="has-text-right">{"Before"}</th>
+                                <th class="has-text-right">{"Now"}</th>
+                                <th class="has-text-right">{"Change"}</th>
+                            </tr>
+                        </thead>
+                        <tbody>
+                        {
+                            deltas.iter().map(|d| {
+                                let class = if d.change() > 0.0 { "has-text-right has-text-danger" } else { "has-text-right has-text-success" };
+                                html!{
+                                    <tr>
+                                        <td>{pfc::label(&d.key)}</td>
+                                        <td class="has-text-right">{format_amount(d.previous, currency)}</td>
+                                        <td class="has-text-right">{format_amount(d.current, currency)}</td>
+                                        <td {class}>
+                                            {format_amount(d.change(), currency)}
+                                            if let Some(percent) = d.percent() {
+                                                {format!(" ({percent:+.0}%)")}
+                                            }
+                                        </td>
+                                    </tr>
+                                }
+                            }).collect::<Html>()
+                        }
+                        </tbody>
+                    </table>
+                }
+            </div>
+        }
+    }
+}
+
+impl Component for ReportsView {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let range = Range::SixMonths;
+        let view = Self {
+            range: Some(range),
+            window: range.window(Local::now().date_naive()),
+            rows: None,
+            last_year: Vec::new(),
+            scopes: Vec::new(),
+            scope: None,
+            error: None,
+        };
+        view.reload(ctx);
+
+        let auth_key = ctx
+            .props()
+            .context
+            .supabase_session
+            .as_ref()
+            .expect("Needs session")
+            .auth_key
+            .clone();
+        ctx.link().send_future(async move {
+            match get_saved_filters(&auth_key).await {
+                Ok(scopes) => Msg::GotScopes(scopes),
+                Err(e) => Msg::Error(e.to_string()),
+            }
+        });
+
+        view
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let rates = &ctx.props().context.exchange_rates;
+        let currency = rates.base().to_string();
+
+        let set_start = ctx.link().callback(|e: Event| {
+            Msg::SetStart(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let set_end = ctx.link().callback(|e: Event| {
+            Msg::SetEnd(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let set_scope = ctx.link().callback(|e: Event| {
+            Msg::SetScope(e.target_unchecked_into::<HtmlSelectElement>().value())
+        });
+        let scope_id = self.scope.as_ref().and_then(|s| s.id);
+        // The end date input is inclusive
+        let last_day = self.window.end - Duration::days(1);
+
+        html! {
+            <div class="column">
+                <div class="is-flex is-justify-content-space-between is-align-items-center">
+                    <h1 class="is-size-3">{"Reports"}</h1>
+                    <div class="is-flex">
+                        if !self.scopes.is_empty() {
+                            <div class="select is-small mr-2">
+                                <select onchange={set_scope}>
+                                    <option value="" selected={scope_id.is_none()}>{"All transactions"}</option>
+                                    {
+                                        self.scopes.iter().map(|s| html!{
+                                            <option value={s.id.map(|id| id.to_string()).unwrap_or_default()} selected={s.id == scope_id}>{s.name.clone()}</option>
+                                        }).collect::<Html>()
+                                    }
+                                </select>
+                            </div>
+                        }
+                        <div class="buttons has-addons mr-2 mb-0">
+                        {
+                            Range::ALL.iter().map(|range| {
+                                let range = *range;
+                                let class = if Some(range) == self.range { "button is-small is-primary is-selected" } else { "button is-small" };
+                                let onclick = ctx.link().callback(move |_| Msg::SetRange(range));
+                                html!{<button {class} {onclick}>{range.label()}</button>}
+                            }).collect::<Html>()
+                        }
+                        </div>
+                        <input class="input is-small" type="date" value={self.window.start.format("%Y-%m-%d").to_string()} onchange={set_start}/>
+                        <input class="input is-small" type="date" value={last_day.format("%Y-%m-%d").to_string()} onchange={set_end}/>
+                    </div>
+                </div>
+
+                if let Some(e) = &self.error {
+                    <p class="help is-danger">{e}</p>
+                }
+
+                {
+                    match &self.rows {
+                        None => html!{<progress class="progress is-small is-primary" max="100"></progress>},
+                        Some(rows) => {
+                            let months = reports::months(&self.window);
+                            let in_range: Vec<&SchemaSpendingRow> = rows.iter().filter(|r| months.contains(&r.month)).collect();
+
+                            let categories = reports::by_category(in_range.iter().copied(), rates);
+                            let merchants = reports::by_merchant(in_range.iter().copied(), rates);
+                            let monthly = reports::by_month(in_range.iter().copied(), rates, &self.window);
+                            let last_year_window = reports::year_before(&self.window);
+                            let monthly_last_year = reports::by_month(&self.last_year, rates, &last_year_window);
+                            let total: f64 = categories.iter().map(|c| c.amount).sum();
+
+                            // The range's last month against the one before it
+                            let latest_start = BudgetPeriod::Monthly.period(1, None, last_day, 0).start;
+                            let latest = latest_start.format("%Y-%m").to_string();
+                            let before = (latest_start - Duration::days(1)).format("%Y-%m").to_string();
+                            let month_over_month = reports::compare(
+                                &reports::by_category(rows.iter().filter(|r| r.month == latest), rates),
+                                &reports::by_category(rows.iter().filter(|r| r.month == before), rates),
+                            );
+                            let year_over_year = reports::compare(&categories, &reports::by_category(&self.last_year, rates));
+                            let unconverted = rates.unconverted(rows.iter().chain(&self.last_year).map(|r| r.iso_currency_code.as_deref()));
+
+                            html!{
+                                <>
+                                    <Unconverted currencies={unconverted}/>
+                                    <div class="box">
+                                        <div class="is-flex is-justify-content-space-between">
+                                            <h2 class="is-size-5">{"Spending by month"}</h2>
+                                            <span>{format!("{} in total", format_amount(total, &currency))}</span>
+                                        </div>
+                                        {self.monthly_chart(&monthly, &monthly_last_year, &currency)}
+                                        <p class="is-size-7 has-text-grey">{"Grey bars are the same month a year earlier"}</p>
+                                    </div>
+
+                                    <div class="columns">
+                                        <div class="column">
+                                            <div class="box">
+                                                <h2 class="is-size-5">{"Top categories"}</h2>
+                                                {self.bars(&categories, pfc::label, &currency)}
+                                            </div>
+                                        </div>
+                                        <div class="column">
+                                            <div class="box">
+                                                <h2 class="is-size-5">{"Top merchants"}</h2>
+                                                {self.bars(&merchants, str::to_string, &currency)}
+                                            </div>
+                                        </div>
+                                    </div>
+
+                                    <div class="columns">
+                                        <div class="column">
+                                            {self.deltas(&format!("{latest} against {before}"), &month_over_month, &currency)}
+                                        </div>
+                                        <div class="column">
+                                            {self.deltas("Against a year earlier", &year_over_year, &currency)}
+                                        </div>
+                                    </div>
+                                </>
+                            }
+                        }
+                    }
+                }
+            </div>
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        self.error = None;
+        let date = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok();
+
+        let window = match msg {
+            Msg::GotRows(rows, last_year) => {
+                self.rows = Some(rows);
+                self.last_year = last_year;
+                return true;
+            }
+            Msg::Error(e) => {
+                log::error!("{e}");
+                self.error = Some(e);
+                return true;
+            }
+            Msg::GotScopes(scopes) => {
+                self.scopes = scopes;
+                return true;
+            }
+            Msg::SetScope(id) => {
+                self.scope = self
+                    .scopes
+                    .iter()
+                    .find(|s| s.id.map(|id| id.to_string()).as_ref() == Some(&id))
+                    .cloned();
+                self.window
+            }
+            Msg::SetRange(range) => {
+                self.range = Some(range);
+                range.window(Local::now().date_naive())
+            }
+            Msg::SetStart(start) => {
+                let Some(start) = date(&start).filter(|s| *s < self.window.end) else {
+                    return false;
+                };
+                self.range = None;
+                Period {
+                    start,
+                    end: self.window.end,
+                }
+            }
+            Msg::SetEnd(end) => {
+                let Some(end) = date(&end).filter(|e| *e >= self.window.start) else {
+                    return false;
+                };
+                self.range = None;
+                Period {
+                    start: self.window.start,
+                    end: end + Duration::days(1),
+                }
+            }
+        };
+
+        self.window = window;
+        self.rows = None;
+        self.reload(ctx);
+
+        true
+    }
+}
+
+/// Spending rows over `window`, from the transactions a saved filter matches
+/// when reports are scoped to one
+async fn get_rows(
+    auth_key: &str,
+    taxonomy: &Taxonomy,
+    scope: Option<&Filter>,
+    window: &Period,
+) -> Result<Vec<SchemaSpendingRow>, recurr_core::Error> {
+    let Some(scope) = scope else {
+        return get_summary(auth_key, window).await;
+    };
+
+    let scope = scope.within(
+        &window.start.format("%Y-%m-%d").to_string(),
+        &window.last_day().format("%Y-%m-%d").to_string(),
+    );
+
+    get_scoped_summary(auth_key, taxonomy, &scope).await
+}
+
+async fn get_summary(
+    auth_key: &str,
+    window: &Period,
+) -> Result<Vec<SchemaSpendingRow>, recurr_core::Error> {
+    let client = get_supbase_client();
+    let params = reports::summary_params(window);
+
+    get_all(|| {
+        client
+            .rpc("spending_summary", &params)
+            .auth(auth_key)
+            .order(reports::SUMMARY_ORDER)
+    })
+    .await
+}
@@ -0,0 +1,160 @@
+use recurr_core::{
+    categories::Taxonomy,
+    currency::ExchangeRates,
+    filters::{range_total, Filter, SchemaSavedFilter},
+    get_all, get_supbase_client,
+    reports::{self, SchemaSpendingRow},
+};
+use serde::Deserialize;
+
+/// A saved filter with what currently matches it
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedFilterLine {
+    pub saved: SchemaSavedFilter,
+    pub transactions: usize,
+    /// Net amount in the base currency, positive when money went out
+    pub total: f64,
+}
+
+pub async fn get_saved_filters(
+    auth_key: &str,
+) -> Result<Vec<SchemaSavedFilter>, recurr_core::Error> {
+    let res = get_supbase_client()
+        .from("saved_filters")
+        .auth(auth_key)
+        .select("*")
+        .order("name")
+        .execute()
+        .await?
+        .error_for_status()?;
+
+    Ok(res.json().await?)
+}
+
+/// Saves `filter` under `name`, replacing a saved filter with the same name
+pub async fn save_filter(
+    auth_key: &str,
+    user_id: &str,
+    name: &str,
+    filter: &Filter,
+) -> Result<(), recurr_core::Error> {
+    let saved = SchemaSavedFilter {
+        id: None,
+        user_id: user_id.to_string(),
+        name: name.to_string(),
+        filter: filter.clone(),
+    };
+
+    get_supbase_client()
+        .from("saved_filters")
+        .auth(auth_key)
+        .upsert(serde_json::to_string(&saved)?)
+        .on_conflict("user_id,name")
+        .execute()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
+pub async fn delete_filter(auth_key: &str, id: i64) -> Result<(), recurr_core::Error> {
+    get_supbase_client()
+        .from("saved_filters")
+        .auth(auth_key)
+        .delete()
+        .eq("id", id.to_string())
+        .execute()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
+/// How many transactions match `filter`, counted by PostgREST
+async fn get_count(
+    auth_key: &str,
+    taxonomy: &Taxonomy,
+    filter: &Filter,
+) -> Result<usize, recurr_core::Error> {
+    let res = filter
+        .query(&get_supbase_client(), taxonomy)?
+        .auth(auth_key)
+        .exact_count()
+        .range(0, 0)
+        .execute()
+        .await?
+        .error_for_status()?;
+
+    Ok(res
+        .headers()
+        .get("content-range")
+        .and_then(|v| v.to_str().ok())
+        .and_then(range_total)
+        .unwrap_or_default() as usize)
+}
+
+/// Net amount of the transactions matching `filter` in the base currency,
+/// transfers left out
+async fn get_total(
+    auth_key: &str,
+    taxonomy: &Taxonomy,
+    rates: &ExchangeRates,
+    filter: &Filter,
+) -> Result<f64, recurr_core::Error> {
+    #[derive(Deserialize)]
+    struct Row {
+        iso_currency_code: Option<String>,
+        amount: f64,
+    }
+
+    let rows: Vec<Row> = get_supbase_client()
+        .rpc("filter_totals", filter.params(taxonomy)?)
+        .auth(auth_key)
+        .execute()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(rows
+        .iter()
+        .filter_map(|r| rates.to_base(r.amount, r.iso_currency_code.as_deref()))
+        .sum())
+}
+
+/// `spending_summary` rows over the transactions matching `filter`, for
+/// reports scoped by a saved filter
+pub async fn get_scoped_summary(
+    auth_key: &str,
+    taxonomy: &Taxonomy,
+    filter: &Filter,
+) -> Result<Vec<SchemaSpendingRow>, recurr_core::Error> {
+    let client = get_supbase_client();
+    let params = filter.params(taxonomy)?;
+
+    get_all(|| {
+        client
+            .rpc("filter_spending_summary", &params)
+            .auth(auth_key)
+            .order(reports::SUMMARY_ORDER)
+    })
+    .await
+}
+
+/// Every saved filter with its count and total, for the sidebar
+pub async fn get_lines(
+    auth_key: &str,
+    taxonomy: &Taxonomy,
+    rates: &ExchangeRates,
+) -> Result<Vec<SavedFilterLine>, recurr_core::Error> {
+    let mut lines = Vec::new();
+    for saved in get_saved_filters(auth_key).await? {
+        lines.push(SavedFilterLine {
+            transactions: get_count(auth_key, taxonomy, &saved.filter).await?,
+            total: get_total(auth_key, taxonomy, rates, &saved.filter).await?,
+            saved,
+        });
+    }
+
+    Ok(lines)
+}
@@ -0,0 +1,476 @@
+use recurr_core::{
+    alerts::{self, DEFAULT_THRESHOLDS},
+    currency::{self, get_exchange_rates, ExchangeRates, SchemaExchangeRate},
+    get_supbase_client,
+};
+use serde::Deserialize;
+use web_sys::{HtmlElement, HtmlInputElement, MouseEvent, SubmitEvent};
+use yew::{html, Component, Context, Html, NodeRef, Properties, TargetCast, UseReducerHandle};
+
+use crate::{
+    commands,
+    context::{ContextUpdate, Session},
+};
+
+use super::notifications::{get_thresholds, set_thresholds};
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub context: UseReducerHandle<Session>,
+}
+
+pub enum Msg {
+    SaveBaseCurrency,
+    AddRate,
+    DeleteRate(String),
+    ImportRates,
+    SaveThresholds,
+    SetExcludePending(bool),
+
+    Saved,
+    GotExchangeRates(ExchangeRates),
+    GotThresholds(Vec<u32>),
+    GotExcludePending(bool),
+
+    Error(String),
+}
+
+pub struct SettingsView {
+    error: Option<String>,
+    thresholds: Vec<u32>,
+    exclude_pending: bool,
+
+    base_currency_ref: NodeRef,
+    currency_ref: NodeRef,
+    rate_ref: NodeRef,
+    import_path_ref: NodeRef,
+    thresholds_ref: NodeRef,
+}
+
+impl SettingsView {
+    fn reload(&self, ctx: &Context<Self>) {
+        let session = ctx
+            .props()
+            .context
+            .supabase_session
+            .clone()
+            .expect("Needs session");
+
+        ctx.link().send_future(async move {
+            match get_exchange_rates(&session.auth_key, &session.user.id).await {
+                Ok(rates) => Msg::GotExchangeRates(rates),
+                Err(e) => Msg::Error(e.to_string()),
+            }
+        });
+    }
+
+    fn reload_thresholds(&self, ctx: &Context<Self>) {
+        let session = ctx
+            .props()
+            .context
+            .supabase_session
+            .clone()
+            .expect("Needs session");
+
+        ctx.link().send_future(async move {
+            match get_thresholds(&session.auth_key, &session.user.id).await {
+                Ok(thresholds) => Msg::GotThresholds(thresholds),
+                Err(e) => Msg::Error(e.to_string()),
+            }
+        });
+    }
+
+    fn reload_exclude_pending(&self, ctx: &Context<Self>) {
+        let session = ctx
+            .props()
+            .context
+            .supabase_session
+            .clone()
+            .expect("Needs session");
+
+        ctx.link().send_future(async move {
+            match get_exclude_pending(&session.auth_key, &session.user.id).await {
+                Ok(exclude) => Msg::GotExcludePending(exclude),
+                Err(e) => Msg::Error(e.to_string()),
+            }
+        });
+    }
+}
+
+impl Component for SettingsView {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let settings = Self {
+            error: None,
+            thresholds: DEFAULT_THRESHOLDS.to_vec(),
+            exclude_pending: false,
+            base_currency_ref: NodeRef::default(),
+            currency_ref: NodeRef::default(),
+            rate_ref: NodeRef::default(),
+            import_path_ref: NodeRef::default(),
+            thresholds_ref: NodeRef::default(),
+        };
+        settings.reload_thresholds(ctx);
+        settings.reload_exclude_pending(ctx);
+
+        settings
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let rates = &ctx.props().context.exchange_rates;
+        let base = rates.base().to_string();
+
+        let save_base = ctx.link().callback(|e: SubmitEvent| {
+            e.prevent_default();
+            Msg::SaveBaseCurrency
+        });
+
+        let add_rate = ctx.link().callback(|e: SubmitEvent| {
+            e.prevent_default();
+            Msg::AddRate
+        });
+
+        let import_rates = ctx.link().callback(|e: SubmitEvent| {
+            e.prevent_default();
+            Msg::ImportRates
+        });
+
+        let save_thresholds = ctx.link().callback(|e: SubmitEvent| {
+            e.prevent_default();
+            Msg::SaveThresholds
+        });
+        let thresholds = self
+            .thresholds
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        let exclude_pending = self.exclude_pending;
+        let toggle_pending = ctx
+            .link()
+            .callback(move |_| Msg::SetExcludePending(!exclude_pending));
+
+        let delete_rate = ctx.link().callback(|e: MouseEvent| {
+            let target = e.target_dyn_into::<HtmlElement>().unwrap();
+            let currency = target.get_attribute("data-currency").unwrap_or_default();
+            Msg::DeleteRate(currency)
+        });
+
+        html! {
+            <div class="column">
+                <h1 class="is-size-3">{"Settings"}</h1>
+
+                if let Some(e) = &self.error {
+                    <p class="help is-danger">{e}</p>
+                }
+
+                <div class="box">
+                    <h2 class="is-size-5">{"Base currency"}</h2>
+                    <p class="is-size-7 mb-2">{"Summaries and budgets are converted into this currency"}</p>
+                    <form class="field has-addons" onsubmit={save_base}>
+                        <div class="control">
+                            <input ref={self.base_currency_ref.clone()} class="input" type="text" maxlength="3" value={base.clone()}/>
+                        </div>
+                        <div class="control">
+                            <button class="button is-success" type="submit">{"Save"}</button>
+                        </div>
+                    </form>
+                </div>
+
+                <div class="box">
+                    <h2 class="is-size-5">{"Exchange rates"}</h2>
+                    <p class="is-size-7 mb-2">{format!("Units of each currency per 1 {base}")}</p>
+                    <table class="table">
+                        <thead>
+                            <th>{"Currency"}</th>
+                            <th>{"Rate"}</th>
+                            <th></th>
+                        </thead>
+                        <tbody>
+                        {
+                            rates.to_schema("").into_iter().map(|r| {
+                                html!{
+                                    <tr>
+                                        <td>{r.currency.clone()}</td>
+                                        <td>{format!("{:.4}", r.rate)}</td>
+                                        <td><a data-currency={r.currency} onclick={delete_rate.clone()}>{"Remove"}</a></td>
+                                    </tr>
+                                }
+                            }).collect::<Html>()
+                        }
+                        </tbody>
+                    </table>
+
+                    <form class="field has-addons" onsubmit={add_rate}>
+                        <div class="control">
+                            <input ref={self.currency_ref.clone()} class="input" type="text" maxlength="3" placeholder="CAD"/>
+                        </div>
+                        <div class="control">
+                            <input ref={self.rate_ref.clone()} class="input" type="number" step="any" placeholder="1.35"/>
+                        </div>
+                        <div class="control">
+                            <button class="button is-success" type="submit">{"Add rate"}</button>
+                        </div>
+                    </form>
+
+                    <form class="field has-addons" onsubmit={import_rates}>
+                        <div class="control is-expanded">
+                            <input ref={self.import_path_ref.clone()} class="input" type="text" placeholder="/path/to/eurofxref-daily.xml"/>
+                        </div>
+                        <div class="control">
+                            <button class="button" type="submit">{"Import ECB rates"}</button>
+                        </div>
+                    </form>
+                </div>
+
+                <div class="box">
+                    <h2 class="is-size-5">{"Budget alerts"}</h2>
+                    <p class="is-size-7 mb-2">{"Percentages of a budget that send a notification after syncing, once each period. Leave empty to turn alerts off"}</p>
+                    <form class="field has-addons" onsubmit={save_thresholds}>
+                        <div class="control">
+                            <input ref={self.thresholds_ref.clone()} class="input" type="text" placeholder="80, 100" value={thresholds}/>
+                        </div>
+                        <div class="control">
+                            <button class="button is-success" type="submit">{"Save"}</button>
+                        </div>
+                    </form>
+                </div>
+
+                <div class="box">
+                    <h2 class="is-size-5">{"Pending transactions"}</h2>
+                    <label class="checkbox">
+                        <input type="checkbox" checked={exclude_pending} onchange={toggle_pending}/>
+                        {" Leave pending transactions out of budgets until they post"}
+                    </label>
+                </div>
+            </div>
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        self.error = None;
+
+        let session = ctx
+            .props()
+            .context
+            .supabase_session
+            .clone()
+            .expect("Needs session");
+        let auth_key = session.auth_key;
+        let user_id = session.user.id;
+
+        match msg {
+            Msg::SaveBaseCurrency => {
+                let input = self
+                    .base_currency_ref
+                    .cast::<HtmlInputElement>()
+                    .expect("Base currency ref not an input element")
+                    .value();
+                let base_currency = match currency::parse_currency(&input) {
+                    Ok(currency) => currency,
+                    Err(e) => {
+                        self.error = Some(e.to_string());
+                        return true;
+                    }
+                };
+
+                // Existing rates are requoted against the new base, which needs a rate itself
+                let current = &ctx.props().context.exchange_rates;
+                let rates = match current.rebase(&base_currency) {
+                    Some(rates) => rates.to_schema(&user_id),
+                    None if current.to_schema("").is_empty() => Vec::new(),
+                    None => {
+                        self.error = Some(format!(
+                            "Add a rate for {base_currency} first so existing rates can be requoted"
+                        ));
+                        return true;
+                    }
+                };
+
+                ctx.link().send_future(async move {
+                    let res = save_base_currency(&auth_key, &base_currency, rates).await;
+                    match res {
+                        Ok(_) => Msg::Saved,
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::AddRate => {
+                let currency = self
+                    .currency_ref
+                    .cast::<HtmlInputElement>()
+                    .expect("Currency ref not an input element")
+                    .value()
+                    .to_uppercase();
+                let rate = self
+                    .rate_ref
+                    .cast::<HtmlInputElement>()
+                    .expect("Rate ref not an input element")
+                    .value()
+                    .parse::<f64>();
+
+                let rate = match rate {
+                    Ok(rate) if rate > 0.0 && !currency.is_empty() => rate,
+                    _ => {
+                        self.error = Some("Enter a currency code and a positive rate".to_string());
+                        return true;
+                    }
+                };
+
+                let body = serde_json::to_string(&SchemaExchangeRate {
+                    user_id,
+                    currency,
+                    rate,
+                })
+                .expect("Failed to serialize");
+
+                ctx.link().send_future(async move {
+                    let res = get_supbase_client()
+                        .from("exchange_rates")
+                        .auth(&auth_key)
+                        .upsert(body)
+                        .execute()
+                        .await
+                        .and_then(|r| r.error_for_status());
+
+                    match res {
+                        Ok(_) => Msg::Saved,
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::DeleteRate(currency) => {
+                ctx.link().send_future(async move {
+                    let res = get_supbase_client()
+                        .from("exchange_rates")
+                        .auth(&auth_key)
+                        .eq("user_id", &user_id)
+                        .eq("currency", currency)
+                        .delete()
+                        .execute()
+                        .await
+                        .and_then(|r| r.error_for_status());
+
+                    match res {
+                        Ok(_) => Msg::Saved,
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::ImportRates => {
+                let path = self
+                    .import_path_ref
+                    .cast::<HtmlInputElement>()
+                    .expect("Path ref not an input element")
+                    .value();
+                let base_currency = ctx.props().context.exchange_rates.base().to_string();
+
+                ctx.link().send_future(async move {
+                    let res =
+                        commands::import_exchange_rates(&auth_key, &user_id, &base_currency, &path)
+                            .await;
+                    match res {
+                        Ok(_) => Msg::Saved,
+                        Err(e) => Msg::Error(e),
+                    }
+                });
+            }
+            Msg::SaveThresholds => {
+                let thresholds = alerts::parse_thresholds(
+                    &self
+                        .thresholds_ref
+                        .cast::<HtmlInputElement>()
+                        .expect("Thresholds ref not an input element")
+                        .value(),
+                );
+
+                ctx.link().send_future(async move {
+                    match set_thresholds(&auth_key, &user_id, &thresholds).await {
+                        Ok(_) => Msg::GotThresholds(thresholds),
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::SetExcludePending(exclude) => {
+                ctx.link().send_future(async move {
+                    match set_exclude_pending(&auth_key, &user_id, exclude).await {
+                        Ok(_) => Msg::GotExcludePending(exclude),
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::Saved => self.reload(ctx),
+            Msg::GotThresholds(thresholds) => self.thresholds = thresholds,
+            Msg::GotExcludePending(exclude) => self.exclude_pending = exclude,
+            Msg::GotExchangeRates(rates) => ctx
+                .props()
+                .context
+                .dispatch(ContextUpdate::ExchangeRates(rates)),
+            Msg::Error(e) => {
+                log::error!("{e}");
+                self.error = Some(e);
+            }
+        }
+
+        true
+    }
+}
+
+async fn get_exclude_pending(auth_key: &str, user_id: &str) -> Result<bool, recurr_core::Error> {
+    #[derive(Deserialize)]
+    struct Row {
+        exclude_pending: bool,
+    }
+
+    let rows: Vec<Row> = get_supbase_client()
+        .from("user_settings")
+        .auth(auth_key)
+        .select("exclude_pending")
+        .eq("user_id", user_id)
+        .execute()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(rows.first().map_or(false, |r| r.exclude_pending))
+}
+
+async fn set_exclude_pending(
+    auth_key: &str,
+    user_id: &str,
+    exclude_pending: bool,
+) -> Result<(), recurr_core::Error> {
+    get_supbase_client()
+        .from("user_settings")
+        .auth(auth_key)
+        .upsert(
+            serde_json::json!({ "user_id": user_id, "exclude_pending": exclude_pending })
+                .to_string(),
+        )
+        .execute()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
+async fn save_base_currency(
+    auth_key: &str,
+    base_currency: &str,
+    rates: Vec<SchemaExchangeRate>,
+) -> Result<(), recurr_core::Error> {
+    let params = serde_json::json!({ "new_base": base_currency, "new_rates": rates });
+
+    get_supbase_client()
+        .rpc("set_base_currency", params.to_string())
+        .auth(auth_key)
+        .execute()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use recurr_core::Account;
+use recurr_core::{balances::AccountClass, currency::format_amount, Account};
 use yew::{
     function_component, html, Callback, Component, Context, ContextHandle, Html, Properties,
     UseReducerHandle,
@@ -7,15 +7,36 @@ use yew_hooks::use_bool_toggle;
 
 use crate::{
     commands,
+    components::unconverted::Unconverted,
     context::{Session, SessionContext},
 };
 
+use super::{accounts::manual::get_manual_accounts, goals::Goals, net_worth::NetWorthChart};
+
 #[derive(Default)]
 pub struct Balances {
     cash: (Vec<Account>, f64),
     credit: (Vec<Account>, f64),
     investments: (Vec<Account>, f64),
     loans: (Vec<Account>, f64),
+    other: (Vec<Account>, f64),
+    /// Currencies of accounts left out of the totals
+    unconverted: Vec<String>,
+}
+
+impl Balances {
+    fn accounts(&self) -> Vec<Account> {
+        [
+            &self.cash,
+            &self.credit,
+            &self.investments,
+            &self.loans,
+            &self.other,
+        ]
+        .into_iter()
+        .flat_map(|(accounts, _)| accounts.clone())
+        .collect()
+    }
 }
 
 #[derive(Properties, PartialEq)]
@@ -58,38 +79,44 @@ impl SummaryView {
             .unwrap()
             .user
             .id;
+        let rates = ctx.props().context.exchange_rates.clone();
 
         ctx.link().send_future(async move {
             let balances = commands::get_balances(&auth_key, &user_id).await;
-            match balances {
-                Ok(b) => {
+            let manual = get_manual_accounts(&auth_key).await;
+            match (balances, manual) {
+                (Ok(mut b), Ok(manual)) => {
+                    b.extend(manual.iter().map(|a| a.to_account()));
+
                     let mut balances = Balances::default();
 
                     for account in &b {
-                        if account.account_type == "investment" {
-                            balances.investments.0.push(account.clone());
-                            balances.investments.1 += account.balances.current.unwrap();
-                        }
-
-                        if account.account_type == "credit" {
-                            balances.credit.0.push(account.clone());
-                            balances.credit.1 += account.balances.current.unwrap();
-                        }
-
-                        if account.account_type == "loan" {
-                            balances.loans.0.push(account.clone());
-                            balances.loans.1 += account.balances.current.unwrap();
-                        }
-
-                        if account.account_type == "depository" {
-                            balances.cash.0.push(account.clone());
-                            balances.cash.1 += account.balances.current.unwrap();
-                        }
+                        let current = rates.to_base(
+                            account.balances.current.unwrap_or(0.0),
+                            account.balances.iso_currency_code.as_deref(),
+                        );
+
+                        let class = match AccountClass::of(&account.account_type) {
+                            Some(class) => class,
+                            None => continue,
+                        };
+                        let group = match class {
+                            AccountClass::Cash => &mut balances.cash,
+                            AccountClass::Credit => &mut balances.credit,
+                            AccountClass::Investments => &mut balances.investments,
+                            AccountClass::Loans => &mut balances.loans,
+                            AccountClass::Other => &mut balances.other,
+                        };
+                        group.0.push(account.clone());
+                        group.1 += current.unwrap_or_default();
                     }
+                    balances.unconverted = rates
+                        .unconverted(b.iter().map(|a| a.balances.iso_currency_code.as_deref()));
 
                     Msg::GotBalances(balances)
                 }
-                Err(e) => Msg::Error(e),
+                (Err(e), _) => Msg::Error(e),
+                (_, Err(e)) => Msg::Error(e.to_string()),
             }
         });
     }
@@ -114,17 +141,27 @@ impl Component for SummaryView {
         }
     }
 
-    fn view(&self, _ctx: &yew::Context<Self>) -> Html {
+    fn view(&self, ctx: &yew::Context<Self>) -> Html {
+        let currency = ctx.props().context.exchange_rates.base().to_string();
+
         html! {
                 {
                 if let Some(balances) = &self.balances {
                     html!{
                         <div class="columns">
                             <div class="column is-narrow">
-                                <Summary name={"Cash"} accounts={balances.cash.0.clone()} total={balances.cash.1}/>
-                                <Summary name={"Credit Cards"} accounts={balances.credit.0.clone()} total={balances.credit.1}/>
-                                <Summary name={"Investments"} accounts={balances.investments.0.clone()} total={balances.investments.1}/>
-                                <Summary name={"Loans"} accounts={balances.loans.0.clone()} total={balances.loans.1}/>
+                                <Unconverted currencies={balances.unconverted.clone()}/>
+                                <Summary name={"Cash"} accounts={balances.cash.0.clone()} total={balances.cash.1} currency={currency.clone()}/>
+                                <Summary name={"Credit Cards"} accounts={balances.credit.0.clone()} total={balances.credit.1} currency={currency.clone()}/>
+                                <Summary name={"Investments"} accounts={balances.investments.0.clone()} total={balances.investments.1} currency={currency.clone()}/>
+                                <Summary name={"Loans"} accounts={balances.loans.0.clone()} total={balances.loans.1} currency={currency.clone()}/>
+                                if !balances.other.0.is_empty() {
+                                    <Summary name={"Property & Other"} accounts={balances.other.0.clone()} total={balances.other.1} currency={currency.clone()}/>
+                                }
+                            </div>
+                            <div class="column">
+                                <NetWorthChart context={ctx.props().context.clone()}/>
+                                <Goals accounts={balances.accounts()} context={ctx.props().context.clone()}/>
                             </div>
                         </div>
                     }
@@ -156,6 +193,7 @@ struct SummaryDetails {
     name: String,
     accounts: Vec<Account>,
     total: f64,
+    currency: String,
 }
 
 #[function_component(Summary)]
@@ -172,7 +210,7 @@ fn summary(props: &SummaryDetails) -> Html {
             <header class="card-header">
                 <div class="card-header-title is-flex is-justify-content-space-between">
                     <p class="mr-4">{props.name.clone()}</p>
-                    <p>{format!("${:.2}", props.total)}</p>
+                    <p>{format_amount(props.total, &props.currency)}</p>
                 </div>
                 <button {onclick} class="card-header-icon" aria-label="more options">
                     <span class="icon">
@@ -184,10 +222,11 @@ fn summary(props: &SummaryDetails) -> Html {
                 <div class="card-content is-flex is-flex-direction-column">
                     {
                         props.accounts.clone().into_iter().map(|a| {
+                            let currency = a.balances.iso_currency_code.unwrap_or(props.currency.clone());
                             html!{
                                 <div class="is-flex is-flex is-justify-content-space-between">
                                     <h1>{a.name}</h1>
-                                    <h1>{format!("${:.2}", a.balances.current.unwrap_or(0.0))}</h1>
+                                    <h1>{format_amount(a.balances.current.unwrap_or(0.0), &currency)}</h1>
                                 </div>
                             }
                         }).collect::<Html>()
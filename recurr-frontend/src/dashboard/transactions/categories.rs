@@ -1,17 +1,22 @@
-use web_sys::MouseEvent;
-use yew::{function_component, html, Callback, Component, Html, Properties};
+use recurr_core::{
+    categories::{self, CategoryNode, SchemaUserCategory, Taxonomy},
+    get_supbase_client, to_pg_array,
+};
+use web_sys::{HtmlInputElement, HtmlSelectElement, MouseEvent};
+use yew::{function_component, html, Callback, Component, Html, NodeRef, Properties};
 
-use crate::commands;
+use crate::{
+    commands,
+    context::{ContextUpdate, SessionContext},
+};
 
-#[derive(Debug, PartialEq, Eq, Clone)]
-struct CategoryComp {
-    category: String,
-    children: Vec<CategoryComp>,
-}
-
-#[derive(Default)]
 pub struct Categories {
-    categories: Vec<CategoryComp>,
+    selected: Option<Vec<String>>,
+    error: Option<String>,
+
+    name_ref: NodeRef,
+    merge_ref: NodeRef,
+    new_ref: NodeRef,
 }
 
 #[derive(Properties, PartialEq)]
@@ -19,68 +24,305 @@ pub struct Props {
     pub show: bool,
 
     pub on_toggle: Callback<MouseEvent>,
+    pub context: SessionContext,
 }
 
 pub enum Messages {
-    GetCategories,
-    GotCategories(Vec<CategoryComp>),
+    Select(Vec<String>),
+
+    Rename,
+    ToggleHidden,
+    Merge,
+    Unmerge(Vec<String>),
+    Add(bool),
+    Remove,
+
+    Save(SchemaUserCategory),
+    Saved,
+    GotTaxonomy(Taxonomy),
+
+    Error(String),
+}
+
+impl Categories {
+    fn user_id(ctx: &yew::Context<Self>) -> String {
+        ctx.props()
+            .context
+            .supabase_session
+            .clone()
+            .expect("Needs session")
+            .user
+            .id
+    }
+
+    fn auth_key(ctx: &yew::Context<Self>) -> String {
+        ctx.props()
+            .context
+            .supabase_session
+            .clone()
+            .expect("Needs session")
+            .auth_key
+    }
+
+    fn input_value(node: &NodeRef) -> String {
+        node.cast::<HtmlInputElement>()
+            .expect("Not an input element")
+            .value()
+    }
 }
 
 impl Component for Categories {
     type Message = Messages;
     type Properties = Props;
 
-    fn create(ctx: &yew::Context<Self>) -> Self {
-        ctx.link().send_message(Messages::GetCategories);
-
-        Self::default()
+    fn create(_ctx: &yew::Context<Self>) -> Self {
+        Self {
+            selected: None,
+            error: None,
+            name_ref: NodeRef::default(),
+            merge_ref: NodeRef::default(),
+            new_ref: NodeRef::default(),
+        }
     }
 
     fn view(&self, ctx: &yew::Context<Self>) -> yew::Html {
+        if !ctx.props().show {
+            return html! {};
+        }
+
         let close_modal = ctx.props().on_toggle.clone();
+        let taxonomy = &ctx.props().context.taxonomy;
+        let on_select = ctx.link().callback(Messages::Select);
+
+        let rename = ctx.link().callback(|_| Messages::Rename);
+        let toggle_hidden = ctx.link().callback(|_| Messages::ToggleHidden);
+        let merge = ctx.link().callback(|_| Messages::Merge);
+        let add_child = ctx.link().callback(|_| Messages::Add(true));
+        let add_root = ctx.link().callback(|_| Messages::Add(false));
+        let remove = ctx.link().callback(|_| Messages::Remove);
 
-        if ctx.props().show {
-            html! {
-                <>
-                <div class="modal is-active">
-                    <div class="modal-background" onclick={close_modal.clone()}></div>
+        let selected = self.selected.clone();
+        let selected_row = selected
+            .as_ref()
+            .map(|path| taxonomy.row(&Self::user_id(ctx), path));
+        let hide_label = match &selected_row {
+            Some(row) if row.hidden => "Show",
+            _ => "Hide",
+        };
 
-                    <div class="modal-card">
-                        <header class="modal-card-head">
-                            <h1 class="modal-card-title">{"Categories"}</h1>
-                        </header>
+        html! {
+            <>
+            <div class="modal is-active">
+                <div class="modal-background" onclick={close_modal.clone()}></div>
 
-                        <section class="modal-card-body">
-                            {self.categories.iter().map(|c| html!{<ul class="ml-4 list"> <Category category={c.clone()}/> </ul>}).collect::<Html>()}
-                        </section>
+                <div class="modal-card">
+                    <header class="modal-card-head">
+                        <h1 class="modal-card-title">{"Categories"}</h1>
+                        <button class="delete" aria-label="close" onclick={close_modal}></button>
+                    </header>
 
-                        <footer class="modal-card-foot"> </footer>
-                    </div>
+                    <section class="modal-card-body">
+                        if let Some(e) = &self.error {
+                            <p class="help is-danger">{e}</p>
+                        }
+
+                        {
+                            taxonomy.roots().iter().map(|c| html!{
+                                <ul class="ml-4 list">
+                                    <Category category={c.clone()} selected={selected.clone()} on_select={on_select.clone()}/>
+                                </ul>
+                            }).collect::<Html>()
+                        }
+
+                        if !taxonomy.merges().is_empty() {
+                            <h2 class="is-size-6 mt-3">{"Merged categories"}</h2>
+                            {
+                                taxonomy.merges().into_iter().map(|(from, into)| {
+                                    let unmerge = {
+                                        let from = from.clone();
+                                        ctx.link().callback(move |_| Messages::Unmerge(from.clone()))
+                                    };
+                                    html!{
+                                        <p class="is-size-7">
+                                            {format!("{} → {} ", from.join(" > "), taxonomy.display_path(&into).join(" > "))}
+                                            <a onclick={unmerge}>{"Unmerge"}</a>
+                                        </p>
+                                    }
+                                }).collect::<Html>()
+                            }
+                        }
+                    </section>
+
+                    <footer class="modal-card-foot is-flex-direction-column is-align-items-stretch">
+                        if let Some(row) = selected_row {
+                            <div class="field has-addons">
+                                <div class="control is-expanded">
+                                    <input ref={self.name_ref.clone()} class="input is-small" type="text" value={taxonomy.display_name(&row.path)}/>
+                                </div>
+                                <div class="control">
+                                    <button class="button is-small" onclick={rename}>{"Rename"}</button>
+                                </div>
+                                <div class="control">
+                                    <button class="button is-small" onclick={toggle_hidden}>{hide_label}</button>
+                                </div>
+                                if row.custom {
+                                    <div class="control">
+                                        <button class="button is-small is-danger" onclick={remove}>{"Remove"}</button>
+                                    </div>
+                                }
+                            </div>
+                            <div class="field has-addons">
+                                <div class="control is-expanded">
+                                    <div class="select is-small is-fullwidth">
+                                        <select ref={self.merge_ref.clone()}>
+                                        {
+                                            taxonomy.flatten().into_iter().filter(|n| !n.path.starts_with(&row.path)).map(|n| {
+                                                html!{<option value={serde_json::to_string(&n.path).unwrap_or_default()}>{taxonomy.display_path(&n.path).join(" > ")}</option>}
+                                            }).collect::<Html>()
+                                        }
+                                        </select>
+                                    </div>
+                                </div>
+                                <div class="control">
+                                    <button class="button is-small" onclick={merge}>{"Merge into"}</button>
+                                </div>
+                            </div>
+                        }
+                        <div class="field has-addons">
+                            <div class="control is-expanded">
+                                <input ref={self.new_ref.clone()} class="input is-small" type="text" placeholder="New category"/>
+                            </div>
+                            if self.selected.is_some() {
+                                <div class="control">
+                                    <button class="button is-small" onclick={add_child}>{"Add under selected"}</button>
+                                </div>
+                            }
+                            <div class="control">
+                                <button class="button is-small is-success" onclick={add_root}>{"Add"}</button>
+                            </div>
+                        </div>
+                    </footer>
                 </div>
-                </>
-            }
-        } else {
-            html! {}
+            </div>
+            </>
         }
     }
 
     fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+        self.error = None;
+
+        let taxonomy = &ctx.props().context.taxonomy;
+        let user_id = Self::user_id(ctx);
+        let selected_row = self
+            .selected
+            .as_ref()
+            .map(|path| taxonomy.row(&user_id, path));
+
         match msg {
-            Messages::GetCategories => {
-                ctx.link().send_future(async {
-                    let categories = commands::get_categories().await.unwrap();
+            Messages::Select(path) => self.selected = Some(path),
+            Messages::Rename => {
+                if let Some(mut row) = selected_row {
+                    let name = Self::input_value(&self.name_ref);
+                    row.name = if name.is_empty() { None } else { Some(name) };
+                    ctx.link().send_message(Messages::Save(row));
+                }
+            }
+            Messages::ToggleHidden => {
+                if let Some(mut row) = selected_row {
+                    row.hidden = !row.hidden;
+                    ctx.link().send_message(Messages::Save(row));
+                }
+            }
+            Messages::Merge => {
+                if let Some(mut row) = selected_row {
+                    let target = self
+                        .merge_ref
+                        .cast::<HtmlSelectElement>()
+                        .expect("Merge ref not a select element")
+                        .value();
+                    row.merged_into = serde_json::from_str(&target).ok();
+                    self.selected = None;
+                    ctx.link().send_message(Messages::Save(row));
+                }
+            }
+            Messages::Unmerge(path) => {
+                let mut row = taxonomy.row(&user_id, &path);
+                row.merged_into = None;
+                ctx.link().send_message(Messages::Save(row));
+            }
+            Messages::Add(under_selected) => {
+                let name = Self::input_value(&self.new_ref);
+                if name.is_empty() {
+                    return false;
+                }
 
-                    let mut c: Vec<CategoryComp> = Vec::new();
-                    for cat in categories {
-                        recursively_update(&mut c, &cat.hierarchy);
-                    }
+                let mut path = match (&self.selected, under_selected) {
+                    (Some(parent), true) => parent.clone(),
+                    _ => Vec::new(),
+                };
+                path.push(name);
+
+                let mut row = taxonomy.row(&user_id, &path);
+                row.custom = true;
+                ctx.link().send_message(Messages::Save(row));
+            }
+            Messages::Remove => {
+                if let Some(row) = selected_row {
+                    let auth_key = Self::auth_key(ctx);
+                    self.selected = None;
+
+                    ctx.link().send_future(async move {
+                        let res = get_supbase_client()
+                            .from("user_categories")
+                            .auth(&auth_key)
+                            .eq("path", to_pg_array(&row.path))
+                            .delete()
+                            .execute()
+                            .await
+                            .and_then(|r| r.error_for_status());
+
+                        match res {
+                            Ok(_) => Messages::Saved,
+                            Err(e) => Messages::Error(e.to_string()),
+                        }
+                    });
+                }
+            }
+            Messages::Save(row) => {
+                let auth_key = Self::auth_key(ctx);
+                let body = serde_json::to_string(&row).expect("Failed to serialize");
+
+                ctx.link().send_future(async move {
+                    let res = get_supbase_client()
+                        .from("user_categories")
+                        .auth(&auth_key)
+                        .upsert(body)
+                        .execute()
+                        .await
+                        .and_then(|r| r.error_for_status());
 
-                    Messages::GotCategories(c)
+                    match res {
+                        Ok(_) => Messages::Saved,
+                        Err(e) => Messages::Error(e.to_string()),
+                    }
                 });
             }
-            Messages::GotCategories(c) => {
-                log::info!("{:?}", c);
-                self.categories = c;
+            Messages::Saved => {
+                let auth_key = Self::auth_key(ctx);
+                ctx.link().send_future(async move {
+                    match get_taxonomy(&auth_key).await {
+                        Ok(taxonomy) => Messages::GotTaxonomy(taxonomy),
+                        Err(e) => Messages::Error(e),
+                    }
+                });
+            }
+            Messages::GotTaxonomy(taxonomy) => ctx
+                .props()
+                .context
+                .dispatch(ContextUpdate::Taxonomy(taxonomy)),
+            Messages::Error(e) => {
+                log::error!("{e}");
+                self.error = Some(e);
             }
         }
 
@@ -90,44 +332,48 @@ impl Component for Categories {
 
 #[derive(Properties, PartialEq)]
 struct CatProps {
-    category: CategoryComp,
+    category: CategoryNode,
+    selected: Option<Vec<String>>,
+    on_select: Callback<Vec<String>>,
 }
 
 #[function_component]
 fn Category(props: &CatProps) -> Html {
-    let name = props.category.category.clone();
+    let name = props.category.name.clone();
+
+    let onclick = {
+        let path = props.category.path.clone();
+        let cb = props.on_select.clone();
+        Callback::from(move |e: MouseEvent| {
+            e.stop_propagation();
+            cb.emit(path.clone())
+        })
+    };
+
+    let mut class = vec!["has-cursor-pointer"];
+    if props.selected.as_ref() == Some(&props.category.path) {
+        class.push("has-text-weight-bold");
+    }
+    if props.category.hidden {
+        class.push("has-text-grey-light");
+    }
 
     html! {
             <li class="list-item">
-                {name}
+                <span class={class.join(" ")} {onclick}>{name}</span>
                     if !props.category.children.is_empty() {
                         <ul class="list ml-4">
-                            {props.category.children.iter().map(|c| html!{<Category category={c.clone()}/>}).collect::<Html>()}
+                            {props.category.children.iter().map(|c| html!{<Category category={c.clone()} selected={props.selected.clone()} on_select={props.on_select.clone()}/>}).collect::<Html>()}
                         </ul>
                     }
             </li>
     }
 }
 
-fn recursively_update(categories: &mut Vec<CategoryComp>, category: &Vec<String>) {
-    let first = category.first();
-    if first.is_none() {
-        return;
-    }
-
-    let first = first.unwrap();
-
-    for c in &mut *categories {
-        if &c.category == first {
-            let mut cat = category.clone();
-            cat.remove(0);
-            recursively_update(&mut c.children, &cat);
-            return;
-        }
-    }
+pub async fn get_taxonomy(auth_key: &str) -> Result<Taxonomy, String> {
+    let plaid = commands::get_categories().await?;
 
-    categories.push(CategoryComp {
-        category: first.to_owned(),
-        children: Vec::new(),
-    });
+    categories::get_taxonomy(auth_key, &plaid)
+        .await
+        .map_err(|e| e.to_string())
 }
@@ -0,0 +1,473 @@
+use chrono::{Duration, NaiveDate};
+use futures::future;
+use recurr_core::{
+    annotations::{parse_tags, SchemaTransactionAnnotation, SchemaTransactionAttachment},
+    currency::format_amount,
+    get_supbase_client,
+    transfers::{SchemaTransferLink, MATCH_DAYS},
+    Transaction,
+};
+use web_sys::{HtmlInputElement, HtmlTextAreaElement, MouseEvent};
+use yew::{html, Callback, Component, Context, Html, NodeRef, Properties};
+
+use crate::{commands, context::SessionContext};
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub transaction: Transaction,
+
+    /// Emits whether notes or tags were changed
+    pub on_close: Callback<bool>,
+    pub context: SessionContext,
+}
+
+pub enum Msg {
+    GetAttachments,
+    GotAttachments(Vec<(SchemaTransactionAttachment, Option<String>)>),
+
+    Save,
+    Saved,
+    Upload,
+    Delete(SchemaTransactionAttachment),
+
+    GetTransfer,
+    GotTransfer(Transfer),
+    /// Links the transaction with the given other side
+    Link(String),
+    Unlink(SchemaTransferLink),
+    TransferSaved,
+
+    Error(String),
+}
+
+/// The transfer a transaction is part of, or what it could be linked with
+#[derive(Default)]
+pub struct Transfer {
+    link: Option<(SchemaTransferLink, Transaction)>,
+    candidates: Vec<Transaction>,
+}
+
+pub struct Detail {
+    attachments: Vec<(SchemaTransactionAttachment, Option<String>)>,
+    transfer: Transfer,
+    changed: bool,
+    status: Option<String>,
+    error: Option<String>,
+
+    notes_ref: NodeRef,
+    tags_ref: NodeRef,
+    path_ref: NodeRef,
+}
+
+impl Detail {
+    fn session(ctx: &Context<Self>) -> (String, String) {
+        let session = ctx
+            .props()
+            .context
+            .supabase_session
+            .clone()
+            .expect("Needs session");
+        (session.auth_key, session.user.id)
+    }
+}
+
+impl Component for Detail {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        ctx.link().send_message(Msg::GetAttachments);
+        ctx.link().send_message(Msg::GetTransfer);
+
+        Self {
+            attachments: Vec::new(),
+            transfer: Transfer::default(),
+            changed: false,
+            status: None,
+            error: None,
+
+            notes_ref: NodeRef::default(),
+            tags_ref: NodeRef::default(),
+            path_ref: NodeRef::default(),
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let transaction = &ctx.props().transaction;
+        let taxonomy = &ctx.props().context.taxonomy;
+        let currency = transaction
+            .iso_currency_code
+            .clone()
+            .or(transaction.unofficial_currency_code.clone())
+            .unwrap_or(ctx.props().context.exchange_rates.base().to_string());
+
+        let close_modal = {
+            let on_close = ctx.props().on_close.clone();
+            let changed = self.changed;
+            Callback::from(move |_: MouseEvent| on_close.emit(changed))
+        };
+        let save = ctx.link().callback(|_| Msg::Save);
+        let upload = ctx.link().callback(|_| Msg::Upload);
+        let unlink = {
+            let link = self.transfer.link.as_ref().map(|(link, _)| link.clone());
+            ctx.link()
+                .batch_callback(move |_: MouseEvent| link.clone().map(Msg::Unlink))
+        };
+
+        html! {
+            <div class="modal is-active">
+                <div class="modal-background" onclick={close_modal.clone()}></div>
+
+                <div class="modal-card">
+                    <header class="modal-card-head">
+                        <h1 class="modal-card-title">{transaction.merchant_name.clone().unwrap_or(transaction.name.clone())}</h1>
+                        <button class="delete" aria-label="close" onclick={close_modal}></button>
+                    </header>
+
+                    <section class="modal-card-body">
+                        if let Some(e) = &self.error {
+                            <p class="help is-danger">{e}</p>
+                        }
+                        if let Some(status) = &self.status {
+                            <p class="help is-success">{status}</p>
+                        }
+
+                        <p>{format!("{} · {}", transaction.date, format_amount(transaction.amount, &currency))}</p>
+                        <p class="is-size-7 mb-3">{taxonomy.display_path(transaction.category.as_deref().unwrap_or_default()).join(" > ")}</p>
+
+                        <div class="field">
+                            <label class="label">{"Notes"}</label>
+                            <textarea ref={self.notes_ref.clone()} class="textarea" value={transaction.notes.clone().unwrap_or_default()}></textarea>
+                        </div>
+                        <div class="field">
+                            <label class="label">{"Tags"}</label>
+                            <input ref={self.tags_ref.clone()} class="input" type="text" placeholder="work, reimburse" value={transaction.tags.clone().unwrap_or_default().join(", ")}/>
+                        </div>
+                        <button class="button is-success is-small mb-4" onclick={save}>{"Save"}</button>
+
+                        <h2 class="is-size-5">{"Transfer"}</h2>
+                        if let Some((link, other)) = &self.transfer.link {
+                            <p class="is-size-7 mb-4">
+                                {format!("{} {} on {}", if transaction.amount > 0.0 { "Moved to" } else { "Moved from" }, other.name, other.date)}
+                                if link.manual {
+                                    {" · linked by hand"}
+                                }
+                                <a class="ml-3 has-text-danger" onclick={unlink.clone()}>{"Unlink"}</a>
+                            </p>
+                        } else if self.transfer.candidates.is_empty() {
+                            <p class="is-size-7 mb-4">{"No transaction in another account matches this one"}</p>
+                        } else {
+                            <div class="mb-4">
+                            {
+                                self.transfer.candidates.iter().map(|t| {
+                                    let link = {
+                                        let id = t.transaction_id.clone();
+                                        ctx.link().callback(move |_| Msg::Link(id.clone()))
+                                    };
+
+                                    html!{
+                                        <p class="is-size-7">
+                                            {format!("{} · {} · {}", t.date, t.name, format_amount(t.amount, &currency))}
+                                            <a class="ml-3" onclick={link}>{"Link as transfer"}</a>
+                                        </p>
+                                    }
+                                }).collect::<Html>()
+                            }
+                            </div>
+                        }
+
+                        <h2 class="is-size-5">{"Receipts"}</h2>
+                        {
+                            self.attachments.iter().map(|(a, url)| {
+                                let delete = {
+                                    let a = a.clone();
+                                    ctx.link().callback(move |_| Msg::Delete(a.clone()))
+                                };
+
+                                html!{
+                                    <div class="box p-2">
+                                        if let (true, Some(url)) = (a.is_image(), url) {
+                                            <img src={url.clone()} alt={a.file_name.clone()}/>
+                                        }
+                                        <p class="is-size-7">
+                                            if let Some(url) = url {
+                                                <a href={url.clone()} target="_blank">{a.file_name.clone()}</a>
+                                            } else {
+                                                {a.file_name.clone()}
+                                            }
+                                            <a class="ml-3 has-text-danger" onclick={delete}>{"Delete"}</a>
+                                        </p>
+                                    </div>
+                                }
+                            }).collect::<Html>()
+                        }
+                        <div class="field has-addons">
+                            <div class="control is-expanded">
+                                <input ref={self.path_ref.clone()} class="input is-small" type="text" placeholder="/path/to/receipt.pdf"/>
+                            </div>
+                            <div class="control">
+                                <button class="button is-small" onclick={upload}>{"Attach"}</button>
+                            </div>
+                        </div>
+                    </section>
+                </div>
+            </div>
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        self.error = None;
+        self.status = None;
+
+        let (auth_key, user_id) = Self::session(ctx);
+        let transaction_id = ctx.props().transaction.transaction_id.clone();
+
+        match msg {
+            Msg::GetAttachments => {
+                ctx.link().send_future(async move {
+                    let res = get_supbase_client()
+                        .from("transaction_attachments")
+                        .auth(&auth_key)
+                        .select("*")
+                        .eq("transaction_id", transaction_id)
+                        .order("created_at")
+                        .execute()
+                        .await
+                        .and_then(|r| r.error_for_status());
+
+                    let attachments: Vec<SchemaTransactionAttachment> = match res {
+                        Ok(res) => match res.json().await {
+                            Ok(a) => a,
+                            Err(e) => return Msg::Error(e.to_string()),
+                        },
+                        Err(e) => return Msg::Error(e.to_string()),
+                    };
+
+                    let urls = future::join_all(
+                        attachments
+                            .iter()
+                            .map(|a| commands::attachment_url(&auth_key, &a.storage_path)),
+                    )
+                    .await;
+
+                    Msg::GotAttachments(
+                        attachments
+                            .into_iter()
+                            .zip(urls.into_iter().map(|u| u.ok()))
+                            .collect(),
+                    )
+                });
+            }
+            Msg::GotAttachments(attachments) => self.attachments = attachments,
+            Msg::Save => {
+                let notes = self
+                    .notes_ref
+                    .cast::<HtmlTextAreaElement>()
+                    .expect("Notes ref not a textarea")
+                    .value();
+                let tags = self
+                    .tags_ref
+                    .cast::<HtmlInputElement>()
+                    .expect("Tags ref not an input element")
+                    .value();
+
+                let body = serde_json::to_string(&SchemaTransactionAnnotation {
+                    transaction_id,
+                    user_id,
+                    notes: if notes.is_empty() { None } else { Some(notes) },
+                    tags: parse_tags(&tags),
+                })
+                .expect("Failed to serialize");
+
+                ctx.link().send_future(async move {
+                    let res = get_supbase_client()
+                        .from("transaction_annotations")
+                        .auth(&auth_key)
+                        .upsert(body)
+                        .execute()
+                        .await
+                        .and_then(|r| r.error_for_status());
+
+                    match res {
+                        Ok(_) => Msg::Saved,
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::Saved => {
+                self.changed = true;
+                self.status = Some("Saved".to_string());
+            }
+            Msg::Upload => {
+                let path = self
+                    .path_ref
+                    .cast::<HtmlInputElement>()
+                    .expect("Path ref not an input element")
+                    .value();
+
+                ctx.link().send_future(async move {
+                    match commands::upload_attachment(&auth_key, &user_id, &transaction_id, &path)
+                        .await
+                    {
+                        Ok(_) => Msg::GetAttachments,
+                        Err(e) => Msg::Error(e),
+                    }
+                });
+            }
+            Msg::Delete(attachment) => {
+                ctx.link().send_future(async move {
+                    match commands::delete_attachment(&auth_key, &attachment).await {
+                        Ok(_) => Msg::GetAttachments,
+                        Err(e) => Msg::Error(e),
+                    }
+                });
+            }
+            Msg::GetTransfer => {
+                let transaction = ctx.props().transaction.clone();
+                ctx.link().send_future(async move {
+                    match get_transfer(&auth_key, &transaction).await {
+                        Ok(transfer) => Msg::GotTransfer(transfer),
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::GotTransfer(transfer) => self.transfer = transfer,
+            Msg::Link(other_id) => {
+                let (outflow_id, inflow_id) = if ctx.props().transaction.amount > 0.0 {
+                    (transaction_id, other_id)
+                } else {
+                    (other_id, transaction_id)
+                };
+                let body = serde_json::to_string(&SchemaTransferLink {
+                    id: None,
+                    user_id: Some(user_id),
+                    outflow_id,
+                    inflow_id,
+                    manual: true,
+                    linked: true,
+                })
+                .expect("Failed to serialize");
+
+                ctx.link().send_future(async move {
+                    // Replaces a matched pair the user unlinked before
+                    let res = get_supbase_client()
+                        .from("transfer_links")
+                        .auth(&auth_key)
+                        .upsert(body)
+                        .on_conflict("user_id,outflow_id,inflow_id")
+                        .execute()
+                        .await
+                        .and_then(|r| r.error_for_status());
+
+                    match res {
+                        Ok(_) => Msg::TransferSaved,
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::Unlink(link) => {
+                let id = link.id.expect("Stored links have an id").to_string();
+                ctx.link().send_future(async move {
+                    let query = get_supbase_client()
+                        .from("transfer_links")
+                        .auth(&auth_key)
+                        .eq("id", id);
+                    // Matched pairs are kept unlinked so they aren't matched again
+                    let query = if link.manual {
+                        query.delete()
+                    } else {
+                        query.update(r#"{"linked":false}"#)
+                    };
+
+                    match query.execute().await.and_then(|r| r.error_for_status()) {
+                        Ok(_) => Msg::TransferSaved,
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::TransferSaved => {
+                self.changed = true;
+                ctx.link().send_message(Msg::GetTransfer);
+            }
+            Msg::Error(e) => {
+                log::error!("{e}");
+                self.error = Some(e);
+            }
+        }
+
+        true
+    }
+}
+
+async fn get_transfer(
+    auth_key: &str,
+    transaction: &Transaction,
+) -> Result<Transfer, recurr_core::Error> {
+    let client = get_supbase_client();
+    let id = &transaction.transaction_id;
+
+    let links: Vec<SchemaTransferLink> = client
+        .from("transfer_links")
+        .auth(auth_key)
+        .select("*")
+        .eq("linked", "true")
+        .or(format!("outflow_id.eq.{id},inflow_id.eq.{id}"))
+        .execute()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    if let Some(link) = links.into_iter().next() {
+        let other: Vec<Transaction> = client
+            .from("user_transactions")
+            .auth(auth_key)
+            .select("*")
+            .eq("transaction_id", link.other(id))
+            .execute()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        return Ok(Transfer {
+            link: other.into_iter().next().map(|other| (link, other)),
+            candidates: Vec::new(),
+        });
+    }
+
+    let Ok(date) = NaiveDate::parse_from_str(&transaction.date, "%Y-%m-%d") else {
+        return Ok(Transfer::default());
+    };
+    let candidates = client
+        .from("user_transactions")
+        .auth(auth_key)
+        .select("*")
+        .eq("amount", (-transaction.amount).to_string())
+        .neq("account_id", &transaction.account_id)
+        .eq("is_transfer", "false")
+        .gte(
+            "date",
+            (date - Duration::days(MATCH_DAYS))
+                .format("%Y-%m-%d")
+                .to_string(),
+        )
+        .lte(
+            "date",
+            (date + Duration::days(MATCH_DAYS))
+                .format("%Y-%m-%d")
+                .to_string(),
+        )
+        .order("date")
+        .limit(10)
+        .execute()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(Transfer {
+        link: None,
+        candidates,
+    })
+}
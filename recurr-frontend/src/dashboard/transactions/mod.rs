@@ -1,38 +1,70 @@
-mod categories;
+pub mod categories;
+mod detail;
+mod rules;
+mod splits;
 
 use categories::Categories;
-use recurr_core::Transaction;
-use serde::{Deserialize, Serialize};
-use web_sys::{HtmlElement, HtmlInputElement, MouseEvent};
+use detail::Detail;
+use recurr_core::{
+    annotations::parse_tags,
+    categories::{self, Taxonomy},
+    currency::format_amount,
+    filters::range_total,
+    get_supbase_client,
+    splits::{SchemaTransactionSplit, SplitMap},
+    Account, Transaction,
+};
+use rules::Rules;
+use splits::Splits;
+use web_sys::{HtmlElement, HtmlInputElement, HtmlSelectElement, KeyboardEvent, MouseEvent};
 use yew::{
     function_component, html, use_node_ref, Callback, Component, Context, ContextHandle, Html,
-    Properties, TargetCast, UseReducerHandle,
+    NodeRef, Properties, TargetCast, UseReducerHandle,
 };
 use yew_hooks::use_bool_toggle;
 
 use crate::{
+    commands,
     components::pagination::Paginate,
     context::{Session, SessionContext},
 };
 
+use super::{accounts::manual::get_manual_accounts, saved_filters::save_filter};
+
+pub use recurr_core::filters::Filter;
+
 #[derive(Properties, PartialEq)]
 pub struct Props {
     pub context: UseReducerHandle<Session>,
     pub filter: Filter,
+    /// Called with every filter applied, so it's kept when leaving the tab
+    pub on_filter: Callback<Filter>,
+    /// Called once the filter was saved under a name
+    pub on_save: Callback<()>,
 }
 
 pub enum Msg {
     UpdatedContext(SessionContext),
 
-    GotTransactions((u64, Vec<Transaction>)),
+    GotTransactions((u64, u64, Vec<Transaction>, SplitMap)),
     GetTransactions,
+    GotAccounts(Vec<Account>),
     SetFilter(Filter),
+    SaveFilter,
+    SavedFilter,
+    FindTransfers,
+    FoundTransfers(usize),
 
     NextPage,
     GotoPage(u64),
     PrevPage,
 
     CategoryModal(bool),
+    RulesModal(bool),
+    SplitModal(Option<Transaction>),
+    SplitsClosed(bool),
+    DetailPanel(Option<Transaction>),
+    DetailClosed(bool),
 
     Error(String),
 }
@@ -42,7 +74,12 @@ pub struct TransactionsView {
     _context_listener: ContextHandle<SessionContext>,
 
     filter: Filter,
+    /// Every account, for the account filter
+    accounts: Vec<Account>,
+    save_name: NodeRef,
     transactions_in_page: Vec<Transaction>,
+    splits: SplitMap,
+    status: Option<String>,
     error: Option<String>,
 
     transactions_per_page: u64,
@@ -51,6 +88,9 @@ pub struct TransactionsView {
     total_transactions: u64,
 
     show_categories: bool,
+    show_rules: bool,
+    splitting: Option<Transaction>,
+    detail: Option<Transaction>,
 }
 
 impl TransactionsView {
@@ -64,19 +104,43 @@ impl TransactionsView {
             .expect("Needs session");
         let auth_key = session.auth_key;
 
-        let page = self.page as u64 - 1;
-        let per_page = self.transactions_per_page as u64 - 1;
-        let start_date = self.filter.start_date.clone();
-        let end_date = self.filter.end_date.clone();
+        let page = self.page - 1;
+        let per_page = self.transactions_per_page;
+        let filter = self.filter.clone();
+        let taxonomy = ctx.props().context.taxonomy.clone();
 
         ctx.link().send_future(async move {
-            let res = get_transactions(&auth_key, page, per_page, start_date, end_date).await;
+            let res = get_transactions(&auth_key, &taxonomy, page, per_page, &filter).await;
             match res {
                 Ok(t) => Msg::GotTransactions(t),
                 Err(e) => Msg::Error(e.to_string()),
             }
         });
     }
+
+    fn get_accounts(&self, ctx: &Context<Self>) {
+        let session = ctx
+            .props()
+            .context
+            .supabase_session
+            .clone()
+            .expect("Needs session");
+
+        ctx.link().send_future(async move {
+            let mut accounts =
+                match commands::get_balances(&session.auth_key, &session.user.id).await {
+                    Ok(accounts) => accounts,
+                    Err(e) => return Msg::Error(e),
+                };
+            match get_manual_accounts(&session.auth_key).await {
+                Ok(manual) => accounts.extend(manual.iter().map(|a| a.to_account())),
+                Err(e) => return Msg::Error(e.to_string()),
+            }
+            accounts.sort_by(|a, b| a.name.cmp(&b.name));
+
+            Msg::GotAccounts(accounts)
+        });
+    }
 }
 
 impl Component for TransactionsView {
@@ -89,23 +153,32 @@ impl Component for TransactionsView {
             .context(ctx.link().callback(Msg::UpdatedContext))
             .expect("No context provided");
 
-        ctx.link().send_message(Msg::GetTransactions);
-
         let filter = ctx.props().filter.clone();
 
-        Self {
+        let view = Self {
             context,
             _context_listener: context_listener,
 
+            status: None,
             error: None,
+            accounts: Vec::new(),
+            save_name: NodeRef::default(),
             transactions_in_page: Vec::new(),
+            splits: SplitMap::new(),
             transactions_per_page: 25,
             page: 1,
             total_pages: 1,
             total_transactions: 0,
             filter,
             show_categories: false,
-        }
+            show_rules: false,
+            splitting: None,
+            detail: None,
+        };
+        view.get_transaction(ctx);
+        view.get_accounts(ctx);
+
+        view
     }
 
     fn view(&self, ctx: &yew::Context<Self>) -> yew::Html {
@@ -131,11 +204,32 @@ impl Component for TransactionsView {
             })
         };
 
+        let base_currency = ctx.props().context.exchange_rates.base().to_string();
+        let taxonomy = &ctx.props().context.taxonomy;
+
         let show_categories_modal = self.show_categories;
         let toggle_cat = ctx
             .link()
             .callback(move |_| Msg::CategoryModal(!show_categories_modal));
 
+        let show_rules_modal = self.show_rules;
+        let toggle_rules = ctx
+            .link()
+            .callback(move |_| Msg::RulesModal(!show_rules_modal));
+        let splits_closed = ctx.link().callback(Msg::SplitsClosed);
+        let detail_closed = ctx.link().callback(Msg::DetailClosed);
+
+        let tag_onclick = {
+            let filter = self.filter.clone();
+            ctx.link().callback(move |e: MouseEvent| {
+                let mut filter = filter.clone();
+                let target = e.target_dyn_into::<HtmlElement>().unwrap();
+                filter.tag = target.get_attribute("data-tag");
+
+                Msg::SetFilter(filter)
+            })
+        };
+
         html! {
             <div class="column">
                 <h1 class="is-size-3"> {"Transaction"} </h1>
@@ -143,36 +237,105 @@ impl Component for TransactionsView {
                 if let Some(e) = &self.error {
                     {e}
                 }
+                if let Some(status) = &self.status {
+                    <p class="help is-success">{status}</p>
+                }
 
                 <div>
-                    <Filters apply_filter={filter_cb} {filter}/>
+                    <Filters apply_filter={filter_cb} {filter} accounts={self.accounts.clone()} taxonomy={taxonomy.clone()}/>
 
                     <button class="button" onclick={toggle_cat.clone()} >{"All Categories"}</button>
-                    <Categories show={show_categories_modal} on_toggle={toggle_cat.clone()}/>
+                    <Categories show={show_categories_modal} on_toggle={toggle_cat.clone()} context={ctx.props().context.clone()}/>
+                    <button class="button" onclick={toggle_rules.clone()} >{"Rules"}</button>
+                    <Rules show={show_rules_modal} on_toggle={toggle_rules} context={ctx.props().context.clone()}/>
+                    <button class="button ml-2" onclick={ctx.link().callback(|_| Msg::FindTransfers)}>{"Find transfers"}</button>
+                    <div class="field has-addons is-inline-flex ml-2 mb-0">
+                        <div class="control">
+                            <input ref={self.save_name.clone()} class="input" type="text" placeholder="Name this filter"/>
+                        </div>
+                        <div class="control">
+                            <button class="button" onclick={ctx.link().callback(|_| Msg::SaveFilter)}>{"Save filter"}</button>
+                        </div>
+                    </div>
+                    if let Some(t) = &self.detail {
+                        <Detail transaction={t.clone()} on_close={detail_closed} context={ctx.props().context.clone()}/>
+                    }
+                    if let Some(t) = &self.splitting {
+                        <Splits transaction={t.clone()} splits={self.splits.get(&t.transaction_id).cloned().unwrap_or_default()} on_close={splits_closed} context={ctx.props().context.clone()}/>
+                    }
                     <table class="table is-hoverable is-full-width mb-0">
                         <thead>
                             <th>{"Data"}</th>
                             <th>{"Name"}</th>
                             <th>{"Category"}</th>
                             <th>{"Amount"}</th>
+                            <th></th>
                         </thead>
                         <tbody>
                         {
                             self.transactions_in_page.clone().into_iter().map(|t| {
-                                 let cat = t.category.unwrap().last().unwrap().clone();
+                                 let path = taxonomy.resolve(t.category.as_deref().unwrap_or_default());
+                                 let cat = taxonomy.display_path(&path).last().cloned().unwrap_or_default();
+                                 let cat_key = categories::key(&path);
+                                 let currency = t.iso_currency_code.clone().or(t.unofficial_currency_code.clone()).unwrap_or(base_currency.clone());
+                                 let splits = self.splits.get(&t.transaction_id).cloned().unwrap_or_default();
+                                 let split = {
+                                     let t = t.clone();
+                                     ctx.link().callback(move |_| Msg::SplitModal(Some(t.clone())))
+                                 };
+                                 let open_detail = {
+                                     let t = t.clone();
+                                     ctx.link().callback(move |_| Msg::DetailPanel(Some(t.clone())))
+                                 };
                                 html!{
-                                    <tr>
+                                    <>
+                                    <tr class={if t.pending { "has-text-grey is-italic" } else { "" }}>
                                         <td> {t.date}</td>
-                                        <td> {t.name}</td>
-                                         <td><a class="has-hover-underline" data-category={cat.clone()} onclick={cat_onclick.clone()}> {cat} </a></td>
+                                        <td>
+                                            <a class="has-hover-underline" onclick={open_detail}>{t.name}</a>
+                                            if t.pending {
+                                                <span class="tag is-warning is-light ml-1">{"Pending"}</span>
+                                            }
+                                            if t.is_transfer == Some(true) {
+                                                <span class="tag is-info is-light ml-1">{"Transfer"}</span>
+                                            }
+                                            {
+                                                t.tags.unwrap_or_default().into_iter().map(|tag| html!{
+                                                    <span class="tag is-light ml-1 has-cursor-pointer" data-tag={tag.clone()} onclick={tag_onclick.clone()}>{tag}</span>
+                                                }).collect::<Html>()
+                                            }
+                                        </td>
+                                        if splits.is_empty() {
+                                            <td><a class="has-hover-underline" data-category={cat_key} onclick={cat_onclick.clone()}> {cat} </a></td>
+                                        } else {
+                                            <td class="has-text-grey">{format!("Split ({})", splits.len())}</td>
+                                        }
                                         {
                                             if t.amount < 0.0 {
-                                                html!{<td class="has-text-success">{format!("${:.2}", t.amount)}</td>}
+                                                html!{<td class="has-text-success">{format_amount(t.amount, &currency)}</td>}
                                             } else {
-                                                html!{<td> {format!("${:.2}", t.amount)}</td>}
+                                                html!{<td> {format_amount(t.amount, &currency)}</td>}
                                             }
                                         }
+                                        <td><a class="is-size-7" onclick={split}>{"Split"}</a></td>
                                     </tr>
+                                    {
+                                        splits.into_iter().map(|s| {
+                                            let path = taxonomy.resolve(&s.category);
+                                            let cat = taxonomy.display_path(&path).last().cloned().unwrap_or_default();
+                                            let cat_key = categories::key(&path);
+                                            html!{
+                                                <tr class="is-size-7">
+                                                    <td></td>
+                                                    <td class="pl-5">{s.memo.unwrap_or_default()}</td>
+                                                    <td><a class="has-hover-underline" data-category={cat_key} onclick={cat_onclick.clone()}> {cat} </a></td>
+                                                    <td>{format_amount(s.amount, &currency)}</td>
+                                                    <td></td>
+                                                </tr>
+                                            }
+                                        }).collect::<Html>()
+                                    }
+                                    </>
                                 }
                             }).collect::<Html>()
                         }
@@ -181,7 +344,7 @@ impl Component for TransactionsView {
 
                     <div class="is-flex is-justify-content-left is-align-content-center is-align-items-center">
                         <h1 class="is-size-7 mr-2">{"Showing 25 transactions"}</h1>
-                        <h1 class="is-size-7 mr-3"> {format!("{}-{} of {}", ((self.page - 1) * self.transactions_per_page + 1), (self.transactions_per_page * self.page).clamp(0, self.total_transactions), self.total_transactions)} </h1>
+                        <h1 class="is-size-7 mr-3"> {format!("{}-{} of {}", ((self.page - 1) * self.transactions_per_page + 1).min(self.total_transactions), (self.transactions_per_page * self.page).clamp(0, self.total_transactions), self.total_transactions)} </h1>
                         <Paginate {next_page} {prev_page} {goto_page} {current_page} {total_pages} />
                     </div>
                 </div>
@@ -189,34 +352,40 @@ impl Component for TransactionsView {
         }
     }
 
+    fn changed(&mut self, ctx: &yew::Context<Self>, _old_props: &Self::Properties) -> bool {
+        if ctx.props().filter != self.filter {
+            self.filter = ctx.props().filter.clone();
+            self.page = 1;
+            self.get_transaction(ctx);
+        }
+
+        true
+    }
+
     fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
         self.error = None;
 
         match msg {
             Msg::GetTransactions => self.get_transaction(ctx),
-            Msg::GotTransactions(t) => {
-                let mut transactions = t.1;
-                if let Some(cat) = &self.filter.category {
-                    transactions = transactions
-                        .drain_filter(|t| t.category.as_ref().unwrap().contains(cat))
-                        .collect();
-                }
-
-                self.total_transactions = t.0;
-                self.total_pages = self.total_transactions / self.transactions_per_page;
-
+            Msg::GotTransactions((page, total, transactions, splits)) => {
+                self.page = page + 1;
+                self.splits = splits;
+                self.total_transactions = total;
+                self.total_pages =
+                    ((total + self.transactions_per_page - 1) / self.transactions_per_page).max(1);
                 self.transactions_in_page = transactions;
             }
+            Msg::GotAccounts(accounts) => self.accounts = accounts,
             Msg::NextPage => {
-                self.page = (self.page + 1).clamp(0, self.total_pages);
+                self.page = (self.page + 1).clamp(1, self.total_pages);
                 ctx.link().send_message(Msg::GetTransactions);
             }
             Msg::PrevPage => {
-                self.page = (self.page - 1).clamp(0, self.total_pages);
+                self.page = self.page.saturating_sub(1).clamp(1, self.total_pages);
                 ctx.link().send_message(Msg::GetTransactions);
             }
             Msg::GotoPage(p) => {
-                self.page = p;
+                self.page = p.clamp(1, self.total_pages);
                 ctx.link().send_message(Msg::GetTransactions);
             }
             Msg::Error(e) => {
@@ -224,28 +393,112 @@ impl Component for TransactionsView {
                 self.error = Some(e);
             }
             Msg::SetFilter(f) => {
+                ctx.props().on_filter.emit(f.clone());
                 self.filter = f;
+                self.page = 1;
                 ctx.link().send_message(Msg::GetTransactions)
             }
+            Msg::SaveFilter => {
+                let Some(input) = self.save_name.cast::<HtmlInputElement>() else {
+                    return false;
+                };
+                let name = input.value().trim().to_string();
+                if name.is_empty() {
+                    self.error = Some("Name the filter to save it".to_string());
+                    return true;
+                }
+
+                let session = ctx
+                    .props()
+                    .context
+                    .supabase_session
+                    .clone()
+                    .expect("Needs session");
+                let filter = self.filter.clone();
+                ctx.link().send_future(async move {
+                    match save_filter(&session.auth_key, &session.user.id, &name, &filter).await {
+                        Ok(()) => Msg::SavedFilter,
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::FindTransfers => {
+                self.status = None;
+                let auth_key = ctx
+                    .props()
+                    .context
+                    .supabase_session
+                    .clone()
+                    .expect("Needs session")
+                    .auth_key;
+                ctx.link().send_future(async move {
+                    match commands::detect_transfers(&auth_key).await {
+                        Ok(count) => Msg::FoundTransfers(count),
+                        Err(e) => Msg::Error(e),
+                    }
+                });
+            }
+            Msg::FoundTransfers(count) => {
+                self.status = Some(format!("Linked {count} new transfers"));
+                ctx.link().send_message(Msg::GetTransactions);
+            }
+            Msg::SavedFilter => {
+                if let Some(input) = self.save_name.cast::<HtmlInputElement>() {
+                    input.set_value("");
+                }
+                ctx.props().on_save.emit(());
+            }
             Msg::UpdatedContext(context) => self.context = context,
             Msg::CategoryModal(show) => self.show_categories = show,
+            Msg::SplitModal(t) => self.splitting = t,
+            Msg::DetailPanel(t) => self.detail = t,
+            Msg::DetailClosed(saved) => {
+                self.detail = None;
+                if saved {
+                    ctx.link().send_message(Msg::GetTransactions);
+                }
+            }
+            Msg::SplitsClosed(saved) => {
+                self.splitting = None;
+                if saved {
+                    ctx.link().send_message(Msg::GetTransactions);
+                }
+            }
+            Msg::RulesModal(show) => {
+                self.show_rules = show;
+                if !show {
+                    ctx.link().send_message(Msg::GetTransactions);
+                }
+            }
         }
 
         true
     }
 }
 
-#[derive(Debug, Default, Deserialize, PartialEq, Clone, Serialize)]
-pub struct Filter {
-    pub start_date: Option<String>,
-    pub end_date: Option<String>,
-    pub category: Option<String>,
-}
-
 #[derive(Properties, PartialEq)]
 struct FilterProps {
     apply_filter: Callback<Filter>,
     filter: Filter,
+    accounts: Vec<Account>,
+    taxonomy: Taxonomy,
+}
+
+/// An input's trimmed value, `None` when it's empty
+fn input_value(node: &NodeRef) -> Option<String> {
+    let value = node.cast::<HtmlInputElement>()?.value().trim().to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+fn filter_chip(label: &str, value: String, on_remove: Callback<MouseEvent>) -> Html {
+    html! {
+        <span class="has-background-grey-light has-radius-1 px-2 mr-1 icon-text">
+            <span>{label} <span class="has-text-weight-bold">{value}</span></span>
+            <span onclick={on_remove} class="icon has-cursor-pointer">
+                <i class="fas fa-solid fa-times-circle"></i>
+            </span>
+        </span>
+    }
 }
 
 #[function_component(Filters)]
@@ -253,6 +506,14 @@ fn filters(props: &FilterProps) -> Html {
     let open = use_bool_toggle(false);
     let start_date_ref = use_node_ref();
     let end_date_ref = use_node_ref();
+    let tag_ref = use_node_ref();
+    let search_ref = use_node_ref();
+    let merchant_ref = use_node_ref();
+    let min_amount_ref = use_node_ref();
+    let max_amount_ref = use_node_ref();
+    let account_ref = use_node_ref();
+    let pending_ref = use_node_ref();
+    let uncategorized_ref = use_node_ref();
 
     let onclick = {
         let open = open.clone();
@@ -266,62 +527,89 @@ fn filters(props: &FilterProps) -> Html {
         let cb = props.apply_filter.clone();
         let start_date_ref = start_date_ref.clone();
         let end_date_ref = end_date_ref.clone();
+        let tag_ref = tag_ref.clone();
+        let merchant_ref = merchant_ref.clone();
+        let min_amount_ref = min_amount_ref.clone();
+        let max_amount_ref = max_amount_ref.clone();
+        let account_ref = account_ref.clone();
+        let pending_ref = pending_ref.clone();
+        let uncategorized_ref = uncategorized_ref.clone();
         let filter = props.filter.clone();
 
         Callback::from(move |_| {
-            let start_date = start_date_ref.cast::<HtmlInputElement>().unwrap().value();
-            let end_date = end_date_ref.cast::<HtmlInputElement>().unwrap().value();
-
-            let start_date = if start_date.is_empty() {
-                None
-            } else {
-                Some(start_date)
-            };
-
-            let end_date = if end_date.is_empty() {
-                None
-            } else {
-                Some(end_date)
+            let select_value = |node: &NodeRef| {
+                node.cast::<HtmlSelectElement>()
+                    .map(|s| s.value())
+                    .filter(|v| !v.is_empty())
             };
 
             let mut filter = filter.clone();
-            filter.start_date = start_date;
-            filter.end_date = end_date;
+            filter.start_date = input_value(&start_date_ref);
+            filter.end_date = input_value(&end_date_ref);
+            filter.tag = input_value(&tag_ref).and_then(|tag| parse_tags(&tag).into_iter().next());
+            filter.merchant = input_value(&merchant_ref);
+            filter.min_amount = input_value(&min_amount_ref).and_then(|v| v.parse().ok());
+            filter.max_amount = input_value(&max_amount_ref).and_then(|v| v.parse().ok());
+            filter.account_id = select_value(&account_ref);
+            filter.pending = select_value(&pending_ref).map(|v| v == "pending");
+            filter.uncategorized = uncategorized_ref
+                .cast::<HtmlInputElement>()
+                .map_or(false, |i| i.checked());
 
             cb.emit(filter);
         })
     };
 
-    let start_date = props.filter.start_date.clone().unwrap_or_default();
-    let end_date = props.filter.end_date.clone().unwrap_or_default();
-
-    let remove_date_filter = {
+    let search = {
         let cb = props.apply_filter.clone();
+        let search_ref = search_ref.clone();
         let filter = props.filter.clone();
 
-        Callback::from(move |_| {
-            let mut filter = filter.clone();
-            filter.start_date = None;
-            filter.end_date = None;
+        Callback::from(move |e: KeyboardEvent| {
+            if e.key() == "Enter" {
+                let mut filter = filter.clone();
+                filter.search = input_value(&search_ref);
 
-            cb.emit(filter);
+                cb.emit(filter);
+            }
         })
     };
 
-    let remove_cat_filter = {
+    let clear = {
+        let cb = props.apply_filter.clone();
+        Callback::from(move |_| cb.emit(Filter::default()))
+    };
+
+    let remove = |clear: fn(&mut Filter)| {
         let cb = props.apply_filter.clone();
         let filter = props.filter.clone();
 
-        Callback::from(move |_| {
+        Callback::from(move |_: MouseEvent| {
             let mut filter = filter.clone();
-            filter.category = None;
+            clear(&mut filter);
 
             cb.emit(filter);
         })
     };
 
+    let filter = &props.filter;
+    let amount = |amount: Option<f64>| amount.map(|a| a.to_string()).unwrap_or_default();
+    let account_name = |account_id: &str| {
+        props
+            .accounts
+            .iter()
+            .find(|a| a.account_id == account_id)
+            .map_or(account_id.to_string(), |a| a.name.clone())
+    };
+
     html! {
         <>
+        <div class="control has-icons-left mb-2">
+            <input ref={search_ref} onkeydown={search} class="input" type="search" placeholder={r#"Search, e.g. merchant:starbucks amount>20 -pending cat:"Food and Drink" before:2024-01-01"#} value={filter.search.clone().unwrap_or_default()}/>
+            <span class="icon is-left">
+                <i class="fas fa-search" aria-hidden="true"></i>
+            </span>
+        </div>
         <div class="dropdown is-active">
             <div class="dropdown-trigger">
                 <button {onclick} class="button" aria-haspopup="true" aria-controls="dropdown-menu">
@@ -339,19 +627,67 @@ fn filters(props: &FilterProps) -> Html {
             if *open {
                 <div class="dropdown-menu" id="dropdown-menu" role="menu">
                     <div class="dropdown-content">
-                        <hr class="dropdown-divider" />
                         <div class="dropdown-item">
                             <label>{"Start date"}</label>
                             <br />
-                            <input ref={start_date_ref} class="input is-small" type="date" value={start_date}/>
+                            <input ref={start_date_ref} class="input is-small" type="date" value={filter.start_date.clone().unwrap_or_default()}/>
                         </div>
                         <div class="dropdown-item">
                             <label>{"End date"}</label>
                             <br />
-                            <input ref={end_date_ref} class="input is-small" type="date" value={end_date}/>
+                            <input ref={end_date_ref} class="input is-small" type="date" value={filter.end_date.clone().unwrap_or_default()}/>
+                        </div>
+                        <div class="dropdown-item">
+                            <label>{"Account"}</label>
+                            <br />
+                            <div class="select is-small">
+                                <select ref={account_ref}>
+                                    <option value="" selected={filter.account_id.is_none()}>{"Any"}</option>
+                                    {
+                                        props.accounts.iter().map(|a| html!{
+                                            <option value={a.account_id.clone()} selected={filter.account_id.as_ref() == Some(&a.account_id)}>{a.name.clone()}</option>
+                                        }).collect::<Html>()
+                                    }
+                                </select>
+                            </div>
                         </div>
                         <div class="dropdown-item">
-                            <button class="button is-small mr-3">{"Clear"}</button>
+                            <label>{"Merchant"}</label>
+                            <br />
+                            <input ref={merchant_ref} class="input is-small" type="text" value={filter.merchant.clone().unwrap_or_default()}/>
+                        </div>
+                        <div class="dropdown-item">
+                            <label>{"Amount"}</label>
+                            <br />
+                            <div class="is-flex">
+                                <input ref={min_amount_ref} class="input is-small mr-1" type="number" step="0.01" placeholder="Min" value={amount(filter.min_amount)}/>
+                                <input ref={max_amount_ref} class="input is-small" type="number" step="0.01" placeholder="Max" value={amount(filter.max_amount)}/>
+                            </div>
+                        </div>
+                        <div class="dropdown-item">
+                            <label>{"Status"}</label>
+                            <br />
+                            <div class="select is-small">
+                                <select ref={pending_ref}>
+                                    <option value="" selected={filter.pending.is_none()}>{"Any"}</option>
+                                    <option value="pending" selected={filter.pending == Some(true)}>{"Pending"}</option>
+                                    <option value="posted" selected={filter.pending == Some(false)}>{"Posted"}</option>
+                                </select>
+                            </div>
+                        </div>
+                        <div class="dropdown-item">
+                            <label class="checkbox">
+                                <input ref={uncategorized_ref} type="checkbox" checked={filter.uncategorized}/>
+                                {" Uncategorized only"}
+                            </label>
+                        </div>
+                        <div class="dropdown-item">
+                            <label>{"Tag"}</label>
+                            <br />
+                            <input ref={tag_ref} class="input is-small" type="text" value={filter.tag.clone().unwrap_or_default()}/>
+                        </div>
+                        <div class="dropdown-item">
+                            <button onclick={clear} class="button is-small mr-3">{"Clear"}</button>
                             <button onclick={apply} class="button is-success is-small">{"Apply"}</button>
                         </div>
                     </div>
@@ -360,71 +696,124 @@ fn filters(props: &FilterProps) -> Html {
         </div>
         <br />
         <div class="m-1 is-flex">
-            if let (Some(start), Some(end)) = (&props.filter.start_date, &props.filter.end_date) {
-                <span class="has-background-grey-light has-radius-1 px-2 icon-text">
-                    <span>{"Date: "} <span class="has-text-weight-bold">{format!("{start}-{end}")}</span></span>
-                    <span onclick={remove_date_filter} class="icon has-cursor-pointer">
-                        <i class="fas fa-solid fa-times-circle"></i>
-                    </span>
-                </span>
+            if let Some(search) = &filter.search {
+                {filter_chip("Search: ", search.clone(), remove(|f| f.search = None))}
             }
 
-            if let Some(cat) = &props.filter.category {
-                <span class="has-background-grey-light has-radius-1 px-2 icon-text">
-                    <span>{"Category: "} <span class="has-text-weight-bold">{cat}</span></span>
-                    <span onclick={remove_cat_filter} class="icon has-cursor-pointer">
-                        <i class="fas fa-solid fa-times-circle"></i>
-                    </span>
-                </span>
+            if filter.start_date.is_some() || filter.end_date.is_some() {
+                {filter_chip("Date: ", format!("{} – {}", filter.start_date.clone().unwrap_or_default(), filter.end_date.clone().unwrap_or_default()), remove(|f| {
+                    f.start_date = None;
+                    f.end_date = None;
+                }))}
+            }
+
+            if let Some(cat) = &filter.category {
+                {filter_chip("Category: ", props.taxonomy.label(cat), remove(|f| f.category = None))}
+            }
+
+            if let Some(account_id) = &filter.account_id {
+                {filter_chip("Account: ", account_name(account_id.as_str()), remove(|f| f.account_id = None))}
+            }
+
+            if let Some(merchant) = &filter.merchant {
+                {filter_chip("Merchant: ", merchant.clone(), remove(|f| f.merchant = None))}
+            }
+
+            if filter.min_amount.is_some() || filter.max_amount.is_some() {
+                {filter_chip("Amount: ", format!("{} – {}", amount(filter.min_amount), amount(filter.max_amount)), remove(|f| {
+                    f.min_amount = None;
+                    f.max_amount = None;
+                }))}
             }
 
+            if let Some(pending) = filter.pending {
+                {filter_chip("Status: ", if pending { "Pending" } else { "Posted" }.to_string(), remove(|f| f.pending = None))}
+            }
+
+            if filter.uncategorized {
+                {filter_chip("Uncategorized", String::new(), remove(|f| f.uncategorized = false))}
+            }
+
+            if let Some(tag) = &filter.tag {
+                {filter_chip("Tag: ", tag.clone(), remove(|f| f.tag = None))}
+            }
         </div>
         </>
     }
 }
 
+/// A page of the filter's transactions with the total count. Pages past the
+/// end, after a filter or a sync shrank the results, are moved back to the
+/// last page, which is returned zero based with the rest
 async fn get_transactions(
     auth_key: &str,
-    page: u64,
+    taxonomy: &Taxonomy,
+    mut page: u64,
     per_page: u64,
-    start_date: Option<String>,
-    end_date: Option<String>,
-) -> Result<(u64, Vec<Transaction>), recurr_core::Error> {
+    filter: &Filter,
+) -> Result<(u64, u64, Vec<Transaction>, SplitMap), recurr_core::Error> {
     let db_client = recurr_core::get_supbase_client();
 
-    let mut query = db_client
-        .from("transactions")
-        .auth(&auth_key)
-        .select("*")
-        .order("date.desc")
-        .exact_count()
-        .range(
-            (page * per_page) as usize,
-            (page * per_page + per_page) as usize,
-        );
-
-    if let Some(start_date) = start_date {
-        query = query.gt("date", start_date);
-    }
+    loop {
+        let res = filter
+            .query(&db_client, taxonomy)?
+            .auth(auth_key)
+            .exact_count()
+            .range(
+                (page * per_page) as usize,
+                (page * per_page + per_page - 1) as usize,
+            )
+            .execute()
+            .await?;
+
+        let total_transactions = res
+            .headers()
+            .get("content-range")
+            .and_then(|v| v.to_str().ok())
+            .and_then(range_total);
+
+        // PostgREST answers a range past the last row with 416 Range Not Satisfiable
+        if res.status().as_u16() == 416 && page > 0 {
+            if let Some(total) = total_transactions {
+                let last_page = total.saturating_sub(1) / per_page;
+                if last_page < page {
+                    page = last_page;
+                    continue;
+                }
+            }
+        }
+
+        let res = res.error_for_status()?;
+        let total_transactions = total_transactions.ok_or_else(|| {
+            recurr_core::Error::Other("No transaction count in the response".to_string())
+        })?;
 
-    if let Some(end_date) = end_date {
-        query = query.lt("date", end_date);
+        let transactions: Vec<Transaction> = res.json().await?;
+        let splits = get_splits(
+            auth_key,
+            transactions.iter().map(|t| t.transaction_id.as_str()),
+        )
+        .await?;
+
+        return Ok((page, total_transactions, transactions, splits));
     }
+}
 
-    let res = query.execute().await?.error_for_status()?;
-
-    let total_transactions = res
-        .headers()
-        .get("content-range")
-        .expect("Failed to get total count")
-        .to_str()
-        .unwrap()
-        .split('/')
-        .last()
-        .unwrap()
-        .parse()
-        .unwrap();
-
-    let transactions: Vec<Transaction> = res.json().await?;
-    Ok((total_transactions, transactions))
+pub async fn get_splits<'a>(
+    auth_key: &str,
+    transaction_ids: impl IntoIterator<Item = &'a str>,
+) -> Result<SplitMap, recurr_core::Error> {
+    let splits: Vec<SchemaTransactionSplit> = get_supbase_client()
+        .from("transaction_splits")
+        .auth(auth_key)
+        .select("*")
+        .in_("transaction_id", transaction_ids)
+        .order("id")
+        .execute()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(recurr_core::splits::group(splits))
 }
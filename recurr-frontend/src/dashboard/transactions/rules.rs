@@ -0,0 +1,361 @@
+use recurr_core::{
+    get_supbase_client,
+    rules::{Rule, SchemaRule},
+    Transaction,
+};
+use web_sys::{HtmlElement, HtmlInputElement, HtmlSelectElement, MouseEvent};
+use yew::{html, Callback, Component, Context, Html, NodeRef, Properties, TargetCast};
+
+use crate::{commands, context::SessionContext};
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub show: bool,
+    pub on_toggle: Callback<MouseEvent>,
+
+    pub context: SessionContext,
+}
+
+pub enum Msg {
+    GetRules,
+    GotRules(Vec<SchemaRule>),
+
+    Preview,
+    GotPreview(Vec<Transaction>),
+    Save,
+    Delete(i64),
+    ApplyAll,
+    Applied(usize),
+
+    Error(String),
+}
+
+pub struct Rules {
+    rules: Vec<SchemaRule>,
+    preview: Option<Vec<Transaction>>,
+    status: Option<String>,
+    error: Option<String>,
+
+    name_ref: NodeRef,
+    pattern_ref: NodeRef,
+    amount_min_ref: NodeRef,
+    amount_max_ref: NodeRef,
+    account_ref: NodeRef,
+    category_ref: NodeRef,
+    rename_ref: NodeRef,
+    transfer_ref: NodeRef,
+    priority_ref: NodeRef,
+}
+
+impl Rules {
+    fn auth_key(ctx: &Context<Self>) -> String {
+        ctx.props()
+            .context
+            .supabase_session
+            .clone()
+            .expect("Needs session")
+            .auth_key
+    }
+
+    fn input_value(node: &NodeRef) -> Option<String> {
+        let value = node
+            .cast::<HtmlInputElement>()
+            .expect("Not an input element")
+            .value();
+
+        if value.is_empty() {
+            None
+        } else {
+            Some(value)
+        }
+    }
+
+    fn read_rule(&self, ctx: &Context<Self>) -> SchemaRule {
+        let user_id = ctx
+            .props()
+            .context
+            .supabase_session
+            .clone()
+            .expect("Needs session")
+            .user
+            .id;
+
+        let category = self
+            .category_ref
+            .cast::<HtmlSelectElement>()
+            .expect("Category ref not a select element")
+            .value();
+        let set_category = serde_json::from_str(&category).ok();
+
+        SchemaRule {
+            id: None,
+            user_id,
+            name: Self::input_value(&self.name_ref).unwrap_or("Rule".to_string()),
+            priority: Self::input_value(&self.priority_ref)
+                .and_then(|p| p.parse().ok())
+                .unwrap_or_default(),
+            name_pattern: Self::input_value(&self.pattern_ref),
+            amount_min: Self::input_value(&self.amount_min_ref).and_then(|a| a.parse().ok()),
+            amount_max: Self::input_value(&self.amount_max_ref).and_then(|a| a.parse().ok()),
+            account_id: Self::input_value(&self.account_ref),
+            set_category,
+            rename_merchant: Self::input_value(&self.rename_ref),
+            mark_transfer: self
+                .transfer_ref
+                .cast::<HtmlInputElement>()
+                .expect("Transfer ref not an input element")
+                .checked(),
+        }
+    }
+}
+
+impl Component for Rules {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        ctx.link().send_message(Msg::GetRules);
+
+        Self {
+            rules: Vec::new(),
+            preview: None,
+            status: None,
+            error: None,
+
+            name_ref: NodeRef::default(),
+            pattern_ref: NodeRef::default(),
+            amount_min_ref: NodeRef::default(),
+            amount_max_ref: NodeRef::default(),
+            account_ref: NodeRef::default(),
+            category_ref: NodeRef::default(),
+            rename_ref: NodeRef::default(),
+            transfer_ref: NodeRef::default(),
+            priority_ref: NodeRef::default(),
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        if !ctx.props().show {
+            return html! {};
+        }
+
+        let close_modal = ctx.props().on_toggle.clone();
+        let taxonomy = &ctx.props().context.taxonomy;
+        let preview = ctx.link().callback(|_| Msg::Preview);
+        let save = ctx.link().callback(|_| Msg::Save);
+        let apply_all = ctx.link().callback(|_| Msg::ApplyAll);
+        let delete = ctx.link().callback(|e: MouseEvent| {
+            let target = e.target_dyn_into::<HtmlElement>().unwrap();
+            let id = target
+                .get_attribute("data-id")
+                .and_then(|id| id.parse().ok())
+                .unwrap_or_default();
+            Msg::Delete(id)
+        });
+
+        html! {
+            <div class="modal is-active">
+                <div class="modal-background" onclick={close_modal.clone()}></div>
+
+                <div class="modal-card">
+                    <header class="modal-card-head">
+                        <h1 class="modal-card-title">{"Rules"}</h1>
+                        <button class="delete" aria-label="close" onclick={close_modal}></button>
+                    </header>
+
+                    <section class="modal-card-body">
+                        if let Some(e) = &self.error {
+                            <p class="help is-danger">{e}</p>
+                        }
+                        if let Some(status) = &self.status {
+                            <p class="help is-success">{status}</p>
+                        }
+
+                        <table class="table is-fullwidth">
+                            <thead>
+                                <th>{"Priority"}</th>
+                                <th>{"Name"}</th>
+                                <th>{"Matches"}</th>
+                                <th></th>
+                            </thead>
+                            <tbody>
+                            {
+                                self.rules.iter().map(|r| {
+                                    html!{
+                                        <tr>
+                                            <td>{r.priority}</td>
+                                            <td>{r.name.clone()}</td>
+                                            <td>{r.name_pattern.clone().unwrap_or_default()}</td>
+                                            <td><a data-id={r.id.unwrap_or_default().to_string()} onclick={delete.clone()}>{"Delete"}</a></td>
+                                        </tr>
+                                    }
+                                }).collect::<Html>()
+                            }
+                            </tbody>
+                        </table>
+
+                        <h2 class="is-size-5">{"New rule"}</h2>
+                        <div class="field">
+                            <input ref={self.name_ref.clone()} class="input is-small" type="text" placeholder="Name"/>
+                        </div>
+                        <div class="field">
+                            <input ref={self.pattern_ref.clone()} class="input is-small" type="text" placeholder="Merchant or name regex, e.g. ^amzn|amazon"/>
+                        </div>
+                        <div class="field is-grouped">
+                            <input ref={self.amount_min_ref.clone()} class="input is-small" type="number" step="any" placeholder="Min amount"/>
+                            <input ref={self.amount_max_ref.clone()} class="input is-small" type="number" step="any" placeholder="Max amount"/>
+                        </div>
+                        <div class="field">
+                            <input ref={self.account_ref.clone()} class="input is-small" type="text" placeholder="Account ID"/>
+                        </div>
+                        <div class="field">
+                            <div class="select is-small">
+                                <select ref={self.category_ref.clone()}>
+                                    <option value="">{"Keep category"}</option>
+                                    {
+                                        taxonomy.flatten().into_iter().map(|c| {
+                                            html!{<option value={serde_json::to_string(&c.path).unwrap_or_default()}>{taxonomy.display_path(&c.path).join(" > ")}</option>}
+                                        }).collect::<Html>()
+                                    }
+                                </select>
+                            </div>
+                        </div>
+                        <div class="field">
+                            <input ref={self.rename_ref.clone()} class="input is-small" type="text" placeholder="Rename merchant to"/>
+                        </div>
+                        <div class="field is-grouped">
+                            <label class="checkbox mr-3">
+                                <input ref={self.transfer_ref.clone()} type="checkbox"/>
+                                {" Mark as transfer"}
+                            </label>
+                            <input ref={self.priority_ref.clone()} class="input is-small" type="number" placeholder="Priority"/>
+                        </div>
+
+                        if let Some(preview) = &self.preview {
+                            <p class="is-size-7">{format!("{} matching transactions", preview.len())}</p>
+                            <table class="table is-narrow is-fullwidth">
+                                <tbody>
+                                {
+                                    preview.iter().take(10).map(|t| {
+                                        html!{
+                                            <tr>
+                                                <td>{t.date.clone()}</td>
+                                                <td>{t.merchant_name.clone().unwrap_or(t.name.clone())}</td>
+                                                <td>{format!("{:.2}", t.amount)}</td>
+                                            </tr>
+                                        }
+                                    }).collect::<Html>()
+                                }
+                                </tbody>
+                            </table>
+                        }
+                    </section>
+
+                    <footer class="modal-card-foot">
+                        <button class="button" onclick={preview}>{"Preview matches"}</button>
+                        <button class="button is-success" onclick={save}>{"Save rule"}</button>
+                        <button class="button is-warning" onclick={apply_all}>{"Apply retroactively"}</button>
+                    </footer>
+                </div>
+            </div>
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        self.error = None;
+        let auth_key = Self::auth_key(ctx);
+
+        match msg {
+            Msg::GetRules => {
+                ctx.link().send_future(async move {
+                    let res = get_supbase_client()
+                        .from("transaction_rules")
+                        .auth(&auth_key)
+                        .select("*")
+                        .order("priority.desc,id")
+                        .execute()
+                        .await
+                        .and_then(|r| r.error_for_status());
+
+                    match res {
+                        Ok(res) => match res.json().await {
+                            Ok(rules) => Msg::GotRules(rules),
+                            Err(e) => Msg::Error(e.to_string()),
+                        },
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::GotRules(rules) => self.rules = rules,
+            Msg::Preview => {
+                let rule = self.read_rule(ctx);
+                ctx.link().send_future(async move {
+                    match commands::preview_rule(&auth_key, &rule).await {
+                        Ok(t) => Msg::GotPreview(t),
+                        Err(e) => Msg::Error(e),
+                    }
+                });
+            }
+            Msg::GotPreview(t) => self.preview = Some(t),
+            Msg::Save => {
+                // Rules with a pattern that doesn't compile would never match
+                let rule = match Rule::new(self.read_rule(ctx)) {
+                    Ok(rule) => rule.schema,
+                    Err(e) => {
+                        self.error = Some(e.to_string());
+                        return true;
+                    }
+                };
+                let rule = serde_json::to_string(&rule).expect("Failed to serialize");
+                ctx.link().send_future(async move {
+                    let res = get_supbase_client()
+                        .from("transaction_rules")
+                        .auth(&auth_key)
+                        .insert(rule)
+                        .execute()
+                        .await
+                        .and_then(|r| r.error_for_status());
+
+                    match res {
+                        Ok(_) => Msg::GetRules,
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::Delete(id) => {
+                ctx.link().send_future(async move {
+                    let res = get_supbase_client()
+                        .from("transaction_rules")
+                        .auth(&auth_key)
+                        .eq("id", id.to_string())
+                        .delete()
+                        .execute()
+                        .await
+                        .and_then(|r| r.error_for_status());
+
+                    match res {
+                        Ok(_) => Msg::GetRules,
+                        Err(e) => Msg::Error(e.to_string()),
+                    }
+                });
+            }
+            Msg::ApplyAll => {
+                ctx.link().send_future(async move {
+                    match commands::apply_rules(&auth_key).await {
+                        Ok(count) => Msg::Applied(count),
+                        Err(e) => Msg::Error(e),
+                    }
+                });
+            }
+            Msg::Applied(count) => {
+                self.status = Some(format!("Rules applied to {count} transactions"));
+            }
+            Msg::Error(e) => {
+                log::error!("{e}");
+                self.error = Some(e);
+            }
+        }
+
+        true
+    }
+}
@@ -0,0 +1,284 @@
+use recurr_core::{
+    currency::format_amount,
+    splits::{self, SchemaTransactionSplit},
+    Transaction,
+};
+use web_sys::{Event, HtmlInputElement, HtmlSelectElement, InputEvent, MouseEvent};
+use yew::{html, Callback, Component, Context, Html, Properties, TargetCast};
+
+use crate::{commands, context::SessionContext};
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub transaction: Transaction,
+    pub splits: Vec<SchemaTransactionSplit>,
+
+    /// Emits whether the splits were saved
+    pub on_close: Callback<bool>,
+    pub context: SessionContext,
+}
+
+pub enum Msg {
+    AddRow,
+    RemoveRow(usize),
+    SetCategory(usize, String),
+    SetAmount(usize, String),
+    SetMemo(usize, String),
+
+    Save,
+    Unsplit,
+    Saved,
+
+    Error(String),
+}
+
+struct Row {
+    category: Option<Vec<String>>,
+    amount: String,
+    memo: String,
+}
+
+pub struct Splits {
+    rows: Vec<Row>,
+    error: Option<String>,
+}
+
+impl Splits {
+    fn session(ctx: &Context<Self>) -> (String, String) {
+        let session = ctx
+            .props()
+            .context
+            .supabase_session
+            .clone()
+            .expect("Needs session");
+        (session.auth_key, session.user.id)
+    }
+
+    fn read_splits(&self, ctx: &Context<Self>) -> Result<Vec<SchemaTransactionSplit>, String> {
+        let (_, user_id) = Self::session(ctx);
+        let transaction_id = ctx.props().transaction.transaction_id.clone();
+
+        self.rows
+            .iter()
+            .map(|r| {
+                let amount = r
+                    .amount
+                    .parse()
+                    .map_err(|_| format!("{} is not an amount", r.amount))?;
+
+                Ok(SchemaTransactionSplit {
+                    id: None,
+                    transaction_id: transaction_id.clone(),
+                    user_id: user_id.clone(),
+                    category: r.category.clone().unwrap_or_default(),
+                    amount,
+                    memo: if r.memo.is_empty() {
+                        None
+                    } else {
+                        Some(r.memo.clone())
+                    },
+                })
+            })
+            .collect()
+    }
+
+    fn save(ctx: &Context<Self>, splits: Vec<SchemaTransactionSplit>) {
+        let (auth_key, _) = Self::session(ctx);
+        let transaction_id = ctx.props().transaction.transaction_id.clone();
+
+        ctx.link().send_future(async move {
+            match commands::save_splits(&auth_key, &transaction_id, &splits).await {
+                Ok(_) => Msg::Saved,
+                Err(e) => Msg::Error(e),
+            }
+        });
+    }
+}
+
+impl Component for Splits {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let transaction = &ctx.props().transaction;
+
+        let rows = if ctx.props().splits.is_empty() {
+            vec![
+                Row {
+                    category: transaction.category.clone(),
+                    amount: format!("{:.2}", transaction.amount),
+                    memo: String::new(),
+                },
+                Row {
+                    category: None,
+                    amount: "0.00".to_string(),
+                    memo: String::new(),
+                },
+            ]
+        } else {
+            ctx.props()
+                .splits
+                .iter()
+                .map(|s| Row {
+                    category: Some(s.category.clone()),
+                    amount: format!("{:.2}", s.amount),
+                    memo: s.memo.clone().unwrap_or_default(),
+                })
+                .collect()
+        };
+
+        Self { rows, error: None }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let transaction = &ctx.props().transaction;
+        let taxonomy = &ctx.props().context.taxonomy;
+        let currency = transaction
+            .iso_currency_code
+            .clone()
+            .or(transaction.unofficial_currency_code.clone())
+            .unwrap_or(ctx.props().context.exchange_rates.base().to_string());
+
+        let close_modal = {
+            let on_close = ctx.props().on_close.clone();
+            Callback::from(move |_: MouseEvent| on_close.emit(false))
+        };
+        let add_row = ctx.link().callback(|_| Msg::AddRow);
+        let save = ctx.link().callback(|_| Msg::Save);
+        let unsplit = ctx.link().callback(|_| Msg::Unsplit);
+
+        let allocated: f64 = self
+            .rows
+            .iter()
+            .filter_map(|r| r.amount.parse::<f64>().ok())
+            .sum();
+        let remaining = transaction.amount - allocated;
+
+        html! {
+            <div class="modal is-active">
+                <div class="modal-background" onclick={close_modal.clone()}></div>
+
+                <div class="modal-card">
+                    <header class="modal-card-head">
+                        <h1 class="modal-card-title">{format!("Split {} ({})", transaction.name, format_amount(transaction.amount, &currency))}</h1>
+                        <button class="delete" aria-label="close" onclick={close_modal}></button>
+                    </header>
+
+                    <section class="modal-card-body">
+                        if let Some(e) = &self.error {
+                            <p class="help is-danger">{e}</p>
+                        }
+
+                        <table class="table is-fullwidth">
+                            <thead>
+                                <th>{"Category"}</th>
+                                <th>{"Amount"}</th>
+                                <th>{"Memo"}</th>
+                                <th></th>
+                            </thead>
+                            <tbody>
+                            {
+                                self.rows.iter().enumerate().map(|(i, r)| {
+                                    let set_category = ctx.link().callback(move |e: Event| {
+                                        Msg::SetCategory(i, e.target_unchecked_into::<HtmlSelectElement>().value())
+                                    });
+                                    let set_amount = ctx.link().callback(move |e: InputEvent| {
+                                        Msg::SetAmount(i, e.target_unchecked_into::<HtmlInputElement>().value())
+                                    });
+                                    let set_memo = ctx.link().callback(move |e: InputEvent| {
+                                        Msg::SetMemo(i, e.target_unchecked_into::<HtmlInputElement>().value())
+                                    });
+                                    let remove = ctx.link().callback(move |_| Msg::RemoveRow(i));
+
+                                    html!{
+                                        <tr>
+                                            <td>
+                                                <div class="select is-small">
+                                                    <select onchange={set_category}>
+                                                        <option value="" selected={r.category.is_none()}>{"Choose a category"}</option>
+                                                        {
+                                                            taxonomy.flatten().into_iter().map(|c| {
+                                                                let selected = r.category.as_ref().map_or(false, |p| taxonomy.resolve(p) == c.path);
+                                                                html!{<option {selected} value={serde_json::to_string(&c.path).unwrap_or_default()}>{taxonomy.display_path(&c.path).join(" > ")}</option>}
+                                                            }).collect::<Html>()
+                                                        }
+                                                    </select>
+                                                </div>
+                                            </td>
+                                            <td><input class="input is-small" type="number" step="0.01" value={r.amount.clone()} oninput={set_amount}/></td>
+                                            <td><input class="input is-small" type="text" value={r.memo.clone()} oninput={set_memo}/></td>
+                                            <td><a onclick={remove}>{"Remove"}</a></td>
+                                        </tr>
+                                    }
+                                }).collect::<Html>()
+                            }
+                            </tbody>
+                        </table>
+
+                        <p class="is-size-7">{format!("Remaining {}", format_amount(remaining, &currency))}</p>
+                    </section>
+
+                    <footer class="modal-card-foot">
+                        <button class="button" onclick={add_row}>{"Add split"}</button>
+                        <button class="button is-success" onclick={save}>{"Save"}</button>
+                        if !ctx.props().splits.is_empty() {
+                            <button class="button is-danger" onclick={unsplit}>{"Remove splits"}</button>
+                        }
+                    </footer>
+                </div>
+            </div>
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        self.error = None;
+
+        match msg {
+            Msg::AddRow => self.rows.push(Row {
+                category: None,
+                amount: "0.00".to_string(),
+                memo: String::new(),
+            }),
+            Msg::RemoveRow(i) => {
+                if i < self.rows.len() {
+                    self.rows.remove(i);
+                }
+            }
+            Msg::SetCategory(i, category) => {
+                if let Some(row) = self.rows.get_mut(i) {
+                    row.category = serde_json::from_str(&category).ok();
+                }
+            }
+            Msg::SetAmount(i, amount) => {
+                if let Some(row) = self.rows.get_mut(i) {
+                    row.amount = amount;
+                }
+            }
+            Msg::SetMemo(i, memo) => {
+                if let Some(row) = self.rows.get_mut(i) {
+                    row.memo = memo;
+                }
+            }
+            Msg::Save => {
+                let res = self.read_splits(ctx).and_then(|s| {
+                    splits::validate(ctx.props().transaction.amount, &s)
+                        .map(|_| s)
+                        .map_err(|e| e.to_string())
+                });
+
+                match res {
+                    Ok(splits) => Self::save(ctx, splits),
+                    Err(e) => self.error = Some(e),
+                }
+            }
+            Msg::Unsplit => Self::save(ctx, Vec::new()),
+            Msg::Saved => ctx.props().on_close.emit(true),
+            Msg::Error(e) => {
+                log::error!("{e}");
+                self.error = Some(e);
+            }
+        }
+
+        true
+    }
+}
@@ -0,0 +1,62 @@
+alter table "public"."transactions"
+    add column "iso_currency_code" text;
+
+alter table "public"."transactions"
+    add column "unofficial_currency_code" text;
+
+create table "public"."user_settings"
+(
+    "user_id"       uuid not null default auth.uid(),
+    "base_currency" text not null default 'USD'
+);
+
+alter table "public"."user_settings" enable row level security;
+
+CREATE UNIQUE INDEX user_settings_pkey ON public.user_settings USING btree (user_id);
+
+alter table "public"."user_settings"
+    add constraint "user_settings_pkey" PRIMARY KEY using index "user_settings_pkey";
+
+alter table "public"."user_settings"
+    add constraint "user_settings_user_id_fkey" FOREIGN KEY (user_id) REFERENCES auth.users (id) ON DELETE CASCADE not valid;
+
+alter table "public"."user_settings" validate constraint "user_settings_user_id_fkey";
+
+create
+policy "Authenticated Users Only"
+on "public"."user_settings"
+as permissive
+for all
+to authenticated
+using ((auth.uid() = user_id))
+with check ((auth.uid() = user_id));
+
+-- Rates are quoted as units of currency per one unit of the user's base currency
+create table "public"."exchange_rates"
+(
+    "user_id"    uuid             not null default auth.uid(),
+    "currency"   text             not null,
+    "rate"       double precision not null,
+    "updated_at" timestamp with time zone default now()
+);
+
+alter table "public"."exchange_rates" enable row level security;
+
+CREATE UNIQUE INDEX exchange_rates_pkey ON public.exchange_rates USING btree (user_id, currency);
+
+alter table "public"."exchange_rates"
+    add constraint "exchange_rates_pkey" PRIMARY KEY using index "exchange_rates_pkey";
+
+alter table "public"."exchange_rates"
+    add constraint "exchange_rates_user_id_fkey" FOREIGN KEY (user_id) REFERENCES auth.users (id) ON DELETE CASCADE not valid;
+
+alter table "public"."exchange_rates" validate constraint "exchange_rates_user_id_fkey";
+
+create
+policy "Authenticated Users Only"
+on "public"."exchange_rates"
+as permissive
+for all
+to authenticated
+using ((auth.uid() = user_id))
+with check ((auth.uid() = user_id));
@@ -0,0 +1,90 @@
+create table "public"."transaction_rules"
+(
+    "id"              bigint generated by default as identity,
+    "created_at"      timestamp with time zone default now(),
+    "user_id"         uuid    not null default auth.uid(),
+    "name"            text    not null,
+    "priority"        integer not null default 0,
+    "name_pattern"    text,
+    "amount_min"      double precision,
+    "amount_max"      double precision,
+    "account_id"      text,
+    "set_category"    text[],
+    "rename_merchant" text,
+    "mark_transfer"   boolean not null default false
+);
+
+alter table "public"."transaction_rules" enable row level security;
+
+CREATE UNIQUE INDEX transaction_rules_pkey ON public.transaction_rules USING btree (id);
+
+alter table "public"."transaction_rules"
+    add constraint "transaction_rules_pkey" PRIMARY KEY using index "transaction_rules_pkey";
+
+alter table "public"."transaction_rules"
+    add constraint "transaction_rules_user_id_fkey" FOREIGN KEY (user_id) REFERENCES auth.users (id) ON DELETE CASCADE not valid;
+
+alter table "public"."transaction_rules" validate constraint "transaction_rules_user_id_fkey";
+
+create
+policy "Authenticated Users Only"
+on "public"."transaction_rules"
+as permissive
+for all
+to authenticated
+using ((auth.uid() = user_id))
+with check ((auth.uid() = user_id));
+
+-- Overrides live outside of "transactions" so Plaid's modified upserts don't clobber them
+create table "public"."transaction_overrides"
+(
+    "transaction_id" text not null,
+    "user_id"        uuid not null default auth.uid(),
+    "category"       text[],
+    "merchant_name"  text,
+    "is_transfer"    boolean,
+    "rule_id"        bigint
+);
+
+alter table "public"."transaction_overrides" enable row level security;
+
+CREATE UNIQUE INDEX transaction_overrides_pkey ON public.transaction_overrides USING btree (transaction_id);
+
+alter table "public"."transaction_overrides"
+    add constraint "transaction_overrides_pkey" PRIMARY KEY using index "transaction_overrides_pkey";
+
+alter table "public"."transaction_overrides"
+    add constraint "transaction_overrides_user_id_fkey" FOREIGN KEY (user_id) REFERENCES auth.users (id) ON DELETE CASCADE not valid;
+
+alter table "public"."transaction_overrides" validate constraint "transaction_overrides_user_id_fkey";
+
+alter table "public"."transaction_overrides"
+    add constraint "transaction_overrides_rule_id_fkey" FOREIGN KEY (rule_id) REFERENCES transaction_rules (id) ON DELETE CASCADE not valid;
+
+alter table "public"."transaction_overrides" validate constraint "transaction_overrides_rule_id_fkey";
+
+create
+policy "Authenticated Users Only"
+on "public"."transaction_overrides"
+as permissive
+for all
+to authenticated
+using ((auth.uid() = user_id))
+with check ((auth.uid() = user_id));
+
+create view "public"."user_transactions" with (security_invoker = true) as
+select t.transaction_id,
+       t.account_id,
+       t.amount,
+       t.name,
+       t.date,
+       coalesce(o.category, t.category)           as category,
+       t.category_id,
+       coalesce(o.merchant_name, t.merchant_name) as merchant_name,
+       t.pending,
+       t.pending_transaction_id,
+       t.iso_currency_code,
+       t.unofficial_currency_code,
+       coalesce(o.is_transfer, false)             as is_transfer
+from transactions t
+         left join transaction_overrides o on o.transaction_id = t.transaction_id;
@@ -0,0 +1,32 @@
+-- Edits to the category taxonomy, keyed by the path a category was created with
+create table "public"."user_categories"
+(
+    "created_at"  timestamp with time zone default now(),
+    "user_id"     uuid    not null default auth.uid(),
+    "path"        text[]  not null,
+    "custom"      boolean not null default false,
+    "name"        text,
+    "merged_into" text[],
+    "hidden"      boolean not null default false
+);
+
+alter table "public"."user_categories" enable row level security;
+
+CREATE UNIQUE INDEX user_categories_pkey ON public.user_categories USING btree (user_id, path);
+
+alter table "public"."user_categories"
+    add constraint "user_categories_pkey" PRIMARY KEY using index "user_categories_pkey";
+
+alter table "public"."user_categories"
+    add constraint "user_categories_user_id_fkey" FOREIGN KEY (user_id) REFERENCES auth.users (id) ON DELETE CASCADE not valid;
+
+alter table "public"."user_categories" validate constraint "user_categories_user_id_fkey";
+
+create
+policy "Authenticated Users Only"
+on "public"."user_categories"
+as permissive
+for all
+to authenticated
+using ((auth.uid() = user_id))
+with check ((auth.uid() = user_id));
@@ -0,0 +1,20 @@
+alter table "public"."transactions"
+    add column "personal_finance_category" jsonb;
+
+create or replace view "public"."user_transactions" with (security_invoker = true) as
+select t.transaction_id,
+       t.account_id,
+       t.amount,
+       t.name,
+       t.date,
+       coalesce(o.category, t.category)           as category,
+       t.category_id,
+       coalesce(o.merchant_name, t.merchant_name) as merchant_name,
+       t.pending,
+       t.pending_transaction_id,
+       t.iso_currency_code,
+       t.unofficial_currency_code,
+       coalesce(o.is_transfer, false)             as is_transfer,
+       t.personal_finance_category
+from transactions t
+         left join transaction_overrides o on o.transaction_id = t.transaction_id;
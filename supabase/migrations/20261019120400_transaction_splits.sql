@@ -0,0 +1,33 @@
+create table "public"."transaction_splits"
+(
+    "id"             bigint generated by default as identity,
+    "created_at"     timestamp with time zone default now(),
+    "user_id"        uuid             not null default auth.uid(),
+    "transaction_id" text             not null,
+    "category"       text[]           not null,
+    "amount"         double precision not null,
+    "memo"           text
+);
+
+alter table "public"."transaction_splits" enable row level security;
+
+CREATE UNIQUE INDEX transaction_splits_pkey ON public.transaction_splits USING btree (id);
+
+CREATE INDEX transaction_splits_transaction_id_idx ON public.transaction_splits USING btree (transaction_id);
+
+alter table "public"."transaction_splits"
+    add constraint "transaction_splits_pkey" PRIMARY KEY using index "transaction_splits_pkey";
+
+alter table "public"."transaction_splits"
+    add constraint "transaction_splits_user_id_fkey" FOREIGN KEY (user_id) REFERENCES auth.users (id) ON DELETE CASCADE not valid;
+
+alter table "public"."transaction_splits" validate constraint "transaction_splits_user_id_fkey";
+
+create
+policy "Authenticated Users Only"
+on "public"."transaction_splits"
+as permissive
+for all
+to authenticated
+using ((auth.uid() = user_id))
+with check ((auth.uid() = user_id));
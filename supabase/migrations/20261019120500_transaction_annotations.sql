@@ -0,0 +1,98 @@
+create table "public"."transaction_annotations"
+(
+    "transaction_id" text   not null,
+    "user_id"        uuid   not null default auth.uid(),
+    "notes"          text,
+    "tags"           text[] not null default '{}'
+);
+
+alter table "public"."transaction_annotations" enable row level security;
+
+CREATE UNIQUE INDEX transaction_annotations_pkey ON public.transaction_annotations USING btree (transaction_id);
+
+CREATE INDEX transaction_annotations_tags_idx ON public.transaction_annotations USING gin (tags);
+
+alter table "public"."transaction_annotations"
+    add constraint "transaction_annotations_pkey" PRIMARY KEY using index "transaction_annotations_pkey";
+
+alter table "public"."transaction_annotations"
+    add constraint "transaction_annotations_user_id_fkey" FOREIGN KEY (user_id) REFERENCES auth.users (id) ON DELETE CASCADE not valid;
+
+alter table "public"."transaction_annotations" validate constraint "transaction_annotations_user_id_fkey";
+
+create
+policy "Authenticated Users Only"
+on "public"."transaction_annotations"
+as permissive
+for all
+to authenticated
+using ((auth.uid() = user_id))
+with check ((auth.uid() = user_id));
+
+create table "public"."transaction_attachments"
+(
+    "id"             bigint generated by default as identity,
+    "created_at"     timestamp with time zone default now(),
+    "user_id"        uuid not null default auth.uid(),
+    "transaction_id" text not null,
+    "file_name"      text not null,
+    "content_type"   text not null,
+    "storage_path"   text not null
+);
+
+alter table "public"."transaction_attachments" enable row level security;
+
+CREATE UNIQUE INDEX transaction_attachments_pkey ON public.transaction_attachments USING btree (id);
+
+CREATE INDEX transaction_attachments_transaction_id_idx ON public.transaction_attachments USING btree (transaction_id);
+
+alter table "public"."transaction_attachments"
+    add constraint "transaction_attachments_pkey" PRIMARY KEY using index "transaction_attachments_pkey";
+
+alter table "public"."transaction_attachments"
+    add constraint "transaction_attachments_user_id_fkey" FOREIGN KEY (user_id) REFERENCES auth.users (id) ON DELETE CASCADE not valid;
+
+alter table "public"."transaction_attachments" validate constraint "transaction_attachments_user_id_fkey";
+
+create
+policy "Authenticated Users Only"
+on "public"."transaction_attachments"
+as permissive
+for all
+to authenticated
+using ((auth.uid() = user_id))
+with check ((auth.uid() = user_id));
+
+-- Receipts are stored under a folder named after the owner's id
+insert into storage.buckets (id, name, public)
+values ('receipts', 'receipts', false);
+
+create
+policy "Users manage their own receipts"
+on "storage"."objects"
+as permissive
+for all
+to authenticated
+using (((bucket_id = 'receipts') and ((storage.foldername(name))[1] = (auth.uid())::text)))
+with check (((bucket_id = 'receipts') and ((storage.foldername(name))[1] = (auth.uid())::text)));
+
+create or replace view "public"."user_transactions" with (security_invoker = true) as
+select t.transaction_id,
+       t.account_id,
+       t.amount,
+       t.name,
+       t.date,
+       coalesce(o.category, t.category)           as category,
+       t.category_id,
+       coalesce(o.merchant_name, t.merchant_name) as merchant_name,
+       t.pending,
+       t.pending_transaction_id,
+       t.iso_currency_code,
+       t.unofficial_currency_code,
+       coalesce(o.is_transfer, false)             as is_transfer,
+       t.personal_finance_category,
+       a.notes,
+       coalesce(a.tags, '{}')                     as tags
+from transactions t
+         left join transaction_overrides o on o.transaction_id = t.transaction_id
+         left join transaction_annotations a on a.transaction_id = t.transaction_id;
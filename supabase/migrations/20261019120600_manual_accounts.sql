@@ -0,0 +1,165 @@
+create table "public"."manual_accounts"
+(
+    "account_id"        text             not null default ('manual-' || gen_random_uuid()),
+    "created_at"        timestamp with time zone default now(),
+    "user_id"           uuid             not null default auth.uid(),
+    "name"              text             not null,
+    "account_type"      text             not null default 'depository',
+    "subtype"           text,
+    "iso_currency_code" text,
+    "current_balance"   double precision not null default 0
+);
+
+alter table "public"."manual_accounts" enable row level security;
+
+CREATE UNIQUE INDEX manual_accounts_pkey ON public.manual_accounts USING btree (account_id);
+
+alter table "public"."manual_accounts"
+    add constraint "manual_accounts_pkey" PRIMARY KEY using index "manual_accounts_pkey";
+
+alter table "public"."manual_accounts"
+    add constraint "manual_accounts_user_id_fkey" FOREIGN KEY (user_id) REFERENCES auth.users (id) ON DELETE CASCADE not valid;
+
+alter table "public"."manual_accounts" validate constraint "manual_accounts_user_id_fkey";
+
+create
+policy "Authenticated Users Only"
+on "public"."manual_accounts"
+as permissive
+for all
+to authenticated
+using ((auth.uid() = user_id))
+with check ((auth.uid() = user_id));
+
+create table "public"."manual_account_balances"
+(
+    "id"         bigint generated by default as identity,
+    "created_at" timestamp with time zone default now(),
+    "user_id"    uuid             not null default auth.uid(),
+    "account_id" text             not null,
+    "balance"    double precision not null,
+    "date"       text             not null
+);
+
+alter table "public"."manual_account_balances" enable row level security;
+
+CREATE UNIQUE INDEX manual_account_balances_pkey ON public.manual_account_balances USING btree (id);
+
+CREATE INDEX manual_account_balances_account_id_idx ON public.manual_account_balances USING btree (account_id, date);
+
+alter table "public"."manual_account_balances"
+    add constraint "manual_account_balances_pkey" PRIMARY KEY using index "manual_account_balances_pkey";
+
+alter table "public"."manual_account_balances"
+    add constraint "manual_account_balances_user_id_fkey" FOREIGN KEY (user_id) REFERENCES auth.users (id) ON DELETE CASCADE not valid;
+
+alter table "public"."manual_account_balances" validate constraint "manual_account_balances_user_id_fkey";
+
+alter table "public"."manual_account_balances"
+    add constraint "manual_account_balances_account_id_fkey" FOREIGN KEY (account_id) REFERENCES manual_accounts (account_id) ON DELETE CASCADE not valid;
+
+alter table "public"."manual_account_balances" validate constraint "manual_account_balances_account_id_fkey";
+
+create
+policy "Authenticated Users Only"
+on "public"."manual_account_balances"
+as permissive
+for all
+to authenticated
+using ((auth.uid() = user_id))
+with check ((auth.uid() = user_id));
+
+create table "public"."manual_transactions"
+(
+    "transaction_id"    text             not null default ('manual-' || gen_random_uuid()),
+    "created_at"        timestamp with time zone default now(),
+    "user_id"           uuid             not null default auth.uid(),
+    "account_id"        text             not null,
+    "amount"            double precision not null,
+    "name"              text             not null,
+    "date"              text             not null,
+    "category"          text[],
+    "merchant_name"     text,
+    "iso_currency_code" text
+);
+
+alter table "public"."manual_transactions" enable row level security;
+
+CREATE UNIQUE INDEX manual_transactions_pkey ON public.manual_transactions USING btree (transaction_id);
+
+alter table "public"."manual_transactions"
+    add constraint "manual_transactions_pkey" PRIMARY KEY using index "manual_transactions_pkey";
+
+alter table "public"."manual_transactions"
+    add constraint "manual_transactions_user_id_fkey" FOREIGN KEY (user_id) REFERENCES auth.users (id) ON DELETE CASCADE not valid;
+
+alter table "public"."manual_transactions" validate constraint "manual_transactions_user_id_fkey";
+
+alter table "public"."manual_transactions"
+    add constraint "manual_transactions_account_id_fkey" FOREIGN KEY (account_id) REFERENCES manual_accounts (account_id) ON DELETE CASCADE not valid;
+
+alter table "public"."manual_transactions" validate constraint "manual_transactions_account_id_fkey";
+
+create
+policy "Authenticated Users Only"
+on "public"."manual_transactions"
+as permissive
+for all
+to authenticated
+using ((auth.uid() = user_id))
+with check ((auth.uid() = user_id));
+
+-- Manual transactions are listed alongside Plaid's so every view and budget picks them up
+drop view "public"."user_transactions";
+
+create view "public"."user_transactions" with (security_invoker = true) as
+select t.transaction_id,
+       t.account_id,
+       t.amount,
+       t.name,
+       t.date,
+       coalesce(o.category, t.category)           as category,
+       t.category_id,
+       coalesce(o.merchant_name, t.merchant_name) as merchant_name,
+       t.pending,
+       t.pending_transaction_id,
+       t.iso_currency_code,
+       t.unofficial_currency_code,
+       coalesce(o.is_transfer, false)             as is_transfer,
+       t.personal_finance_category,
+       a.notes,
+       coalesce(a.tags, '{}')                     as tags,
+       t.manual
+from (select transaction_id,
+             account_id,
+             amount,
+             name,
+             date,
+             category,
+             category_id,
+             merchant_name,
+             pending,
+             pending_transaction_id,
+             iso_currency_code,
+             unofficial_currency_code,
+             personal_finance_category,
+             false as manual
+      from transactions
+      union all
+      select transaction_id,
+             account_id,
+             amount,
+             name,
+             date,
+             category,
+             null,
+             merchant_name,
+             false,
+             null,
+             iso_currency_code,
+             null,
+             null,
+             true
+      from manual_transactions) t
+         left join transaction_overrides o on o.transaction_id = t.transaction_id
+         left join transaction_annotations a on a.transaction_id = t.transaction_id;
@@ -0,0 +1,34 @@
+-- One row per account per day, later fetches on the same day overwrite it
+create table "public"."balance_snapshots"
+(
+    "user_id"           uuid             not null default auth.uid(),
+    "account_id"        text             not null,
+    "date"              text             not null,
+    "account_type"      text             not null,
+    "current"           double precision not null,
+    "iso_currency_code" text,
+    "updated_at"        timestamp with time zone default now()
+);
+
+alter table "public"."balance_snapshots" enable row level security;
+
+CREATE UNIQUE INDEX balance_snapshots_pkey ON public.balance_snapshots USING btree (user_id, account_id, date);
+
+CREATE INDEX balance_snapshots_date_idx ON public.balance_snapshots USING btree (user_id, date);
+
+alter table "public"."balance_snapshots"
+    add constraint "balance_snapshots_pkey" PRIMARY KEY using index "balance_snapshots_pkey";
+
+alter table "public"."balance_snapshots"
+    add constraint "balance_snapshots_user_id_fkey" FOREIGN KEY (user_id) REFERENCES auth.users (id) ON DELETE CASCADE not valid;
+
+alter table "public"."balance_snapshots" validate constraint "balance_snapshots_user_id_fkey";
+
+create
+policy "Authenticated Users Only"
+on "public"."balance_snapshots"
+as permissive
+for all
+to authenticated
+using ((auth.uid() = user_id))
+with check ((auth.uid() = user_id));
@@ -0,0 +1,11 @@
+-- Budgets reset on their own period instead of always the calendar month
+alter table "public"."budgets"
+    add column "period" text not null default 'monthly',
+    add column "start_day" integer not null default 1,
+    add column "anchor_date" date;
+
+alter table "public"."budgets"
+    add constraint "budgets_period_check" CHECK (period in ('weekly', 'biweekly', 'monthly', 'quarterly', 'yearly'));
+
+alter table "public"."budgets"
+    add constraint "budgets_start_day_check" CHECK (start_day between 1 and 28);
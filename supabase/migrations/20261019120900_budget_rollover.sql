@@ -0,0 +1,33 @@
+alter table "public"."budgets" add column "rollover" boolean not null default false;
+
+-- Envelope ledger for rollover budgets, one row per budget per period
+create table "public"."budget_periods"
+(
+    "user_id"      uuid             not null default auth.uid(),
+    "category_id"  text             not null,
+    "period_start" date             not null,
+    "allocated"    double precision not null,
+    "carried"      double precision not null default 0,
+    "spent"        double precision not null default 0
+);
+
+alter table "public"."budget_periods" enable row level security;
+
+CREATE UNIQUE INDEX budget_periods_pkey ON public.budget_periods USING btree (user_id, category_id, period_start);
+
+alter table "public"."budget_periods"
+    add constraint "budget_periods_pkey" PRIMARY KEY using index "budget_periods_pkey";
+
+alter table "public"."budget_periods"
+    add constraint "budget_periods_budget_fkey" FOREIGN KEY (category_id, user_id) REFERENCES budgets (category_id, user_id) ON UPDATE CASCADE ON DELETE CASCADE not valid;
+
+alter table "public"."budget_periods" validate constraint "budget_periods_budget_fkey";
+
+create
+policy "Authenticated Users Only"
+on "public"."budget_periods"
+as permissive
+for all
+to authenticated
+using ((auth.uid() = user_id))
+with check ((auth.uid() = user_id));
@@ -0,0 +1,40 @@
+-- Income the user expects per period, from a payer or a category
+create table "public"."income_targets"
+(
+    "id"          bigint generated by default as identity,
+    "user_id"     uuid             not null default auth.uid(),
+    "source"      text             not null,
+    "expected"    double precision not null,
+    "period"      text             not null default 'monthly',
+    "start_day"   integer          not null default 1,
+    "anchor_date" date
+);
+
+alter table "public"."income_targets" enable row level security;
+
+CREATE UNIQUE INDEX income_targets_pkey ON public.income_targets USING btree (id);
+
+CREATE UNIQUE INDEX income_targets_source_idx ON public.income_targets USING btree (user_id, source);
+
+alter table "public"."income_targets"
+    add constraint "income_targets_pkey" PRIMARY KEY using index "income_targets_pkey";
+
+alter table "public"."income_targets"
+    add constraint "income_targets_period_check" CHECK (period in ('weekly', 'biweekly', 'monthly', 'quarterly', 'yearly'));
+
+alter table "public"."income_targets"
+    add constraint "income_targets_start_day_check" CHECK (start_day between 1 and 28);
+
+alter table "public"."income_targets"
+    add constraint "income_targets_user_id_fkey" FOREIGN KEY (user_id) REFERENCES auth.users (id) ON DELETE CASCADE not valid;
+
+alter table "public"."income_targets" validate constraint "income_targets_user_id_fkey";
+
+create
+policy "Authenticated Users Only"
+on "public"."income_targets"
+as permissive
+for all
+to authenticated
+using ((auth.uid() = user_id))
+with check ((auth.uid() = user_id));
@@ -0,0 +1,41 @@
+alter table "public"."user_settings" add column "zero_based" boolean not null default false;
+
+alter table "public"."budgets" add column "kind" text not null default 'spending';
+
+alter table "public"."budgets"
+    add constraint "budgets_kind_check" CHECK (kind in ('spending', 'goal', 'savings'));
+
+-- Assignments and moves between budgets in zero-based mode, a null side is unassigned income
+create table "public"."budget_allocations"
+(
+    "id"            bigint generated by default as identity,
+    "user_id"       uuid             not null default auth.uid(),
+    "period_start"  date             not null,
+    "from_category" text,
+    "to_category"   text,
+    "amount"        double precision not null,
+    "created_at"    timestamp with time zone default now()
+);
+
+alter table "public"."budget_allocations" enable row level security;
+
+CREATE UNIQUE INDEX budget_allocations_pkey ON public.budget_allocations USING btree (id);
+
+CREATE INDEX budget_allocations_period_idx ON public.budget_allocations USING btree (user_id, period_start);
+
+alter table "public"."budget_allocations"
+    add constraint "budget_allocations_pkey" PRIMARY KEY using index "budget_allocations_pkey";
+
+alter table "public"."budget_allocations"
+    add constraint "budget_allocations_user_id_fkey" FOREIGN KEY (user_id) REFERENCES auth.users (id) ON DELETE CASCADE not valid;
+
+alter table "public"."budget_allocations" validate constraint "budget_allocations_user_id_fkey";
+
+create
+policy "Authenticated Users Only"
+on "public"."budget_allocations"
+as permissive
+for all
+to authenticated
+using ((auth.uid() = user_id))
+with check ((auth.uid() = user_id));
@@ -0,0 +1,39 @@
+-- Percentages of a budget's max that send an alert, once each per period
+alter table "public"."user_settings" add column "alert_thresholds" integer[] not null default '{80,100}';
+
+create table "public"."budget_alerts"
+(
+    "id"           bigint generated by default as identity,
+    "user_id"      uuid             not null default auth.uid(),
+    "category_id"  text             not null,
+    "period_start" date             not null,
+    "period_end"   date             not null,
+    "threshold"    integer          not null,
+    "spent"        double precision not null,
+    "max"          double precision not null,
+    "read"         boolean          not null default false,
+    "created_at"   timestamp with time zone default now()
+);
+
+alter table "public"."budget_alerts" enable row level security;
+
+CREATE UNIQUE INDEX budget_alerts_pkey ON public.budget_alerts USING btree (id);
+
+CREATE UNIQUE INDEX budget_alerts_once_idx ON public.budget_alerts USING btree (user_id, category_id, period_start, threshold);
+
+alter table "public"."budget_alerts"
+    add constraint "budget_alerts_pkey" PRIMARY KEY using index "budget_alerts_pkey";
+
+alter table "public"."budget_alerts"
+    add constraint "budget_alerts_user_id_fkey" FOREIGN KEY (user_id) REFERENCES auth.users (id) ON DELETE CASCADE not valid;
+
+alter table "public"."budget_alerts" validate constraint "budget_alerts_user_id_fkey";
+
+create
+policy "Authenticated Users Only"
+on "public"."budget_alerts"
+as permissive
+for all
+to authenticated
+using ((auth.uid() = user_id))
+with check ((auth.uid() = user_id));
@@ -0,0 +1,39 @@
+-- Savings goals, tracked through linked accounts or a virtual sub-balance with tagged transfers
+create table "public"."goals"
+(
+    "id"            bigint generated by default as identity,
+    "user_id"       uuid             not null default auth.uid(),
+    "name"          text             not null,
+    "target_amount" double precision not null,
+    "target_date"   date,
+    "account_ids"   text[]           not null default '{}',
+    "tag"           text,
+    "saved"         double precision not null default 0,
+    "budgeted"      boolean          not null default false
+);
+
+alter table "public"."goals" enable row level security;
+
+CREATE UNIQUE INDEX goals_pkey ON public.goals USING btree (id);
+
+CREATE UNIQUE INDEX goals_name_idx ON public.goals USING btree (user_id, name);
+
+alter table "public"."goals"
+    add constraint "goals_pkey" PRIMARY KEY using index "goals_pkey";
+
+alter table "public"."goals"
+    add constraint "goals_target_amount_check" CHECK (target_amount > 0);
+
+alter table "public"."goals"
+    add constraint "goals_user_id_fkey" FOREIGN KEY (user_id) REFERENCES auth.users (id) ON DELETE CASCADE not valid;
+
+alter table "public"."goals" validate constraint "goals_user_id_fkey";
+
+create
+policy "Authenticated Users Only"
+on "public"."goals"
+as permissive
+for all
+to authenticated
+using ((auth.uid() = user_id))
+with check ((auth.uid() = user_id));
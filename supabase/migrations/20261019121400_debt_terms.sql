@@ -0,0 +1,35 @@
+-- APR and minimum payment per credit or loan account, for the payoff planner
+create table "public"."debt_terms"
+(
+    "user_id"         uuid             not null default auth.uid(),
+    "account_id"      text             not null,
+    "apr"             double precision not null default 0,
+    "minimum_payment" double precision not null default 0
+);
+
+alter table "public"."debt_terms" enable row level security;
+
+CREATE UNIQUE INDEX debt_terms_pkey ON public.debt_terms USING btree (user_id, account_id);
+
+alter table "public"."debt_terms"
+    add constraint "debt_terms_pkey" PRIMARY KEY using index "debt_terms_pkey";
+
+alter table "public"."debt_terms"
+    add constraint "debt_terms_apr_check" CHECK (apr >= 0);
+
+alter table "public"."debt_terms"
+    add constraint "debt_terms_minimum_payment_check" CHECK (minimum_payment >= 0);
+
+alter table "public"."debt_terms"
+    add constraint "debt_terms_user_id_fkey" FOREIGN KEY (user_id) REFERENCES auth.users (id) ON DELETE CASCADE not valid;
+
+alter table "public"."debt_terms" validate constraint "debt_terms_user_id_fkey";
+
+create
+policy "Authenticated Users Only"
+on "public"."debt_terms"
+as permissive
+for all
+to authenticated
+using ((auth.uid() = user_id))
+with check ((auth.uid() = user_id));
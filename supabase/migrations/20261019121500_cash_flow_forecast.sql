@@ -0,0 +1,40 @@
+-- Days the forecast total drops below this are highlighted
+alter table "public"."user_settings" add column "low_balance_threshold" double precision not null default 0;
+
+-- One-off or repeating payments the user expects, for the cash flow forecast
+create table "public"."scheduled_items"
+(
+    "id"                bigint generated by default as identity,
+    "user_id"           uuid             not null default auth.uid(),
+    "account_id"        text             not null,
+    "name"              text             not null,
+    "amount"            double precision not null,
+    "iso_currency_code" text,
+    "date"              date             not null,
+    "frequency"         text,
+    "end_date"          date
+);
+
+alter table "public"."scheduled_items" enable row level security;
+
+CREATE UNIQUE INDEX scheduled_items_pkey ON public.scheduled_items USING btree (id);
+
+alter table "public"."scheduled_items"
+    add constraint "scheduled_items_pkey" PRIMARY KEY using index "scheduled_items_pkey";
+
+alter table "public"."scheduled_items"
+    add constraint "scheduled_items_frequency_check" CHECK (frequency in ('weekly', 'biweekly', 'monthly', 'quarterly', 'yearly'));
+
+alter table "public"."scheduled_items"
+    add constraint "scheduled_items_user_id_fkey" FOREIGN KEY (user_id) REFERENCES auth.users (id) ON DELETE CASCADE not valid;
+
+alter table "public"."scheduled_items" validate constraint "scheduled_items_user_id_fkey";
+
+create
+policy "Authenticated Users Only"
+on "public"."scheduled_items"
+as permissive
+for all
+to authenticated
+using ((auth.uid() = user_id))
+with check ((auth.uid() = user_id));
@@ -0,0 +1,36 @@
+-- Spending summed per month, category, merchant and currency for reports, so
+-- ranges of any length come back as a few hundred rows instead of every
+-- transaction. Splits replace their parent, transfers are left out
+create or replace function "public"."spending_summary"(start_date text, end_date text)
+    returns table
+            (
+                "month"             text,
+                "category"          text[],
+                "pfc_primary"       text,
+                "merchant"          text,
+                "iso_currency_code" text,
+                "amount"            double precision,
+                "transactions"      bigint
+            )
+    language sql
+    stable
+    security invoker
+as
+$$
+select left(t.date, 7)                                                   as month,
+       coalesce(s.category, t.category)                                  as category,
+       case when s.id is null then t.personal_finance_category ->> 'primary' end as pfc_primary,
+       coalesce(t.merchant_name, t.name)                                 as merchant,
+       t.iso_currency_code,
+       sum(coalesce(s.amount, t.amount))                                 as amount,
+       count(distinct t.transaction_id)                                  as transactions
+from user_transactions t
+         left join transaction_splits s on s.transaction_id = t.transaction_id
+where not t.is_transfer
+  and not t.pending
+  and t.date >= start_date
+  and t.date < end_date
+group by 1, 2, 3, 4, 5;
+$$;
+
+grant execute on function "public"."spending_summary"(text, text) to authenticated;
@@ -0,0 +1,26 @@
+-- Transactions under a category filter, so the transactions view can filter,
+-- count and page on the server. `paths` holds every stored category path the
+-- filter covers, `pfc` the filter key when it's a PFC category. A split
+-- transaction matches when any of its splits does
+create or replace function "public"."transactions_in_category"(paths jsonb, pfc text default null)
+    returns setof user_transactions
+    language sql
+    stable
+    security invoker
+as
+$$
+select t.*
+from user_transactions t
+where case
+          when exists (select 1 from transaction_splits s where s.transaction_id = t.transaction_id)
+              then exists (select 1
+                           from transaction_splits s
+                                    join jsonb_array_elements(paths) p on p = to_jsonb(s.category)
+                           where s.transaction_id = t.transaction_id)
+          when pfc is not null and t.personal_finance_category is not null
+              then pfc in (t.personal_finance_category ->> 'primary', t.personal_finance_category ->> 'detailed')
+          else exists (select 1 from jsonb_array_elements(paths) p where p = to_jsonb(t.category))
+          end;
+$$;
+
+grant execute on function "public"."transactions_in_category"(jsonb, text) to authenticated;
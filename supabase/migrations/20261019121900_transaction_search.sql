@@ -0,0 +1,13 @@
+-- Trigram indexes so searching names and merchants with ilike doesn't scan
+-- every transaction
+create extension if not exists "pg_trgm" with schema "extensions";
+
+create index transactions_name_trgm_idx on public.transactions using gin (name extensions.gin_trgm_ops);
+
+create index transactions_merchant_name_trgm_idx on public.transactions using gin (merchant_name extensions.gin_trgm_ops);
+
+create index manual_transactions_name_trgm_idx on public.manual_transactions using gin (name extensions.gin_trgm_ops);
+
+create index manual_transactions_merchant_name_trgm_idx on public.manual_transactions using gin (merchant_name extensions.gin_trgm_ops);
+
+create index transaction_overrides_merchant_name_trgm_idx on public.transaction_overrides using gin (merchant_name extensions.gin_trgm_ops);
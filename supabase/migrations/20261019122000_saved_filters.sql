@@ -0,0 +1,33 @@
+-- Named transaction filters shown in the sidebar and used as report scopes.
+-- "filter" holds the frontend's `Filter` as JSON
+create table "public"."saved_filters"
+(
+    "id"         bigint generated by default as identity,
+    "created_at" timestamp with time zone default now(),
+    "user_id"    uuid  not null default auth.uid(),
+    "name"       text  not null,
+    "filter"     jsonb not null default '{}'
+);
+
+alter table "public"."saved_filters" enable row level security;
+
+CREATE UNIQUE INDEX saved_filters_pkey ON public.saved_filters USING btree (id);
+
+CREATE UNIQUE INDEX saved_filters_name_idx ON public.saved_filters USING btree (user_id, name);
+
+alter table "public"."saved_filters"
+    add constraint "saved_filters_pkey" PRIMARY KEY using index "saved_filters_pkey";
+
+alter table "public"."saved_filters"
+    add constraint "saved_filters_user_id_fkey" FOREIGN KEY (user_id) REFERENCES auth.users (id) ON DELETE CASCADE not valid;
+
+alter table "public"."saved_filters" validate constraint "saved_filters_user_id_fkey";
+
+create
+policy "Authenticated Users Only"
+on "public"."saved_filters"
+as permissive
+for all
+to authenticated
+using ((auth.uid() = user_id))
+with check ((auth.uid() = user_id));
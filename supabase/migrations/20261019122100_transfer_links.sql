@@ -0,0 +1,99 @@
+-- Pairs of transactions moving money between the user's own accounts, like a
+-- card payment and the payment received on the card. Linked pairs count as
+-- transfers, so neither side is spending or income. Unlinked pairs are kept so
+-- the matcher doesn't pair them again
+create table "public"."transfer_links"
+(
+    "id"         bigint generated by default as identity,
+    "created_at" timestamp with time zone default now(),
+    "user_id"    uuid    not null default auth.uid(),
+    "outflow_id" text    not null,
+    "inflow_id"  text    not null,
+    "manual"     boolean not null default false,
+    "linked"     boolean not null default true
+);
+
+alter table "public"."transfer_links" enable row level security;
+
+CREATE UNIQUE INDEX transfer_links_pkey ON public.transfer_links USING btree (id);
+
+CREATE UNIQUE INDEX transfer_links_pair_idx ON public.transfer_links USING btree (user_id, outflow_id, inflow_id);
+
+-- A transaction is one side of at most one linked transfer
+CREATE UNIQUE INDEX transfer_links_outflow_idx ON public.transfer_links USING btree (outflow_id) WHERE linked;
+
+CREATE UNIQUE INDEX transfer_links_inflow_idx ON public.transfer_links USING btree (inflow_id) WHERE linked;
+
+alter table "public"."transfer_links"
+    add constraint "transfer_links_pkey" PRIMARY KEY using index "transfer_links_pkey";
+
+alter table "public"."transfer_links"
+    add constraint "transfer_links_user_id_fkey" FOREIGN KEY (user_id) REFERENCES auth.users (id) ON DELETE CASCADE not valid;
+
+alter table "public"."transfer_links" validate constraint "transfer_links_user_id_fkey";
+
+create
+policy "Authenticated Users Only"
+on "public"."transfer_links"
+as permissive
+for all
+to authenticated
+using ((auth.uid() = user_id))
+with check ((auth.uid() = user_id));
+
+-- Overrides still win, so a transaction marked by hand or by a rule keeps its flag
+create or replace view "public"."user_transactions" with (security_invoker = true) as
+select t.transaction_id,
+       t.account_id,
+       t.amount,
+       t.name,
+       t.date,
+       coalesce(o.category, t.category)           as category,
+       t.category_id,
+       coalesce(o.merchant_name, t.merchant_name) as merchant_name,
+       t.pending,
+       t.pending_transaction_id,
+       t.iso_currency_code,
+       t.unofficial_currency_code,
+       coalesce(o.is_transfer, exists(select 1
+                                      from transfer_links l
+                                      where l.linked
+                                        and (l.outflow_id = t.transaction_id or l.inflow_id = t.transaction_id)))
+                                                  as is_transfer,
+       t.personal_finance_category,
+       a.notes,
+       coalesce(a.tags, '{}')                     as tags,
+       t.manual
+from (select transaction_id,
+             account_id,
+             amount,
+             name,
+             date,
+             category,
+             category_id,
+             merchant_name,
+             pending,
+             pending_transaction_id,
+             iso_currency_code,
+             unofficial_currency_code,
+             personal_finance_category,
+             false as manual
+      from transactions
+      union all
+      select transaction_id,
+             account_id,
+             amount,
+             name,
+             date,
+             category,
+             null,
+             merchant_name,
+             false,
+             null,
+             iso_currency_code,
+             null,
+             null,
+             true
+      from manual_transactions) t
+         left join transaction_overrides o on o.transaction_id = t.transaction_id
+         left join transaction_annotations a on a.transaction_id = t.transaction_id;
@@ -0,0 +1,38 @@
+-- Leaves pending transactions out of budgets until they post
+alter table "public"."user_settings" add column "exclude_pending" boolean not null default false;
+
+create or replace function "public"."category_totals"(start_date text, end_date text)
+    returns table
+            (
+                "date"              text,
+                "category"          text[],
+                "pfc_primary"       text,
+                "pfc_detailed"      text,
+                "payer"             text,
+                "iso_currency_code" text,
+                "amount"            double precision,
+                "transactions"      bigint
+            )
+    language sql
+    stable
+    security invoker
+as
+$$
+select t.date,
+       coalesce(s.category, t.category)                                           as category,
+       case when s.id is null then t.personal_finance_category ->> 'primary' end  as pfc_primary,
+       case when s.id is null then t.personal_finance_category ->> 'detailed' end as pfc_detailed,
+       case when coalesce(s.amount, t.amount) < 0 then coalesce(t.merchant_name, t.name) end as payer,
+       t.iso_currency_code,
+       sum(coalesce(s.amount, t.amount))                                          as amount,
+       count(distinct t.transaction_id)                                           as transactions
+from user_transactions t
+         left join transaction_splits s on s.transaction_id = t.transaction_id
+where not t.is_transfer
+  and not (t.pending and coalesce((select u.exclude_pending
+                                   from user_settings u
+                                   where u.user_id = auth.uid()), false))
+  and t.date >= start_date
+  and t.date < end_date
+group by 1, 2, 3, 4, 5, 6, coalesce(s.amount, t.amount) < 0;
+$$;
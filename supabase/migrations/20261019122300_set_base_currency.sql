@@ -0,0 +1,26 @@
+-- Changes the base currency and replaces the requoted rates in one transaction,
+-- so a failed insert can't leave the user without rates
+create or replace function "public"."set_base_currency"(new_base text, new_rates jsonb)
+    returns void
+    language plpgsql
+    security invoker
+as
+$$
+begin
+    if new_base is null or new_base !~ '^[A-Z]{3}$' then
+        raise exception 'Invalid base currency %', new_base;
+    end if;
+
+    insert into user_settings (user_id, base_currency)
+    values (auth.uid(), new_base)
+    on conflict (user_id) do update set base_currency = excluded.base_currency;
+
+    delete from exchange_rates where user_id = auth.uid();
+
+    insert into exchange_rates (user_id, currency, rate)
+    select auth.uid(), r.currency, r.rate
+    from jsonb_to_recordset(new_rates) as r(currency text, rate double precision);
+end;
+$$;
+
+grant execute on function "public"."set_base_currency"(text, jsonb) to authenticated;
@@ -0,0 +1,65 @@
+-- Budgets, alerts, allocations, income targets and saved filters referred to
+-- categories by display name, so renaming one orphaned them. Re-key names of
+-- renamed and custom categories by the path the category was created with,
+-- see recurr_core::categories::key. Plaid's original names still resolve
+create temporary table "category_keys"
+(
+    "user_id" uuid not null,
+    "name"    text not null,
+    "key"     text not null,
+    primary key (user_id, name)
+);
+
+insert into category_keys (user_id, name, key)
+select distinct on (c.user_id, c.name) c.user_id, c.name, array_to_string(c.path, ' > ')
+from user_categories c
+where c.name is not null
+  and c.name <> array_to_string(c.path, ' > ')
+order by c.user_id, c.name, array_length(c.path, 1);
+
+-- Budget periods follow through their foreign key
+update budgets b
+set category_id = k.key
+from category_keys k
+where k.user_id = b.user_id
+  and k.name = b.category_id
+  and not exists (select 1 from budgets e where e.user_id = b.user_id and e.category_id = k.key);
+
+update budget_alerts a
+set category_id = k.key
+from category_keys k
+where k.user_id = a.user_id
+  and k.name = a.category_id
+  and not exists (select 1
+                  from budget_alerts e
+                  where e.user_id = a.user_id
+                    and e.category_id = k.key
+                    and e.period_start = a.period_start
+                    and e.threshold = a.threshold);
+
+update budget_allocations a
+set from_category = k.key
+from category_keys k
+where k.user_id = a.user_id
+  and k.name = a.from_category;
+
+update budget_allocations a
+set to_category = k.key
+from category_keys k
+where k.user_id = a.user_id
+  and k.name = a.to_category;
+
+update income_targets t
+set source = k.key
+from category_keys k
+where k.user_id = t.user_id
+  and k.name = t.source
+  and not exists (select 1 from income_targets e where e.user_id = t.user_id and e.source = k.key);
+
+update saved_filters f
+set filter = jsonb_set(f.filter, '{category}', to_jsonb(k.key))
+from category_keys k
+where k.user_id = f.user_id
+  and k.name = f.filter ->> 'category';
+
+drop table category_keys;
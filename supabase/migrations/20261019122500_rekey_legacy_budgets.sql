@@ -0,0 +1,31 @@
+-- Moves budgets keyed by legacy category names onto PFC keys in one
+-- transaction. The app works out the moves from recurr_core::pfc::LEGACY_TO_PFC,
+-- budgets moving onto the same key are summed
+create or replace function "public"."rekey_budgets"(moves jsonb)
+    returns void
+    language plpgsql
+    security invoker
+as
+$$
+begin
+    with moved as (select m.to_key, sum(b.max) as max, min(b.category_id) as first
+                   from budgets b
+                            join jsonb_to_recordset(moves) as m(from_key text, to_key text)
+                                 on m.from_key = b.category_id
+                   where b.user_id = auth.uid()
+                   group by m.to_key)
+    insert
+    into budgets (category_id, user_id, max, period, start_day, anchor_date, rollover, kind)
+    select moved.to_key, b.user_id, moved.max, b.period, b.start_day, b.anchor_date, b.rollover, b.kind
+    from moved
+             join budgets b on b.category_id = moved.first and b.user_id = auth.uid()
+    on conflict (category_id, user_id) do update set max = budgets.max + excluded.max;
+
+    delete
+    from budgets b using jsonb_to_recordset(moves) as m(from_key text, to_key text)
+    where b.user_id = auth.uid()
+      and b.category_id = m.from_key;
+end;
+$$;
+
+grant execute on function "public"."rekey_budgets"(jsonb) to authenticated;
@@ -0,0 +1,22 @@
+-- Replaces a transaction's splits in one transaction, so a failed insert can't
+-- leave it with none. An empty list removes them
+create or replace function "public"."replace_splits"(split_transaction_id text, new_splits jsonb)
+    returns setof transaction_splits
+    language plpgsql
+    security invoker
+as
+$$
+begin
+    delete from transaction_splits
+    where user_id = auth.uid()
+      and transaction_id = split_transaction_id;
+
+    return query
+        insert into transaction_splits (user_id, transaction_id, category, amount, memo)
+            select auth.uid(), split_transaction_id, r.category, r.amount, r.memo
+            from jsonb_to_recordset(new_splits) as r(category text[], amount double precision, memo text)
+            returning *;
+end;
+$$;
+
+grant execute on function "public"."replace_splits"(text, jsonb) to authenticated;
@@ -0,0 +1,159 @@
+-- Searches go through user_transactions, where the merchant is coalesced with
+-- overrides and notes come from annotations, so trigram indexes on the base
+-- columns went unused. Keep what's searched on the transaction rows themselves,
+-- refreshed when overrides or annotations change, and index that instead
+alter table "public"."transactions"
+    add column "search_merchant" text,
+    add column "search_text"     text;
+
+alter table "public"."manual_transactions"
+    add column "search_merchant" text,
+    add column "search_text"     text;
+
+-- The merchant after overrides, and the name, merchant and notes a bare word
+-- matches, one per line so a search can't match across them
+create or replace function "public"."transaction_search"(row_id text, row_name text, row_merchant_name text,
+                                                         out search_merchant text, out search_text text)
+    language sql
+    stable
+    security invoker
+as
+$$
+select m.merchant,
+       concat_ws(E'\n', row_name, m.merchant,
+                 (select a.notes from transaction_annotations a where a.transaction_id = row_id))
+from (select coalesce((select o.merchant_name from transaction_overrides o where o.transaction_id = row_id),
+                      row_merchant_name) as merchant) m;
+$$;
+
+create or replace function "public"."set_transaction_search"()
+    returns trigger
+    language plpgsql
+    security invoker
+as
+$$
+begin
+    select s.search_merchant, s.search_text
+    into new.search_merchant, new.search_text
+    from transaction_search(new.transaction_id, new.name, new.merchant_name) s;
+
+    return new;
+end;
+$$;
+
+create trigger "transactions_search"
+    before insert or update of name, merchant_name
+    on "public"."transactions"
+    for each row
+execute function set_transaction_search();
+
+create trigger "manual_transactions_search"
+    before insert or update of name, merchant_name
+    on "public"."manual_transactions"
+    for each row
+execute function set_transaction_search();
+
+-- Touching the columns the triggers above watch recomputes the search columns
+create or replace function "public"."refresh_transaction_search"()
+    returns trigger
+    language plpgsql
+    security invoker
+as
+$$
+begin
+    update transactions t set name = t.name where t.transaction_id in (new.transaction_id, old.transaction_id);
+    update manual_transactions t set name = t.name where t.transaction_id in (new.transaction_id, old.transaction_id);
+
+    return null;
+end;
+$$;
+
+create trigger "transaction_overrides_search"
+    after insert or update or delete
+    on "public"."transaction_overrides"
+    for each row
+execute function refresh_transaction_search();
+
+create trigger "transaction_annotations_search"
+    after insert or update or delete
+    on "public"."transaction_annotations"
+    for each row
+execute function refresh_transaction_search();
+
+update transactions set name = name;
+update manual_transactions set name = name;
+
+drop index if exists transactions_merchant_name_trgm_idx;
+drop index if exists manual_transactions_merchant_name_trgm_idx;
+drop index if exists transaction_overrides_merchant_name_trgm_idx;
+
+create index transactions_search_merchant_trgm_idx on public.transactions using gin (search_merchant extensions.gin_trgm_ops);
+
+create index transactions_search_text_trgm_idx on public.transactions using gin (search_text extensions.gin_trgm_ops);
+
+create index manual_transactions_search_merchant_trgm_idx on public.manual_transactions using gin (search_merchant extensions.gin_trgm_ops);
+
+create index manual_transactions_search_text_trgm_idx on public.manual_transactions using gin (search_text extensions.gin_trgm_ops);
+
+-- Same as before with the search columns added at the end
+create or replace view "public"."user_transactions" with (security_invoker = true) as
+select t.transaction_id,
+       t.account_id,
+       t.amount,
+       t.name,
+       t.date,
+       coalesce(o.category, t.category)           as category,
+       t.category_id,
+       coalesce(o.merchant_name, t.merchant_name) as merchant_name,
+       t.pending,
+       t.pending_transaction_id,
+       t.iso_currency_code,
+       t.unofficial_currency_code,
+       coalesce(o.is_transfer, exists(select 1
+                                      from transfer_links l
+                                      where l.linked
+                                        and (l.outflow_id = t.transaction_id or l.inflow_id = t.transaction_id)))
+                                                  as is_transfer,
+       t.personal_finance_category,
+       a.notes,
+       coalesce(a.tags, '{}')                     as tags,
+       t.manual,
+       t.search_merchant,
+       t.search_text
+from (select transaction_id,
+             account_id,
+             amount,
+             name,
+             date,
+             category,
+             category_id,
+             merchant_name,
+             pending,
+             pending_transaction_id,
+             iso_currency_code,
+             unofficial_currency_code,
+             personal_finance_category,
+             false as manual,
+             search_merchant,
+             search_text
+      from transactions
+      union all
+      select transaction_id,
+             account_id,
+             amount,
+             name,
+             date,
+             category,
+             null,
+             merchant_name,
+             false,
+             null,
+             iso_currency_code,
+             null,
+             null,
+             true,
+             search_merchant,
+             search_text
+      from manual_transactions) t
+         left join transaction_overrides o on o.transaction_id = t.transaction_id
+         left join transaction_annotations a on a.transaction_id = t.transaction_id;
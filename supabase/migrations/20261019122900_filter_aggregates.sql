@@ -0,0 +1,113 @@
+-- Saved filters summed and summarized on the server. The sidebar and reports
+-- scoped by a saved filter used to download every transaction it matched.
+-- `filter` comes from recurr_core::filters::Filter::params and holds the same
+-- conditions Filter::query sends as PostgREST filters
+create or replace function "public"."filter_transactions"(filter jsonb)
+    returns setof user_transactions
+    language sql
+    stable
+    security invoker
+as
+$$
+select t.*
+from user_transactions t
+where (filter ->> 'paths' is null
+    or case
+           when exists (select 1 from transaction_splits s where s.transaction_id = t.transaction_id)
+               then exists (select 1
+                            from transaction_splits s
+                                     join jsonb_array_elements(filter -> 'paths') p on p = to_jsonb(s.category)
+                            where s.transaction_id = t.transaction_id)
+           when filter ->> 'pfc' is not null and t.personal_finance_category is not null
+               then filter ->> 'pfc' in (t.personal_finance_category ->> 'primary',
+                                         t.personal_finance_category ->> 'detailed')
+           else exists (select 1 from jsonb_array_elements(filter -> 'paths') p where p = to_jsonb(t.category))
+           end)
+  and (filter ->> 'start_date' is null or t.date >= filter ->> 'start_date')
+  and (filter ->> 'end_date' is null or t.date <= filter ->> 'end_date')
+  and (filter ->> 'tag' is null or t.tags @> array [filter ->> 'tag'])
+  and (filter ->> 'account_id' is null or t.account_id = filter ->> 'account_id')
+  and (filter ->> 'min_amount' is null or t.amount >= (filter ->> 'min_amount')::double precision)
+  and (filter ->> 'max_amount' is null or t.amount <= (filter ->> 'max_amount')::double precision)
+  and (filter ->> 'merchant' is null or t.search_merchant ilike '%' || (filter ->> 'merchant') || '%')
+  and (filter ->> 'pending' is null or t.pending = (filter ->> 'pending')::boolean)
+  and (not coalesce((filter ->> 'uncategorized')::boolean, false)
+    or (t.personal_finance_category is null and (t.category is null or t.category = '{}')))
+  -- Every search term matches, or doesn't when negated. Rows with a null
+  -- column never match, so negated terms keep them like Query::filters does
+  and not exists (select 1
+                  from jsonb_array_elements(coalesce(filter -> 'terms', '[]')) c
+                  where (c ->> 'negated')::boolean = coalesce(
+                          case c ->> 'kind'
+                              when 'text' then t.search_text ilike '%' || (c ->> 'value') || '%'
+                              when 'merchant' then t.search_merchant ilike '%' || (c ->> 'value') || '%'
+                              when 'name' then t.name ilike '%' || (c ->> 'value') || '%'
+                              when 'tag' then t.tags @> array [c ->> 'value']
+                              when 'amount' then case c ->> 'op'
+                                                     when 'lt' then t.amount < (c ->> 'value')::double precision
+                                                     when 'lte' then t.amount <= (c ->> 'value')::double precision
+                                                     when 'eq' then t.amount = (c ->> 'value')::double precision
+                                                     when 'gte' then t.amount >= (c ->> 'value')::double precision
+                                                     when 'gt' then t.amount > (c ->> 'value')::double precision
+                                  end
+                              when 'before' then t.date < c ->> 'value'
+                              when 'after' then t.date > c ->> 'value'
+                              when 'on' then t.date = c ->> 'value'
+                              when 'pending' then t.pending
+                              end, false));
+$$;
+
+grant execute on function "public"."filter_transactions"(jsonb) to authenticated;
+
+-- Net amount of a saved filter per currency, transfers left out
+create or replace function "public"."filter_totals"(filter jsonb)
+    returns table
+            (
+                "iso_currency_code" text,
+                "amount"            double precision
+            )
+    language sql
+    stable
+    security invoker
+as
+$$
+select t.iso_currency_code, sum(t.amount) as amount
+from filter_transactions(filter) t
+where not t.is_transfer
+group by 1;
+$$;
+
+grant execute on function "public"."filter_totals"(jsonb) to authenticated;
+
+-- spending_summary over what a saved filter matches, for scoped reports
+create or replace function "public"."filter_spending_summary"(filter jsonb)
+    returns table
+            (
+                "month"             text,
+                "category"          text[],
+                "pfc_primary"       text,
+                "merchant"          text,
+                "iso_currency_code" text,
+                "amount"            double precision,
+                "transactions"      bigint
+            )
+    language sql
+    stable
+    security invoker
+as
+$$
+select left(t.date, 7)                                                   as month,
+       coalesce(s.category, t.category)                                  as category,
+       case when s.id is null then t.personal_finance_category ->> 'primary' end as pfc_primary,
+       coalesce(t.merchant_name, t.name)                                 as merchant,
+       t.iso_currency_code,
+       sum(coalesce(s.amount, t.amount))                                 as amount,
+       count(distinct t.transaction_id)                                  as transactions
+from filter_transactions(filter) t
+         left join transaction_splits s on s.transaction_id = t.transaction_id
+where not t.is_transfer
+  and not t.pending
+group by 1, 2, 3, 4, 5;
+$$;
+
+grant execute on function "public"."filter_spending_summary"(jsonb) to authenticated;
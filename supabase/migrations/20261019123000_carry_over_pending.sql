@@ -0,0 +1,62 @@
+-- Moves what the user added to pending transactions onto the posted ones in
+-- one transaction, so a failed write can't leave it split between them.
+-- `successors` maps pending transaction ids to the posted ones, `annotations`
+-- holds the merged annotations from recurr_core::pending::carry_annotations
+create or replace function "public"."carry_over_pending"(successors jsonb, annotations jsonb)
+    returns void
+    language plpgsql
+    security invoker
+as
+$$
+begin
+    insert into transaction_annotations (transaction_id, user_id, notes, tags)
+    select r.transaction_id, r.user_id, r.notes, r.tags
+    from jsonb_to_recordset(annotations) as r(transaction_id text, user_id uuid, notes text, tags text[])
+    on conflict (transaction_id) do update set notes = excluded.notes,
+                                               tags  = excluded.tags;
+
+    -- Rule overrides were already made for the posted transactions
+    insert into transaction_overrides (transaction_id, user_id, category, merchant_name, is_transfer, rule_id)
+    select m.posted_id, o.user_id, o.category, o.merchant_name, o.is_transfer, o.rule_id
+    from transaction_overrides o
+             join jsonb_each_text(successors) as m(pending_id, posted_id) on m.pending_id = o.transaction_id
+    where o.rule_id is null
+    on conflict (transaction_id) do update set category      = excluded.category,
+                                               merchant_name = excluded.merchant_name,
+                                               is_transfer   = excluded.is_transfer,
+                                               rule_id       = excluded.rule_id;
+
+    delete from transaction_annotations a
+    using jsonb_each_text(successors) as m(pending_id, posted_id)
+    where a.transaction_id = m.pending_id;
+
+    delete from transaction_overrides o
+    using jsonb_each_text(successors) as m(pending_id, posted_id)
+    where o.transaction_id = m.pending_id;
+
+    -- Splits already on the posted transaction win like its notes, two sets
+    -- would each add up to the amount
+    delete from transaction_splits s
+    using jsonb_each_text(successors) as m(pending_id, posted_id)
+    where s.transaction_id = m.pending_id
+      and exists (select 1 from transaction_splits p where p.transaction_id = m.posted_id);
+
+    update transaction_splits s set transaction_id = m.posted_id
+    from jsonb_each_text(successors) as m(pending_id, posted_id)
+    where s.transaction_id = m.pending_id;
+
+    update transaction_attachments a set transaction_id = m.posted_id
+    from jsonb_each_text(successors) as m(pending_id, posted_id)
+    where a.transaction_id = m.pending_id;
+
+    update transfer_links l set outflow_id = m.posted_id
+    from jsonb_each_text(successors) as m(pending_id, posted_id)
+    where l.outflow_id = m.pending_id;
+
+    update transfer_links l set inflow_id = m.posted_id
+    from jsonb_each_text(successors) as m(pending_id, posted_id)
+    where l.inflow_id = m.pending_id;
+end;
+$$;
+
+grant execute on function "public"."carry_over_pending"(jsonb, jsonb) to authenticated;
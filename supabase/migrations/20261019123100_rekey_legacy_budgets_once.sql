@@ -0,0 +1,129 @@
+-- Moves spending budgets keyed by a legacy category name onto the PFC key
+-- once, replacing the rekey the budgets page ran on every load. Root categories
+-- of the taxonomy share those names, so budgets made on them from now on stay
+-- as they are. The keys are recurr_core::pfc::LEGACY_TO_PFC at the time
+drop function if exists "public"."rekey_budgets"(jsonb);
+
+create temporary table "legacy_keys"
+(
+    "legacy" text not null primary key,
+    "pfc"    text not null
+);
+
+insert into legacy_keys (legacy, pfc)
+values
+       ('Bank Fees', 'BANK_FEES'),
+       ('Community', 'GOVERNMENT_AND_NON_PROFIT'),
+       ('Food and Drink', 'FOOD_AND_DRINK'),
+       ('Restaurants', 'FOOD_AND_DRINK_RESTAURANT'),
+       ('Coffee Shop', 'FOOD_AND_DRINK_COFFEE'),
+       ('Fast Food', 'FOOD_AND_DRINK_FAST_FOOD'),
+       ('Bar', 'FOOD_AND_DRINK_BEER_WINE_AND_LIQUOR'),
+       ('Healthcare', 'MEDICAL'),
+       ('Interest', 'INCOME'),
+       ('Interest Earned', 'INCOME_INTEREST_EARNED'),
+       ('Payment', 'LOAN_PAYMENTS'),
+       ('Credit Card', 'LOAN_PAYMENTS_CREDIT_CARD_PAYMENT'),
+       ('Rent', 'RENT_AND_UTILITIES_RENT'),
+       ('Recreation', 'ENTERTAINMENT'),
+       ('Gyms and Fitness Centers', 'PERSONAL_CARE_GYMS_AND_FITNESS_CENTERS'),
+       ('Service', 'GENERAL_SERVICES'),
+       ('Utilities', 'RENT_AND_UTILITIES'),
+       ('Personal Care', 'PERSONAL_CARE'),
+       ('Home Improvement', 'HOME_IMPROVEMENT'),
+       ('Hardware Store', 'HOME_IMPROVEMENT_HARDWARE'),
+       ('Shops', 'GENERAL_MERCHANDISE'),
+       ('Supermarkets and Groceries', 'FOOD_AND_DRINK_GROCERIES'),
+       ('Pharmacies', 'MEDICAL_PHARMACIES_AND_SUPPLEMENTS'),
+       ('Tax', 'GOVERNMENT_AND_NON_PROFIT_TAX_PAYMENT'),
+       ('Transfer', 'TRANSFER_OUT'),
+       ('Deposit', 'TRANSFER_IN'),
+       ('Payroll', 'INCOME_WAGES'),
+       ('Travel', 'TRAVEL'),
+       ('Gas Stations', 'TRANSPORTATION_GAS'),
+       ('Public Transportation Services', 'TRANSPORTATION_PUBLIC_TRANSIT'),
+       ('Taxi', 'TRANSPORTATION_TAXIS_AND_RIDE_SHARES'),
+       ('Car Service', 'TRANSPORTATION_TAXIS_AND_RIDE_SHARES'),
+       ('Parking', 'TRANSPORTATION_PARKING');
+
+-- The first budget moving onto a key nobody has takes it over with an update,
+-- so its rollover ledger follows through the foreign key. The rest are merged
+create temporary table "budget_moves" as
+select b.user_id,
+       b.category_id as from_key,
+       k.pfc         as to_key,
+       row_number() over (partition by b.user_id, k.pfc order by b.category_id) = 1
+           and not exists (select 1 from budgets e where e.user_id = b.user_id and e.category_id = k.pfc)
+                     as renamed
+from budgets b
+         join legacy_keys k on k.legacy = b.category_id
+where b.kind = 'spending';
+
+update budgets b
+set category_id = m.to_key
+from budget_moves m
+where m.renamed
+  and b.user_id = m.user_id
+  and b.category_id = m.from_key;
+
+update budgets b
+set max = b.max + m.max
+from (select m.user_id, m.to_key, sum(f.max) as max
+      from budget_moves m
+               join budgets f on f.user_id = m.user_id and f.category_id = m.from_key
+      where not m.renamed
+      group by m.user_id, m.to_key) m
+where b.user_id = m.user_id
+  and b.category_id = m.to_key;
+
+insert into budget_periods (user_id, category_id, period_start, allocated, carried, spent)
+select p.user_id, m.to_key, p.period_start, sum(p.allocated), sum(p.carried), sum(p.spent)
+from budget_periods p
+         join budget_moves m on m.user_id = p.user_id and m.from_key = p.category_id
+where not m.renamed
+group by p.user_id, m.to_key, p.period_start
+on conflict (user_id, category_id, period_start) do update set allocated = budget_periods.allocated + excluded.allocated,
+                                                              carried   = budget_periods.carried + excluded.carried,
+                                                              spent     = budget_periods.spent + excluded.spent;
+
+-- Alerts follow the budget that took the key over, ones already sent for the
+-- key cover the merged budgets
+update budget_alerts a
+set category_id = m.to_key
+from budget_moves m
+where m.renamed
+  and a.user_id = m.user_id
+  and a.category_id = m.from_key
+  and not exists (select 1
+                  from budget_alerts e
+                  where e.user_id = a.user_id
+                    and e.category_id = m.to_key
+                    and e.period_start = a.period_start
+                    and e.threshold = a.threshold);
+
+delete
+from budget_alerts a using budget_moves m
+where a.user_id = m.user_id
+  and a.category_id = m.from_key;
+
+update budget_allocations a
+set from_category = m.to_key
+from budget_moves m
+where a.user_id = m.user_id
+  and a.from_category = m.from_key;
+
+update budget_allocations a
+set to_category = m.to_key
+from budget_moves m
+where a.user_id = m.user_id
+  and a.to_category = m.from_key;
+
+-- Their ledgers were merged above and go with them
+delete
+from budgets b using budget_moves m
+where not m.renamed
+  and b.user_id = m.user_id
+  and b.category_id = m.from_key;
+
+drop table budget_moves;
+drop table legacy_keys;
@@ -0,0 +1,71 @@
+-- Reports left pending transactions out regardless of the `exclude_pending`
+-- setting budgets follow. Both summaries now follow it as well
+create or replace function "public"."spending_summary"(start_date text, end_date text)
+    returns table
+            (
+                "month"             text,
+                "category"          text[],
+                "pfc_primary"       text,
+                "merchant"          text,
+                "iso_currency_code" text,
+                "amount"            double precision,
+                "transactions"      bigint
+            )
+    language sql
+    stable
+    security invoker
+as
+$$
+select left(t.date, 7)                                                   as month,
+       coalesce(s.category, t.category)                                  as category,
+       case when s.id is null then t.personal_finance_category ->> 'primary' end as pfc_primary,
+       coalesce(t.merchant_name, t.name)                                 as merchant,
+       t.iso_currency_code,
+       sum(coalesce(s.amount, t.amount))                                 as amount,
+       count(distinct t.transaction_id)                                  as transactions
+from user_transactions t
+         left join transaction_splits s on s.transaction_id = t.transaction_id
+where not t.is_transfer
+  and not (t.pending and coalesce((select u.exclude_pending
+                                   from user_settings u
+                                   where u.user_id = auth.uid()), false))
+  and t.date >= start_date
+  and t.date < end_date
+group by 1, 2, 3, 4, 5;
+$$;
+
+grant execute on function "public"."spending_summary"(text, text) to authenticated;
+
+create or replace function "public"."filter_spending_summary"(filter jsonb)
+    returns table
+            (
+                "month"             text,
+                "category"          text[],
+                "pfc_primary"       text,
+                "merchant"          text,
+                "iso_currency_code" text,
+                "amount"            double precision,
+                "transactions"      bigint
+            )
+    language sql
+    stable
+    security invoker
+as
+$$
+select left(t.date, 7)                                                   as month,
+       coalesce(s.category, t.category)                                  as category,
+       case when s.id is null then t.personal_finance_category ->> 'primary' end as pfc_primary,
+       coalesce(t.merchant_name, t.name)                                 as merchant,
+       t.iso_currency_code,
+       sum(coalesce(s.amount, t.amount))                                 as amount,
+       count(distinct t.transaction_id)                                  as transactions
+from filter_transactions(filter) t
+         left join transaction_splits s on s.transaction_id = t.transaction_id
+where not t.is_transfer
+  and not (t.pending and coalesce((select u.exclude_pending
+                                   from user_settings u
+                                   where u.user_id = auth.uid()), false))
+group by 1, 2, 3, 4, 5;
+$$;
+
+grant execute on function "public"."filter_spending_summary"(jsonb) to authenticated;
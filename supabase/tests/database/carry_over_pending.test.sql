@@ -0,0 +1,53 @@
+-- Runs with `supabase test db`
+begin;
+create extension if not exists pgtap with schema extensions;
+
+select plan(3);
+
+insert into auth.users (id, email)
+values ('00000000-0000-0000-0000-000000000050', 'pending@example.com');
+
+insert into access_tokens (id, access_token, user_id)
+values (5000, 'access-sandbox-pending', '00000000-0000-0000-0000-000000000050');
+
+insert into plaid_accounts (user_id, account_id, access_token_id)
+values ('00000000-0000-0000-0000-000000000050', 'pending-checking', 5000);
+
+insert into transactions (transaction_id, account_id, amount, name, date, pending, pending_transaction_id)
+values ('costco-pending', 'pending-checking', 100, 'Costco', '2026-10-17', true, null),
+       ('costco', 'pending-checking', 100, 'Costco', '2026-10-19', false, 'costco-pending'),
+       ('market-pending', 'pending-checking', 40, 'Market', '2026-10-17', true, null),
+       ('market', 'pending-checking', 40, 'Market', '2026-10-19', false, 'market-pending');
+
+insert into transaction_splits (transaction_id, user_id, category, amount)
+values ('costco-pending', '00000000-0000-0000-0000-000000000050', '{Shops}', 70),
+       ('costco-pending', '00000000-0000-0000-0000-000000000050', '{Food and Drink}', 30),
+       ('costco', '00000000-0000-0000-0000-000000000050', '{Shops}', 60),
+       ('costco', '00000000-0000-0000-0000-000000000050', '{Home Improvement}', 40),
+       ('market-pending', '00000000-0000-0000-0000-000000000050', '{Food and Drink}', 25),
+       ('market-pending', '00000000-0000-0000-0000-000000000050', '{Shops}', 15);
+
+set local role authenticated;
+set local request.jwt.claims = '{"sub": "00000000-0000-0000-0000-000000000050"}';
+
+select carry_over_pending('{"costco-pending": "costco", "market-pending": "market"}', '[]');
+
+select results_eq(
+    $$ select category, amount from transaction_splits where transaction_id = 'costco' order by amount $$,
+    $$ values ('{Home Improvement}'::text[], 40::double precision), ('{Shops}', 60) $$,
+    'Splits already on the posted transaction are kept instead of adding the pending ones'
+);
+
+select results_eq(
+    $$ select category, amount from transaction_splits where transaction_id = 'market' order by amount $$,
+    $$ values ('{Shops}'::text[], 15::double precision), ('{Food and Drink}', 25) $$,
+    'Splits move onto a posted transaction without any'
+);
+
+select is_empty(
+    $$ select * from transaction_splits where transaction_id in ('costco-pending', 'market-pending') $$,
+    'No splits are left on the pending transactions'
+);
+
+select * from finish();
+rollback;
@@ -0,0 +1,87 @@
+-- Runs with `supabase test db`. The same transactions as the budgets tests in
+-- recurr-core, so the rows checked here are the ones they roll up
+begin;
+create extension if not exists pgtap with schema extensions;
+
+select plan(4);
+
+insert into auth.users (id, email)
+values ('00000000-0000-0000-0000-000000000045', 'budgets@example.com');
+
+insert into access_tokens (id, access_token, user_id)
+values (4500, 'access-sandbox-budgets', '00000000-0000-0000-0000-000000000045');
+
+insert into plaid_accounts (user_id, account_id, access_token_id)
+values ('00000000-0000-0000-0000-000000000045', 'budgets-checking', 4500);
+
+insert into user_settings (user_id, base_currency, exclude_pending)
+values ('00000000-0000-0000-0000-000000000045', 'USD', false);
+
+insert into transactions (transaction_id, account_id, amount, name, date, category, pending,
+                          iso_currency_code, personal_finance_category)
+values ('dinner', 'budgets-checking', 40, 'dinner', '2026-10-02', '{Food and Drink,Restaurants}', false, null, null),
+       ('brunch', 'budgets-checking', 25, 'brunch', '2026-10-02', '{Food and Drink,Restaurants}', false, null, null),
+       ('latte', 'budgets-checking', 5, 'latte', '2026-10-19', '{Food and Drink,Restaurants,Coffee Shop}', false, null, null),
+       ('groceries', 'budgets-checking', 60, 'groceries', '2026-10-10', '{Food and Drink}', false, null, null),
+       ('lamp', 'budgets-checking', 30, 'lamp', '2026-10-11', '{Shops}', false, null, null),
+       ('refund', 'budgets-checking', -10, 'refund', '2026-10-11', '{Shops}', false, null, null),
+       ('salary', 'budgets-checking', -2000, 'salary', '2026-10-01', '{Transfer,Payroll}', false, null, null),
+       ('market', 'budgets-checking', 80, 'market', '2026-10-19', '{Shops}', true, 'USD',
+        '{"primary": "FOOD_AND_DRINK", "detailed": "FOOD_AND_DRINK_GROCERIES"}'),
+       ('savings', 'budgets-checking', 500, 'savings', '2026-10-05', '{Transfer}', false, null, null),
+       ('november', 'budgets-checking', 12, 'november', '2026-11-01', '{Shops}', false, null, null);
+
+insert into transaction_overrides (transaction_id, user_id, is_transfer)
+values ('savings', '00000000-0000-0000-0000-000000000045', true);
+
+insert into transaction_splits (transaction_id, user_id, category, amount)
+values ('lamp', '00000000-0000-0000-0000-000000000045', '{Shops}', 20),
+       ('lamp', '00000000-0000-0000-0000-000000000045', '{Food and Drink}', 10);
+
+set local role authenticated;
+set local request.jwt.claims = '{"sub": "00000000-0000-0000-0000-000000000045"}';
+
+select results_eq(
+    $$
+        select *
+        from daily_category_totals('2026-10-01', '2026-11-01')
+        order by date, category, amount
+    $$,
+    $$
+        values ('2026-10-01', '{Transfer,Payroll}'::text[], null::text, null::text, 'salary'::text, null::text, -2000::double precision, 1::bigint),
+               ('2026-10-02', '{Food and Drink,Restaurants}', null, null, null, null, 65, 2),
+               ('2026-10-10', '{Food and Drink}', null, null, null, null, 60, 1),
+               ('2026-10-11', '{Food and Drink}', null, null, null, null, 10, 1),
+               ('2026-10-11', '{Shops}', null, null, 'refund', null, -10, 1),
+               ('2026-10-11', '{Shops}', null, null, null, null, 20, 1),
+               ('2026-10-19', '{Food and Drink,Restaurants,Coffee Shop}', null, null, null, null, 5, 1),
+               ('2026-10-19', '{Shops}', 'FOOD_AND_DRINK', 'FOOD_AND_DRINK_GROCERIES', null, 'USD', 80, 1)
+    $$,
+    'Splits replace their parent, transfers and other months are left out'
+);
+
+select is(
+    (select sum(amount) from daily_category_totals('2026-10-01', '2026-11-01') where amount > 0),
+    240::double precision,
+    'Spending adds up to the transactions it sums'
+);
+
+update user_settings set exclude_pending = true;
+
+select is(
+    (select count(*) from daily_category_totals('2026-10-01', '2026-11-01') where pfc_primary is not null),
+    0::bigint,
+    'Pending transactions are left out when the user excludes them'
+);
+
+reset role;
+set local role authenticated;
+set local request.jwt.claims = '{"sub": "00000000-0000-0000-0000-000000000046"}';
+
+select is_empty(
+    $$ select * from daily_category_totals('2026-10-01', '2026-11-01') $$,
+    'Other users see none of the rows'
+);
+
+select * from finish();
+rollback;